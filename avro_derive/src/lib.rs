@@ -0,0 +1,346 @@
+//! Derive macros for [avro-rs](https://docs.rs/avro-rs).
+//!
+//! They are re-exported by `avro-rs` when its `derive` feature is enabled, and should be used
+//! from there.
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Fields, Generics, Ident,
+    Lit, Meta, NestedMeta, Type,
+};
+
+/// Derive `avro_rs::AvroSchema`.
+///
+/// Structs with named fields are mapped to records, and enums with unit variants to enums. The
+/// schema of each field is the `AvroSchema` of its type.
+///
+/// The following attributes are supported:
+/// * `#[avro(rename = "name")]` on the type, a field or a variant, to change its name;
+/// * `#[avro(namespace = "org.example")]` on the type;
+/// * `#[avro(doc = "...")]` on the type or a field;
+/// * `#[avro(default = literal)]` on a field, e.g. `42`, `"foo"` or `true`;
+/// * `#[avro(skip)]` on a field, to leave it out of the schema.
+///
+/// Renamed fields and variants should be renamed with `#[serde(rename = "name")]` as well, so
+/// that the values built by `avro_rs::to_value` match the schema.
+#[proc_macro_derive(AvroSchema, attributes(avro))]
+pub fn derive_avro_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    Container::parse(&input)
+        .map(|container| container.expand_avro_schema())
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Derive `avro_rs::types::ToAvro`, for types which also implement `avro_rs::AvroSchema`.
+///
+/// Structs are converted to a `Value::Record` of their `AvroSchema`, holding the `ToAvro`
+/// conversion of each field, and unit variants to a `Value::Enum`. The `#[avro(...)]`
+/// attributes are the ones of [`AvroSchema`](derive.AvroSchema.html): fields marked with
+/// `#[avro(skip)]` are not converted.
+#[proc_macro_derive(ToAvro, attributes(avro))]
+pub fn derive_to_avro(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    Container::parse(&input)
+        .map(|container| container.expand_to_avro())
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Values of the `#[avro(...)]` attributes of an item.
+#[derive(Default)]
+struct Attributes {
+    rename: Option<String>,
+    namespace: Option<String>,
+    doc: Option<String>,
+    default: Option<Lit>,
+    skip: bool,
+}
+
+impl Attributes {
+    fn parse(attrs: &[Attribute]) -> Result<Attributes, Error> {
+        let mut attributes = Attributes::default();
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("avro")) {
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                meta => return Err(Error::new_spanned(meta, "expected #[avro(...)]")),
+            };
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("skip") => {
+                        attributes.skip = true;
+                    }
+                    NestedMeta::Meta(Meta::NameValue(pair)) => {
+                        if pair.path.is_ident("rename") {
+                            attributes.rename = Some(lit_str(&pair.lit)?);
+                        } else if pair.path.is_ident("namespace") {
+                            attributes.namespace = Some(lit_str(&pair.lit)?);
+                        } else if pair.path.is_ident("doc") {
+                            attributes.doc = Some(lit_str(&pair.lit)?);
+                        } else if pair.path.is_ident("default") {
+                            attributes.default = Some(pair.lit);
+                        } else {
+                            return Err(Error::new_spanned(pair.path, "unknown avro attribute"));
+                        }
+                    }
+                    nested => return Err(Error::new_spanned(nested, "unknown avro attribute")),
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    /// Reject the attributes that do not apply to fields.
+    fn check_field(&self, tokens: impl ToTokens) -> Result<(), Error> {
+        if self.namespace.is_some() {
+            return Err(Error::new_spanned(tokens, "fields have no namespace"));
+        }
+        Ok(())
+    }
+
+    /// Reject the attributes that do not apply to types and variants.
+    fn check_named(&self, tokens: impl ToTokens) -> Result<(), Error> {
+        if self.default.is_some() {
+            return Err(Error::new_spanned(tokens, "only fields have a default"));
+        }
+        if self.skip {
+            return Err(Error::new_spanned(tokens, "only fields can be skipped"));
+        }
+        Ok(())
+    }
+}
+
+fn lit_str(lit: &Lit) -> Result<String, Error> {
+    match *lit {
+        Lit::Str(ref s) => Ok(s.value()),
+        ref lit => Err(Error::new_spanned(lit, "expected a string")),
+    }
+}
+
+/// A type deriving the macros of this crate.
+struct Container<'a> {
+    ident: &'a Ident,
+    generics: &'a Generics,
+    name: String,
+    attributes: Attributes,
+    body: Body<'a>,
+}
+
+enum Body<'a> {
+    /// The fields of a struct, except the skipped ones.
+    Record(Vec<Field<'a>>),
+    /// The variants of an enum.
+    Enum(Vec<Variant<'a>>),
+}
+
+struct Field<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    name: String,
+    attributes: Attributes,
+}
+
+struct Variant<'a> {
+    ident: &'a Ident,
+    symbol: String,
+}
+
+impl<'a> Container<'a> {
+    fn parse(input: &'a DeriveInput) -> Result<Container<'a>, Error> {
+        let attributes = Attributes::parse(&input.attrs)?;
+        attributes.check_named(&input.ident)?;
+
+        let body = match input.data {
+            Data::Struct(ref data) => match data.fields {
+                Fields::Named(ref fields) => {
+                    let mut record_fields = Vec::new();
+                    for field in &fields.named {
+                        let attributes = Attributes::parse(&field.attrs)?;
+                        attributes.check_field(field)?;
+                        if attributes.skip {
+                            continue;
+                        }
+                        let ident = field.ident.as_ref().expect("named field");
+                        record_fields.push(Field {
+                            ident,
+                            ty: &field.ty,
+                            name: attributes
+                                .rename
+                                .clone()
+                                .unwrap_or_else(|| ident.to_string()),
+                            attributes,
+                        });
+                    }
+                    Body::Record(record_fields)
+                }
+                _ => {
+                    return Err(Error::new_spanned(
+                        &input.ident,
+                        "only structs with named fields are supported",
+                    ))
+                }
+            },
+            Data::Enum(ref data) => Body::Enum(
+                data.variants
+                    .iter()
+                    .map(|variant| {
+                        if variant.fields != Fields::Unit {
+                            return Err(Error::new_spanned(
+                                variant,
+                                "only enums with unit variants are supported",
+                            ));
+                        }
+                        let attributes = Attributes::parse(&variant.attrs)?;
+                        attributes.check_named(variant)?;
+                        Ok(Variant {
+                            ident: &variant.ident,
+                            symbol: attributes
+                                .rename
+                                .unwrap_or_else(|| variant.ident.to_string()),
+                        })
+                    })
+                    .collect::<Result<_, Error>>()?,
+            ),
+            Data::Union(_) => {
+                return Err(Error::new_spanned(&input.ident, "unions are not supported"))
+            }
+        };
+
+        Ok(Container {
+            ident: &input.ident,
+            generics: &input.generics,
+            name: attributes
+                .rename
+                .clone()
+                .unwrap_or_else(|| input.ident.to_string()),
+            attributes,
+            body,
+        })
+    }
+
+    /// Like serde, require every type parameter to implement the derived traits.
+    fn bounded_generics(&self, bounds: &[TokenStream2]) -> Generics {
+        let mut generics = self.generics.clone();
+        for param in generics.type_params_mut() {
+            for bound in bounds {
+                param.bounds.push(parse_quote!(#bound));
+            }
+        }
+        generics
+    }
+
+    fn expand_avro_schema(&self) -> TokenStream2 {
+        let name = &self.name;
+        let namespace = self
+            .attributes
+            .namespace
+            .as_ref()
+            .map(|namespace| quote!(.namespace(#namespace)));
+        let doc = self.attributes.doc.as_ref().map(|doc| quote!(.doc(#doc)));
+
+        let builder = match self.body {
+            Body::Record(ref fields) => {
+                let fields = fields.iter().map(|field| {
+                    let name = &field.name;
+                    let doc = match field.attributes.doc {
+                        Some(ref doc) => quote!(Some(#doc.to_owned())),
+                        None => quote!(None),
+                    };
+                    let default = match field.attributes.default {
+                        Some(ref default) => quote!(Some(::std::convert::Into::into(#default))),
+                        None => quote!(None),
+                    };
+                    let ty = field.ty;
+                    quote! {
+                        .record_field(::avro_rs::schema::RecordField {
+                            name: #name.to_owned(),
+                            doc: #doc,
+                            default: #default,
+                            schema: <#ty as ::avro_rs::AvroSchema>::schema(),
+                            order: ::avro_rs::schema::RecordFieldOrder::Ascending,
+                            position: 0,
+                        })
+                    }
+                });
+                quote! {
+                    ::avro_rs::schema::SchemaBuilder::record(#name)
+                        #namespace
+                        #doc
+                        #(#fields)*
+                }
+            }
+            Body::Enum(ref variants) => {
+                let symbols = variants.iter().map(|variant| &variant.symbol);
+                quote! {
+                    ::avro_rs::schema::SchemaBuilder::enumeration(#name, vec![#(#symbols),*])
+                        #namespace
+                        #doc
+                }
+            }
+        };
+
+        let ident = self.ident;
+        let generics = self.bounded_generics(&[quote!(::avro_rs::AvroSchema)]);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        quote! {
+            impl #impl_generics ::avro_rs::AvroSchema for #ident #ty_generics #where_clause {
+                fn schema() -> ::avro_rs::Schema {
+                    #builder
+                        .build()
+                        .unwrap_or_else(|err| panic!("Invalid Avro schema for {}: {}", #name, err))
+                }
+            }
+        }
+    }
+
+    fn expand_to_avro(&self) -> TokenStream2 {
+        let ident = self.ident;
+        let body = match self.body {
+            Body::Record(ref fields) => {
+                let puts = fields.iter().map(|field| {
+                    let name = &field.name;
+                    let field = field.ident;
+                    quote!(record.put(#name, self.#field);)
+                });
+                quote! {
+                    let schema = <Self as ::avro_rs::AvroSchema>::schema();
+                    let mut record = ::avro_rs::types::Record::new(&schema)
+                        .expect("the AvroSchema of a struct is a record");
+                    #(#puts)*
+                    ::avro_rs::types::ToAvro::avro(record)
+                }
+            }
+            Body::Enum(ref variants) => {
+                let arms = variants.iter().enumerate().map(|(index, variant)| {
+                    let index = index as i32;
+                    let symbol = &variant.symbol;
+                    let variant = variant.ident;
+                    quote! {
+                        #ident::#variant => ::avro_rs::types::Value::Enum(#index, #symbol.to_owned()),
+                    }
+                });
+                quote! {
+                    match self {
+                        #(#arms)*
+                    }
+                }
+            }
+        };
+
+        let generics = self.bounded_generics(&[
+            quote!(::avro_rs::AvroSchema),
+            quote!(::avro_rs::types::ToAvro),
+        ]);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        quote! {
+            impl #impl_generics ::avro_rs::types::ToAvro for #ident #ty_generics #where_clause {
+                fn avro(self) -> ::avro_rs::types::Value {
+                    #body
+                }
+            }
+        }
+    }
+}
@@ -3,9 +3,12 @@ extern crate test;
 
 use avro_rs::{
     schema::Schema,
+    to_value,
     types::{Record, ToAvro, Value},
     Reader, Writer,
 };
+use serde::Serialize;
+use std::collections::HashMap;
 
 static RAW_SMALL_SCHEMA: &'static str = r#"
 {
@@ -280,3 +283,64 @@ fn bench_big_schema_read_100000_record(b: &mut test::Bencher) {
 fn bench_file_quickstop_null(b: &mut test::Bencher) {
     bench_from_file(b, "benches/quickstop-null.avro");
 }
+
+#[derive(Clone, Serialize)]
+struct Wide {
+    f0: i64,
+    f1: i64,
+    f2: i64,
+    f3: i64,
+    f4: String,
+    f5: String,
+    f6: String,
+    f7: String,
+    f8: f64,
+    f9: f64,
+    f10: f64,
+    f11: f64,
+    f12: bool,
+    f13: bool,
+    f14: bool,
+    f15: bool,
+}
+
+fn make_wide() -> Wide {
+    Wide {
+        f0: 0,
+        f1: 1,
+        f2: 2,
+        f3: 3,
+        f4: "four".to_owned(),
+        f5: "five".to_owned(),
+        f6: "six".to_owned(),
+        f7: "seven".to_owned(),
+        f8: 8.0,
+        f9: 9.0,
+        f10: 10.0,
+        f11: 11.0,
+        f12: true,
+        f13: false,
+        f14: true,
+        f15: false,
+    }
+}
+
+#[bench]
+fn bench_serialize_wide_struct(b: &mut test::Bencher) {
+    let wide = make_wide();
+    b.iter(|| to_value(&wide).unwrap());
+}
+
+#[bench]
+fn bench_serialize_100_wide_structs(b: &mut test::Bencher) {
+    let wides = vec![make_wide(); 100];
+    b.iter(|| to_value(&wides).unwrap());
+}
+
+#[bench]
+fn bench_serialize_100_entry_map(b: &mut test::Bencher) {
+    let map = (0..100)
+        .map(|i| (format!("key{}", i), i as i64))
+        .collect::<HashMap<_, _>>();
+    b.iter(|| to_value(&map).unwrap());
+}
@@ -2,6 +2,7 @@
 extern crate test;
 
 use avro_rs::{
+    estimate_encoded_size,
     schema::Schema,
     to_avro_datum,
     types::{Record, ToAvro, Value},
@@ -170,3 +171,18 @@ fn bench_small_schema_write_record(b: &mut test::Bencher) {
 fn bench_big_schema_write_record(b: &mut test::Bencher) {
     bench_write(b, &make_big_record);
 }
+
+fn bench_estimate(b: &mut test::Bencher, make_record: &Fn() -> (Schema, Value)) {
+    let (schema, record) = make_record();
+    b.iter(|| estimate_encoded_size(&record, &schema));
+}
+
+#[bench]
+fn bench_small_schema_estimate_size(b: &mut test::Bencher) {
+    bench_estimate(b, &make_small_record);
+}
+
+#[bench]
+fn bench_big_schema_estimate_size(b: &mut test::Bencher) {
+    bench_estimate(b, &make_big_record);
+}
@@ -0,0 +1,212 @@
+//! Baseline throughput of serialization, encoding and schema parsing, in records per second.
+//!
+//! Run with `cargo bench --bench throughput`: unlike the other benchmarks, it uses `criterion`
+//! and works on stable Rust.
+use std::collections::HashMap;
+
+use avro_rs::{
+    from_avro_datum, from_value, schema::Schema, to_avro_datum, to_value, types::Value, Reader,
+    Writer,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use serde::{Deserialize, Serialize};
+
+static RAW_TEN_FIELDS_SCHEMA: &str = r#"
+{
+  "type": "record",
+  "name": "TenFields",
+  "fields": [
+    {"name": "id", "type": "long"},
+    {"name": "name", "type": "string"},
+    {"name": "email", "type": "string"},
+    {"name": "age", "type": "int"},
+    {"name": "score", "type": "double"},
+    {"name": "ratio", "type": "float"},
+    {"name": "active", "type": "boolean"},
+    {"name": "avatar", "type": "bytes"},
+    {"name": "nickname", "type": ["null", "string"]},
+    {"name": "tags", "type": {"type": "array", "items": "string"}}
+  ]
+}
+"#;
+
+static RAW_COMPLEX_SCHEMA: &str = r#"
+{
+  "type": "record",
+  "name": "Complex",
+  "fields": [
+    {"name": "id", "type": "long"},
+    {"name": "name", "type": "string"},
+    {"name": "kind", "type": {"type": "enum", "name": "Kind", "symbols": ["A", "B", "C"]}},
+    {"name": "checksum", "type": {"type": "fixed", "name": "Checksum", "size": 16}},
+    {"name": "parent", "type": ["null", "long"]},
+    {"name": "scores", "type": {"type": "array", "items": "double"}},
+    {"name": "labels", "type": {"type": "map", "values": "string"}},
+    {
+      "name": "address",
+      "type": {
+        "type": "record",
+        "name": "Address",
+        "fields": [
+          {"name": "street", "type": "string"},
+          {"name": "city", "type": "string"},
+          {"name": "zip", "type": "int"}
+        ]
+      }
+    },
+    {"name": "previous", "type": {"type": "array", "items": "Address"}}
+  ]
+}
+"#;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct TenFields {
+    id: i64,
+    name: String,
+    email: String,
+    age: i32,
+    score: f64,
+    ratio: f32,
+    active: bool,
+    avatar: Vec<u8>,
+    nickname: Option<String>,
+    tags: Vec<String>,
+}
+
+fn make_ten_fields() -> TenFields {
+    TenFields {
+        id: 42,
+        name: "Jane Doe".to_owned(),
+        email: "jane@example.com".to_owned(),
+        age: 37,
+        score: 98.5,
+        ratio: 0.25,
+        active: true,
+        avatar: vec![0xff; 32],
+        nickname: Some("jd".to_owned()),
+        tags: vec!["admin".to_owned(), "staff".to_owned()],
+    }
+}
+
+fn make_address(n: i32) -> Value {
+    Value::Record(vec![
+        (
+            "street".to_owned(),
+            Value::String(format!("{} Main Street", n)),
+        ),
+        ("city".to_owned(), Value::String("Springfield".to_owned())),
+        ("zip".to_owned(), Value::Int(10_000 + n)),
+    ])
+}
+
+fn make_complex_record(id: i64) -> Value {
+    let labels: HashMap<String, Value> = (0..4)
+        .map(|i| (format!("label{}", i), Value::String(format!("value{}", i))))
+        .collect();
+    Value::Record(vec![
+        ("id".to_owned(), Value::Long(id)),
+        ("name".to_owned(), Value::String(format!("record {}", id))),
+        ("kind".to_owned(), Value::Enum(1, "B".to_owned())),
+        ("checksum".to_owned(), Value::Fixed(16, vec![7; 16])),
+        (
+            "parent".to_owned(),
+            Value::Union(Box::new(Value::Long(id - 1))),
+        ),
+        (
+            "scores".to_owned(),
+            Value::Array((0..8).map(|i| Value::Double(f64::from(i) / 3.0)).collect()),
+        ),
+        ("labels".to_owned(), Value::Map(labels)),
+        ("address".to_owned(), make_address(0)),
+        (
+            "previous".to_owned(),
+            Value::Array((1..3).map(make_address).collect()),
+        ),
+    ])
+}
+
+fn bench_to_value(c: &mut Criterion) {
+    let record = make_ten_fields();
+    let mut group = c.benchmark_group("to_value");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("10_fields", |b| {
+        b.iter(|| to_value(black_box(&record)).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_from_value(c: &mut Criterion) {
+    let value = to_value(make_ten_fields()).unwrap();
+    let mut group = c.benchmark_group("from_value");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("10_fields", |b| {
+        b.iter(|| from_value::<TenFields>(black_box(&value)).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_encode(c: &mut Criterion) {
+    let schema = Schema::parse_str(RAW_COMPLEX_SCHEMA).unwrap();
+    let record = make_complex_record(1);
+    let mut group = c.benchmark_group("encode");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("complex_record", |b| {
+        b.iter(|| to_avro_datum(&schema, black_box(record.clone())).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let schema = Schema::parse_str(RAW_COMPLEX_SCHEMA).unwrap();
+    let encoded = to_avro_datum(&schema, make_complex_record(1)).unwrap();
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("complex_record", |b| {
+        b.iter(|| from_avro_datum(&schema, &mut black_box(&encoded[..]), None).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_parse_schema(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_schema");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("ten_fields", |b| {
+        b.iter(|| Schema::parse_str(black_box(RAW_TEN_FIELDS_SCHEMA)).unwrap())
+    });
+    group.bench_function("complex", |b| {
+        b.iter(|| Schema::parse_str(black_box(RAW_COMPLEX_SCHEMA)).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_ocf_round_trip(c: &mut Criterion) {
+    const RECORDS: u64 = 10_000;
+
+    let schema = Schema::parse_str(RAW_COMPLEX_SCHEMA).unwrap();
+    let records: Vec<Value> = (0..RECORDS as i64).map(make_complex_record).collect();
+    let mut group = c.benchmark_group("ocf_round_trip");
+    group.throughput(Throughput::Elements(RECORDS));
+    group.sample_size(10);
+    group.bench_function("10k_complex_records", |b| {
+        b.iter(|| {
+            let mut writer = Writer::new(&schema, Vec::new());
+            writer.extend_from_slice(black_box(&records)).unwrap();
+            let input = writer.into_inner();
+
+            let reader = Reader::new(&input[..]).unwrap();
+            assert_eq!(reader.map(Result::unwrap).count() as u64, RECORDS);
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_to_value,
+    bench_from_value,
+    bench_encode,
+    bench_decode,
+    bench_parse_schema,
+    bench_ocf_round_trip
+);
+criterion_main!(benches);
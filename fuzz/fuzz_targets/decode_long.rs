@@ -0,0 +1,9 @@
+//! Decode arbitrary bytes as a `long`.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use avro_rs::{decode_from_slice, Schema};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = decode_from_slice(&Schema::Long, data);
+});
@@ -0,0 +1,44 @@
+//! Decode arbitrary bytes with a record schema holding every kind of type.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use std::str::FromStr;
+
+use avro_rs::{decode_from_slice, Schema};
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::from_str(
+        r#"
+        {
+            "type": "record",
+            "name": "Complex",
+            "fields": [
+                {"name": "b", "type": "boolean"},
+                {"name": "i", "type": "int"},
+                {"name": "f", "type": "float"},
+                {"name": "d", "type": "double"},
+                {"name": "bytes", "type": "bytes"},
+                {"name": "s", "type": "string"},
+                {"name": "e", "type": {"type": "enum", "name": "E", "symbols": ["A", "B"]}},
+                {"name": "fixed", "type": {"type": "fixed", "name": "F", "size": 4}},
+                {"name": "array", "type": {"type": "array", "items": "long"}},
+                {"name": "map", "type": {"type": "map", "values": "string"}},
+                {"name": "union", "type": ["null", "long", "string"]},
+                {"name": "date", "type": {"type": "int", "logicalType": "date"}},
+                {"name": "decimal", "type": {
+                    "type": "bytes", "logicalType": "decimal", "precision": 4, "scale": 2
+                }},
+                {"name": "duration", "type": {
+                    "type": "fixed", "name": "D", "size": 12, "logicalType": "duration"
+                }},
+                {"name": "next", "type": ["null", "Complex"]}
+            ]
+        }
+    "#
+    )
+    .unwrap();
+}
+
+fuzz_target!(|data: &[u8]| {
+    let _ = decode_from_slice(&SCHEMA, data);
+});
@@ -0,0 +1,10 @@
+//! Parse arbitrary strings as a schema.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use std::str::FromStr;
+
+use avro_rs::Schema;
+
+fuzz_target!(|data: &str| {
+    let _ = Schema::from_str(data);
+});
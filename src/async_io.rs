@@ -0,0 +1,381 @@
+//! Logic handling reading from and writing in Avro format with [tokio](https://docs.rs/tokio)
+//! asynchronous I/O.
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::sync::Arc;
+
+use failure::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::reader::{from_avro_datum, read_header_metadata};
+use crate::schema::Schema;
+use crate::types::{ToAvro, Value};
+use crate::util::{self, DecodeError};
+use crate::writer::{self, AVRO_OBJECT_HEADER, SYNC_INTERVAL};
+use crate::Codec;
+
+/// Asynchronous counterpart of [`Reader`](struct.Reader.html), reading Avro formatted values
+/// from anything implementing `tokio::io::AsyncRead`.
+///
+/// Blocks are read asynchronously as a whole, then decompressed and decoded in memory:
+///
+/// ```no_run
+/// # use avro_rs::AsyncReader;
+/// # async fn read<R: tokio::io::AsyncRead + Unpin>(input: R) -> Result<(), failure::Error> {
+/// let mut reader = AsyncReader::new(input).await?;
+/// while let Some(value) = reader.next().await {
+///     println!("{:?}", value?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncReader<'a, R> {
+    reader: R,
+    writer_schema: Arc<Schema>,
+    reader_schema: Option<&'a Schema>,
+    should_resolve_schema: bool,
+    codec: Codec,
+    marker: [u8; 16],
+    // Internal buffering to reduce allocation.
+    buf: Vec<u8>,
+    buf_idx: usize,
+    // Number of elements expected to exist within the current block.
+    message_count: usize,
+    errored: bool,
+}
+
+impl<'a, R: AsyncRead + Unpin> AsyncReader<'a, R> {
+    /// Creates an `AsyncReader` given something implementing the `tokio::io::AsyncRead` trait to
+    /// read from.
+    /// No reader `Schema` will be set.
+    ///
+    /// **NOTE** The avro header is going to be read upon creation of the `AsyncReader`.
+    pub async fn new(reader: R) -> Result<AsyncReader<'a, R>, Error> {
+        let mut reader = AsyncReader {
+            reader,
+            writer_schema: Arc::new(Schema::Null),
+            reader_schema: None,
+            should_resolve_schema: false,
+            codec: Codec::Null,
+            marker: [0; 16],
+            buf: vec![],
+            buf_idx: 0,
+            message_count: 0,
+            errored: false,
+        };
+        reader.read_header().await?;
+        Ok(reader)
+    }
+
+    /// Creates an `AsyncReader` given a reader `Schema` and something implementing the
+    /// `tokio::io::AsyncRead` trait to read from.
+    ///
+    /// **NOTE** The avro header is going to be read upon creation of the `AsyncReader`.
+    pub async fn with_schema(schema: &'a Schema, reader: R) -> Result<AsyncReader<'a, R>, Error> {
+        let mut reader = Self::new(reader).await?;
+        reader.set_reader_schema(schema);
+        Ok(reader)
+    }
+
+    /// Get a reference to the writer `Schema`.
+    pub fn writer_schema(&self) -> &Schema {
+        &self.writer_schema
+    }
+
+    /// Get a shared handle on the writer `Schema`, which outlives the `AsyncReader` without
+    /// being cloned.
+    pub fn shared_writer_schema(&self) -> Arc<Schema> {
+        self.writer_schema.clone()
+    }
+
+    /// Get a reference to the optional reader `Schema`.
+    pub fn reader_schema(&self) -> Option<&Schema> {
+        self.reader_schema
+    }
+
+    /// Set the reader `Schema` used to perform schema resolution on the values read from now on.
+    pub fn set_reader_schema(&mut self, schema: &'a Schema) {
+        self.should_resolve_schema = *self.writer_schema != *schema;
+        self.reader_schema = Some(schema);
+    }
+
+    /// Read the next value, or `None` once the end of the input has been reached.
+    ///
+    /// As for [`Reader`](struct.Reader.html), nothing more is read after the first error.
+    pub async fn next(&mut self) -> Option<Result<Value, Error>> {
+        // to prevent keep on reading after the first error occurs
+        if self.errored {
+            return None;
+        }
+        match self.read_next().await {
+            Ok(opt) => opt.map(Ok),
+            Err(e) => {
+                self.errored = true;
+                Some(Err(e))
+            }
+        }
+    }
+
+    /// Return what the `AsyncReader` is reading from, consuming the `AsyncReader` itself.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Read the header and set the writer `Schema`, the `Codec` and the marker based on its
+    /// content.
+    async fn read_header(&mut self) -> Result<(), Error> {
+        let mut buf = [0u8; 4];
+        self.reader.read_exact(&mut buf).await?;
+
+        if buf != AVRO_OBJECT_HEADER {
+            return Err(DecodeError::new("wrong magic in header").into());
+        }
+
+        let meta = self.read_metadata().await?;
+        let (writer_schema, codec) = read_header_metadata(&meta, None)?;
+        self.writer_schema = writer_schema;
+        self.codec = codec;
+
+        self.reader.read_exact(&mut self.marker).await?;
+
+        Ok(())
+    }
+
+    /// Read the metadata of the header, a map of bytes.
+    async fn read_metadata(&mut self) -> Result<HashMap<String, Value>, Error> {
+        let mut meta = HashMap::new();
+        loop {
+            let len = read_long(&mut self.reader).await?;
+            let len = if len < 0 {
+                // the size in bytes of the block is useless here
+                read_long(&mut self.reader).await?;
+                -len
+            } else {
+                len
+            };
+            if len == 0 {
+                return Ok(meta);
+            }
+
+            for _ in 0..len {
+                let key = String::from_utf8(read_bytes(&mut self.reader).await?)
+                    .map_err(|_| DecodeError::new("metadata key is not a valid utf-8 string"))?;
+                let value = read_bytes(&mut self.reader).await?;
+                meta.insert(key, Value::Bytes(value));
+            }
+        }
+    }
+
+    async fn read_next(&mut self) -> Result<Option<Value>, Error> {
+        if self.message_count == 0 {
+            self.read_block_next().await?;
+            if self.message_count == 0 {
+                return Ok(None);
+            }
+        }
+
+        let read_schema = if self.should_resolve_schema {
+            self.reader_schema
+        } else {
+            None
+        };
+
+        let mut block_bytes = &self.buf[self.buf_idx..];
+        let b_original = block_bytes.len();
+        let item = from_avro_datum(&self.writer_schema, &mut block_bytes, read_schema)?;
+        self.buf_idx += b_original - block_bytes.len();
+        self.message_count -= 1;
+        Ok(Some(item))
+    }
+
+    /// Try to read a data block into the internal buffer, decompressing it.
+    async fn read_block_next(&mut self) -> Result<(), Error> {
+        let block_len = match read_long(&mut self.reader).await {
+            Ok(block_len) => block_len,
+            Err(e) => {
+                return match e.downcast::<std::io::Error>() {
+                    // to not return any error in case we only finished to read cleanly from the
+                    // stream
+                    Ok(ref e) if e.kind() == ErrorKind::UnexpectedEof => Ok(()),
+                    Ok(e) => Err(e.into()),
+                    Err(e) => Err(e),
+                };
+            }
+        };
+        let block_bytes = read_long(&mut self.reader).await?;
+        if block_len < 0 || block_bytes < 0 {
+            return Err(DecodeError::new(format!(
+                "invalid block of {} objects and {} bytes",
+                block_len, block_bytes
+            ))
+            .into());
+        }
+
+        self.buf.resize(util::safe_len(block_bytes as usize)?, 0);
+        self.reader.read_exact(&mut self.buf).await?;
+        self.buf_idx = 0;
+
+        let mut marker = [0u8; 16];
+        self.reader.read_exact(&mut marker).await?;
+        if marker != self.marker {
+            return Err(DecodeError::new("block marker does not match header marker").into());
+        }
+
+        self.codec.decompress(&mut self.buf)?;
+        self.message_count = block_len as usize;
+
+        Ok(())
+    }
+}
+
+/// Read a zigzag-encoded `long`.
+async fn read_long<R: AsyncRead + Unpin>(reader: &mut R) -> Result<i64, Error> {
+    let mut buf = Vec::with_capacity(10);
+    loop {
+        if buf.len() == 10 {
+            return Err(DecodeError::new("Overflow when decoding integer value").into());
+        }
+        let byte = reader.read_u8().await?;
+        buf.push(byte);
+        if byte >> 7 == 0 {
+            return util::zag_i64(&mut &buf[..]);
+        }
+    }
+}
+
+/// Read a length-prefixed sequence of bytes.
+async fn read_bytes<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>, Error> {
+    let len = read_long(reader).await?;
+    if len < 0 {
+        return Err(DecodeError::new(format!("invalid length of bytes {}", len)).into());
+    }
+    let mut bytes = vec![0u8; util::safe_len(len as usize)?];
+    reader.read_exact(&mut bytes).await?;
+    Ok(bytes)
+}
+
+/// Asynchronous counterpart of [`Writer`](struct.Writer.html), writing Avro formatted values to
+/// anything implementing `tokio::io::AsyncWrite`.
+///
+/// Values are encoded and compressed in memory, and whole blocks are written asynchronously.
+pub struct AsyncWriter<'a, W> {
+    schema: &'a Schema,
+    writer: W,
+    buffer: Vec<u8>,
+    num_values: usize,
+    codec: Codec,
+    marker: Vec<u8>,
+    has_header: bool,
+}
+
+impl<'a, W: AsyncWrite + Unpin> AsyncWriter<'a, W> {
+    /// Creates an `AsyncWriter` given a `Schema` and something implementing the
+    /// `tokio::io::AsyncWrite` trait to write to.
+    /// No compression `Codec` will be used.
+    pub fn new(schema: &'a Schema, writer: W) -> AsyncWriter<'a, W> {
+        Self::with_codec(schema, writer, Codec::Null)
+    }
+
+    /// Creates an `AsyncWriter` with a specific `Codec` given a `Schema` and something
+    /// implementing the `tokio::io::AsyncWrite` trait to write to.
+    pub fn with_codec(schema: &'a Schema, writer: W, codec: Codec) -> AsyncWriter<'a, W> {
+        AsyncWriter {
+            schema,
+            writer,
+            buffer: Vec::with_capacity(SYNC_INTERVAL),
+            num_values: 0,
+            codec,
+            marker: writer::generate_marker(),
+            has_header: false,
+        }
+    }
+
+    /// Get a reference to the `Schema` associated to an `AsyncWriter`.
+    pub fn schema(&self) -> &'a Schema {
+        self.schema
+    }
+
+    /// Append a compatible value (implementing the `ToAvro` trait) to an `AsyncWriter`, also
+    /// performing schema validation.
+    ///
+    /// Return the number of bytes written (it might be 0, see below).
+    ///
+    /// **NOTE** This function is not guaranteed to perform any actual write, since it relies on
+    /// internal buffering for performance reasons. If you want to be sure the value has been
+    /// written, then call [`flush`](struct.AsyncWriter.html#method.flush).
+    pub async fn append<T: ToAvro>(&mut self, value: T) -> Result<usize, Error> {
+        self.append_value_ref(&value.avro()).await
+    }
+
+    /// Append a compatible value to an `AsyncWriter`, also performing schema validation.
+    ///
+    /// Return the number of bytes written (it might be 0, see below).
+    ///
+    /// **NOTE** This function is not guaranteed to perform any actual write, since it relies on
+    /// internal buffering for performance reasons. If you want to be sure the value has been
+    /// written, then call [`flush`](struct.AsyncWriter.html#method.flush).
+    pub async fn append_value_ref(&mut self, value: &Value) -> Result<usize, Error> {
+        let n = self.maybe_write_header().await?;
+
+        writer::write_value_ref(self.schema, value, &mut self.buffer)?;
+
+        self.num_values += 1;
+
+        if self.buffer.len() >= SYNC_INTERVAL {
+            return self.flush().await.map(|b| b + n);
+        }
+
+        Ok(n)
+    }
+
+    /// Flush the content appended to an `AsyncWriter`. Call this function to make sure all the
+    /// content has been written before releasing the `AsyncWriter`.
+    ///
+    /// Return the number of bytes written.
+    ///
+    /// The header is written even if no value has been appended, so that the output is always a
+    /// valid Avro container.
+    pub async fn flush(&mut self) -> Result<usize, Error> {
+        let mut num_bytes = self.maybe_write_header().await?;
+
+        if self.num_values > 0 {
+            self.codec.compress(&mut self.buffer)?;
+
+            let mut block = Vec::with_capacity(self.buffer.len() + 2 * 10 + self.marker.len());
+            util::zig_i64(self.num_values as i64, &mut block);
+            util::zig_i64(self.buffer.len() as i64, &mut block);
+            block.extend_from_slice(&self.buffer);
+            block.extend_from_slice(&self.marker);
+
+            self.writer.write_all(&block).await?;
+            num_bytes += block.len();
+
+            self.buffer.clear();
+            self.num_values = 0;
+        }
+
+        self.writer.flush().await?;
+
+        Ok(num_bytes)
+    }
+
+    /// Return what the `AsyncWriter` is writing to, consuming the `AsyncWriter` itself.
+    ///
+    /// **NOTE** This function doesn't guarantee that everything gets written before consuming the
+    /// buffer. Please call [`flush`](struct.AsyncWriter.html#method.flush) before.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Write the Avro header, unless it has already been written.
+    async fn maybe_write_header(&mut self) -> Result<usize, Error> {
+        if self.has_header {
+            return Ok(0);
+        }
+
+        let header = writer::header(self.schema, self.codec, &self.marker)?;
+        self.writer.write_all(&header).await?;
+        self.has_header = true;
+        Ok(header.len())
+    }
+}
@@ -0,0 +1,51 @@
+//! Serialize a `chrono::NaiveDate` field as an Avro `date`, with
+//! `#[serde(with = "avro_rs::avro_date")]`.
+//!
+//! [`to_value`](../fn.to_value.html) turns such a field into a `Value::Date`, holding the number
+//! of days since the Unix epoch, and [`from_value`](../fn.from_value.html) reads it back. Other
+//! serializers see the number of days as an `i32`.
+//!
+//! ```
+//! use avro_rs::{from_value, to_value, types::Value};
+//! use chrono::NaiveDate;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Release {
+//!     #[serde(with = "avro_rs::avro_date")]
+//!     day: NaiveDate,
+//! }
+//!
+//! let release = Release {
+//!     day: NaiveDate::from_ymd_opt(2016, 7, 18).unwrap(),
+//! };
+//! let value = to_value(&release).unwrap();
+//! assert_eq!(value, Value::Record(vec![("day".to_owned(), Value::Date(17_000))]));
+//! assert_eq!(from_value::<Release>(&value).unwrap(), release);
+//! ```
+use std::convert::TryFrom;
+
+use chrono::{Datelike, NaiveDate};
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+use crate::types::{Value, UNIX_EPOCH_DAYS_FROM_CE};
+
+/// Name of the newtype struct which `to_value` serializes into a `Value::Date`.
+pub(crate) const NAME: &str = "$avro_rs::avro_date";
+
+/// Serialize `date` as its number of days since the Unix epoch.
+pub fn serialize<S>(date: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct(NAME, &(date.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE))
+}
+
+/// Deserialize a date from its number of days since the Unix epoch.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let days = i32::deserialize(deserializer)?;
+    NaiveDate::try_from(Value::Date(days)).map_err(de::Error::custom)
+}
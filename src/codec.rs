@@ -4,12 +4,20 @@ use std::str::FromStr;
 
 #[cfg(feature = "snappy")]
 use byteorder;
+#[cfg(feature = "bzip2")]
+use bzip2::{read::BzDecoder, write::BzEncoder, Compression};
 #[cfg(feature = "snappy")]
 use crc;
 use failure::Error;
 use libflate::deflate::{Decoder, Encoder};
+#[cfg(feature = "xz")]
+use xz2::{read::XzDecoder, write::XzEncoder};
 
 use crate::types::{ToAvro, Value};
+#[cfg(any(feature = "bzip2", feature = "xz"))]
+use crate::util::allocation_limit;
+#[cfg(feature = "snappy")]
+use crate::util::safe_len;
 use crate::util::DecodeError;
 
 /// The compression codec used to compress blocks.
@@ -26,8 +34,19 @@ pub enum Codec {
     /// compression library. Each compressed block is followed by the 4-byte, big-endian
     /// CRC32 checksum of the uncompressed data in the block.
     Snappy,
+    #[cfg(feature = "bzip2")]
+    /// The `Bzip2` codec uses the [bzip2](https://sourceware.org/bzip2/) compression library.
+    Bzip2,
+    #[cfg(feature = "xz")]
+    /// The `Xz` codec uses the [xz](https://tukaani.org/xz/) compression library, with its
+    /// default preset.
+    Xz,
 }
 
+/// Compression level of the `Xz` codec, the default preset of the `xz` tool.
+#[cfg(feature = "xz")]
+const XZ_PRESET: u32 = 6;
+
 impl ToAvro for Codec {
     fn avro(self) -> Value {
         Value::Bytes(
@@ -36,6 +55,10 @@ impl ToAvro for Codec {
                 Codec::Deflate => "deflate",
                 #[cfg(feature = "snappy")]
                 Codec::Snappy => "snappy",
+                #[cfg(feature = "bzip2")]
+                Codec::Bzip2 => "bzip2",
+                #[cfg(feature = "xz")]
+                Codec::Xz => "xz",
             }
             .to_owned()
             .into_bytes(),
@@ -52,6 +75,10 @@ impl FromStr for Codec {
             "deflate" => Ok(Codec::Deflate),
             #[cfg(feature = "snappy")]
             "snappy" => Ok(Codec::Snappy),
+            #[cfg(feature = "bzip2")]
+            "bzip2" => Ok(Codec::Bzip2),
+            #[cfg(feature = "xz")]
+            "xz" => Ok(Codec::Xz),
             _ => Err(DecodeError::new("unrecognized codec")),
         }
     }
@@ -81,6 +108,18 @@ impl Codec {
 
                 *stream = encoded;
             }
+            #[cfg(feature = "bzip2")]
+            Codec::Bzip2 => {
+                let mut encoder = BzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(stream)?;
+                *stream = encoder.finish()?;
+            }
+            #[cfg(feature = "xz")]
+            Codec::Xz => {
+                let mut encoder = XzEncoder::new(Vec::new(), XZ_PRESET);
+                encoder.write_all(stream)?;
+                *stream = encoder.finish()?;
+            }
         };
 
         Ok(())
@@ -103,11 +142,16 @@ impl Codec {
             Codec::Snappy => {
                 use byteorder::ByteOrder;
 
-                let decompressed_size = snap::decompress_len(&stream[..stream.len() - 4])?;
+                if stream.len() < 4 {
+                    return Err(DecodeError::new("Snappy block is missing its CRC32").into());
+                }
+                let (compressed, crc) = stream.split_at(stream.len() - 4);
+
+                let decompressed_size = safe_len(snap::decompress_len(compressed)?)?;
                 let mut decoded = vec![0; decompressed_size];
-                snap::Decoder::new().decompress(&stream[..stream.len() - 4], &mut decoded[..])?;
+                snap::Decoder::new().decompress(compressed, &mut decoded[..])?;
 
-                let expected_crc = byteorder::BigEndian::read_u32(&stream[stream.len() - 4..]);
+                let expected_crc = byteorder::BigEndian::read_u32(crc);
                 let actual_crc = crc::crc32::checksum_ieee(&decoded);
 
                 if expected_crc != actual_crc {
@@ -119,12 +163,36 @@ impl Codec {
                 }
                 *stream = decoded;
             }
+            #[cfg(feature = "bzip2")]
+            Codec::Bzip2 => {
+                *stream = read_to_end_limited(BzDecoder::new(&stream[..]), allocation_limit())?;
+            }
+            #[cfg(feature = "xz")]
+            Codec::Xz => {
+                *stream = read_to_end_limited(XzDecoder::new(&stream[..]), allocation_limit())?;
+            }
         };
 
         Ok(())
     }
 }
 
+/// Read a decompressed block out of `decoder`, failing instead of reading more than `limit` bytes
+/// so that a small block cannot decompress into an arbitrarily large one.
+#[cfg(any(feature = "bzip2", feature = "xz"))]
+fn read_to_end_limited<R: Read>(decoder: R, limit: usize) -> Result<Vec<u8>, Error> {
+    let mut decoded = Vec::new();
+    decoder.take(limit as u64 + 1).read_to_end(&mut decoded)?;
+    if decoded.len() > limit {
+        return Err(DecodeError::new(format!(
+            "decompressed block is larger than the allocation limit of {} bytes",
+            limit
+        ))
+        .into());
+    }
+    Ok(decoded)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,4 +231,95 @@ mod tests {
         codec.decompress(&mut stream).unwrap();
         assert_eq!(INPUT, stream.as_slice());
     }
+
+    #[cfg(feature = "snappy")]
+    #[test]
+    fn snappy_decompress_invalid() {
+        let codec = Codec::Snappy;
+        assert!(codec.decompress(&mut vec![0u8; 3]).is_err());
+
+        let mut stream = INPUT.to_vec();
+        codec.compress(&mut stream).unwrap();
+        let last = stream.len() - 1;
+        stream[last] ^= 0xff;
+        assert!(codec.decompress(&mut stream).is_err());
+    }
+
+    #[cfg(feature = "snappy")]
+    #[test]
+    fn snappy_decompress_too_large() {
+        // A block claiming a 4GiB uncompressed size, over the allocation limit
+        let mut stream = vec![0xff, 0xff, 0xff, 0xff, 0x0f, 0, 0, 0, 0];
+        let err = Codec::Snappy.decompress(&mut stream).unwrap_err();
+        assert!(err.downcast_ref::<crate::util::AllocationError>().is_some());
+    }
+
+    #[cfg(feature = "bzip2")]
+    #[test]
+    fn bzip2_compress_and_decompress() {
+        let codec = Codec::Bzip2;
+        let mut stream = INPUT.to_vec();
+        codec.compress(&mut stream).unwrap();
+        assert_ne!(INPUT, stream.as_slice());
+        assert!(INPUT.len() > stream.len());
+        codec.decompress(&mut stream).unwrap();
+        assert_eq!(INPUT, stream.as_slice());
+
+        assert!(codec.decompress(&mut vec![0xffu8; 8]).is_err());
+    }
+
+    #[cfg(feature = "bzip2")]
+    #[test]
+    fn bzip2_decompress_over_limit() {
+        let mut stream = vec![0u8; 4096];
+        Codec::Bzip2.compress(&mut stream).unwrap();
+        assert_eq!(
+            read_to_end_limited(BzDecoder::new(&stream[..]), 4096).unwrap(),
+            vec![0u8; 4096]
+        );
+        assert!(read_to_end_limited(BzDecoder::new(&stream[..]), 4095).is_err());
+    }
+
+    #[cfg(feature = "xz")]
+    #[test]
+    fn xz_compress_and_decompress() {
+        let codec = Codec::Xz;
+        let mut stream = INPUT.to_vec();
+        codec.compress(&mut stream).unwrap();
+        assert_ne!(INPUT, stream.as_slice());
+        assert!(INPUT.len() > stream.len());
+        codec.decompress(&mut stream).unwrap();
+        assert_eq!(INPUT, stream.as_slice());
+
+        assert!(codec.decompress(&mut vec![0xffu8; 8]).is_err());
+    }
+
+    #[cfg(feature = "xz")]
+    #[test]
+    fn xz_decompress_over_limit() {
+        let mut stream = vec![0u8; 4096];
+        Codec::Xz.compress(&mut stream).unwrap();
+        assert_eq!(
+            read_to_end_limited(XzDecoder::new(&stream[..]), 4096).unwrap(),
+            vec![0u8; 4096]
+        );
+        assert!(read_to_end_limited(XzDecoder::new(&stream[..]), 4095).is_err());
+    }
+
+    #[test]
+    fn deflate_decompress_invalid() {
+        let mut stream = vec![0xffu8; 8];
+        assert!(Codec::Deflate.decompress(&mut stream).is_err());
+    }
+
+    #[test]
+    fn codec_from_str() {
+        assert_eq!(Codec::from_str("null").unwrap(), Codec::Null);
+        assert_eq!(Codec::from_str("deflate").unwrap(), Codec::Deflate);
+        assert!(Codec::from_str("zstandard").is_err());
+        #[cfg(feature = "bzip2")]
+        assert_eq!(Codec::from_str("bzip2").unwrap(), Codec::Bzip2);
+        #[cfg(feature = "xz")]
+        assert_eq!(Codec::from_str("xz").unwrap(), Codec::Xz);
+    }
 }
@@ -0,0 +1,785 @@
+//! Logic for checking the compatibility of two versions of a schema.
+//!
+//! The checks follow the
+//! [Schema Resolution](https://avro.apache.org/docs/current/spec.html#Schema+Resolution)
+//! rules of the Avro specification: a schema can read data written with another one if every
+//! value written with the latter can be resolved against the former.
+use std::fmt;
+
+use crate::schema::{Name, RecordField, Schema, SchemaKind};
+
+/// Direction(s) in which two versions of a schema have to be compatible.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompatibilityMode {
+    /// The new schema can read data written with the old schema.
+    Backward,
+    /// The old schema can read data written with the new schema.
+    Forward,
+    /// Both `Backward` and `Forward`.
+    Full,
+    /// Like `Backward`, against every previous version of the schema.
+    BackwardTransitive,
+    /// Like `Forward`, against every previous version of the schema.
+    ForwardTransitive,
+    /// Like `Full`, against every previous version of the schema.
+    FullTransitive,
+}
+
+impl CompatibilityMode {
+    /// Returns true if the new schema has to be checked against every previous version.
+    pub fn is_transitive(self) -> bool {
+        matches!(
+            self,
+            CompatibilityMode::BackwardTransitive
+                | CompatibilityMode::ForwardTransitive
+                | CompatibilityMode::FullTransitive
+        )
+    }
+
+    fn is_backward(self) -> bool {
+        !matches!(
+            self,
+            CompatibilityMode::Forward | CompatibilityMode::ForwardTransitive
+        )
+    }
+
+    fn is_forward(self) -> bool {
+        !matches!(
+            self,
+            CompatibilityMode::Backward | CompatibilityMode::BackwardTransitive
+        )
+    }
+}
+
+/// Describes a change between two versions of a schema which breaks compatibility.
+///
+/// Fields are identified by their dotted path from the top-level schema, which is empty for the
+/// top-level schema itself.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum IncompatibleChange {
+    /// A field without default was added, so data written with the old schema cannot be read.
+    FieldAdded { name: String },
+    /// A field was removed whereas the old schema has no default for it, so data written with
+    /// the new schema cannot be read by the old one.
+    FieldRemoved { name: String },
+    /// The type of a value changed to one it cannot be resolved to.
+    TypeChanged {
+        field: String,
+        from: Schema,
+        to: Schema,
+    },
+    /// A named type was renamed.
+    NameChanged { from: String, to: String },
+    /// A symbol was added to an enum, and cannot be read by the old schema.
+    SymbolAdded { name: String, symbol: String },
+    /// A symbol was removed from an enum, and cannot be read by the new schema.
+    SymbolRemoved { name: String, symbol: String },
+}
+
+impl fmt::Display for IncompatibleChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IncompatibleChange::FieldAdded { ref name } => {
+                write!(f, "field {} was added without default", name)
+            }
+            IncompatibleChange::FieldRemoved { ref name } => {
+                write!(f, "field {} without default was removed", name)
+            }
+            IncompatibleChange::TypeChanged {
+                ref field,
+                ref from,
+                ref to,
+            } => write!(
+                f,
+                "type of {} changed from {} to {}",
+                if field.is_empty() { "schema" } else { field },
+                from.canonical_form(),
+                to.canonical_form()
+            ),
+            IncompatibleChange::NameChanged { ref from, ref to } => {
+                write!(f, "name changed from {} to {}", from, to)
+            }
+            IncompatibleChange::SymbolAdded {
+                ref name,
+                ref symbol,
+            } => write!(f, "symbol {} was added to enum {}", symbol, name),
+            IncompatibleChange::SymbolRemoved {
+                ref name,
+                ref symbol,
+            } => write!(f, "symbol {} was removed from enum {}", symbol, name),
+        }
+    }
+}
+
+/// Result of a compatibility check, listing every incompatible change on failure.
+pub type CompatibilityResult = Result<(), Vec<IncompatibleChange>>;
+
+/// Check that `new` is compatible with `old` in the given `mode`.
+///
+/// Transitive modes behave like their non-transitive counterparts here, as there is a single
+/// previous version; use
+/// [`check_compatibility_with_history`](fn.check_compatibility_with_history.html) to check
+/// against all of them.
+///
+/// ```
+/// use avro_rs::{check_compatibility, CompatibilityMode, IncompatibleChange, Schema};
+///
+/// let old = Schema::parse_str(
+///     r#"{"type": "record", "name": "r", "fields": [{"name": "a", "type": "int"}]}"#,
+/// ).unwrap();
+/// let new = Schema::parse_str(
+///     r#"{"type": "record", "name": "r", "fields": [
+///         {"name": "a", "type": "long"},
+///         {"name": "b", "type": "string"}
+///     ]}"#,
+/// ).unwrap();
+///
+/// assert_eq!(
+///     check_compatibility(&old, &new, CompatibilityMode::Backward),
+///     Err(vec![IncompatibleChange::FieldAdded { name: "b".to_owned() }])
+/// );
+/// ```
+pub fn check_compatibility(
+    old: &Schema,
+    new: &Schema,
+    mode: CompatibilityMode,
+) -> CompatibilityResult {
+    let mut errors = Vec::new();
+    if mode.is_backward() {
+        Checker::new(true, &mut errors).check(old, new, "");
+    }
+    if mode.is_forward() {
+        Checker::new(false, &mut errors).check(new, old, "");
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Check that `new` is compatible with the `previous` versions of a schema, ordered from the
+/// oldest to the latest.
+///
+/// Non-transitive modes only check against the latest version.
+pub fn check_compatibility_with_history(
+    previous: &[Schema],
+    new: &Schema,
+    mode: CompatibilityMode,
+) -> CompatibilityResult {
+    let to_check = if mode.is_transitive() {
+        previous
+    } else {
+        &previous[previous.len().saturating_sub(1)..]
+    };
+
+    let mut errors = Vec::new();
+    for old in to_check {
+        if let Err(changes) = check_compatibility(old, new, mode) {
+            for change in changes {
+                if !errors.contains(&change) {
+                    errors.push(change);
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Collects the reasons why a reader schema cannot read data written with a writer schema.
+struct Checker<'a> {
+    /// Whether the reader is the new version of the schema, which decides how changes are
+    /// reported.
+    reader_is_new: bool,
+    errors: &'a mut Vec<IncompatibleChange>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(reader_is_new: bool, errors: &'a mut Vec<IncompatibleChange>) -> Self {
+        Checker {
+            reader_is_new,
+            errors,
+        }
+    }
+
+    fn push(&mut self, change: IncompatibleChange) {
+        if !self.errors.contains(&change) {
+            self.errors.push(change);
+        }
+    }
+
+    fn type_changed(&mut self, writer: &Schema, reader: &Schema, path: &str) {
+        let (from, to) = if self.reader_is_new {
+            (writer, reader)
+        } else {
+            (reader, writer)
+        };
+        self.push(IncompatibleChange::TypeChanged {
+            field: path.to_owned(),
+            from: from.clone(),
+            to: to.clone(),
+        });
+    }
+
+    fn check_name(&mut self, writer: &Name, reader: &Name) -> bool {
+        if unqualified(&writer.name) == unqualified(&reader.name)
+            || reader
+                .aliases
+                .iter()
+                .flatten()
+                .any(|alias| unqualified(alias) == unqualified(&writer.name))
+        {
+            return true;
+        }
+
+        let (from, to) = if self.reader_is_new {
+            (writer, reader)
+        } else {
+            (reader, writer)
+        };
+        self.push(IncompatibleChange::NameChanged {
+            from: from.name.clone(),
+            to: to.name.clone(),
+        });
+        false
+    }
+
+    fn check(&mut self, writer: &Schema, reader: &Schema, path: &str) {
+        match (writer, reader) {
+            (&Schema::Union(ref writer_union), _) => {
+                for variant in writer_union.variants() {
+                    self.check(variant, reader, path);
+                }
+            }
+            (_, &Schema::Union(ref reader_union)) => {
+                let kind = SchemaKind::from(writer);
+                match reader_union
+                    .variants()
+                    .iter()
+                    .find(|variant| SchemaKind::from(*variant) == kind)
+                {
+                    Some(variant) => self.check(writer, variant, path),
+                    None => {
+                        if !reader_union
+                            .variants()
+                            .iter()
+                            .any(|variant| can_read(writer, variant))
+                        {
+                            self.type_changed(writer, reader, path);
+                        }
+                    }
+                }
+            }
+            (&Schema::Array(ref writer_items), &Schema::Array(ref reader_items)) => {
+                self.check(writer_items, reader_items, path)
+            }
+            (&Schema::Map(ref writer_values), &Schema::Map(ref reader_values)) => {
+                self.check(writer_values, reader_values, path)
+            }
+            (
+                &Schema::Record {
+                    name: ref writer_name,
+                    fields: ref writer_fields,
+                    ..
+                },
+                &Schema::Record {
+                    name: ref reader_name,
+                    fields: ref reader_fields,
+                    ..
+                },
+            ) => {
+                if self.check_name(writer_name, reader_name) {
+                    self.check_fields(writer_fields, reader_fields, path);
+                }
+            }
+            (
+                &Schema::Enum {
+                    name: ref writer_name,
+                    symbols: ref writer_symbols,
+                    ..
+                },
+                &Schema::Enum {
+                    name: ref reader_name,
+                    symbols: ref reader_symbols,
+                    ..
+                },
+            ) => {
+                if self.check_name(writer_name, reader_name) {
+                    for symbol in writer_symbols {
+                        if !reader_symbols.contains(symbol) {
+                            let name = reader_name.name.clone();
+                            let symbol = symbol.clone();
+                            self.push(if self.reader_is_new {
+                                IncompatibleChange::SymbolRemoved { name, symbol }
+                            } else {
+                                IncompatibleChange::SymbolAdded { name, symbol }
+                            });
+                        }
+                    }
+                }
+            }
+            (
+                &Schema::Fixed {
+                    name: ref writer_name,
+                    size: writer_size,
+                },
+                &Schema::Fixed {
+                    name: ref reader_name,
+                    size: reader_size,
+                },
+            ) => {
+                if self.check_name(writer_name, reader_name) && writer_size != reader_size {
+                    self.type_changed(writer, reader, path);
+                }
+            }
+            (
+                &Schema::Decimal {
+                    precision: writer_precision,
+                    scale: writer_scale,
+                    inner: ref writer_inner,
+                },
+                &Schema::Decimal {
+                    precision: reader_precision,
+                    scale: reader_scale,
+                    inner: ref reader_inner,
+                },
+            ) => {
+                if writer_precision != reader_precision || writer_scale != reader_scale {
+                    self.type_changed(writer, reader, path);
+                } else {
+                    self.check(writer_inner, reader_inner, path);
+                }
+            }
+            // References to records are checked along with their definitions.
+            (
+                &Schema::Ref {
+                    name: ref writer_name,
+                },
+                &Schema::Ref {
+                    name: ref reader_name,
+                },
+            )
+            | (
+                &Schema::Ref {
+                    name: ref writer_name,
+                },
+                &Schema::Record {
+                    name: ref reader_name,
+                    ..
+                },
+            )
+            | (
+                &Schema::Record {
+                    name: ref writer_name,
+                    ..
+                },
+                &Schema::Ref {
+                    name: ref reader_name,
+                },
+            ) => {
+                self.check_name(writer_name, reader_name);
+            }
+            _ => match underlying(reader) {
+                // A logical type reads values of its underlying type, as resolution does.
+                Some(ref underlying)
+                    if SchemaKind::from(writer) == SchemaKind::from(underlying)
+                        || matches!((writer, underlying), (&Schema::Int, &Schema::Long)) =>
+                {
+                    self.check(writer, underlying, path)
+                }
+                _ => {
+                    if !can_promote(writer, reader) {
+                        self.type_changed(writer, reader, path);
+                    }
+                }
+            },
+        }
+    }
+
+    fn check_fields(
+        &mut self,
+        writer_fields: &[RecordField],
+        reader_fields: &[RecordField],
+        path: &str,
+    ) {
+        for reader_field in reader_fields {
+            let field_path = if path.is_empty() {
+                reader_field.name.clone()
+            } else {
+                format!("{}.{}", path, reader_field.name)
+            };
+
+            match writer_fields
+                .iter()
+                .find(|writer_field| writer_field.name == reader_field.name)
+            {
+                Some(writer_field) => {
+                    self.check(&writer_field.schema, &reader_field.schema, &field_path)
+                }
+                None if reader_field.default.is_none() => self.push(if self.reader_is_new {
+                    IncompatibleChange::FieldAdded { name: field_path }
+                } else {
+                    IncompatibleChange::FieldRemoved { name: field_path }
+                }),
+                None => (),
+            }
+        }
+    }
+}
+
+/// Returns true if data written with `writer` can be read with `reader`.
+fn can_read(writer: &Schema, reader: &Schema) -> bool {
+    let mut errors = Vec::new();
+    Checker::new(true, &mut errors).check(writer, reader, "");
+    errors.is_empty()
+}
+
+/// Returns true if `writer` and `reader` are the same non-named schema, or if `writer` can be
+/// promoted to `reader`.
+fn can_promote(writer: &Schema, reader: &Schema) -> bool {
+    matches!(
+        (writer, reader),
+        (&Schema::Int, &Schema::Long)
+            | (&Schema::Int, &Schema::Float)
+            | (&Schema::Int, &Schema::Double)
+            | (&Schema::Long, &Schema::Float)
+            | (&Schema::Long, &Schema::Double)
+            | (&Schema::Float, &Schema::Double)
+            | (&Schema::String, &Schema::Bytes)
+            | (&Schema::Bytes, &Schema::String)
+            | (&Schema::Null, &Schema::Null)
+            | (&Schema::Boolean, &Schema::Boolean)
+            | (&Schema::Int, &Schema::Int)
+            | (&Schema::Long, &Schema::Long)
+            | (&Schema::Float, &Schema::Float)
+            | (&Schema::Double, &Schema::Double)
+            | (&Schema::Bytes, &Schema::Bytes)
+            | (&Schema::String, &Schema::String)
+            | (&Schema::Date, &Schema::Date)
+            | (&Schema::TimeMillis, &Schema::TimeMillis)
+            | (&Schema::TimeMicros, &Schema::TimeMicros)
+            | (&Schema::TimestampMillis, &Schema::TimestampMillis)
+            | (&Schema::TimestampMicros, &Schema::TimestampMicros)
+            | (&Schema::Duration { .. }, &Schema::Duration { .. })
+            | (&Schema::Uuid, &Schema::Uuid)
+    )
+}
+
+/// Returns the type underlying a logical type, or `None` for other schemas.
+fn underlying(schema: &Schema) -> Option<Schema> {
+    match *schema {
+        Schema::Date | Schema::TimeMillis => Some(Schema::Int),
+        Schema::TimeMicros | Schema::TimestampMillis | Schema::TimestampMicros => {
+            Some(Schema::Long)
+        }
+        Schema::Decimal { ref inner, .. } => Some((**inner).clone()),
+        Schema::Duration { ref name } => Some(Schema::Fixed {
+            name: name.clone(),
+            size: 12,
+        }),
+        Schema::Uuid => Some(Schema::String),
+        _ => None,
+    }
+}
+
+fn unqualified(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::UnionSchema;
+    use crate::types::Value;
+
+    fn record(fields: &str) -> Schema {
+        Schema::parse_str(&format!(
+            r#"{{"type": "record", "name": "r", "fields": [{}]}}"#,
+            fields
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_identical_schemas() {
+        let schema = record(
+            r#"{"name": "a", "type": "int"},
+               {"name": "b", "type": {"type": "array", "items": "string"}}"#,
+        );
+        for mode in &[
+            CompatibilityMode::Backward,
+            CompatibilityMode::Forward,
+            CompatibilityMode::Full,
+        ] {
+            assert_eq!(check_compatibility(&schema, &schema, *mode), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_added_and_removed_fields() {
+        let old = record(r#"{"name": "a", "type": "int"}"#);
+        let with_default = record(
+            r#"{"name": "a", "type": "int"},
+               {"name": "b", "type": "string", "default": "foo"}"#,
+        );
+        let without_default = record(
+            r#"{"name": "a", "type": "int"},
+               {"name": "b", "type": "string"}"#,
+        );
+
+        assert_eq!(
+            check_compatibility(&old, &with_default, CompatibilityMode::Full),
+            Ok(())
+        );
+        assert_eq!(
+            check_compatibility(&old, &without_default, CompatibilityMode::Forward),
+            Ok(())
+        );
+        assert_eq!(
+            check_compatibility(&old, &without_default, CompatibilityMode::Backward),
+            Err(vec![IncompatibleChange::FieldAdded {
+                name: "b".to_owned()
+            }])
+        );
+        assert_eq!(
+            check_compatibility(&without_default, &old, CompatibilityMode::Forward),
+            Err(vec![IncompatibleChange::FieldRemoved {
+                name: "b".to_owned()
+            }])
+        );
+        assert_eq!(
+            check_compatibility(&without_default, &old, CompatibilityMode::Backward),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_type_changes() {
+        let int = record(r#"{"name": "a", "type": "int"}"#);
+        let long = record(r#"{"name": "a", "type": "long"}"#);
+        let string = record(r#"{"name": "a", "type": "string"}"#);
+
+        assert_eq!(
+            check_compatibility(&int, &long, CompatibilityMode::Backward),
+            Ok(())
+        );
+        assert_eq!(
+            check_compatibility(&int, &long, CompatibilityMode::Forward),
+            Err(vec![IncompatibleChange::TypeChanged {
+                field: "a".to_owned(),
+                from: Schema::Int,
+                to: Schema::Long,
+            }])
+        );
+        assert_eq!(
+            check_compatibility(&int, &string, CompatibilityMode::Full),
+            Err(vec![IncompatibleChange::TypeChanged {
+                field: "a".to_owned(),
+                from: Schema::Int,
+                to: Schema::String,
+            }])
+        );
+        assert_eq!(
+            check_compatibility(&Schema::String, &Schema::Bytes, CompatibilityMode::Full),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_logical_types() {
+        let fixed = |size| Schema::Fixed {
+            name: Name::new("f"),
+            size,
+        };
+        let decimal = |inner| Schema::Decimal {
+            precision: 4,
+            scale: 2,
+            inner: Box::new(inner),
+        };
+        let duration = Schema::Duration {
+            name: Name::new("f"),
+        };
+
+        let readable = vec![
+            (Schema::Int, Schema::Date, Value::Int(1)),
+            (Schema::Int, Schema::TimeMillis, Value::Int(1)),
+            (Schema::Int, Schema::TimestampMillis, Value::Int(1)),
+            (Schema::Long, Schema::TimeMicros, Value::Long(1)),
+            (Schema::Long, Schema::TimestampMillis, Value::Long(1)),
+            (Schema::Long, Schema::TimestampMicros, Value::Long(1)),
+            (Schema::Bytes, decimal(Schema::Bytes), Value::Bytes(vec![1])),
+            (fixed(2), decimal(fixed(2)), Value::Fixed(2, vec![0, 1])),
+            (fixed(12), duration.clone(), Value::Fixed(12, vec![0; 12])),
+            (
+                Schema::String,
+                Schema::Uuid,
+                Value::String("936da01f-9abd-4d9d-80c7-02af85c822a8".to_owned()),
+            ),
+        ];
+        for (writer, reader, value) in readable {
+            assert_eq!(
+                check_compatibility(&writer, &reader, CompatibilityMode::Backward),
+                Ok(()),
+                "{:?} -> {:?}",
+                writer,
+                reader
+            );
+            assert!(value.clone().resolve(&reader).is_ok());
+
+            let union = Schema::Union(UnionSchema::new(vec![Schema::Null, reader]).unwrap());
+            assert_eq!(
+                check_compatibility(&writer, &union, CompatibilityMode::Backward),
+                Ok(())
+            );
+            assert!(value.resolve(&union).is_ok());
+        }
+
+        let unreadable = vec![
+            (Schema::Date, Schema::Int, Value::Date(1)),
+            (Schema::Date, Schema::TimestampMillis, Value::Date(1)),
+            (
+                Schema::String,
+                decimal(Schema::Bytes),
+                Value::String("1".to_owned()),
+            ),
+            (Schema::Bytes, Schema::Uuid, Value::Bytes(vec![1])),
+        ];
+        for (writer, reader, value) in unreadable {
+            assert!(
+                check_compatibility(&writer, &reader, CompatibilityMode::Backward).is_err(),
+                "{:?} -> {:?}",
+                writer,
+                reader
+            );
+            assert!(value.resolve(&reader).is_err());
+        }
+
+        assert_eq!(
+            check_compatibility(&fixed(12), &duration, CompatibilityMode::Full),
+            Err(vec![IncompatibleChange::TypeChanged {
+                field: "".to_owned(),
+                from: fixed(12),
+                to: duration,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_nested_records() {
+        let old = record(
+            r#"{"name": "a", "type": {"type": "record", "name": "inner", "fields": [
+                {"name": "b", "type": "int"}
+            ]}}"#,
+        );
+        let new = record(
+            r#"{"name": "a", "type": {"type": "record", "name": "inner", "fields": [
+                {"name": "b", "type": "int"},
+                {"name": "c", "type": "int"}
+            ]}}"#,
+        );
+        assert_eq!(
+            check_compatibility(&old, &new, CompatibilityMode::Backward),
+            Err(vec![IncompatibleChange::FieldAdded {
+                name: "a.c".to_owned()
+            }])
+        );
+
+        let renamed = record(
+            r#"{"name": "a", "type": {"type": "record", "name": "other", "fields": [
+                {"name": "b", "type": "int"}
+            ]}}"#,
+        );
+        assert_eq!(
+            check_compatibility(&old, &renamed, CompatibilityMode::Backward),
+            Err(vec![IncompatibleChange::NameChanged {
+                from: "inner".to_owned(),
+                to: "other".to_owned(),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_enum_symbols() {
+        let old =
+            Schema::parse_str(r#"{"type": "enum", "name": "e", "symbols": ["A", "B"]}"#).unwrap();
+        let new =
+            Schema::parse_str(r#"{"type": "enum", "name": "e", "symbols": ["A", "C"]}"#).unwrap();
+
+        assert_eq!(
+            check_compatibility(&old, &new, CompatibilityMode::Full),
+            Err(vec![
+                IncompatibleChange::SymbolRemoved {
+                    name: "e".to_owned(),
+                    symbol: "B".to_owned(),
+                },
+                IncompatibleChange::SymbolAdded {
+                    name: "e".to_owned(),
+                    symbol: "C".to_owned(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_unions() {
+        let old = Schema::parse_str(r#"["null", "int"]"#).unwrap();
+        let new = Schema::parse_str(r#"["null", "long", "string"]"#).unwrap();
+
+        assert_eq!(
+            check_compatibility(&old, &new, CompatibilityMode::Backward),
+            Ok(())
+        );
+        assert_eq!(
+            check_compatibility(&Schema::Int, &new, CompatibilityMode::Backward),
+            Ok(())
+        );
+        assert_eq!(
+            check_compatibility(&old, &new, CompatibilityMode::Forward),
+            Err(vec![
+                IncompatibleChange::TypeChanged {
+                    field: "".to_owned(),
+                    from: old.clone(),
+                    to: Schema::Long,
+                },
+                IncompatibleChange::TypeChanged {
+                    field: "".to_owned(),
+                    from: old.clone(),
+                    to: Schema::String,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_history() {
+        let v1 = record(r#"{"name": "a", "type": "int"}"#);
+        let v2 = record(
+            r#"{"name": "a", "type": "int"},
+               {"name": "b", "type": "int", "default": 0}"#,
+        );
+        let v3 = record(r#"{"name": "b", "type": "int", "default": 0}"#);
+        let history = vec![v1, v2];
+
+        assert_eq!(
+            check_compatibility_with_history(&history, &v3, CompatibilityMode::Forward),
+            Err(vec![IncompatibleChange::FieldRemoved {
+                name: "a".to_owned()
+            }])
+        );
+        assert_eq!(
+            check_compatibility_with_history(&history, &v3, CompatibilityMode::Backward),
+            Ok(())
+        );
+        assert_eq!(
+            check_compatibility_with_history(&[], &v3, CompatibilityMode::FullTransitive),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Logic handling the wire format of the
+//! [Confluent Schema Registry](https://docs.confluent.io/current/schema-registry/serializer-formatter.html#wire-format),
+//! as used by Kafka producers and consumers.
+//!
+//! Each datum is prefixed by a zero magic byte and by the ID of its schema in the registry, as a
+//! four-byte big-endian integer:
+//!
+//! ```
+//! use avro_rs::confluent::{decode_confluent, encode_confluent, InMemorySchemaRegistry};
+//! use avro_rs::{types::Value, Schema};
+//!
+//! let mut registry = InMemorySchemaRegistry::new();
+//! let id = registry.register(Schema::Long);
+//!
+//! let bytes = encode_confluent(id, &Value::Long(42), &Schema::Long).unwrap();
+//! assert_eq!(bytes, vec![0, 0, 0, 0, 1, 84]);
+//! assert_eq!(
+//!     decode_confluent(&bytes, |id| registry.get(id)).unwrap(),
+//!     (id, Value::Long(42))
+//! );
+//! ```
+use std::fmt;
+use std::sync::Arc;
+
+use failure::Error;
+
+use crate::decode::decode;
+use crate::schema::Schema;
+use crate::types::Value;
+use crate::util::DecodeError;
+use crate::writer::write_value_ref;
+
+/// Magic byte starting any datum written in the Confluent wire format.
+const CONFLUENT_MAGIC: u8 = 0;
+
+/// The ID of a schema in a schema registry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SchemaId(pub u32);
+
+impl fmt::Display for SchemaId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Encode `value` in the Confluent wire format, given its `schema` and the ID of that schema
+/// in the registry, also performing schema validation.
+pub fn encode_confluent(
+    schema_id: SchemaId,
+    value: &Value,
+    schema: &Schema,
+) -> Result<Vec<u8>, Error> {
+    let mut buffer = vec![CONFLUENT_MAGIC];
+    buffer.extend_from_slice(&schema_id.0.to_be_bytes());
+    write_value_ref(schema, value, &mut buffer)?;
+    Ok(buffer)
+}
+
+/// Decode a value written in the Confluent wire format, returning it along with the ID of its
+/// schema.
+///
+/// The schema is looked up by ID with `lookup`, e.g. from a local cache or by fetching it from a
+/// registry: an error is returned if it is not found.
+pub fn decode_confluent<F>(bytes: &[u8], lookup: F) -> Result<(SchemaId, Value), Error>
+where
+    F: Fn(SchemaId) -> Option<Arc<Schema>>,
+{
+    if bytes.len() < 5 {
+        return Err(DecodeError::new("Confluent datum is too short").into());
+    }
+    if bytes[0] != CONFLUENT_MAGIC {
+        return Err(
+            DecodeError::new(format!("wrong magic byte {} in Confluent datum", bytes[0])).into(),
+        );
+    }
+
+    let mut id = [0u8; 4];
+    id.copy_from_slice(&bytes[1..5]);
+    let schema_id = SchemaId(u32::from_be_bytes(id));
+    let schema = lookup(schema_id)
+        .ok_or_else(|| DecodeError::new(format!("unknown schema ID {}", schema_id)))?;
+
+    let mut datum = &bytes[5..];
+    Ok((schema_id, decode(&schema, &mut datum)?))
+}
+
+/// A schema registry held in memory, e.g. for testing.
+///
+/// IDs are assigned sequentially from 1, and registering an already registered schema returns
+/// its existing ID.
+#[derive(Clone, Debug, Default)]
+pub struct InMemorySchemaRegistry {
+    schemas: Vec<Arc<Schema>>,
+}
+
+impl InMemorySchemaRegistry {
+    /// Create an empty `InMemorySchemaRegistry`.
+    pub fn new() -> InMemorySchemaRegistry {
+        InMemorySchemaRegistry::default()
+    }
+
+    /// Register `schema`, returning its ID.
+    pub fn register(&mut self, schema: Schema) -> SchemaId {
+        let index = match self.schemas.iter().position(|known| **known == schema) {
+            Some(index) => index,
+            None => {
+                self.schemas.push(Arc::new(schema));
+                self.schemas.len() - 1
+            }
+        };
+        SchemaId(index as u32 + 1)
+    }
+
+    /// Get the schema registered with `id`, if any.
+    pub fn get(&self, id: SchemaId) -> Option<Arc<Schema>> {
+        (id.0 as usize)
+            .checked_sub(1)
+            .and_then(|index| self.schemas.get(index))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Record, ToAvro};
+
+    #[test]
+    fn test_confluent_round_trip() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "test",
+                "fields": [
+                    {"name": "a", "type": "long"},
+                    {"name": "b", "type": "string"}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+        let mut registry = InMemorySchemaRegistry::new();
+        assert_eq!(registry.register(Schema::String), SchemaId(1));
+        let id = registry.register(schema.clone());
+        assert_eq!(id, SchemaId(2));
+        assert_eq!(registry.register(schema.clone()), id);
+
+        let mut record = Record::new(&schema).unwrap();
+        record.put("a", 27i64);
+        record.put("b", "foo");
+        let value = record.avro();
+
+        let bytes = encode_confluent(SchemaId(0x0102_0304), &value, &schema).unwrap();
+        assert_eq!(&bytes[..5], &[0, 1, 2, 3, 4]);
+        assert_eq!(&bytes[5..], &[54, 6, 102, 111, 111]);
+
+        let bytes = encode_confluent(id, &value, &schema).unwrap();
+        assert_eq!(
+            decode_confluent(&bytes, |id| registry.get(id)).unwrap(),
+            (id, value)
+        );
+        assert!(encode_confluent(id, &Value::Long(1), &schema).is_err());
+    }
+
+    #[test]
+    fn test_confluent_invalid() {
+        let mut registry = InMemorySchemaRegistry::new();
+        let id = registry.register(Schema::Long);
+        assert_eq!(registry.get(SchemaId(0)), None);
+        assert_eq!(registry.get(SchemaId(2)), None);
+
+        let lookup = |id| registry.get(id);
+        assert!(decode_confluent(&[0, 0, 0, 1], lookup).is_err());
+        assert!(decode_confluent(&[1, 0, 0, 0, 1, 84], lookup).is_err());
+        assert!(decode_confluent(&[0, 0, 0, 0, 2, 84], lookup).is_err());
+        // truncated datum
+        assert!(decode_confluent(&[0, 0, 0, 0, 1], lookup).is_err());
+        assert_eq!(
+            decode_confluent(&[0, 0, 0, 0, 1, 84], lookup).unwrap(),
+            (id, Value::Long(42))
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Logic handling the reading and writing of single datums, as plugged into `Reader` and
+//! `Writer`.
+use std::any::Any;
+use std::io::{Read, Write};
+
+use failure::{err_msg, Error};
+
+use crate::decode::decode;
+use crate::schema::Schema;
+use crate::types::Value;
+use crate::writer::write_value_ref;
+
+/// A strategy to read a datum out of its Avro encoding, into any type.
+///
+/// Plug it into a [`Reader`](struct.Reader.html) with
+/// [`set_datum_reader`](struct.Reader.html#method.set_datum_reader) to read its datums with
+/// [`read_datum`](struct.Reader.html#method.read_datum).
+pub trait DatumReader: Send + Sync {
+    /// Read a datum written with `writer_schema` from `reader`, as expected by `reader_schema`.
+    ///
+    /// When no reader schema is set, `reader_schema` is the same as `writer_schema`.
+    fn read(
+        &self,
+        writer_schema: &Schema,
+        reader_schema: &Schema,
+        reader: &mut dyn Read,
+    ) -> Result<Box<dyn Any>, Error>;
+}
+
+/// A strategy to write a datum of any type in its Avro encoding.
+///
+/// Plug it into a [`Writer`](struct.Writer.html) with
+/// [`set_datum_writer`](struct.Writer.html#method.set_datum_writer) to write datums with
+/// [`append_datum`](struct.Writer.html#method.append_datum).
+pub trait DatumWriter: Send + Sync {
+    /// Write `datum` to `writer`, following `schema`.
+    fn write(&self, schema: &Schema, datum: &dyn Any, writer: &mut dyn Write) -> Result<(), Error>;
+}
+
+/// A `DatumReader` reading datums as `Value`, performing schema resolution.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GenericDatumReader;
+
+impl DatumReader for GenericDatumReader {
+    fn read(
+        &self,
+        writer_schema: &Schema,
+        reader_schema: &Schema,
+        mut reader: &mut dyn Read,
+    ) -> Result<Box<dyn Any>, Error> {
+        let value = decode(writer_schema, &mut reader)?;
+        let value = if writer_schema == reader_schema {
+            value
+        } else {
+            value.resolve(reader_schema)?
+        };
+        Ok(Box::new(value))
+    }
+}
+
+/// A `DatumWriter` writing `Value` datums, performing schema validation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GenericDatumWriter;
+
+impl DatumWriter for GenericDatumWriter {
+    fn write(&self, schema: &Schema, datum: &dyn Any, writer: &mut dyn Write) -> Result<(), Error> {
+        let value = datum
+            .downcast_ref::<Value>()
+            .ok_or_else(|| err_msg("GenericDatumWriter only writes Value datums"))?;
+        let mut buffer = Vec::new();
+        write_value_ref(schema, value, &mut buffer)?;
+        writer.write_all(&buffer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::de::from_value;
+    use crate::ser::to_value;
+    use crate::{Reader, Writer};
+    use serde::{Deserialize, Serialize};
+
+    static SCHEMA: &str = r#"
+        {
+            "type": "record",
+            "name": "Test",
+            "fields": [
+                {"name": "a", "type": "long"},
+                {"name": "b", "type": "string"}
+            ]
+        }
+    "#;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Test {
+        a: i64,
+        b: String,
+    }
+
+    /// Reads and writes `Test` datums through serde.
+    struct TestDatum;
+
+    impl DatumReader for TestDatum {
+        fn read(
+            &self,
+            writer_schema: &Schema,
+            reader_schema: &Schema,
+            reader: &mut dyn Read,
+        ) -> Result<Box<dyn Any>, Error> {
+            let value = GenericDatumReader.read(writer_schema, reader_schema, reader)?;
+            let value = value.downcast::<Value>().unwrap();
+            Ok(Box::new(from_value::<Test>(&value)?))
+        }
+    }
+
+    impl DatumWriter for TestDatum {
+        fn write(
+            &self,
+            schema: &Schema,
+            datum: &dyn Any,
+            writer: &mut dyn Write,
+        ) -> Result<(), Error> {
+            let test = datum
+                .downcast_ref::<Test>()
+                .ok_or_else(|| err_msg("not a Test"))?;
+            GenericDatumWriter.write(schema, &to_value(test)?, writer)
+        }
+    }
+
+    #[test]
+    fn test_generic_datum() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let value = Value::Record(vec![
+            ("a".to_string(), Value::Long(27)),
+            ("b".to_string(), Value::String("foo".to_string())),
+        ]);
+
+        let mut buffer = Vec::new();
+        GenericDatumWriter
+            .write(&schema, &value, &mut buffer)
+            .unwrap();
+        assert_eq!(buffer, vec![54, 6, 102, 111, 111]);
+        assert!(GenericDatumWriter
+            .write(&schema, &Value::Long(1), &mut buffer)
+            .is_err());
+        assert!(GenericDatumWriter
+            .write(&schema, &1i64, &mut buffer)
+            .is_err());
+
+        let datum = GenericDatumReader
+            .read(&schema, &schema, &mut &buffer[..])
+            .unwrap();
+        assert_eq!(datum.downcast_ref::<Value>(), Some(&value));
+    }
+
+    #[test]
+    fn test_custom_datum() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let test = Test {
+            a: 27,
+            b: "foo".to_string(),
+        };
+
+        let mut writer = Writer::new(&schema, Vec::new());
+        writer.set_datum_writer(Box::new(TestDatum));
+        writer.append_datum(&test).unwrap();
+        assert!(writer.append_datum(&Value::Long(1)).is_err());
+        writer.append_datum(&test).unwrap();
+        writer.flush().unwrap();
+        let input = writer.into_inner();
+
+        let mut reader = Reader::new(&input[..]).unwrap();
+        reader.set_datum_reader(Box::new(TestDatum));
+        for _ in 0..2 {
+            let datum = reader.read_datum().unwrap().unwrap();
+            assert_eq!(datum.downcast_ref::<Test>(), Some(&test));
+        }
+        assert!(reader.read_datum().is_none());
+
+        // the default datum reader reads values
+        let mut reader = Reader::new(&input[..]).unwrap();
+        let datum = reader.read_datum().unwrap().unwrap();
+        assert_eq!(
+            datum
+                .downcast::<Value>()
+                .unwrap()
+                .as_record()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}
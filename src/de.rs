@@ -1,14 +1,12 @@
 //! Logic for serde-compatible deserialization.
-use std::collections::{
-    hash_map::{Keys, Values},
-    HashMap,
-};
+use std::collections::{HashMap, HashSet};
 use std::error::{self, Error as StdError};
 use std::fmt;
 use std::slice::Iter;
+use std::vec::IntoIter;
 
 use serde::{
-    de::{self, DeserializeSeed, Error as SerdeError, Visitor},
+    de::{self, DeserializeOwned, DeserializeSeed, Error as SerdeError, Visitor},
     forward_to_deserialize_any, Deserialize,
 };
 
@@ -48,13 +46,20 @@ struct SeqDeserializer<'de> {
 }
 
 struct MapDeserializer<'de> {
-    input_keys: Keys<'de, String, Value>,
-    input_values: Values<'de, String, Value>,
+    input: IntoIter<(&'de String, &'de Value)>,
+    value: Option<&'de Value>,
 }
 
 struct StructDeserializer<'de> {
     input: Iter<'de, (String, Value)>,
     value: Option<&'de Value>,
+    // Names of the fields visited so far, to reject duplicate fields.
+    seen: HashSet<&'de str>,
+}
+
+struct EnumDeserializer<'de> {
+    input: &'de str,
+    value: &'de Value,
 }
 
 impl<'de> Deserializer<'de> {
@@ -73,11 +78,12 @@ impl<'de> SeqDeserializer<'de> {
 
 impl<'de> MapDeserializer<'de> {
     pub fn new(input: &'de HashMap<String, Value>) -> Self {
+        // Entries are visited in key order, as the iteration order of a `HashMap` is arbitrary.
+        let mut entries = input.iter().collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
         MapDeserializer {
-            input_keys: input.keys(), // input.keys().map(|k| Value::String(k.clone())).collect::<Vec<_>>().iter(),
-            input_values: input.values(),
-            // keys: input.keys().map(|s| Value::String(s.to_owned())).collect::<Vec<Value>>(),
-            // values: input.values().map(|s| s.to_owned()).collect::<Vec<Value>>(),
+            input: entries.into_iter(),
+            value: None,
         }
     }
 }
@@ -87,10 +93,17 @@ impl<'de> StructDeserializer<'de> {
         StructDeserializer {
             input: input.iter(),
             value: None,
+            seen: HashSet::with_capacity(input.len()),
         }
     }
 }
 
+impl<'de> EnumDeserializer<'de> {
+    pub fn new(input: &'de str, value: &'de Value) -> Self {
+        EnumDeserializer { input, value }
+    }
+}
+
 impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     type Error = Error;
 
@@ -101,11 +114,23 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         match *self.input {
             Value::Null => visitor.visit_unit(),
             Value::Boolean(b) => visitor.visit_bool(b),
-            Value::Int(i) => visitor.visit_i32(i),
-            Value::Long(i) => visitor.visit_i64(i),
+            Value::Int(i) | Value::Date(i) | Value::TimeMillis(i) => visitor.visit_i32(i),
+            Value::Long(i)
+            | Value::TimeMicros(i)
+            | Value::TimestampMillis(i)
+            | Value::TimestampMicros(i) => visitor.visit_i64(i),
             Value::Float(x) => visitor.visit_f32(x),
             Value::Double(x) => visitor.visit_f64(x),
-            _ => Err(Error::custom("incorrect value")),
+            Value::String(ref s) | Value::Enum(_, ref s) => visitor.visit_str(s),
+            #[cfg(feature = "uuid")]
+            Value::Uuid(uuid) => visitor.visit_string(uuid.to_string()),
+            Value::Bytes(ref bytes) | Value::Fixed(_, ref bytes) => visitor.visit_bytes(bytes),
+            Value::Decimal(ref decimal) => visitor.visit_bytes(&decimal.to_vec()),
+            Value::Duration(duration) => visitor.visit_bytes(&<[u8; 12]>::from(duration)),
+            Value::Union(ref inner) => Deserializer::new(inner).deserialize_any(visitor),
+            Value::Array(ref items) => visitor.visit_seq(SeqDeserializer::new(items)),
+            Value::Map(ref items) => visitor.visit_map(MapDeserializer::new(items)),
+            Value::Record(ref fields) => visitor.visit_map(StructDeserializer::new(fields)),
         }
     }
 
@@ -126,6 +151,8 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     {
         match *self.input {
             Value::String(ref s) => visitor.visit_str(s),
+            #[cfg(feature = "uuid")]
+            Value::Uuid(uuid) => visitor.visit_str(&uuid.to_string()),
             Value::Bytes(ref bytes) | Value::Fixed(_, ref bytes) => ::std::str::from_utf8(bytes)
                 .map_err(|e| Error::custom(e.description()))
                 .and_then(|s| visitor.visit_str(s)),
@@ -139,6 +166,8 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     {
         match *self.input {
             Value::String(ref s) => visitor.visit_string(s.to_owned()),
+            #[cfg(feature = "uuid")]
+            Value::Uuid(uuid) => visitor.visit_string(uuid.to_string()),
             Value::Bytes(ref bytes) | Value::Fixed(_, ref bytes) => {
                 String::from_utf8(bytes.to_owned())
                     .map_err(|e| Error::custom(e.description()))
@@ -155,7 +184,8 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         match *self.input {
             Value::String(ref s) => visitor.visit_bytes(s.as_bytes()),
             Value::Bytes(ref bytes) | Value::Fixed(_, ref bytes) => visitor.visit_bytes(bytes),
-            _ => Err(Error::custom("not a string|bytes|fixed")),
+            Value::Decimal(ref decimal) => visitor.visit_bytes(&decimal.to_vec()),
+            _ => Err(Error::custom("not a string|bytes|fixed|decimal")),
         }
     }
 
@@ -168,7 +198,8 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             Value::Bytes(ref bytes) | Value::Fixed(_, ref bytes) => {
                 visitor.visit_byte_buf(bytes.to_owned())
             }
-            _ => Err(Error::custom("not a string|bytes|fixed")),
+            Value::Decimal(ref decimal) => visitor.visit_byte_buf(decimal.to_vec()),
+            _ => Err(Error::custom("not a string|bytes|fixed|decimal")),
         }
     }
 
@@ -250,6 +281,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     {
         match *self.input {
             Value::Map(ref items) => visitor.visit_map(MapDeserializer::new(items)),
+            Value::Record(ref fields) => visitor.visit_map(StructDeserializer::new(fields)),
             _ => Err(Error::custom("not a map")),
         }
     }
@@ -273,13 +305,15 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         self,
         _: &'static str,
         _variants: &'static [&'static str],
-        _visitor: V,
+        visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
         match *self.input {
-            // &Value::Enum(i) => ,  TODO
+            Value::Enum(_, ref symbol) => {
+                visitor.visit_enum(EnumDeserializer::new(symbol, &Value::Null))
+            }
             _ => Err(Error::custom("not an enum")),
         }
     }
@@ -311,6 +345,10 @@ impl<'de> de::SeqAccess<'de> for SeqDeserializer<'de> {
             None => Ok(None),
         }
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.input.len())
+    }
 }
 
 impl<'de> de::MapAccess<'de> for MapDeserializer<'de> {
@@ -320,12 +358,12 @@ impl<'de> de::MapAccess<'de> for MapDeserializer<'de> {
     where
         K: DeserializeSeed<'de>,
     {
-        match self.input_keys.next() {
-            Some(ref key) => seed
-                .deserialize(StringDeserializer {
-                    input: (*key).clone(),
-                })
-                .map(Some),
+        match self.input.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(StringDeserializer { input: key.clone() })
+                    .map(Some)
+            }
             None => Ok(None),
         }
     }
@@ -334,11 +372,15 @@ impl<'de> de::MapAccess<'de> for MapDeserializer<'de> {
     where
         V: DeserializeSeed<'de>,
     {
-        match self.input_values.next() {
-            Some(ref value) => seed.deserialize(&mut Deserializer::new(value)),
+        match self.value.take() {
+            Some(value) => seed.deserialize(&mut Deserializer::new(value)),
             None => Err(Error::custom("should not happen - too many values")),
         }
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.input.len())
+    }
 }
 
 impl<'de> de::MapAccess<'de> for StructDeserializer<'de> {
@@ -351,6 +393,9 @@ impl<'de> de::MapAccess<'de> for StructDeserializer<'de> {
         match self.input.next() {
             Some(item) => {
                 let (ref field, ref value) = *item;
+                if !self.seen.insert(field) {
+                    return Err(Error::custom(format!("duplicate record field: {}", field)));
+                }
                 self.value = Some(value);
                 seed.deserialize(StringDeserializer {
                     input: field.clone(),
@@ -370,6 +415,63 @@ impl<'de> de::MapAccess<'de> for StructDeserializer<'de> {
             None => Err(Error::custom("should not happen - too many values")),
         }
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.input.len())
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(StringDeserializer {
+            input: self.input.to_owned(),
+        })?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for EnumDeserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut Deserializer::new(self.value))
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_tuple(&mut Deserializer::new(self.value), len, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_struct(
+            &mut Deserializer::new(self.value),
+            "",
+            fields,
+            visitor,
+        )
+    }
 }
 
 struct StringDeserializer {
@@ -401,3 +503,350 @@ pub fn from_value<'de, D: Deserialize<'de>>(value: &'de Value) -> Result<D, Erro
     let mut de = Deserializer::new(value);
     D::deserialize(&mut de)
 }
+
+/// Interpret an owned `Value` as an instance of type `D`.
+///
+/// Unlike [`from_value`](fn.from_value.html), the value does not need to outlive the result,
+/// e.g. when it was just produced by schema resolution.
+pub fn from_value_owned<D: DeserializeOwned>(value: Value) -> Result<D, Error> {
+    from_value(&value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::from_avro_datum;
+    use crate::schema::Schema;
+    use crate::writer::to_avro_datum;
+    use serde::de::{MapAccess, SeqAccess};
+
+    fn round_trip(schema: &Schema, value: Value) -> Value {
+        let encoded = to_avro_datum(schema, value).unwrap();
+        from_avro_datum(schema, &mut encoded.as_slice(), None).unwrap()
+    }
+
+    #[test]
+    fn test_option_null_last() {
+        let schema = Schema::parse_str(r#"["string", "null"]"#).unwrap();
+
+        let some = round_trip(
+            &schema,
+            Value::Union(Box::new(Value::String("foo".to_string()))),
+        );
+        assert_eq!(
+            from_value::<Option<String>>(&some).unwrap(),
+            Some("foo".to_string())
+        );
+
+        let none = round_trip(&schema, Value::Union(Box::new(Value::Null)));
+        assert_eq!(from_value::<Option<String>>(&none).unwrap(), None);
+    }
+
+    #[test]
+    fn test_option_multi_variant_union() {
+        let schema = Schema::parse_str(r#"["int", "string", "null"]"#).unwrap();
+
+        let int = round_trip(&schema, Value::Union(Box::new(Value::Int(42))));
+        assert_eq!(from_value::<Option<i32>>(&int).unwrap(), Some(42));
+
+        let string = round_trip(
+            &schema,
+            Value::Union(Box::new(Value::String("foo".to_string()))),
+        );
+        assert_eq!(
+            from_value::<Option<String>>(&string).unwrap(),
+            Some("foo".to_string())
+        );
+
+        let none = round_trip(&schema, Value::Union(Box::new(Value::Null)));
+        assert_eq!(from_value::<Option<i32>>(&none).unwrap(), None);
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Suit {
+        #[serde(rename = "SPADES")]
+        Spades,
+        #[serde(rename = "HEARTS")]
+        Hearts,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Card {
+        suit: Suit,
+    }
+
+    #[test]
+    fn test_unit_variant_from_enum() {
+        let value = Value::Enum(1, "HEARTS".to_string());
+        assert_eq!(from_value::<Suit>(&value).unwrap(), Suit::Hearts);
+
+        let card = Value::Record(vec![(
+            "suit".to_string(),
+            Value::Enum(0, "SPADES".to_string()),
+        )]);
+        assert_eq!(
+            from_value::<Card>(&card).unwrap(),
+            Card { suit: Suit::Spades }
+        );
+    }
+
+    #[test]
+    fn test_unit_variant_unknown_symbol() {
+        let value = Value::Enum(2, "CLUBS".to_string());
+        assert!(from_value::<Suit>(&value).is_err());
+        assert!(from_value::<Suit>(&Value::Int(1)).is_err());
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Payload {
+        Empty(()),
+        Pair(i32, i32),
+        Named { a: i32 },
+    }
+
+    #[test]
+    fn test_variant_payload_errors() {
+        let empty = Value::Enum(0, "Empty".to_string());
+        assert_eq!(from_value::<Payload>(&empty).unwrap(), Payload::Empty(()));
+
+        // Avro enums carry no payload: this must fail instead of panicking.
+        let pair = Value::Enum(1, "Pair".to_string());
+        assert!(from_value::<Payload>(&pair).is_err());
+        let named = Value::Enum(2, "Named".to_string());
+        assert!(from_value::<Payload>(&named).is_err());
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Test {
+        a: i64,
+        b: String,
+    }
+
+    #[test]
+    fn test_from_value_owned() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "Test",
+                "fields": [
+                    {"name": "a", "type": "long", "default": 42},
+                    {"name": "b", "type": "string"}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+        let value = Value::Record(vec![("b".to_string(), Value::String("foo".to_string()))]);
+
+        let test: Test = from_value_owned(value.resolve(&schema).unwrap()).unwrap();
+        assert_eq!(
+            test,
+            Test {
+                a: 42,
+                b: "foo".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_value_any() {
+        let mut items = HashMap::new();
+        items.insert("a".to_string(), Value::Array(vec![Value::Long(1)]));
+        let value = Value::Array(vec![
+            Value::String("foo".to_string()),
+            Value::Map(items.clone()),
+            Value::Union(Box::new(Value::Double(1.5))),
+        ]);
+        assert_eq!(
+            from_value::<Value>(&value).unwrap(),
+            Value::Array(vec![
+                Value::String("foo".to_string()),
+                Value::Map(items),
+                Value::Double(1.5),
+            ])
+        );
+
+        let record = Value::Record(vec![
+            ("a".to_string(), Value::Enum(0, "A".to_string())),
+            ("b".to_string(), Value::Bytes(vec![1, 2])),
+        ]);
+        let map = from_value::<HashMap<String, Value>>(&record).unwrap();
+        assert_eq!(map["a"], Value::String("A".to_string()));
+        assert_eq!(map["b"], Value::Bytes(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_from_value_fields_in_any_order() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Reordered {
+            c: Option<i64>,
+            b: String,
+            a: i64,
+        }
+
+        // fields of the record in another order than declared by the struct
+        let value = Value::Record(vec![
+            ("a".to_string(), Value::Long(1)),
+            ("b".to_string(), Value::String("foo".to_string())),
+            ("c".to_string(), Value::Union(Box::new(Value::Long(3)))),
+        ]);
+        let expected = Reordered {
+            c: Some(3),
+            b: "foo".to_string(),
+            a: 1,
+        };
+        assert_eq!(from_value::<Reordered>(&value).unwrap(), expected);
+
+        let value = Value::Record(vec![
+            ("b".to_string(), Value::String("foo".to_string())),
+            ("c".to_string(), Value::Union(Box::new(Value::Long(3)))),
+            ("a".to_string(), Value::Long(1)),
+        ]);
+        assert_eq!(from_value::<Reordered>(&value).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_from_value_duplicate_fields() {
+        let value = Value::Record(vec![
+            ("a".to_string(), Value::Long(1)),
+            ("b".to_string(), Value::String("foo".to_string())),
+            ("a".to_string(), Value::Long(2)),
+        ]);
+
+        let error = from_value::<Test>(&value).unwrap_err();
+        assert_eq!(error.to_string(), "duplicate record field: a");
+        // serde would otherwise keep the last value for maps
+        assert!(from_value::<HashMap<String, Value>>(&value).is_err());
+    }
+
+    /// Entries of a map, in the order they are visited.
+    #[derive(Debug, PartialEq)]
+    struct Entries(Vec<(String, i64)>);
+
+    impl<'de> Deserialize<'de> for Entries {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            struct EntriesVisitor;
+
+            impl<'de> Visitor<'de> for EntriesVisitor {
+                type Value = Entries;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a map")
+                }
+
+                fn visit_map<A>(self, mut map: A) -> Result<Entries, A::Error>
+                where
+                    A: de::MapAccess<'de>,
+                {
+                    let mut entries = Vec::new();
+                    while let Some(entry) = map.next_entry()? {
+                        entries.push(entry);
+                    }
+                    Ok(Entries(entries))
+                }
+            }
+
+            deserializer.deserialize_map(EntriesVisitor)
+        }
+    }
+
+    #[test]
+    fn test_map_in_key_order() {
+        let mut items = HashMap::new();
+        for i in (0..16).rev() {
+            items.insert(format!("{:02}", i), Value::Long(i));
+        }
+
+        let entries = from_value::<Entries>(&Value::Map(items)).unwrap();
+        assert_eq!(
+            entries,
+            Entries((0..16).map(|i| (format!("{:02}", i), i)).collect())
+        );
+    }
+
+    #[test]
+    fn test_map_keys_paired_with_values() {
+        let mut items = HashMap::new();
+        let mut expected = HashMap::new();
+        for i in 0..64 {
+            items.insert(format!("key{}", i), Value::Long(i * 10));
+            expected.insert(format!("key{}", i), i * 10);
+        }
+
+        let map = from_value::<HashMap<String, i64>>(&Value::Map(items)).unwrap();
+        assert_eq!(map, expected);
+    }
+
+    #[test]
+    fn test_option_not_a_union() {
+        assert!(from_value::<Option<i32>>(&Value::Int(42)).is_err());
+    }
+
+    /// The size hints given when deserializing a sequence or a map, item after item.
+    #[derive(Debug, PartialEq)]
+    struct SizeHints(Vec<Option<usize>>);
+
+    impl<'de> Deserialize<'de> for SizeHints {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct SizeHintsVisitor;
+
+            impl<'de> Visitor<'de> for SizeHintsVisitor {
+                type Value = SizeHints;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a sequence or a map")
+                }
+
+                fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<SizeHints, A::Error> {
+                    let mut hints = vec![seq.size_hint()];
+                    while seq.next_element::<Value>()?.is_some() {
+                        hints.push(seq.size_hint());
+                    }
+                    Ok(SizeHints(hints))
+                }
+
+                fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<SizeHints, A::Error> {
+                    let mut hints = vec![map.size_hint()];
+                    while map.next_entry::<String, Value>()?.is_some() {
+                        hints.push(map.size_hint());
+                    }
+                    Ok(SizeHints(hints))
+                }
+            }
+
+            deserializer.deserialize_any(SizeHintsVisitor)
+        }
+    }
+
+    #[test]
+    fn test_size_hints() {
+        let array = Value::Array((0..1000).map(Value::Int).collect());
+        let hints = from_value::<SizeHints>(&array).unwrap().0;
+        assert_eq!(hints.len(), 1001);
+        assert_eq!(hints[0], Some(1000));
+        assert_eq!(hints[1], Some(999));
+        assert_eq!(hints[1000], Some(0));
+
+        let map = Value::Map(
+            vec![
+                ("a".to_string(), Value::Null),
+                ("b".to_string(), Value::Null),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(
+            from_value::<SizeHints>(&map).unwrap(),
+            SizeHints(vec![Some(2), Some(1), Some(0)])
+        );
+        let record = Value::Record(vec![("a".to_string(), Value::Null)]);
+        assert_eq!(
+            from_value::<SizeHints>(&record).unwrap(),
+            SizeHints(vec![Some(1), Some(0)])
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Logic handling the `decimal` logical type.
+use std::fmt;
+
+use num_bigint::{BigInt, Sign};
+use serde::{
+    de::{self, Deserialize, Deserializer, Visitor},
+    ser::{Serialize, Serializer},
+};
+
+/// Represents an Avro `decimal` value.
+///
+/// A `Decimal` only holds the unscaled integer value of the decimal number. Its `scale` and
+/// `precision` are properties of the `Schema::Decimal` it is written or read with.
+///
+/// More information about decimals can be found in the
+/// [Avro specification](https://avro.apache.org/docs/current/spec.html#Decimal)
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Decimal {
+    value: BigInt,
+}
+
+impl Decimal {
+    /// Return the number of decimal digits of the unscaled value, ignoring its sign.
+    pub fn num_digits(&self) -> usize {
+        if self.value.sign() == Sign::NoSign {
+            return 1;
+        }
+        self.value.to_str_radix(10).trim_start_matches('-').len()
+    }
+
+    /// Return the minimal two's-complement big-endian representation of the unscaled value.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.value.to_signed_bytes_be()
+    }
+
+    /// Return an upper bound of the length of [`to_vec`](#method.to_vec), without allocating.
+    pub(crate) fn max_len(&self) -> usize {
+        self.value.bits() / 8 + 1
+    }
+
+    /// Return the two's-complement big-endian representation of the unscaled value, sign-extended
+    /// to exactly `len` bytes, as required by `fixed`-backed decimals.
+    ///
+    /// Return `None` if the value does not fit in `len` bytes.
+    pub fn to_sign_extended_vec(&self, len: usize) -> Option<Vec<u8>> {
+        let bytes = self.to_vec();
+        if bytes.len() > len {
+            return None;
+        }
+
+        let fill = if self.value.sign() == Sign::Minus {
+            0xFF
+        } else {
+            0x00
+        };
+        let mut extended = vec![fill; len - bytes.len()];
+        extended.extend_from_slice(&bytes);
+        Some(extended)
+    }
+}
+
+/// Return the maximum number of decimal digits that a two's-complement number stored in `len`
+/// bytes can hold.
+pub(crate) fn max_precision_for_len(len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    ((8 * len - 1) as f64 * 2f64.log10()).floor() as usize
+}
+
+impl From<BigInt> for Decimal {
+    fn from(value: BigInt) -> Self {
+        Decimal { value }
+    }
+}
+
+impl<'a> From<&'a [u8]> for Decimal {
+    /// Create a `Decimal` from the two's-complement big-endian representation of its unscaled
+    /// value.
+    fn from(bytes: &'a [u8]) -> Self {
+        Decimal {
+            value: BigInt::from_signed_bytes_be(bytes),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Decimal {
+    fn from(bytes: Vec<u8>) -> Self {
+        Decimal::from(&bytes[..])
+    }
+}
+
+impl From<Decimal> for BigInt {
+    fn from(decimal: Decimal) -> Self {
+        decimal.value
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_vec())
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DecimalVisitor;
+
+        impl<'de> Visitor<'de> for DecimalVisitor {
+            type Value = Decimal;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("two's-complement big-endian bytes")
+            }
+
+            fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Decimal, E> {
+                Ok(Decimal::from(bytes))
+            }
+        }
+
+        deserializer.deserialize_bytes(DecimalVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_from_bytes() {
+        assert_eq!(Decimal::from(vec![0x00, 0x7B]).to_string(), "123");
+        assert_eq!(Decimal::from(vec![0xFF, 0x85]).to_string(), "-123");
+        assert_eq!(Decimal::from(vec![0x7B]).to_vec(), vec![0x7B]);
+    }
+
+    #[test]
+    fn test_decimal_sign_extension() {
+        let positive = Decimal::from(BigInt::from(123));
+        assert_eq!(positive.to_sign_extended_vec(4), Some(vec![0, 0, 0, 0x7B]));
+
+        let negative = Decimal::from(BigInt::from(-123));
+        assert_eq!(
+            negative.to_sign_extended_vec(4),
+            Some(vec![0xFF, 0xFF, 0xFF, 0x85])
+        );
+
+        let large = Decimal::from(BigInt::from(1_000_000));
+        assert_eq!(large.to_sign_extended_vec(2), None);
+    }
+
+    #[test]
+    fn test_decimal_num_digits() {
+        assert_eq!(Decimal::from(BigInt::from(0)).num_digits(), 1);
+        assert_eq!(Decimal::from(BigInt::from(-99_999)).num_digits(), 5);
+        assert_eq!(Decimal::from(BigInt::from(123_456)).num_digits(), 6);
+    }
+
+    #[test]
+    fn test_max_precision_for_len() {
+        assert_eq!(max_precision_for_len(1), 2);
+        assert_eq!(max_precision_for_len(4), 9);
+        assert_eq!(max_precision_for_len(8), 18);
+        assert_eq!(max_precision_for_len(16), 38);
+    }
+
+    #[test]
+    fn test_decimal_serde_round_trip() {
+        use crate::{from_value, to_value, types::Value};
+
+        let decimal = Decimal::from(BigInt::from(-1_234_567));
+        let value = to_value(decimal.clone()).unwrap();
+        assert_eq!(value, Value::Bytes(decimal.to_vec()));
+        assert_eq!(from_value::<Decimal>(&value).unwrap(), decimal);
+        assert_eq!(
+            from_value::<Decimal>(&Value::Decimal(decimal.clone())).unwrap(),
+            decimal
+        );
+    }
+}
@@ -1,11 +1,13 @@
 use std::collections::HashMap;
 use std::io::Read;
-use std::mem::transmute;
+use std::mem::{self, transmute};
 
 use failure::Error;
 
-use crate::schema::Schema;
-use crate::types::Value;
+use crate::decimal::Decimal;
+use crate::duration::Duration;
+use crate::schema::{resolve_ref, Definitions, Schema};
+use crate::types::{uuid_value, Value};
 use crate::util::{safe_len, zag_i32, zag_i64, DecodeError};
 
 #[inline]
@@ -20,11 +22,71 @@ fn decode_int<R: Read>(reader: &mut R) -> Result<Value, Error> {
 
 #[inline]
 fn decode_len<R: Read>(reader: &mut R) -> Result<usize, Error> {
-    zag_i64(reader).and_then(|len| safe_len(len as usize))
+    let len = zag_i64(reader)?;
+    if len < 0 {
+        return Err(DecodeError::new(format!("negative length: {}", len)).into());
+    }
+    safe_len(len as usize)
+}
+
+/// Decode the item count of an array or map block.
+///
+/// A negative count is followed by the size of the block in bytes, which is not needed as the
+/// items are decoded anyway.
+fn decode_block_len<R: Read>(reader: &mut R) -> Result<usize, Error> {
+    let len = zag_i64(reader)?;
+    if len < 0 {
+        let size = zag_i64(reader)?;
+        if size < 0 {
+            return Err(DecodeError::new(format!("invalid block size {}", size)).into());
+        }
+    }
+    let len = len
+        .checked_abs()
+        .ok_or_else(|| DecodeError::new("block count out of range"))?;
+    safe_len(len as usize)
+}
+
+fn decode_seq<R: Read>(reader: &mut R, len: usize) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![0u8; len];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn decode_string<R: Read>(reader: &mut R) -> Result<String, Error> {
+    let len = decode_len(reader)?;
+    let buf = decode_seq(reader, len)?;
+
+    String::from_utf8(buf).map_err(|_| DecodeError::new("not a valid utf-8 string").into())
 }
 
 /// Decode a `Value` from avro format given its `Schema`.
 pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> Result<Value, Error> {
+    decode_internal(schema, &mut Definitions::new(schema), reader)
+}
+
+/// Decode a `Value` from avro format given its `Schema`, out of `bytes` holding exactly one
+/// datum.
+///
+/// Bytes left over after the datum are an error, as they mean that `bytes` were not written
+/// with `schema`. Use [`from_avro_datum`](fn.from_avro_datum.html) to decode a datum followed by
+/// other data instead.
+pub fn decode_from_slice(schema: &Schema, bytes: &[u8]) -> Result<Value, Error> {
+    let mut reader = bytes;
+    let value = decode(schema, &mut reader)?;
+    if !reader.is_empty() {
+        return Err(
+            DecodeError::new(format!("{} trailing bytes after the datum", reader.len())).into(),
+        );
+    }
+    Ok(value)
+}
+
+fn decode_internal<'s, R: Read>(
+    schema: &'s Schema,
+    definitions: &mut Definitions<'s>,
+    reader: &mut R,
+) -> Result<Value, Error> {
     match *schema {
         Schema::Null => Ok(Value::Null),
         Schema::Boolean => {
@@ -39,6 +101,16 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> Result<Value, Error>
         }
         Schema::Int => decode_int(reader),
         Schema::Long => decode_long(reader),
+        Schema::Date => zag_i32(reader).map(Value::Date),
+        Schema::TimeMillis => zag_i32(reader).map(Value::TimeMillis),
+        Schema::TimeMicros => zag_i64(reader).map(Value::TimeMicros),
+        Schema::TimestampMillis => zag_i64(reader).map(Value::TimestampMillis),
+        Schema::TimestampMicros => zag_i64(reader).map(Value::TimestampMicros),
+        Schema::Duration { .. } => {
+            let mut buf = [0u8; 12];
+            reader.read_exact(&mut buf)?;
+            Ok(Value::Duration(Duration::from(buf)))
+        }
         Schema::Float => {
             let mut buf = [0u8; 4];
             reader.read_exact(&mut buf[..])?;
@@ -51,25 +123,10 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> Result<Value, Error>
         }
         Schema::Bytes => {
             let len = decode_len(reader)?;
-            let mut buf = Vec::with_capacity(len);
-            unsafe {
-                buf.set_len(len);
-            }
-            reader.read_exact(&mut buf)?;
-            Ok(Value::Bytes(buf))
-        }
-        Schema::String => {
-            let len = decode_len(reader)?;
-            let mut buf = Vec::with_capacity(len);
-            unsafe {
-                buf.set_len(len);
-            }
-            reader.read_exact(&mut buf)?;
-
-            String::from_utf8(buf)
-                .map(Value::String)
-                .map_err(|_| DecodeError::new("not a valid utf-8 string").into())
+            decode_seq(reader, len).map(Value::Bytes)
         }
+        Schema::String => decode_string(reader).map(Value::String),
+        Schema::Uuid => uuid_value(decode_string(reader)?),
         Schema::Fixed { size, .. } => {
             let mut buf = vec![0u8; size as usize];
             reader.read_exact(&mut buf)?;
@@ -79,16 +136,17 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> Result<Value, Error>
             let mut items = Vec::new();
 
             loop {
-                let len = decode_len(reader)?;
+                let len = decode_block_len(reader)?;
                 // arrays are 0-terminated, 0i64 is also encoded as 0 in Avro
                 // reading a length of 0 means the end of the array
                 if len == 0 {
                     break;
                 }
 
-                items.reserve(len as usize);
+                safe_len((items.len() + len).saturating_mul(mem::size_of::<Value>()))?;
+                items.reserve(len);
                 for _ in 0..len {
-                    items.push(decode(inner, reader)?);
+                    items.push(decode_internal(inner, definitions, reader)?);
                 }
             }
 
@@ -98,17 +156,18 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> Result<Value, Error>
             let mut items = HashMap::new();
 
             loop {
-                let len = decode_len(reader)?;
+                let len = decode_block_len(reader)?;
                 // maps are 0-terminated, 0i64 is also encoded as 0 in Avro
                 // reading a length of 0 means the end of the map
                 if len == 0 {
                     break;
                 }
 
-                items.reserve(len as usize);
+                safe_len((items.len() + len).saturating_mul(mem::size_of::<(String, Value)>()))?;
+                items.reserve(len);
                 for _ in 0..len {
                     if let Value::String(key) = decode(&Schema::String, reader)? {
-                        let value = decode(inner, reader)?;
+                        let value = decode_internal(inner, definitions, reader)?;
                         items.insert(key, value);
                     } else {
                         return Err(DecodeError::new("map key is not a string").into());
@@ -121,9 +180,18 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> Result<Value, Error>
         Schema::Union(ref inner) => {
             let index = zag_i64(reader)?;
             let variants = inner.variants();
-            match variants.get(index as usize) {
-                Some(variant) => decode(variant, reader).map(|x| Value::Union(Box::new(x))),
-                None => Err(DecodeError::new("Union index out of bounds").into()),
+            let variant = if index < 0 {
+                None
+            } else {
+                variants.get(index as usize)
+            };
+            match variant {
+                Some(variant) => {
+                    decode_internal(variant, definitions, reader).map(|x| Value::Union(Box::new(x)))
+                }
+                None => {
+                    Err(DecodeError::new(format!("Union index {} out of bounds", index)).into())
+                }
             }
         }
         Schema::Record { ref fields, .. } => {
@@ -131,7 +199,10 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> Result<Value, Error>
             let mut items = Vec::new();
             for field in fields {
                 // This clone is also expensive. See if we can do away with it...
-                items.push((field.name.clone(), decode(&field.schema, reader)?));
+                items.push((
+                    field.name.clone(),
+                    decode_internal(&field.schema, definitions, reader)?,
+                ));
             }
             Ok(Value::Record(items))
             // fields
@@ -140,9 +211,19 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> Result<Value, Error>
             // .collect::<Result<Vec<(String, Value)>, _>>()
             // .map(|items| Value::Record(items))
         }
+        Schema::Ref { ref name } => {
+            let definition = resolve_ref(name, definitions)?;
+            decode_internal(definition, definitions, reader)
+        }
+        Schema::Decimal { ref inner, .. } => match decode(inner, reader)? {
+            Value::Bytes(bytes) | Value::Fixed(_, bytes) => {
+                Ok(Value::Decimal(Decimal::from(bytes)))
+            }
+            _ => Err(DecodeError::new("decimal is not backed by bytes or fixed").into()),
+        },
         Schema::Enum { ref symbols, .. } => {
             if let Value::Int(index) = decode_int(reader)? {
-                if index >= 0 && (index as usize) <= symbols.len() {
+                if index >= 0 && (index as usize) < symbols.len() {
                     let symbol = symbols[index as usize].clone();
                     Ok(Value::Enum(index, symbol))
                 } else {
@@ -154,3 +235,172 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> Result<Value, Error>
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::encode_to_vec;
+    use crate::schema::Name;
+    use crate::util::zig_i64;
+
+    #[test]
+    fn test_decode_round_trip() {
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "r", "fields": [
+                {"name": "null", "type": "null"},
+                {"name": "boolean", "type": "boolean"},
+                {"name": "int", "type": "int"},
+                {"name": "long", "type": "long"},
+                {"name": "float", "type": "float"},
+                {"name": "double", "type": "double"},
+                {"name": "bytes", "type": "bytes"},
+                {"name": "string", "type": "string"},
+                {"name": "fixed", "type": {"type": "fixed", "name": "f", "size": 2}},
+                {"name": "enum", "type": {"type": "enum", "name": "e", "symbols": ["A", "B"]}},
+                {"name": "union", "type": ["null", "long"]},
+                {"name": "array", "type": {"type": "array", "items": "int"}},
+                {"name": "map", "type": {"type": "map", "values": "string"}}
+            ]}"#,
+        )
+        .unwrap();
+        let value = Value::Record(vec![
+            ("null".to_owned(), Value::Null),
+            ("boolean".to_owned(), Value::Boolean(true)),
+            ("int".to_owned(), Value::Int(-42)),
+            ("long".to_owned(), Value::Long(i64::MIN)),
+            ("float".to_owned(), Value::Float(-1.5)),
+            ("double".to_owned(), Value::Double(2.25)),
+            ("bytes".to_owned(), Value::Bytes(vec![0, 1, 2])),
+            ("string".to_owned(), Value::String("foo".to_owned())),
+            ("fixed".to_owned(), Value::Fixed(2, vec![3, 4])),
+            ("enum".to_owned(), Value::Enum(1, "B".to_owned())),
+            ("union".to_owned(), Value::Union(Box::new(Value::Long(7)))),
+            (
+                "array".to_owned(),
+                Value::Array(vec![Value::Int(1), Value::Int(-1)]),
+            ),
+            (
+                "map".to_owned(),
+                Value::Map(
+                    vec![("a".to_owned(), Value::String("b".to_owned()))]
+                        .into_iter()
+                        .collect(),
+                ),
+            ),
+        ]);
+
+        let encoded = encode_to_vec(&value, &schema).unwrap();
+        assert_eq!(decode(&schema, &mut &encoded[..]).unwrap(), value);
+    }
+
+    #[test]
+    fn test_decode_from_slice() {
+        let encoded = encode_to_vec(&Value::Long(42), &Schema::Long).unwrap();
+        assert_eq!(
+            decode_from_slice(&Schema::Long, &encoded).unwrap(),
+            Value::Long(42)
+        );
+
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert!(decode_from_slice(&Schema::Long, &trailing).is_err());
+        assert!(decode_from_slice(&Schema::Long, &[]).is_err());
+    }
+
+    #[test]
+    fn test_decode_negative_block_count() {
+        // a block of -2 items spanning 2 bytes, then the terminating empty block
+        let encoded = vec![0x03, 0x04, 0x02, 0x04, 0x00];
+        let schema = Schema::Array(Box::new(Schema::Int));
+        assert_eq!(
+            decode(&schema, &mut &encoded[..]).unwrap(),
+            Value::Array(vec![Value::Int(1), Value::Int(2)])
+        );
+
+        let encoded = vec![0x01, 0x06, 0x02, b'a', 0x02, 0x00];
+        let schema = Schema::Map(Box::new(Schema::Int));
+        assert_eq!(
+            decode(&schema, &mut &encoded[..]).unwrap(),
+            Value::Map(vec![("a".to_owned(), Value::Int(1))].into_iter().collect())
+        );
+    }
+
+    #[test]
+    fn test_decode_multiple_blocks() {
+        // a block of 2 items, a block of -1 item spanning 1 byte, then the terminating empty block
+        let encoded = vec![0x04, 0x02, 0x04, 0x01, 0x02, 0x06, 0x00];
+        let schema = Schema::Array(Box::new(Schema::Int));
+        assert_eq!(
+            decode(&schema, &mut &encoded[..]).unwrap(),
+            Value::Array(vec![Value::Int(1), Value::Int(2), Value::Int(3)])
+        );
+
+        // nested arrays split into blocks: [1] in a block of -1 item spanning 3 bytes, then
+        // [2, 3] in a block of 1 item, itself split into two blocks
+        let encoded = vec![
+            0x01, 0x06, 0x02, 0x02, 0x00, 0x02, 0x02, 0x04, 0x02, 0x06, 0x00, 0x00,
+        ];
+        let schema = Schema::Array(Box::new(Schema::Array(Box::new(Schema::Int))));
+        assert_eq!(
+            decode(&schema, &mut &encoded[..]).unwrap(),
+            Value::Array(vec![
+                Value::Array(vec![Value::Int(1)]),
+                Value::Array(vec![Value::Int(2), Value::Int(3)]),
+            ])
+        );
+
+        // a block of 1 entry, then a block of -2 entries spanning 6 bytes
+        let encoded = vec![
+            0x02, 0x02, b'a', 0x02, 0x03, 0x0c, 0x02, b'b', 0x04, 0x02, b'c', 0x06, 0x00,
+        ];
+        let schema = Schema::Map(Box::new(Schema::Int));
+        assert_eq!(
+            decode(&schema, &mut &encoded[..]).unwrap(),
+            Value::Map(
+                vec![
+                    ("a".to_owned(), Value::Int(1)),
+                    ("b".to_owned(), Value::Int(2)),
+                    ("c".to_owned(), Value::Int(3)),
+                ]
+                .into_iter()
+                .collect()
+            )
+        );
+
+        // a negative block size
+        let encoded = vec![0x01, 0x01, 0x02, 0x00];
+        let schema = Schema::Array(Box::new(Schema::Int));
+        assert!(decode(&schema, &mut &encoded[..]).is_err());
+    }
+
+    #[test]
+    fn test_decode_invalid_data() {
+        let enum_schema = Schema::Enum {
+            name: Name::new("e"),
+            doc: None,
+            symbols: vec!["A".to_owned(), "B".to_owned()],
+        };
+        // index 2 and -1 are out of bounds
+        assert!(decode(&enum_schema, &mut &[0x04][..]).is_err());
+        assert!(decode(&enum_schema, &mut &[0x01][..]).is_err());
+
+        let union_schema = Schema::parse_str(r#"["null", "long"]"#).unwrap();
+        assert!(decode(&union_schema, &mut &[0x04][..]).is_err());
+        assert!(decode(&union_schema, &mut &[0x01][..]).is_err());
+
+        // negative length
+        assert!(decode(&Schema::Bytes, &mut &[0x01][..]).is_err());
+        // truncated input
+        assert!(decode(&Schema::String, &mut &[0x06, b'f'][..]).is_err());
+        assert!(decode(&Schema::Long, &mut &[0x80][..]).is_err());
+        assert!(decode(&Schema::Boolean, &mut &[0x02][..]).is_err());
+
+        // blocks too large to allocate their items
+        let mut encoded = Vec::new();
+        zig_i64(100_000_000, &mut encoded);
+        let schema = Schema::Array(Box::new(Schema::Null));
+        assert!(decode(&schema, &mut &encoded[..]).is_err());
+        let schema = Schema::Map(Box::new(Schema::Null));
+        assert!(decode(&schema, &mut &encoded[..]).is_err());
+    }
+}
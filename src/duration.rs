@@ -0,0 +1,135 @@
+//! Logic handling the `duration` logical type.
+use std::convert::TryFrom;
+use std::time;
+
+use failure::Fail;
+
+/// Describes errors happened while converting an Avro `duration` from or into a
+/// `std::time::Duration`.
+#[derive(Fail, Debug)]
+#[fail(display = "Duration conversion error: {}", _0)]
+pub struct DurationError(String);
+
+impl DurationError {
+    pub fn new<S>(msg: S) -> DurationError
+    where
+        S: Into<String>,
+    {
+        DurationError(msg.into())
+    }
+}
+
+/// Represents an Avro `duration` value.
+///
+/// A `Duration` is made of three independent components: a number of months, a number of days
+/// and a number of milliseconds. It is stored as a `fixed` of size 12, each component being
+/// encoded as a little-endian unsigned 32-bit integer.
+///
+/// More information about durations can be found in the
+/// [Avro specification](https://avro.apache.org/docs/current/spec.html#Duration)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Duration {
+    pub months: u32,
+    pub days: u32,
+    pub millis: u32,
+}
+
+impl Duration {
+    /// Create a `Duration` out of its three components.
+    pub fn new(months: u32, days: u32, millis: u32) -> Self {
+        Duration {
+            months,
+            days,
+            millis,
+        }
+    }
+}
+
+impl From<Duration> for [u8; 12] {
+    fn from(duration: Duration) -> Self {
+        let mut bytes = [0u8; 12];
+        bytes[0..4].copy_from_slice(&duration.months.to_le_bytes());
+        bytes[4..8].copy_from_slice(&duration.days.to_le_bytes());
+        bytes[8..12].copy_from_slice(&duration.millis.to_le_bytes());
+        bytes
+    }
+}
+
+impl From<[u8; 12]> for Duration {
+    fn from(bytes: [u8; 12]) -> Self {
+        let component = |offset: usize| {
+            let mut buf = [0u8; 4];
+            buf.copy_from_slice(&bytes[offset..offset + 4]);
+            u32::from_le_bytes(buf)
+        };
+
+        Duration {
+            months: component(0),
+            days: component(4),
+            millis: component(8),
+        }
+    }
+}
+
+impl TryFrom<Duration> for time::Duration {
+    type Error = DurationError;
+
+    /// Only durations made of milliseconds can be converted: months and days do not have a fixed
+    /// length and thus no `std::time::Duration` equivalent.
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        if duration.months != 0 || duration.days != 0 {
+            return Err(DurationError::new(format!(
+                "{:?} has months or days, which have no fixed length",
+                duration
+            )));
+        }
+        Ok(time::Duration::from_millis(u64::from(duration.millis)))
+    }
+}
+
+impl TryFrom<time::Duration> for Duration {
+    type Error = DurationError;
+
+    /// The conversion truncates sub-millisecond precision, and fails if the number of
+    /// milliseconds does not fit in 32 bits.
+    fn try_from(duration: time::Duration) -> Result<Self, Self::Error> {
+        let millis = duration.as_millis();
+        if millis > u128::from(u32::MAX) {
+            return Err(DurationError::new(format!(
+                "{:?} does not fit in 32 bits of milliseconds",
+                duration
+            )));
+        }
+        Ok(Duration::new(0, 0, millis as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_byte_layout() {
+        let duration = Duration::new(1, 2, 0x0403_0201);
+        let bytes: [u8; 12] = duration.into();
+        assert_eq!(bytes, [1, 0, 0, 0, 2, 0, 0, 0, 1, 2, 3, 4]);
+        assert_eq!(Duration::from(bytes), duration);
+    }
+
+    #[test]
+    fn test_duration_std_conversion() {
+        let duration = Duration::new(0, 0, 1_500);
+        assert_eq!(
+            time::Duration::try_from(duration).unwrap(),
+            time::Duration::from_millis(1_500)
+        );
+        assert_eq!(
+            Duration::try_from(time::Duration::from_micros(1_500_999)).unwrap(),
+            duration
+        );
+
+        assert!(time::Duration::try_from(Duration::new(1, 0, 0)).is_err());
+        assert!(time::Duration::try_from(Duration::new(0, 1, 0)).is_err());
+        assert!(Duration::try_from(time::Duration::from_secs(5_000_000)).is_err());
+    }
+}
@@ -1,21 +1,27 @@
 use std::mem::transmute;
 
-use crate::schema::Schema;
-use crate::types::Value;
+use failure::Error;
+
+use crate::schema::{resolve_ref, Definitions, Schema};
+use crate::types::{describe_schema, describe_value, SchemaResolutionError, Value};
 use crate::util::{zig_i32, zig_i64};
 
+#[cfg(feature = "uuid")]
+use uuid::{fmt::Hyphenated, Uuid};
+
 /// Encode a `Value` into avro format.
 ///
 /// **NOTE** This will not perform schema validation. The value is assumed to
 /// be valid with regards to the schema. Schema are needed only to guide the
-/// encoding for complex type values.
-pub fn encode(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) {
+/// encoding for complex type values, and an error is returned when a complex
+/// value does not match its schema.
+pub fn encode(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) -> Result<(), Error> {
     encode_ref(&value, schema, buffer)
 }
 
 fn encode_bytes<B: AsRef<[u8]> + ?Sized>(s: &B, buffer: &mut Vec<u8>) {
     let bytes = s.as_ref();
-    encode(&Value::Long(bytes.len() as i64), &Schema::Long, buffer);
+    encode_long(bytes.len() as i64, buffer);
     buffer.extend_from_slice(bytes);
 }
 
@@ -31,86 +37,279 @@ fn encode_int(i: i32, buffer: &mut Vec<u8>) {
 ///
 /// **NOTE** This will not perform schema validation. The value is assumed to
 /// be valid with regards to the schema. Schema are needed only to guide the
-/// encoding for complex type values.
-pub fn encode_ref(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) {
+/// encoding for complex type values, and an error is returned when a complex
+/// value does not match its schema.
+pub fn encode_ref(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) -> Result<(), Error> {
+    encode_internal(value, schema, &mut Definitions::new(schema), buffer)
+}
+
+fn encode_internal<'s>(
+    value: &Value,
+    schema: &'s Schema,
+    definitions: &mut Definitions<'s>,
+    buffer: &mut Vec<u8>,
+) -> Result<(), Error> {
+    if let Schema::Ref { ref name } = *schema {
+        let definition = resolve_ref(name, definitions)?;
+        return encode_internal(value, definition, definitions, buffer);
+    }
+
     match value {
         Value::Null => (),
         Value::Boolean(b) => buffer.push(if *b { 1u8 } else { 0u8 }),
-        Value::Int(i) => encode_int(*i, buffer),
-        Value::Long(i) => encode_long(*i, buffer),
+        Value::Int(i) | Value::Date(i) | Value::TimeMillis(i) => encode_int(*i, buffer),
+        Value::Long(i)
+        | Value::TimeMicros(i)
+        | Value::TimestampMillis(i)
+        | Value::TimestampMicros(i) => encode_long(*i, buffer),
         Value::Float(x) => buffer.extend_from_slice(&unsafe { transmute::<f32, [u8; 4]>(*x) }),
         Value::Double(x) => buffer.extend_from_slice(&unsafe { transmute::<f64, [u8; 8]>(*x) }),
         Value::Bytes(bytes) => encode_bytes(bytes, buffer),
         Value::String(s) => match *schema {
-            Schema::String => {
+            Schema::String | Schema::Uuid => {
                 encode_bytes(s, buffer);
             }
             Schema::Enum { ref symbols, .. } => {
-                if let Some(index) = symbols.iter().position(|item| item == s) {
-                    encode_int(index as i32, buffer);
+                let index = symbols
+                    .iter()
+                    .position(|item| item == s)
+                    .ok_or_else(|| mismatch(value, schema))?;
+                encode_int(index as i32, buffer);
+            }
+            _ => return Err(mismatch(value, schema)),
+        },
+        #[cfg(feature = "uuid")]
+        Value::Uuid(uuid) => encode_bytes(
+            uuid.hyphenated().encode_lower(&mut Uuid::encode_buffer()),
+            buffer,
+        ),
+        Value::Fixed(_, bytes) => {
+            let size = match *schema {
+                Schema::Fixed { size, .. } => size,
+                Schema::Duration { .. } => 12,
+                Schema::Decimal { ref inner, .. } => match **inner {
+                    Schema::Fixed { size, .. } => size,
+                    _ => return Err(mismatch(value, schema)),
+                },
+                _ => return Err(mismatch(value, schema)),
+            };
+            if bytes.len() != size {
+                return Err(mismatch(value, schema));
+            }
+            buffer.extend(bytes);
+        }
+        Value::Duration(duration) => buffer.extend(&<[u8; 12]>::from(*duration)),
+        Value::Decimal(decimal) => match *schema {
+            Schema::Decimal { ref inner, .. } => match **inner {
+                Schema::Fixed { size, .. } => {
+                    let bytes = decimal.to_sign_extended_vec(size).ok_or_else(|| {
+                        SchemaResolutionError::new(format!(
+                            "Decimal {:?} does not fit in {} bytes",
+                            decimal, size
+                        ))
+                    })?;
+                    buffer.extend(bytes);
                 }
+                _ => encode_bytes(&decimal.to_vec(), buffer),
+            },
+            _ => return Err(mismatch(value, schema)),
+        },
+        Value::Enum(i, _) => match *schema {
+            Schema::Enum { ref symbols, .. } if (*i as usize) < symbols.len() => {
+                encode_int(*i, buffer)
             }
-            _ => (),
+            _ => return Err(mismatch(value, schema)),
         },
-        Value::Fixed(_, bytes) => buffer.extend(bytes),
-        Value::Enum(i, _) => encode_int(*i, buffer),
-        Value::Union(item) => {
-            if let Schema::Union(ref inner) = *schema {
-                // Find the schema that is matched here. Due to validation, this should always
-                // return a value.
-                let (idx, inner_schema) = inner
-                    .find_schema(item)
-                    .expect("Invalid Union validation occurred");
+        Value::Union(item) => match *schema {
+            Schema::Union(ref inner) => {
+                let (idx, inner_schema) = inner.find_schema(item).ok_or_else(|| {
+                    SchemaResolutionError::new(format!(
+                        "No variant of {:?} matches {:?}",
+                        inner, item
+                    ))
+                })?;
                 encode_long(idx as i64, buffer);
-                encode_ref(&*item, inner_schema, buffer);
+                encode_internal(item, inner_schema, definitions, buffer)?;
             }
-        }
-        Value::Array(items) => {
-            if let Schema::Array(ref inner) = *schema {
+            _ => return Err(mismatch(value, schema)),
+        },
+        Value::Array(items) => match *schema {
+            Schema::Array(ref inner) => {
                 if items.len() > 0 {
                     encode_long(items.len() as i64, buffer);
                     for item in items.iter() {
-                        encode_ref(item, inner, buffer);
+                        encode_internal(item, inner, definitions, buffer)?;
                     }
                 }
                 buffer.push(0u8);
             }
-        }
-        Value::Map(items) => {
-            if let Schema::Map(ref inner) = *schema {
+            _ => return Err(mismatch(value, schema)),
+        },
+        Value::Map(items) => match *schema {
+            Schema::Map(ref inner) => {
                 if items.len() > 0 {
                     encode_long(items.len() as i64, buffer);
                     for (key, value) in items {
                         encode_bytes(key, buffer);
-                        encode_ref(value, inner, buffer);
+                        encode_internal(value, inner, definitions, buffer)?;
                     }
                 }
                 buffer.push(0u8);
             }
-        }
-        Value::Record(fields) => {
-            if let Schema::Record {
+            _ => return Err(mismatch(value, schema)),
+        },
+        Value::Record(fields) => match *schema {
+            Schema::Record {
                 fields: ref schema_fields,
                 ..
-            } = *schema
-            {
-                for (i, &(_, ref value)) in fields.iter().enumerate() {
-                    encode_ref(value, &schema_fields[i].schema, buffer);
+            } if fields.len() == schema_fields.len() => {
+                for (&(_, ref value), field) in fields.iter().zip(schema_fields) {
+                    encode_internal(value, &field.schema, definitions, buffer)?;
                 }
             }
-        }
+            _ => return Err(mismatch(value, schema)),
+        },
     }
+    Ok(())
+}
+
+/// Error for a `value` that cannot be written with `schema`.
+fn mismatch(value: &Value, schema: &Schema) -> Error {
+    SchemaResolutionError::new(format!(
+        "Cannot encode {} with schema {}",
+        describe_value(value),
+        describe_schema(schema)
+    ))
+    .into()
 }
 
-pub fn encode_to_vec(value: &Value, schema: &Schema) -> Vec<u8> {
-    let mut buffer = Vec::new();
-    encode(&value, schema, &mut buffer);
-    buffer
+/// Encode a `Value` into avro format, returning the encoded bytes.
+///
+/// **NOTE** Like [`encode`](fn.encode.html), this will not perform schema validation.
+pub fn encode_to_vec(value: &Value, schema: &Schema) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::with_capacity(estimate_encoded_size(value, schema));
+    encode(&value, schema, &mut buffer)?;
+    Ok(buffer)
+}
+
+/// Maximum size of a variable-length encoded `int`.
+const MAX_INT_SIZE: usize = 5;
+/// Maximum size of a variable-length encoded `long`.
+const MAX_LONG_SIZE: usize = 10;
+
+/// Estimate the size of a `Value` encoded in avro format, without actually encoding it.
+///
+/// The estimate is an upper bound for values that are valid with regards to the schema: `int`
+/// and `long` values, as well as union indexes and block counts, are counted with the maximum size
+/// of their variable-length encoding. It is meant to preallocate encoding buffers.
+pub fn estimate_encoded_size(value: &Value, schema: &Schema) -> usize {
+    estimate_internal(value, schema, &mut Definitions::new(schema))
+}
+
+/// Size of the variable-length encoding of a non-negative `len`.
+fn len_size(len: usize) -> usize {
+    let mut len = (len as u64) << 1;
+    let mut size = 1;
+    while len >= 0x80 {
+        len >>= 7;
+        size += 1;
+    }
+    size
+}
+
+fn estimate_internal<'s>(
+    value: &Value,
+    schema: &'s Schema,
+    definitions: &mut Definitions<'s>,
+) -> usize {
+    if let Schema::Ref { ref name } = *schema {
+        return match resolve_ref(name, definitions) {
+            Ok(definition) => estimate_internal(value, definition, definitions),
+            Err(_) => 0,
+        };
+    }
+
+    match value {
+        Value::Null => 0,
+        Value::Boolean(_) => 1,
+        Value::Int(_) | Value::Date(_) | Value::TimeMillis(_) | Value::Enum(..) => MAX_INT_SIZE,
+        Value::Long(_)
+        | Value::TimeMicros(_)
+        | Value::TimestampMillis(_)
+        | Value::TimestampMicros(_) => MAX_LONG_SIZE,
+        Value::Float(_) => 4,
+        Value::Double(_) => 8,
+        Value::Bytes(bytes) => len_size(bytes.len()) + bytes.len(),
+        Value::String(s) => match *schema {
+            Schema::Enum { .. } => MAX_INT_SIZE,
+            _ => len_size(s.len()) + s.len(),
+        },
+        #[cfg(feature = "uuid")]
+        Value::Uuid(_) => len_size(Hyphenated::LENGTH) + Hyphenated::LENGTH,
+        Value::Fixed(_, bytes) => bytes.len(),
+        Value::Duration(_) => 12,
+        Value::Decimal(decimal) => match *schema {
+            Schema::Decimal { ref inner, .. } => match **inner {
+                Schema::Fixed { size, .. } => size,
+                _ => len_size(decimal.max_len()) + decimal.max_len(),
+            },
+            _ => 0,
+        },
+        Value::Union(item) => match *schema {
+            Schema::Union(ref inner) => match inner.find_schema(item) {
+                Some((_, inner_schema)) => {
+                    MAX_LONG_SIZE + estimate_internal(item, inner_schema, definitions)
+                }
+                None => 0,
+            },
+            _ => 0,
+        },
+        Value::Array(items) => match *schema {
+            Schema::Array(ref inner) => {
+                MAX_LONG_SIZE
+                    + items
+                        .iter()
+                        .map(|item| estimate_internal(item, inner, definitions))
+                        .sum::<usize>()
+                    + 1
+            }
+            _ => 0,
+        },
+        Value::Map(items) => match *schema {
+            Schema::Map(ref inner) => {
+                MAX_LONG_SIZE
+                    + items
+                        .iter()
+                        .map(|(key, value)| {
+                            len_size(key.len())
+                                + key.len()
+                                + estimate_internal(value, inner, definitions)
+                        })
+                        .sum::<usize>()
+                    + 1
+            }
+            _ => 0,
+        },
+        Value::Record(fields) => match *schema {
+            Schema::Record {
+                fields: ref schema_fields,
+                ..
+            } => fields
+                .iter()
+                .zip(schema_fields)
+                .map(|(&(_, ref value), field)| {
+                    estimate_internal(value, &field.schema, definitions)
+                })
+                .sum(),
+            _ => 0,
+        },
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::Name;
     use std::collections::HashMap;
 
     #[test]
@@ -121,7 +320,8 @@ mod tests {
             &Value::Array(empty),
             &Schema::Array(Box::new(Schema::Int)),
             &mut buf,
-        );
+        )
+        .unwrap();
         assert_eq!(vec![0u8], buf);
     }
 
@@ -133,7 +333,265 @@ mod tests {
             &Value::Map(empty),
             &Schema::Map(Box::new(Schema::Int)),
             &mut buf,
-        );
+        )
+        .unwrap();
         assert_eq!(vec![0u8], buf);
     }
+
+    #[test]
+    fn test_encode_primitives() {
+        let cases = vec![
+            (Value::Null, Schema::Null, vec![]),
+            (Value::Boolean(true), Schema::Boolean, vec![1u8]),
+            (Value::Boolean(false), Schema::Boolean, vec![0u8]),
+            (Value::Int(-64), Schema::Int, vec![0x7f]),
+            (Value::Long(64), Schema::Long, vec![0x80, 0x01]),
+            (
+                Value::Float(1.0),
+                Schema::Float,
+                vec![0x00, 0x00, 0x80, 0x3f],
+            ),
+            (
+                Value::Double(1.0),
+                Schema::Double,
+                vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf0, 0x3f],
+            ),
+            (Value::Bytes(vec![0xff]), Schema::Bytes, vec![0x02, 0xff]),
+            (
+                Value::String("foo".to_owned()),
+                Schema::String,
+                vec![0x06, b'f', b'o', b'o'],
+            ),
+        ];
+
+        for (value, schema, expected) in cases {
+            assert_eq!(
+                encode_to_vec(&value, &schema).unwrap(),
+                expected,
+                "{:?}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode_complex() {
+        let fixed = Schema::Fixed {
+            name: Name::new("fixed"),
+            size: 2,
+        };
+        assert_eq!(
+            encode_to_vec(&Value::Fixed(2, vec![1, 2]), &fixed).unwrap(),
+            vec![1, 2]
+        );
+
+        let enum_schema = Schema::Enum {
+            name: Name::new("enum"),
+            doc: None,
+            symbols: vec!["A".to_owned(), "B".to_owned()],
+        };
+        assert_eq!(
+            encode_to_vec(&Value::Enum(1, "B".to_owned()), &enum_schema).unwrap(),
+            vec![0x02]
+        );
+        assert_eq!(
+            encode_to_vec(&Value::String("B".to_owned()), &enum_schema).unwrap(),
+            vec![0x02]
+        );
+
+        let union = Schema::parse_str(r#"["null", "long"]"#).unwrap();
+        assert_eq!(
+            encode_to_vec(&Value::Union(Box::new(Value::Long(1))), &union).unwrap(),
+            vec![0x02, 0x02]
+        );
+        assert_eq!(
+            encode_to_vec(&Value::Union(Box::new(Value::Null)), &union).unwrap(),
+            vec![0x00]
+        );
+
+        let array = Schema::Array(Box::new(Schema::Long));
+        assert_eq!(
+            encode_to_vec(&Value::Array(vec![Value::Long(1), Value::Long(2)]), &array).unwrap(),
+            vec![0x04, 0x02, 0x04, 0x00]
+        );
+
+        let mut items = HashMap::new();
+        items.insert("a".to_owned(), Value::Long(1));
+        let map = Schema::Map(Box::new(Schema::Long));
+        assert_eq!(
+            encode_to_vec(&Value::Map(items), &map).unwrap(),
+            vec![0x02, 0x02, b'a', 0x02, 0x00]
+        );
+
+        let record = Schema::parse_str(
+            r#"{"type": "record", "name": "r", "fields": [
+                {"name": "a", "type": "long"},
+                {"name": "b", "type": "string"}
+            ]}"#,
+        )
+        .unwrap();
+        let value = Value::Record(vec![
+            ("a".to_owned(), Value::Long(27)),
+            ("b".to_owned(), Value::String("foo".to_owned())),
+        ]);
+        assert_eq!(
+            encode_to_vec(&value, &record).unwrap(),
+            vec![0x36, 0x06, b'f', b'o', b'o']
+        );
+    }
+
+    #[test]
+    fn test_encode_unresolved_ref() {
+        let mut buf = Vec::new();
+        let schema = Schema::Ref {
+            name: Name::new("Missing"),
+        };
+        assert!(encode(&Value::Record(vec![]), &schema, &mut buf).is_err());
+    }
+
+    #[test]
+    fn test_encode_mismatch() {
+        let enum_schema = Schema::Enum {
+            name: Name::new("enum"),
+            doc: None,
+            symbols: vec!["A".to_owned(), "B".to_owned()],
+        };
+        let fixed = Schema::Fixed {
+            name: Name::new("fixed"),
+            size: 2,
+        };
+        let record = Schema::parse_str(
+            r#"{"type": "record", "name": "r", "fields": [{"name": "a", "type": "long"}]}"#,
+        )
+        .unwrap();
+        let cases = vec![
+            (Value::String("foo".to_owned()), Schema::Long),
+            (Value::String("C".to_owned()), enum_schema.clone()),
+            (Value::Enum(2, "C".to_owned()), enum_schema.clone()),
+            (Value::Enum(0, "A".to_owned()), Schema::Int),
+            (Value::Fixed(2, vec![1, 2]), Schema::Bytes),
+            (Value::Fixed(3, vec![1, 2, 3]), fixed),
+            (
+                Value::Decimal(crate::decimal::Decimal::from(vec![1])),
+                Schema::Bytes,
+            ),
+            (Value::Array(vec![]), Schema::Map(Box::new(Schema::Long))),
+            (
+                Value::Map(HashMap::new()),
+                Schema::Array(Box::new(Schema::Long)),
+            ),
+            (Value::Union(Box::new(Value::Null)), Schema::Null),
+            (Value::Record(vec![]), Schema::Long),
+            (
+                Value::Record(vec![
+                    ("a".to_owned(), Value::Long(1)),
+                    ("b".to_owned(), Value::Long(2)),
+                ]),
+                record,
+            ),
+        ];
+
+        for (value, schema) in cases {
+            assert!(encode_to_vec(&value, &schema).is_err(), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn test_encode_decimal() {
+        use crate::decimal::Decimal;
+
+        let decimal = Value::Decimal(Decimal::from(vec![0xFF, 0x85]));
+
+        let mut buf = Vec::new();
+        let schema = Schema::Decimal {
+            precision: 3,
+            scale: 0,
+            inner: Box::new(Schema::Bytes),
+        };
+        encode(&decimal, &schema, &mut buf).unwrap();
+        assert_eq!(vec![0x02, 0x85], buf);
+
+        let mut buf = Vec::new();
+        let schema = Schema::Decimal {
+            precision: 3,
+            scale: 0,
+            inner: Box::new(Schema::Fixed {
+                name: Name::new("decimal"),
+                size: 4,
+            }),
+        };
+        encode(&decimal, &schema, &mut buf).unwrap();
+        assert_eq!(vec![0xFF, 0xFF, 0xFF, 0x85], buf);
+
+        let schema = Schema::Decimal {
+            precision: 3,
+            scale: 0,
+            inner: Box::new(Schema::Fixed {
+                name: Name::new("decimal"),
+                size: 1,
+            }),
+        };
+        let decimal = Value::Decimal(Decimal::from(vec![0x04, 0xD2]));
+        assert!(encode(&decimal, &schema, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_estimate_encoded_size() {
+        let record = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "test",
+                "fields": [
+                    {"name": "a", "type": "long"},
+                    {"name": "b", "type": ["null", "string"]},
+                    {"name": "c", "type": {"type": "array", "items": "double"}},
+                    {"name": "d", "type": {"type": "map", "values": "boolean"}},
+                    {"name": "e", "type": {"type": "enum", "name": "E", "symbols": ["X", "Y"]}},
+                    {"name": "f", "type": {"type": "fixed", "name": "F", "size": 3}},
+                    {"name": "next", "type": ["null", "test"]}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+        let mut items = HashMap::new();
+        items.insert("key".to_owned(), Value::Boolean(true));
+        let leaf = Value::Record(vec![
+            ("a".to_owned(), Value::Long(1)),
+            ("b".to_owned(), Value::Union(Box::new(Value::Null))),
+            ("c".to_owned(), Value::Array(vec![])),
+            ("d".to_owned(), Value::Map(HashMap::new())),
+            ("e".to_owned(), Value::Enum(0, "X".to_owned())),
+            ("f".to_owned(), Value::Fixed(3, vec![1, 2, 3])),
+            ("next".to_owned(), Value::Union(Box::new(Value::Null))),
+        ]);
+        let value = Value::Record(vec![
+            ("a".to_owned(), Value::Long(i64::min_value())),
+            (
+                "b".to_owned(),
+                Value::Union(Box::new(Value::String("foo".to_owned()))),
+            ),
+            (
+                "c".to_owned(),
+                Value::Array(vec![Value::Double(1.0), Value::Double(2.0)]),
+            ),
+            ("d".to_owned(), Value::Map(items)),
+            ("e".to_owned(), Value::String("Y".to_owned())),
+            ("f".to_owned(), Value::Fixed(3, vec![4, 5, 6])),
+            ("next".to_owned(), Value::Union(Box::new(leaf))),
+        ]);
+        assert!(value.validate(&record));
+
+        let estimate = estimate_encoded_size(&value, &record);
+        assert!(estimate >= encode_to_vec(&value, &record).unwrap().len());
+        assert_eq!(estimate, 145);
+
+        assert_eq!(estimate_encoded_size(&Value::Null, &Schema::Null), 0);
+        assert_eq!(estimate_encoded_size(&Value::Float(1.0), &Schema::Float), 4);
+        assert_eq!(
+            estimate_encoded_size(&Value::String("a".repeat(100)), &Schema::String),
+            102
+        );
+    }
 }
@@ -0,0 +1,117 @@
+//! Zigzag variable-length encoding of integers, used by Avro for all `int` and `long` values.
+//!
+//! Integers are first mapped to unsigned integers with the zigzag encoding, so that values of
+//! small magnitude have a short encoding whatever their sign: `n` is encoded as `n * 2` when
+//! positive and as `-n * 2 - 1` (i.e. `|n| * 2 - 1`) when negative. The result is then written
+//! 7 bits at a time, least significant group first, the high bit of each byte being set when
+//! more bytes follow.
+//!
+//! ```
+//! use avro_rs::encoding::{decode_long, encode_long};
+//!
+//! let mut buf = Vec::new();
+//! encode_long(-64, &mut buf);
+//! assert_eq!(buf, vec![0x7f]);
+//! assert_eq!(decode_long(&mut &buf[..]).unwrap(), -64);
+//! ```
+use std::io::Read;
+
+use failure::Error;
+
+use crate::util::{zag_i32, zag_i64, zig_i32, zig_i64};
+
+/// Append the zigzag variable-length encoding of `value` to `buf`.
+pub fn encode_varint(value: i64, buf: &mut Vec<u8>) {
+    zig_i64(value, buf)
+}
+
+/// Read a zigzag variable-length encoded integer from `reader`.
+///
+/// Fails if the encoding is longer than 10 bytes or if `reader` ends before the last byte.
+pub fn decode_varint(reader: &mut dyn Read) -> Result<i64, Error> {
+    zag_i64(reader)
+}
+
+/// Append the encoding of an Avro `int` to `buf`.
+pub fn encode_int(value: i32, buf: &mut Vec<u8>) {
+    zig_i32(value, buf)
+}
+
+/// Read an Avro `int` from `reader`.
+///
+/// Fails if the decoded integer does not fit into an `i32`.
+pub fn decode_int(reader: &mut dyn Read) -> Result<i32, Error> {
+    zag_i32(reader)
+}
+
+/// Append the encoding of an Avro `long` to `buf`.
+pub fn encode_long(value: i64, buf: &mut Vec<u8>) {
+    encode_varint(value, buf)
+}
+
+/// Read an Avro `long` from `reader`.
+pub fn decode_long(reader: &mut dyn Read) -> Result<i64, Error> {
+    decode_varint(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Integers around the powers of two, where the length of the encoding changes.
+    fn edge_cases() -> Vec<i64> {
+        let mut cases = vec![0, 1, -1, i64::MIN, i64::MAX];
+        for shift in 0..63 {
+            let n = 1i64 << shift;
+            cases.extend_from_slice(&[n - 1, n, n + 1, -n - 1, -n, -n + 1]);
+        }
+        cases
+    }
+
+    #[test]
+    fn test_long_round_trip() {
+        for n in edge_cases() {
+            let mut buf = Vec::new();
+            encode_long(n, &mut buf);
+            assert!(buf.len() <= 10);
+            let mut reader = &buf[..];
+            assert_eq!(decode_long(&mut reader).unwrap(), n);
+            assert!(reader.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_int_round_trip() {
+        for n in edge_cases()
+            .into_iter()
+            .filter(|&n| n >= i64::from(i32::MIN) && n <= i64::from(i32::MAX))
+        {
+            let mut buf = Vec::new();
+            encode_int(n as i32, &mut buf);
+            assert!(buf.len() <= 5);
+            assert_eq!(decode_int(&mut &buf[..]).unwrap(), n as i32);
+        }
+    }
+
+    #[test]
+    fn test_zigzag_mapping() {
+        for &(n, encoded) in &[(0, 0u8), (-1, 1), (1, 2), (-2, 3), (2, 4), (-64, 127)] {
+            let mut buf = Vec::new();
+            encode_varint(n, &mut buf);
+            assert_eq!(buf, vec![encoded]);
+        }
+        let mut buf = Vec::new();
+        encode_varint(64, &mut buf);
+        assert_eq!(buf, vec![0x80, 0x01]);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let mut buf = Vec::new();
+        encode_long(i64::from(i32::MAX) + 1, &mut buf);
+        assert!(decode_int(&mut &buf[..]).is_err());
+
+        assert!(decode_long(&mut &[0x80u8, 0x80][..]).is_err());
+        assert!(decode_long(&mut &[0xffu8; 11][..]).is_err());
+    }
+}
@@ -0,0 +1,1060 @@
+//! Logic for parsing and writing schemas in [Avro IDL](https://avro.apache.org/docs/current/idl.html).
+//!
+//! Named types are read out of a `protocol`, or out of a list of declarations:
+//!
+//! ```
+//! use avro_rs::idl::parse_idl;
+//!
+//! let schemas = parse_idl(
+//!     r#"
+//!     @namespace("org.example")
+//!     protocol Example {
+//!         enum Color { RED, GREEN, BLUE }
+//!
+//!         /** A pixel. */
+//!         record Pixel {
+//!             int x;
+//!             int y;
+//!             Color color = "RED";
+//!         }
+//!     }
+//!     "#,
+//! )
+//! .unwrap();
+//! assert_eq!(schemas.len(), 2);
+//! ```
+//!
+//! Each declaration is turned into the JSON form of its schema, so that references to named
+//! types and namespaces follow the same rules as with
+//! [`Schema::parse_list`](../schema/enum.Schema.html#method.parse_list). Annotations such as
+//! `@namespace("...")`, `@aliases([...])` or `@order("...")` become properties of the declared
+//! type or field. The messages of a protocol are skipped, and imports are not supported.
+//!
+//! Schemas are written back in Avro IDL by [`schema_to_idl`](fn.schema_to_idl.html).
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use failure::{Error, Fail};
+use serde_json::{json, Map, Value};
+
+use crate::schema::{Documentation, Name, RecordFieldOrder, Schema, SchemaStore};
+
+/// Describes errors happened while parsing Avro IDL.
+#[derive(Fail, Debug)]
+#[fail(display = "Failed to parse IDL: {}", _0)]
+pub struct ParseIdlError(String);
+
+impl ParseIdlError {
+    pub fn new<S>(msg: S) -> ParseIdlError
+    where
+        S: Into<String>,
+    {
+        ParseIdlError(msg.into())
+    }
+}
+
+/// Parse the named types declared in Avro IDL, in order of declaration.
+///
+/// Types can only reference the types declared before them, or themselves.
+pub fn parse_idl(source: &str) -> Result<Vec<Schema>, Error> {
+    let declarations = Parser::new(source).parse_file()?;
+    let mut store = SchemaStore::new();
+    declarations
+        .iter()
+        .map(|declaration| store.parse(declaration))
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// An identifier, a keyword or a number.
+    Ident(String),
+    Symbol(char),
+    Eof,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match *self {
+            Token::Ident(ref ident) => format!("`{}`", ident),
+            Token::Symbol(symbol) => format!("`{}`", symbol),
+            Token::Eof => "end of input".to_owned(),
+        }
+    }
+
+    fn is_symbol(&self, symbol: char) -> bool {
+        *self == Token::Symbol(symbol)
+    }
+
+    fn is_ident(&self, ident: &str) -> bool {
+        match *self {
+            Token::Ident(ref other) => other == ident,
+            _ => false,
+        }
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '-'
+}
+
+/// Recursive descent parser, reading tokens on demand so that JSON values can be read out of the
+/// source as well.
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+    line: usize,
+    /// The last documentation comment, until the next token is read.
+    doc: Option<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Parser<'a> {
+        Parser {
+            source,
+            pos: 0,
+            line: 1,
+            doc: None,
+        }
+    }
+
+    fn error<S: AsRef<str>>(&self, msg: S) -> Error {
+        ParseIdlError::new(format!("line {}: {}", self.line, msg.as_ref())).into()
+    }
+
+    fn advance(&mut self, len: usize) {
+        self.line += self.source[self.pos..self.pos + len].matches('\n').count();
+        self.pos += len;
+    }
+
+    /// Skip whitespaces and comments, keeping the content of documentation comments.
+    fn skip_trivia(&mut self) -> Result<(), Error> {
+        loop {
+            let rest = &self.source[self.pos..];
+            let trimmed = rest.trim_start();
+            self.advance(rest.len() - trimmed.len());
+
+            if trimmed.starts_with("//") {
+                self.advance(trimmed.find('\n').unwrap_or(trimmed.len()));
+            } else if let Some(comment) = trimmed.strip_prefix("/*") {
+                let end = match comment.find("*/") {
+                    Some(end) => end + 4,
+                    None => return Err(self.error("unterminated comment")),
+                };
+                if trimmed.starts_with("/**") && end > 4 {
+                    self.doc = Some(doc_comment(&trimmed[3..end - 2]));
+                }
+                self.advance(end);
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Take the documentation comment preceding the next token.
+    fn take_doc(&mut self) -> Result<Option<String>, Error> {
+        self.skip_trivia()?;
+        Ok(self.doc.take())
+    }
+
+    fn next_token(&mut self) -> Result<Token, Error> {
+        self.skip_trivia()?;
+        self.doc = None;
+
+        let rest = &self.source[self.pos..];
+        let token = match rest.chars().next() {
+            None => Token::Eof,
+            Some('`') => {
+                let len = match rest[1..].find('`') {
+                    Some(len) => len,
+                    None => return Err(self.error("unterminated escaped identifier")),
+                };
+                let ident = rest[1..=len].to_owned();
+                self.advance(len + 2);
+                Token::Ident(ident)
+            }
+            Some(c) if is_ident_char(c) => {
+                let len = rest.find(|c| !is_ident_char(c)).unwrap_or(rest.len());
+                let ident = rest[..len].to_owned();
+                self.advance(len);
+                Token::Ident(ident)
+            }
+            Some(c) => {
+                self.advance(c.len_utf8());
+                Token::Symbol(c)
+            }
+        };
+        Ok(token)
+    }
+
+    fn peek(&mut self) -> Result<Token, Error> {
+        let (pos, line, doc) = (self.pos, self.line, self.doc.clone());
+        let token = self.next_token();
+        self.pos = pos;
+        self.line = line;
+        self.doc = doc;
+        token
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), Error> {
+        match self.next_token()? {
+            ref token if token.is_symbol(symbol) => Ok(()),
+            token => Err(self.error(format!("expected `{}`, found {}", symbol, token.describe()))),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, Error> {
+        match self.next_token()? {
+            Token::Ident(ident) => Ok(ident),
+            token => Err(self.error(format!(
+                "expected an identifier, found {}",
+                token.describe()
+            ))),
+        }
+    }
+
+    fn number(&mut self) -> Result<u64, Error> {
+        match self.next_token()? {
+            Token::Ident(ref ident) if ident.parse::<u64>().is_ok() => Ok(ident.parse()?),
+            token => Err(self.error(format!("expected a number, found {}", token.describe()))),
+        }
+    }
+
+    /// Read a JSON value, ending before the next `,`, `;` or `)` outside of any array, object
+    /// or string.
+    fn json(&mut self) -> Result<Value, Error> {
+        self.skip_trivia()?;
+        self.doc = None;
+
+        let rest = &self.source[self.pos..];
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut len = rest.len();
+        for (i, c) in rest.char_indices() {
+            if in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => (),
+                }
+                continue;
+            }
+            match c {
+                '"' => in_string = true,
+                '[' | '{' => depth += 1,
+                ']' | '}' if depth > 0 => depth -= 1,
+                ',' | ';' | ')' | ']' | '}' if depth == 0 => {
+                    len = i;
+                    break;
+                }
+                _ => (),
+            }
+        }
+
+        let json = rest[..len].trim();
+        let value = serde_json::from_str(json)
+            .map_err(|e| self.error(format!("invalid JSON value `{}`: {}", json, e)))?;
+        self.advance(len);
+        Ok(value)
+    }
+
+    /// Read annotations such as `@namespace("org.example")`.
+    fn annotations(&mut self) -> Result<Map<String, Value>, Error> {
+        let mut annotations = Map::new();
+        while self.peek()?.is_symbol('@') {
+            self.next_token()?;
+            let name = self.ident()?;
+            self.expect('(')?;
+            let value = self.json()?;
+            self.expect(')')?;
+            annotations.insert(name, value);
+        }
+        Ok(annotations)
+    }
+
+    fn parse_file(&mut self) -> Result<Vec<Value>, Error> {
+        let mut declarations = Vec::new();
+        loop {
+            if self.peek()? == Token::Eof {
+                return Ok(declarations);
+            }
+
+            let doc = self.take_doc()?;
+            let annotations = self.annotations()?;
+            if self.peek()?.is_ident("protocol") {
+                self.next_token()?;
+                self.parse_protocol(&annotations, &mut declarations)?;
+            } else if let Some(declaration) = self.parse_declaration(doc, annotations, false)? {
+                declarations.push(declaration);
+            }
+        }
+    }
+
+    fn parse_protocol(
+        &mut self,
+        annotations: &Map<String, Value>,
+        declarations: &mut Vec<Value>,
+    ) -> Result<(), Error> {
+        let namespace = annotations.get("namespace");
+        self.ident()?;
+        self.expect('{')?;
+
+        while !self.peek()?.is_symbol('}') {
+            let doc = self.take_doc()?;
+            let annotations = self.annotations()?;
+            if let Some(mut declaration) = self.parse_declaration(doc, annotations, true)? {
+                if let (Some(namespace), Value::Object(ref mut declaration)) =
+                    (namespace, &mut declaration)
+                {
+                    // The namespace of the protocol is the default one of its types.
+                    declaration
+                        .entry("namespace")
+                        .or_insert_with(|| namespace.clone());
+                }
+                declarations.push(declaration);
+            }
+        }
+        self.expect('}')
+    }
+
+    /// Parse a named type declaration, or skip a message if `in_protocol`.
+    fn parse_declaration(
+        &mut self,
+        doc: Option<String>,
+        annotations: Map<String, Value>,
+        in_protocol: bool,
+    ) -> Result<Option<Value>, Error> {
+        let token = self.peek()?;
+        let mut declaration = match token {
+            Token::Ident(ref keyword) if keyword == "record" || keyword == "error" => {
+                self.next_token()?;
+                self.parse_record()?
+            }
+            Token::Ident(ref keyword) if keyword == "enum" => {
+                self.next_token()?;
+                self.parse_enum()?
+            }
+            Token::Ident(ref keyword) if keyword == "fixed" => {
+                self.next_token()?;
+                self.parse_fixed()?
+            }
+            Token::Ident(ref keyword) if keyword == "import" => {
+                return Err(self.error("imports are not supported"));
+            }
+            _ if in_protocol => {
+                self.skip_message()?;
+                return Ok(None);
+            }
+            ref token => {
+                return Err(self.error(format!(
+                    "expected a record, enum or fixed declaration, found {}",
+                    token.describe()
+                )))
+            }
+        };
+
+        if let Value::Object(ref mut declaration) = declaration {
+            if let Some(doc) = doc {
+                declaration.insert("doc".to_owned(), Value::String(doc));
+            }
+            declaration.extend(annotations);
+        }
+        Ok(Some(declaration))
+    }
+
+    fn parse_record(&mut self) -> Result<Value, Error> {
+        let name = self.ident()?;
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        while !self.peek()?.is_symbol('}') {
+            self.parse_fields(&mut fields)?;
+        }
+        self.expect('}')?;
+        Ok(json!({"type": "record", "name": name, "fields": fields}))
+    }
+
+    /// Parse a field declaration, which can declare several fields of the same type, such as
+    /// `string a, b = "foo";`.
+    fn parse_fields(&mut self, fields: &mut Vec<Value>) -> Result<(), Error> {
+        let doc = self.take_doc()?;
+        let schema = self.parse_annotated_type()?;
+        loop {
+            let mut field = Map::new();
+            field.extend(self.annotations()?);
+            field.insert("name".to_owned(), Value::String(self.ident()?));
+            field.insert("type".to_owned(), schema.clone());
+            if let Some(ref doc) = doc {
+                field.insert("doc".to_owned(), Value::String(doc.clone()));
+            }
+            if self.peek()?.is_symbol('=') {
+                self.next_token()?;
+                field.insert("default".to_owned(), self.json()?);
+            }
+            fields.push(Value::Object(field));
+
+            match self.next_token()? {
+                ref token if token.is_symbol(',') => continue,
+                ref token if token.is_symbol(';') => return Ok(()),
+                token => {
+                    return Err(self.error(format!("expected `;`, found {}", token.describe())))
+                }
+            }
+        }
+    }
+
+    fn parse_enum(&mut self) -> Result<Value, Error> {
+        let name = self.ident()?;
+        self.expect('{')?;
+        let mut symbols = Vec::new();
+        while !self.peek()?.is_symbol('}') {
+            symbols.push(Value::String(self.ident()?));
+            if !self.peek()?.is_symbol(',') {
+                break;
+            }
+            self.next_token()?;
+        }
+        self.expect('}')?;
+
+        let mut schema = json!({"type": "enum", "name": name, "symbols": symbols});
+        if self.peek()?.is_symbol('=') {
+            self.next_token()?;
+            schema["default"] = Value::String(self.ident()?);
+            self.expect(';')?;
+        }
+        Ok(schema)
+    }
+
+    fn parse_fixed(&mut self) -> Result<Value, Error> {
+        let name = self.ident()?;
+        self.expect('(')?;
+        let size = self.number()?;
+        self.expect(')')?;
+        self.expect(';')?;
+        Ok(json!({"type": "fixed", "name": name, "size": size}))
+    }
+
+    /// Parse a type preceded by annotations, which become properties of its schema.
+    fn parse_annotated_type(&mut self) -> Result<Value, Error> {
+        let annotations = self.annotations()?;
+        let schema = self.parse_type()?;
+        if annotations.is_empty() {
+            return Ok(schema);
+        }
+        match schema {
+            Value::String(name) => {
+                let mut schema = Map::new();
+                schema.insert("type".to_owned(), Value::String(name));
+                schema.extend(annotations);
+                Ok(Value::Object(schema))
+            }
+            Value::Object(mut schema) => {
+                schema.extend(annotations);
+                Ok(Value::Object(schema))
+            }
+            _ => Err(self.error("unions cannot be annotated")),
+        }
+    }
+
+    fn parse_type(&mut self) -> Result<Value, Error> {
+        let name = self.ident()?;
+        let schema = match name.as_str() {
+            "array" => {
+                self.expect('<')?;
+                let items = self.parse_annotated_type()?;
+                self.expect('>')?;
+                json!({"type": "array", "items": items})
+            }
+            "map" => {
+                self.expect('<')?;
+                let values = self.parse_annotated_type()?;
+                self.expect('>')?;
+                json!({"type": "map", "values": values})
+            }
+            "union" => {
+                self.expect('{')?;
+                let mut variants = vec![self.parse_annotated_type()?];
+                while self.peek()?.is_symbol(',') {
+                    self.next_token()?;
+                    variants.push(self.parse_annotated_type()?);
+                }
+                self.expect('}')?;
+                Value::Array(variants)
+            }
+            "decimal" => {
+                self.expect('(')?;
+                let precision = self.number()?;
+                self.expect(',')?;
+                let scale = self.number()?;
+                self.expect(')')?;
+                json!({
+                    "type": "bytes",
+                    "logicalType": "decimal",
+                    "precision": precision,
+                    "scale": scale,
+                })
+            }
+            "date" => json!({"type": "int", "logicalType": "date"}),
+            "time_ms" => json!({"type": "int", "logicalType": "time-millis"}),
+            "timestamp_ms" => json!({"type": "long", "logicalType": "timestamp-millis"}),
+            "uuid" => json!({"type": "string", "logicalType": "uuid"}),
+            // primitive types, and references to named types
+            _ => Value::String(name),
+        };
+
+        // `T?` is a shorthand for `union { null, T }`
+        if self.peek()?.is_symbol('?') {
+            self.next_token()?;
+            return Ok(Value::Array(vec![json!("null"), schema]));
+        }
+        Ok(schema)
+    }
+
+    /// Skip a message of a protocol, such as `string hello(string greeting) throws Oops;`.
+    fn skip_message(&mut self) -> Result<(), Error> {
+        if self.peek()?.is_ident("void") {
+            self.next_token()?;
+        } else {
+            self.parse_annotated_type()?;
+        }
+        self.ident()?;
+
+        self.expect('(')?;
+        if !self.peek()?.is_symbol(')') {
+            loop {
+                self.parse_annotated_type()?;
+                self.annotations()?;
+                self.ident()?;
+                if self.peek()?.is_symbol('=') {
+                    self.next_token()?;
+                    self.json()?;
+                }
+                if !self.peek()?.is_symbol(',') {
+                    break;
+                }
+                self.next_token()?;
+            }
+        }
+        self.expect(')')?;
+
+        if self.peek()?.is_ident("oneway") {
+            self.next_token()?;
+        } else if self.peek()?.is_ident("throws") {
+            self.next_token()?;
+            self.ident()?;
+            while self.peek()?.is_symbol(',') {
+                self.next_token()?;
+                self.ident()?;
+            }
+        }
+        self.expect(';')
+    }
+}
+
+/// Write `schema` in Avro IDL, as the declarations of the named types it contains.
+///
+/// Each named type is declared once, before the types using it, which reference it by fullname.
+/// The output can be read back with [`parse_idl`](fn.parse_idl.html), the last schema being
+/// `schema` itself:
+///
+/// ```
+/// use avro_rs::idl::{parse_idl, schema_to_idl};
+/// use avro_rs::Schema;
+///
+/// let schema = Schema::parse_str(
+///     r#"{"type": "record", "name": "Test", "fields": [{"name": "a", "type": "long"}]}"#,
+/// )
+/// .unwrap();
+/// assert_eq!(schema_to_idl(&schema), "record Test {\n  long a;\n}\n");
+/// assert_eq!(parse_idl(&schema_to_idl(&schema)).unwrap(), vec![schema]);
+/// ```
+///
+/// A schema which is not a named type is written as its type, such as `array<string>`, after the
+/// declarations of the named types it contains.
+pub fn schema_to_idl(schema: &Schema) -> String {
+    let mut writer = IdlWriter::default();
+    let ty = writer.write_type(schema);
+    if !schema.is_named() {
+        if !writer.out.is_empty() {
+            writer.out.push('\n');
+        }
+        writer.out.push_str(&ty);
+        writer.out.push('\n');
+    }
+    writer.out
+}
+
+/// Keywords of Avro IDL, escaped with backticks when used as identifiers.
+const KEYWORDS: &[&str] = &[
+    "array",
+    "boolean",
+    "bytes",
+    "date",
+    "decimal",
+    "double",
+    "enum",
+    "error",
+    "false",
+    "fixed",
+    "float",
+    "idl",
+    "import",
+    "int",
+    "long",
+    "map",
+    "null",
+    "oneway",
+    "protocol",
+    "record",
+    "schema",
+    "string",
+    "throws",
+    "time_ms",
+    "timestamp_ms",
+    "true",
+    "union",
+    "uuid",
+    "void",
+];
+
+fn escape(ident: &str) -> Cow<'_, str> {
+    if KEYWORDS.contains(&ident) {
+        Cow::Owned(format!("`{}`", ident))
+    } else {
+        Cow::Borrowed(ident)
+    }
+}
+
+#[derive(Default)]
+struct IdlWriter {
+    /// Fullnames of the named types declared so far.
+    declared: HashSet<String>,
+    out: String,
+}
+
+impl IdlWriter {
+    /// Return the IDL type of `schema`, declaring the named types it contains first.
+    fn write_type(&mut self, schema: &Schema) -> String {
+        match *schema {
+            Schema::Null => "null".to_owned(),
+            Schema::Boolean => "boolean".to_owned(),
+            Schema::Int => "int".to_owned(),
+            Schema::Long => "long".to_owned(),
+            Schema::Float => "float".to_owned(),
+            Schema::Double => "double".to_owned(),
+            Schema::Bytes => "bytes".to_owned(),
+            Schema::String => "string".to_owned(),
+            Schema::Array(ref items) => format!("array<{}>", self.write_type(items)),
+            Schema::Map(ref values) => format!("map<{}>", self.write_type(values)),
+            Schema::Union(ref union) => format!(
+                "union {{ {} }}",
+                union
+                    .variants()
+                    .iter()
+                    .map(|variant| self.write_type(variant))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Schema::Decimal {
+                precision,
+                scale,
+                ref inner,
+            } => match **inner {
+                Schema::Fixed { ref name, size } => {
+                    let annotations = format!(
+                        "@logicalType(\"decimal\")\n@precision({})\n@scale({})\n",
+                        precision, scale
+                    );
+                    self.declare_fixed(name, size, &annotations)
+                }
+                _ => format!("decimal({}, {})", precision, scale),
+            },
+            Schema::Date => "date".to_owned(),
+            Schema::TimeMillis => "time_ms".to_owned(),
+            Schema::TimeMicros => "@logicalType(\"time-micros\") long".to_owned(),
+            Schema::TimestampMillis => "timestamp_ms".to_owned(),
+            Schema::TimestampMicros => "@logicalType(\"timestamp-micros\") long".to_owned(),
+            Schema::Uuid => "uuid".to_owned(),
+            Schema::Duration { ref name } => {
+                self.declare_fixed(name, 12, "@logicalType(\"duration\")\n")
+            }
+            Schema::Fixed { ref name, size } => self.declare_fixed(name, size, ""),
+            Schema::Enum {
+                ref name,
+                ref doc,
+                ref symbols,
+            } => {
+                if self.declared.insert(name.full_name()) {
+                    let symbols = symbols
+                        .iter()
+                        .map(|symbol| escape(symbol))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    self.write_header(name, doc);
+                    self.out.push_str(&format!(
+                        "enum {} {{\n  {}\n}}\n",
+                        escape(&name.name),
+                        symbols
+                    ));
+                }
+                name.full_name()
+            }
+            Schema::Record {
+                ref name,
+                ref doc,
+                ref fields,
+                ..
+            } => {
+                if self.declared.insert(name.full_name()) {
+                    // The named types of the fields are declared before the record.
+                    let mut body = String::new();
+                    for field in fields {
+                        if let Some(ref doc) = field.doc {
+                            body.push_str(&format!("  /** {} */\n", doc));
+                        }
+                        body.push_str(&format!("  {} ", self.write_type(&field.schema)));
+                        match field.order {
+                            RecordFieldOrder::Ascending => (),
+                            RecordFieldOrder::Descending => {
+                                body.push_str("@order(\"descending\") ")
+                            }
+                            RecordFieldOrder::Ignore => body.push_str("@order(\"ignore\") "),
+                        }
+                        body.push_str(&escape(&field.name));
+                        if let Some(ref default) = field.default {
+                            body.push_str(&format!(" = {}", default));
+                        }
+                        body.push_str(";\n");
+                    }
+                    self.write_header(name, doc);
+                    self.out
+                        .push_str(&format!("record {} {{\n{}}}\n", escape(&name.name), body));
+                }
+                name.full_name()
+            }
+            Schema::Ref { ref name } => name.full_name(),
+        }
+    }
+
+    fn declare_fixed(&mut self, name: &Name, size: usize, annotations: &str) -> String {
+        if self.declared.insert(name.full_name()) {
+            self.write_header(name, &None);
+            self.out.push_str(annotations);
+            self.out
+                .push_str(&format!("fixed {}({});\n", escape(&name.name), size));
+        }
+        name.full_name()
+    }
+
+    /// Write the documentation, namespace and aliases of a named type.
+    fn write_header(&mut self, name: &Name, doc: &Documentation) {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        if let Some(ref doc) = *doc {
+            self.out.push_str(&format!("/** {} */\n", doc));
+        }
+        if let Some(ref namespace) = name.namespace {
+            self.out.push_str(&format!(
+                "@namespace({})\n",
+                Value::String(namespace.clone())
+            ));
+        }
+        if let Some(ref aliases) = name.aliases {
+            self.out
+                .push_str(&format!("@aliases({})\n", json!(aliases)));
+        }
+    }
+}
+
+/// Content of a documentation comment, without the leading `*` of its lines.
+fn doc_comment(comment: &str) -> String {
+    comment
+        .lines()
+        .map(|line| line.trim().trim_start_matches('*').trim())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{SchemaBuilder, UnionSchema};
+
+    fn parse_one(source: &str) -> Schema {
+        let mut schemas = parse_idl(source).unwrap();
+        assert_eq!(schemas.len(), 1);
+        schemas.remove(0)
+    }
+
+    fn field_schema(source: &str) -> Schema {
+        match parse_one(&format!("record R {{ {} f; }}", source)) {
+            Schema::Record { mut fields, .. } => fields.remove(0).schema,
+            schema => panic!("{:?} is not a record", schema),
+        }
+    }
+
+    #[test]
+    fn test_idl_protocol() {
+        let schemas = parse_idl(
+            r#"
+            // A comment
+            @namespace("org.example")
+            protocol Example {
+                /** The colors. */
+                enum Color { RED, GREEN, BLUE }
+
+                fixed Md5(16);
+
+                /* Not a doc comment */
+                record Pixel {
+                    int x, y;
+                    Color color = "RED";
+                    org.example.Md5 hash;
+                }
+
+                Pixel get(int x, int y = 0) throws Oops;
+                void ping() oneway;
+            }
+            "#,
+        )
+        .unwrap();
+
+        let color = SchemaBuilder::enumeration("Color", vec!["RED", "GREEN", "BLUE"])
+            .namespace("org.example")
+            .doc("The colors.")
+            .build()
+            .unwrap();
+        let md5 = Schema::Fixed {
+            name: Name::new("org.example.Md5"),
+            size: 16,
+        };
+        let pixel = SchemaBuilder::record("Pixel")
+            .namespace("org.example")
+            .field("x", Schema::Int)
+            .field("y", Schema::Int)
+            .field_with_default("color", color.clone(), json!("RED"))
+            .field("hash", md5.clone())
+            .build()
+            .unwrap();
+        assert_eq!(schemas, vec![color, md5, pixel]);
+    }
+
+    #[test]
+    fn test_idl_declarations() {
+        let schemas = parse_idl(
+            r#"
+            @namespace("org.example") @aliases(["org.example.OldColor"])
+            enum Color { RED, GREEN } = RED;
+            @namespace("org.example")
+            record Node {
+                long `value`;
+                Node? next = null;
+                org.example.Color color;
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(schemas.len(), 2);
+        assert_eq!(
+            schemas[0].name().map(Name::full_name),
+            Some("org.example.Color".to_owned())
+        );
+        assert_eq!(
+            schemas[1].to_json()["fields"][1],
+            json!({"name": "next", "type": ["null", "org.example.Node"], "default": null})
+        );
+    }
+
+    #[test]
+    fn test_idl_types() {
+        let primitives = vec![
+            ("null", Schema::Null),
+            ("boolean", Schema::Boolean),
+            ("int", Schema::Int),
+            ("long", Schema::Long),
+            ("float", Schema::Float),
+            ("double", Schema::Double),
+            ("bytes", Schema::Bytes),
+            ("string", Schema::String),
+        ];
+        for (idl, schema) in primitives {
+            assert_eq!(field_schema(idl), schema);
+        }
+
+        assert_eq!(
+            field_schema("array<string>"),
+            Schema::Array(Box::new(Schema::String))
+        );
+        assert_eq!(
+            field_schema("map<array<int>>"),
+            Schema::Map(Box::new(Schema::Array(Box::new(Schema::Int))))
+        );
+        assert_eq!(
+            field_schema("union { null, string, map<long> }"),
+            Schema::Union(
+                UnionSchema::new(vec![
+                    Schema::Null,
+                    Schema::String,
+                    Schema::Map(Box::new(Schema::Long))
+                ])
+                .unwrap()
+            )
+        );
+        assert_eq!(
+            field_schema("string?"),
+            Schema::Union(UnionSchema::new(vec![Schema::Null, Schema::String]).unwrap())
+        );
+        assert_eq!(
+            field_schema("decimal(9, 2)"),
+            Schema::Decimal {
+                precision: 9,
+                scale: 2,
+                inner: Box::new(Schema::Bytes),
+            }
+        );
+        assert_eq!(field_schema("date"), Schema::Date);
+        assert_eq!(field_schema("time_ms"), Schema::TimeMillis);
+        assert_eq!(field_schema("timestamp_ms"), Schema::TimestampMillis);
+        assert_eq!(
+            field_schema(r#"@logicalType("timestamp-micros") long"#),
+            Schema::TimestampMicros
+        );
+    }
+
+    #[test]
+    fn test_idl_field_properties() {
+        let schema = parse_one(
+            r#"
+            record R {
+                /**
+                 * The a field.
+                 */
+                string @order("descending") @aliases(["old_a"]) a = "foo";
+                array<int> b = [1, 2];
+                map<string> c = {"k": "v, ;)"};
+            }
+            "#,
+        );
+        assert_eq!(
+            schema.to_json()["fields"],
+            json!([
+                {"name": "a", "type": "string", "doc": "The a field.", "default": "foo", "order": "descending"},
+                {"name": "b", "type": {"type": "array", "items": "int"}, "default": [1, 2]},
+                {"name": "c", "type": {"type": "map", "values": "string"}, "default": {"k": "v, ;)"}}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_idl_errors() {
+        let invalid = vec![
+            r#"import idl "other.avdl";"#,
+            r#"protocol P { import schema "other.avsc"; }"#,
+            "record R { long a }",
+            "record R { long a; ",
+            "record R { Unknown a; }",
+            "record R { long a = ; }",
+            "record R { record S {} }",
+            "fixed F(big);",
+            "enum E { A B }",
+            "long hello();",
+            "/* unterminated",
+        ];
+        for source in invalid {
+            assert!(parse_idl(source).is_err(), "{}", source);
+        }
+
+        let err = parse_idl("record R {\n long a\n}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse IDL: line 3: expected `;`, found `}`"
+        );
+    }
+
+    #[test]
+    fn test_schema_to_idl() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "Pixel",
+                "namespace": "org.example",
+                "doc": "A pixel",
+                "aliases": ["Dot"],
+                "fields": [
+                    {"name": "x", "type": "int", "doc": "The x coordinate", "default": 0},
+                    {"name": "color", "type": {"type": "enum", "name": "Color", "symbols": ["RED", "GREEN"]}},
+                    {"name": "next", "type": ["null", "Pixel"], "default": null, "order": "ignore"},
+                    {"name": "tags", "type": {"type": "map", "values": {"type": "array", "items": "Color"}}}
+                ]
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            schema_to_idl(&schema),
+            r#"@namespace("org.example")
+enum Color {
+  RED, GREEN
+}
+
+/** A pixel */
+@namespace("org.example")
+@aliases(["Dot"])
+record Pixel {
+  /** The x coordinate */
+  int x = 0;
+  org.example.Color color;
+  union { null, org.example.Pixel } @order("ignore") next = null;
+  map<array<org.example.Color>> tags;
+}
+"#
+        );
+        assert_eq!(
+            schema_to_idl(&Schema::Array(Box::new(Schema::String))),
+            "array<string>\n"
+        );
+    }
+
+    #[test]
+    fn test_idl_round_trip() {
+        let source = r#"
+            @namespace("org.example")
+            protocol Example {
+                enum Color { RED, GREEN, BLUE }
+                fixed Md5(16);
+                @logicalType("decimal") @precision(4) @scale(2) fixed Price(2);
+                @logicalType("duration") fixed Elapsed(12);
+
+                /** A record */
+                record `Record` {
+                    /** A field */
+                    long `long` = 42;
+                    string @order("descending") name;
+                    union { null, Color, array<Md5> } choice = null;
+                    map<Record> children = {};
+                    decimal(9, 2) amount;
+                    Price price;
+                    Elapsed elapsed;
+                    date day;
+                    time_ms time;
+                    @logicalType("time-micros") long time_us;
+                    timestamp_ms timestamp;
+                    @logicalType("timestamp-micros") long timestamp_us;
+                }
+            }
+        "#;
+        let schemas = parse_idl(source).unwrap();
+        let schema = schemas.last().unwrap();
+
+        let idl = schema_to_idl(schema);
+        let round_trip = parse_idl(&idl).unwrap();
+        assert_eq!(round_trip.last(), Some(schema));
+        assert_eq!(schema_to_idl(round_trip.last().unwrap()), idl);
+    }
+}
@@ -0,0 +1,430 @@
+//! Logic handling the JSON encoding of Avro data, as defined by the
+//! [Avro Specification](https://avro.apache.org/docs/current/spec.html#json_encoding).
+use failure::{err_msg, Error};
+use serde_json::{Map, Number, Value as JsonValue};
+
+use crate::schema::{default_to_bytes, resolve_ref, Definitions, Schema};
+use crate::types::{describe_schema, latin1_string, uuid_value, Value};
+use crate::util::DecodeError;
+
+/// Encode a `Value` into its JSON representation, according to the given `Schema`.
+///
+/// Non-null union values are wrapped into an object keyed by the name of their branch, such as
+/// `{"string": "foo"}`, `bytes` and `fixed` values are strings whose characters are the code
+/// points 0-255 of their bytes (ISO-8859-1), and enum values are their symbol.
+///
+/// ```
+/// use avro_rs::{types::Value, value_to_avro_json, Schema};
+/// use serde_json::json;
+///
+/// let schema = Schema::parse_str(r#"["null", "string"]"#).unwrap();
+/// let value = Value::Union(Box::new(Value::String("foo".into())));
+///
+/// assert_eq!(value_to_avro_json(&value, &schema).unwrap(), json!({"string": "foo"}));
+/// ```
+pub fn value_to_avro_json(value: &Value, schema: &Schema) -> Result<JsonValue, Error> {
+    if let Err(mut errors) = value.validate_detailed(schema) {
+        return Err(errors.remove(0).into());
+    }
+    to_json(value, schema, &mut Definitions::new(schema))
+}
+
+/// Decode the JSON representation of a `Value` written with the given `Schema`.
+///
+/// This is the inverse of [`value_to_avro_json`](fn.value_to_avro_json.html). Union branches are
+/// looked up by name or fullname, and missing record fields take their default value.
+pub fn value_from_avro_json(json: JsonValue, schema: &Schema) -> Result<Value, Error> {
+    from_json(&json, schema, &mut Definitions::new(schema))
+}
+
+/// Name keying a union branch of type `schema` in the JSON encoding.
+fn branch_name(schema: &Schema) -> String {
+    match *schema {
+        Schema::Null => "null".to_owned(),
+        Schema::Boolean => "boolean".to_owned(),
+        Schema::Int | Schema::Date | Schema::TimeMillis => "int".to_owned(),
+        Schema::Long | Schema::TimeMicros | Schema::TimestampMillis | Schema::TimestampMicros => {
+            "long".to_owned()
+        }
+        Schema::Float => "float".to_owned(),
+        Schema::Double => "double".to_owned(),
+        Schema::Bytes => "bytes".to_owned(),
+        Schema::String | Schema::Uuid => "string".to_owned(),
+        Schema::Array(_) => "array".to_owned(),
+        Schema::Map(_) => "map".to_owned(),
+        Schema::Union(_) => "union".to_owned(),
+        Schema::Decimal { ref inner, .. } => branch_name(inner),
+        Schema::Record { ref name, .. }
+        | Schema::Enum { ref name, .. }
+        | Schema::Fixed { ref name, .. }
+        | Schema::Duration { ref name }
+        | Schema::Ref { ref name } => name.full_name(),
+    }
+}
+
+fn float_to_json(f: f64) -> Result<JsonValue, Error> {
+    Number::from_f64(f)
+        .map(JsonValue::Number)
+        .ok_or_else(|| err_msg(format!("{} cannot be represented in JSON", f)))
+}
+
+fn to_json<'s>(
+    value: &Value,
+    schema: &'s Schema,
+    definitions: &mut Definitions<'s>,
+) -> Result<JsonValue, Error> {
+    let json = match (value, schema) {
+        (_, &Schema::Ref { ref name }) => {
+            let definition = resolve_ref(name, definitions)?;
+            return to_json(value, definition, definitions);
+        }
+        (&Value::Union(ref inner), &Schema::Union(_)) => {
+            return to_json(inner, schema, definitions)
+        }
+        (_, &Schema::Union(ref inner)) => {
+            let variant = inner
+                .variants()
+                .iter()
+                .find(|variant| value.validate_internal(variant, definitions))
+                .ok_or_else(|| err_msg("value does not match given schema"))?;
+            match *variant {
+                Schema::Null => JsonValue::Null,
+                _ => {
+                    let mut object = Map::new();
+                    object.insert(branch_name(variant), to_json(value, variant, definitions)?);
+                    JsonValue::Object(object)
+                }
+            }
+        }
+        (&Value::Null, _) => JsonValue::Null,
+        (&Value::Boolean(b), _) => JsonValue::Bool(b),
+        (&Value::Int(i), _) | (&Value::Date(i), _) | (&Value::TimeMillis(i), _) => {
+            JsonValue::from(i)
+        }
+        (&Value::Long(i), _)
+        | (&Value::TimeMicros(i), _)
+        | (&Value::TimestampMillis(i), _)
+        | (&Value::TimestampMicros(i), _) => JsonValue::from(i),
+        (&Value::Float(f), _) => float_to_json(f64::from(f))?,
+        (&Value::Double(d), _) => float_to_json(d)?,
+        (&Value::Bytes(ref bytes), _) | (&Value::Fixed(_, ref bytes), _) => {
+            JsonValue::String(latin1_string(bytes))
+        }
+        (&Value::Decimal(ref decimal), _) => JsonValue::String(latin1_string(&decimal.to_vec())),
+        (&Value::Duration(duration), _) => {
+            let bytes: [u8; 12] = duration.into();
+            JsonValue::String(latin1_string(&bytes))
+        }
+        (&Value::String(ref s), _) | (&Value::Enum(_, ref s), _) => JsonValue::String(s.clone()),
+        #[cfg(feature = "uuid")]
+        (&Value::Uuid(uuid), _) => JsonValue::String(uuid.to_string()),
+        (&Value::Array(ref items), &Schema::Array(ref inner)) => JsonValue::Array(
+            items
+                .iter()
+                .map(|item| to_json(item, inner, definitions))
+                .collect::<Result<_, _>>()?,
+        ),
+        (&Value::Map(ref items), &Schema::Map(ref inner)) => JsonValue::Object(
+            items
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), to_json(value, inner, definitions)?)))
+                .collect::<Result<_, Error>>()?,
+        ),
+        (&Value::Record(ref record_fields), &Schema::Record { ref fields, .. }) => {
+            let object = fields
+                .iter()
+                .zip(record_fields.iter())
+                .map(|(field, &(ref name, ref value))| {
+                    Ok((name.clone(), to_json(value, &field.schema, definitions)?))
+                })
+                .collect::<Result<_, Error>>();
+            JsonValue::Object(object?)
+        }
+        _ => return Err(err_msg("value does not match given schema")),
+    };
+
+    Ok(json)
+}
+
+fn from_json<'s>(
+    json: &JsonValue,
+    schema: &'s Schema,
+    definitions: &mut Definitions<'s>,
+) -> Result<Value, Error> {
+    let mismatch = || {
+        DecodeError::new(format!(
+            "{} is not a valid {}",
+            json,
+            describe_schema(schema)
+        ))
+    };
+    let int = |n: &Number| {
+        n.as_i64()
+            .filter(|&n| n >= i64::from(i32::MIN) && n <= i64::from(i32::MAX))
+            .map(|n| n as i32)
+            .ok_or_else(mismatch)
+    };
+    let bytes = |s: &str, size: Option<usize>| {
+        default_to_bytes(s)
+            .filter(|bytes| size.iter().all(|&size| bytes.len() == size))
+            .ok_or_else(mismatch)
+    };
+
+    let value = match (json, schema) {
+        (_, &Schema::Ref { ref name }) => {
+            let definition = resolve_ref(name, definitions)?;
+            return from_json(json, definition, definitions);
+        }
+        (&JsonValue::Null, &Schema::Union(ref inner)) => match inner.null_index() {
+            Some(_) => Value::Union(Box::new(Value::Null)),
+            None => return Err(mismatch().into()),
+        },
+        (&JsonValue::Object(ref object), &Schema::Union(ref inner)) if object.len() == 1 => {
+            let (name, json) = object.iter().next().ok_or_else(mismatch)?;
+            let (_, variant) = inner.find_variant_by_name(name).ok_or_else(mismatch)?;
+            Value::Union(Box::new(from_json(json, variant, definitions)?))
+        }
+        (&JsonValue::Null, &Schema::Null) => Value::Null,
+        (&JsonValue::Bool(b), &Schema::Boolean) => Value::Boolean(b),
+        (&JsonValue::Number(ref n), &Schema::Int) => Value::Int(int(n)?),
+        (&JsonValue::Number(ref n), &Schema::Date) => Value::Date(int(n)?),
+        (&JsonValue::Number(ref n), &Schema::TimeMillis) => Value::TimeMillis(int(n)?),
+        (&JsonValue::Number(ref n), &Schema::Long) => Value::Long(n.as_i64().ok_or_else(mismatch)?),
+        (&JsonValue::Number(ref n), &Schema::TimeMicros) => {
+            Value::TimeMicros(n.as_i64().ok_or_else(mismatch)?)
+        }
+        (&JsonValue::Number(ref n), &Schema::TimestampMillis) => {
+            Value::TimestampMillis(n.as_i64().ok_or_else(mismatch)?)
+        }
+        (&JsonValue::Number(ref n), &Schema::TimestampMicros) => {
+            Value::TimestampMicros(n.as_i64().ok_or_else(mismatch)?)
+        }
+        (&JsonValue::Number(ref n), &Schema::Float) => {
+            Value::Float(n.as_f64().ok_or_else(mismatch)? as f32)
+        }
+        (&JsonValue::Number(ref n), &Schema::Double) => {
+            Value::Double(n.as_f64().ok_or_else(mismatch)?)
+        }
+        (&JsonValue::String(ref s), &Schema::String) => Value::String(s.clone()),
+        (&JsonValue::String(ref s), &Schema::Uuid) => uuid_value(s.clone())?,
+        (&JsonValue::String(ref s), &Schema::Bytes) => Value::Bytes(bytes(s, None)?),
+        (&JsonValue::String(ref s), &Schema::Fixed { size, .. }) => {
+            Value::Fixed(size, bytes(s, Some(size))?)
+        }
+        (&JsonValue::String(_), &Schema::Decimal { ref inner, .. }) => {
+            match from_json(json, inner, definitions)? {
+                Value::Bytes(bytes) | Value::Fixed(_, bytes) => Value::Decimal(bytes.into()),
+                _ => return Err(mismatch().into()),
+            }
+        }
+        (&JsonValue::String(ref s), &Schema::Duration { .. }) => {
+            let mut array = [0u8; 12];
+            array.copy_from_slice(&bytes(s, Some(12))?);
+            Value::Duration(array.into())
+        }
+        (&JsonValue::String(ref s), &Schema::Enum { ref symbols, .. }) => {
+            let index = symbols
+                .iter()
+                .position(|symbol| symbol == s)
+                .ok_or_else(mismatch)?;
+            Value::Enum(index as i32, s.clone())
+        }
+        (&JsonValue::Array(ref items), &Schema::Array(ref inner)) => Value::Array(
+            items
+                .iter()
+                .map(|item| from_json(item, inner, definitions))
+                .collect::<Result<_, _>>()?,
+        ),
+        (&JsonValue::Object(ref items), &Schema::Map(ref inner)) => Value::Map(
+            items
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), from_json(value, inner, definitions)?)))
+                .collect::<Result<_, Error>>()?,
+        ),
+        (&JsonValue::Object(ref items), &Schema::Record { ref fields, .. }) => {
+            let record_fields = fields
+                .iter()
+                .map(|field| {
+                    let value = match items.get(&field.name) {
+                        Some(value) => from_json(value, &field.schema, definitions)?,
+                        None => field.default_as_value()?.ok_or_else(|| {
+                            DecodeError::new(format!("missing field {}", field.name))
+                        })?,
+                    };
+                    Ok((field.name.clone(), value))
+                })
+                .collect::<Result<_, Error>>();
+            Value::Record(record_fields?)
+        }
+        _ => return Err(mismatch().into()),
+    };
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Duration;
+    use serde_json::json;
+
+    fn check(schema: &str, value: Value, json: JsonValue) {
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(value_to_avro_json(&value, &schema).unwrap(), json);
+        assert_eq!(value_from_avro_json(json, &schema).unwrap(), value);
+    }
+
+    #[test]
+    fn test_primitives() {
+        check(r#""null""#, Value::Null, json!(null));
+        check(r#""boolean""#, Value::Boolean(true), json!(true));
+        check(r#""int""#, Value::Int(-42), json!(-42));
+        check(r#""long""#, Value::Long(1 << 40), json!(1i64 << 40));
+        check(r#""float""#, Value::Float(1.5), json!(1.5));
+        check(r#""double""#, Value::Double(-0.25), json!(-0.25));
+        check(r#""string""#, Value::String("é".into()), json!("é"));
+        check(
+            r#""bytes""#,
+            Value::Bytes(vec![0, 0x41, 0xff]),
+            json!("\u{0}A\u{ff}"),
+        );
+    }
+
+    #[test]
+    fn test_logical_types() {
+        check(
+            r#"{"type": "int", "logicalType": "date"}"#,
+            Value::Date(17000),
+            json!(17000),
+        );
+        check(
+            r#"{"type": "long", "logicalType": "timestamp-micros"}"#,
+            Value::TimestampMicros(1_500_000_000_000_000),
+            json!(1_500_000_000_000_000i64),
+        );
+        check(
+            r#"{"type": "bytes", "logicalType": "decimal", "precision": 4, "scale": 2}"#,
+            Value::Decimal(vec![0x01, 0xe8].into()),
+            json!("\u{1}\u{e8}"),
+        );
+        check(
+            r#"{"type": "fixed", "name": "d", "size": 12, "logicalType": "duration"}"#,
+            Value::Duration(Duration::from([1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0])),
+            json!("\u{1}\u{0}\u{0}\u{0}\u{2}\u{0}\u{0}\u{0}\u{3}\u{0}\u{0}\u{0}"),
+        );
+    }
+
+    #[test]
+    fn test_complex_types() {
+        check(
+            r#"{"type": "fixed", "name": "f", "size": 2}"#,
+            Value::Fixed(2, vec![0x80, 0x7f]),
+            json!("\u{80}\u{7f}"),
+        );
+        check(
+            r#"{"type": "enum", "name": "e", "symbols": ["a", "b"]}"#,
+            Value::Enum(1, "b".into()),
+            json!("b"),
+        );
+        check(
+            r#"{"type": "array", "items": "long"}"#,
+            Value::Array(vec![Value::Long(1), Value::Long(2)]),
+            json!([1, 2]),
+        );
+        check(
+            r#"{"type": "map", "values": "string"}"#,
+            Value::Map(
+                vec![("a".to_owned(), Value::String("b".into()))]
+                    .into_iter()
+                    .collect(),
+            ),
+            json!({"a": "b"}),
+        );
+        check(
+            r#"["null", "long", {"type": "enum", "name": "e", "namespace": "ns", "symbols": ["a"]}]"#,
+            Value::Union(Box::new(Value::Enum(0, "a".into()))),
+            json!({"ns.e": "a"}),
+        );
+        check(
+            r#"["null", "long"]"#,
+            Value::Union(Box::new(Value::Null)),
+            json!(null),
+        );
+    }
+
+    #[test]
+    fn test_nested_records() {
+        let schema = r#"{
+            "type": "record",
+            "name": "Node",
+            "namespace": "ns",
+            "fields": [
+                {"name": "label", "type": ["null", "string"]},
+                {"name": "children", "type": {"type": "array", "items": "Node"}},
+                {"name": "next", "type": ["null", "Node"]}
+            ]
+        }"#;
+        let leaf = |label: Value| {
+            Value::Record(vec![
+                ("label".to_owned(), Value::Union(Box::new(label))),
+                ("children".to_owned(), Value::Array(vec![])),
+                ("next".to_owned(), Value::Union(Box::new(Value::Null))),
+            ])
+        };
+        let value = Value::Record(vec![
+            ("label".to_owned(), Value::Union(Box::new(Value::Null))),
+            (
+                "children".to_owned(),
+                Value::Array(vec![leaf(Value::String("a".into()))]),
+            ),
+            ("next".to_owned(), Value::Union(Box::new(leaf(Value::Null)))),
+        ]);
+        check(
+            schema,
+            value,
+            json!({
+                "label": null,
+                "children": [{"label": {"string": "a"}, "children": [], "next": null}],
+                "next": {"ns.Node": {"label": null, "children": [], "next": null}}
+            }),
+        );
+    }
+
+    #[test]
+    fn test_from_json_lenient_inputs() {
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "r", "namespace": "ns", "fields": [
+                {"name": "a", "type": ["null", {"type": "fixed", "name": "f", "size": 1}]},
+                {"name": "b", "type": "int", "default": 3}
+            ]}"#,
+        )
+        .unwrap();
+
+        // Named branches can be keyed by their name, and missing fields take their default.
+        assert_eq!(
+            value_from_avro_json(json!({"a": {"f": "x"}}), &schema).unwrap(),
+            Value::Record(vec![
+                (
+                    "a".to_owned(),
+                    Value::Union(Box::new(Value::Fixed(1, vec![b'x'])))
+                ),
+                ("b".to_owned(), Value::Int(3)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let schema = Schema::parse_str(r#"["null", "int"]"#).unwrap();
+        assert!(value_to_avro_json(&Value::String("foo".into()), &schema).is_err());
+        assert!(value_from_avro_json(json!(1), &schema).is_err());
+        assert!(value_from_avro_json(json!({"long": 1}), &schema).is_err());
+        assert!(value_from_avro_json(json!({"int": 1i64 << 40}), &schema).is_err());
+
+        let schema = Schema::parse_str(r#"{"type": "fixed", "name": "f", "size": 2}"#).unwrap();
+        assert!(value_from_avro_json(json!("a"), &schema).is_err());
+        assert!(value_from_avro_json(json!("\u{100}a"), &schema).is_err());
+
+        assert!(value_to_avro_json(&Value::Double(std::f64::NAN), &Schema::Double).is_err());
+    }
+}
@@ -42,6 +42,50 @@
 //! features = ["snappy"]
 //! ```
 //!
+//! The **Bzip2** and **Xz** codecs are available through the `bzip2` and `xz` features
+//! respectively.
+//!
+//! Conversions between logical types and their [chrono](https://docs.rs/chrono) counterparts
+//! (e.g. `Value::Date` and `chrono::NaiveDate`) are available through the `chrono` feature:
+//!
+//! ```text
+//! [dependencies.avro-rs]
+//! version = "x.y"
+//! features = ["chrono"]
+//! ```
+//!
+//! It also provides [`avro_date`](avro_date/index.html), to serialize `chrono::NaiveDate` fields
+//! as `Value::Date` with `#[serde(with = "avro_rs::avro_date")]`.
+//!
+//! Values of the `uuid` logical type are read as `Value::Uuid`, holding a
+//! [uuid](https://docs.rs/uuid) `Uuid`, through the `uuid` feature (and as `Value::String`
+//! otherwise):
+//!
+//! ```text
+//! [dependencies.avro-rs]
+//! version = "x.y"
+//! features = ["uuid"]
+//! ```
+//!
+//! Schemas and conversions to `Value` can be derived from Rust types with
+//! `#[derive(AvroSchema, ToAvro)]` through the `derive` feature (see
+//! [`AvroSchema`](schema/trait.AvroSchema.html)):
+//!
+//! ```text
+//! [dependencies.avro-rs]
+//! version = "x.y"
+//! features = ["derive"]
+//! ```
+//!
+//! Values can be read and written with [tokio](https://docs.rs/tokio) asynchronous I/O through
+//! `AsyncReader` and `AsyncWriter`, which are available through the `async` feature:
+//!
+//! ```text
+//! [dependencies.avro-rs]
+//! version = "x.y"
+//! features = ["async"]
+//! ```
+//!
 //! # Defining a schema
 //!
 //! An Avro data cannot exist without an Avro schema. Schemas **must** be used while writing and
@@ -198,7 +242,7 @@
 //!
 //! ## Using codecs to compress data
 //!
-//! Avro supports three different compression codecs when encoding data:
+//! Avro supports five different compression codecs when encoding data:
 //!
 //! * **Null**: leaves data uncompressed;
 //! * **Deflate**: writes the data block using the deflate algorithm as specified in RFC 1951, and
@@ -207,6 +251,10 @@
 //! * **Snappy**: uses Google's [Snappy](http://google.github.io/snappy/) compression library. Each
 //! compressed block is followed by the 4-byte, big-endianCRC32 checksum of the uncompressed data in
 //! the block. You must enable the `snappy` feature to use this codec.
+//! * **Bzip2**: uses the [bzip2](https://sourceware.org/bzip2/) compression library. You must
+//! enable the `bzip2` feature to use this codec.
+//! * **Xz**: uses the [xz](https://tukaani.org/xz/) compression library. You must enable the `xz`
+//! feature to use this codec.
 //!
 //! To specify a codec to use to compress data, just specify it while creating a `Writer`:
 //! ```
@@ -465,26 +513,55 @@
 //! }
 //! ```
 
+#[cfg(feature = "async")]
+mod async_io;
 mod codec;
+mod compatibility;
+mod datum;
 mod de;
+mod decimal;
 mod decode;
+mod duration;
 mod encode;
+mod json;
+mod rabin;
 mod reader;
 mod ser;
 mod util;
 mod writer;
 
+#[cfg(feature = "chrono")]
+pub mod avro_date;
+pub mod confluent;
+pub mod encoding;
+pub mod idl;
 pub mod schema;
 pub mod types;
 
+#[cfg(feature = "async")]
+pub use crate::async_io::{AsyncReader, AsyncWriter};
 pub use crate::codec::Codec;
-pub use crate::de::from_value;
-pub use crate::reader::{from_avro_datum, Reader};
-pub use crate::schema::{ParseSchemaError, Schema};
-pub use crate::ser::to_value;
-pub use crate::types::SchemaResolutionError;
+pub use crate::compatibility::{
+    check_compatibility, check_compatibility_with_history, CompatibilityMode, CompatibilityResult,
+    IncompatibleChange,
+};
+pub use crate::datum::{DatumReader, DatumWriter, GenericDatumReader, GenericDatumWriter};
+pub use crate::de::{from_value, from_value_owned};
+pub use crate::decimal::Decimal;
+pub use crate::decode::decode_from_slice;
+pub use crate::duration::{Duration, DurationError};
+pub use crate::encode::{encode, encode_to_vec, estimate_encoded_size};
+pub use crate::json::{value_from_avro_json, value_to_avro_json};
+pub use crate::rabin::Rabin;
+pub use crate::reader::{decode_single_object, from_avro_datum, Reader, ValueDecoder};
+pub use crate::schema::{AvroSchema, ParseSchemaError, Schema};
+pub use crate::ser::{to_value, to_value_with_schema, SchemaGuidedSerializer};
+pub use crate::types::{resolve, SchemaResolutionError, ValidationError};
 pub use crate::util::{max_allocation_bytes, DecodeError};
-pub use crate::writer::{to_avro_datum, ValidationError, Writer};
+pub use crate::writer::{encode_single_object, to_avro_datum, ValueEncoder, Writer};
+
+#[cfg(feature = "derive")]
+pub use avro_derive::AvroSchema;
 
 #[cfg(test)]
 mod tests {
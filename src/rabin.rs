@@ -0,0 +1,107 @@
+//! Implementation of the Rabin fingerprint algorithm, as specified by Avro.
+use digest::{
+    generic_array::{typenum::U8, GenericArray},
+    FixedOutput, Input, Reset,
+};
+
+/// Both the initial value of the fingerprint and the polynomial used to build `FP_TABLE`.
+const EMPTY: u64 = 0xc15d_213a_a4d7_a795;
+
+const FP_TABLE: [u64; 256] = fp_table();
+
+const fn fp_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut fp = i as u64;
+        let mut j = 0;
+        while j < 8 {
+            fp = (fp >> 1) ^ (EMPTY & (fp & 1).wrapping_neg());
+            j += 1;
+        }
+        table[i] = fp;
+        i += 1;
+    }
+    table
+}
+
+/// Implementation of the 64-bit Rabin fingerprint (CRC-64-AVRO) used by Avro for
+/// [schema fingerprints](https://avro.apache.org/docs/current/spec.html#schema_fingerprints).
+///
+/// It implements the `Digest` trait, so it can be used with
+/// [Schema::fingerprint](../schema/enum.Schema.html#method.fingerprint). The resulting 8 bytes
+/// are the little-endian representation of the fingerprint, as expected by the
+/// [single object encoding](https://avro.apache.org/docs/current/spec.html#single_object_encoding).
+///
+/// ```
+/// use avro_rs::{Rabin, Schema};
+///
+/// let schema = Schema::parse_str(r#""null""#).unwrap();
+/// assert_eq!(format!("{}", schema.fingerprint::<Rabin>()), "8a8f25cce724dd63");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Rabin {
+    result: u64,
+}
+
+impl Default for Rabin {
+    fn default() -> Self {
+        Rabin { result: EMPTY }
+    }
+}
+
+impl Input for Rabin {
+    fn input<B: AsRef<[u8]>>(&mut self, data: B) {
+        for b in data.as_ref() {
+            self.result =
+                (self.result >> 8) ^ FP_TABLE[((self.result ^ u64::from(*b)) & 0xff) as usize];
+        }
+    }
+}
+
+impl FixedOutput for Rabin {
+    type OutputSize = U8;
+
+    fn fixed_result(self) -> GenericArray<u8, Self::OutputSize> {
+        GenericArray::clone_from_slice(&self.result.to_le_bytes())
+    }
+}
+
+impl Reset for Rabin {
+    fn reset(&mut self) {
+        self.result = EMPTY;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use digest::Digest;
+
+    #[test]
+    fn test_rabin_fingerprint() {
+        // Test vectors from the Avro specification test suite (share/test/data/schema-tests.txt)
+        let vectors: &[(&str, i64)] = &[
+            (r#""null""#, 7_195_948_357_588_979_594),
+            (r#""boolean""#, -6_970_731_678_124_411_036),
+            (
+                r#"{"name":"foo","type":"fixed","size":15}"#,
+                1_756_455_273_707_447_556,
+            ),
+            (
+                r#"{"name":"PigValue","type":"record","fields":[{"name":"value","type":["null","int","long","PigValue"]}]}"#,
+                -1_759_257_747_318_642_341,
+            ),
+        ];
+
+        for &(canonical_form, fingerprint) in vectors {
+            let result = Rabin::digest(canonical_form.as_bytes());
+            assert_eq!(
+                &result[..],
+                &fingerprint.to_le_bytes()[..],
+                "{}",
+                canonical_form
+            );
+        }
+    }
+}
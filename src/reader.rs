@@ -1,15 +1,21 @@
 //! Logic handling reading from Avro format at user level.
-use std::io::{ErrorKind, Read};
+use std::any::Any;
+use std::collections::HashMap;
+use std::io::{self, ErrorKind, Read};
 use std::str::{from_utf8, FromStr};
+use std::sync::Arc;
 
+use digest::Digest;
 use failure::Error;
 use serde_json::from_slice;
 
+use crate::datum::{DatumReader, GenericDatumReader};
 use crate::decode::decode;
-use crate::schema::ParseSchemaError;
-use crate::schema::Schema;
+use crate::rabin::Rabin;
+use crate::schema::{ParseSchemaError, Schema, SchemaCache, SchemaFingerprint};
 use crate::types::Value;
 use crate::util::{self, DecodeError};
+use crate::writer::{AVRO_OBJECT_HEADER, SINGLE_OBJECT_MAGIC};
 use crate::Codec;
 
 // Internal Block reader.
@@ -23,68 +29,41 @@ struct Block<R> {
     message_count: usize,
     marker: [u8; 16],
     codec: Codec,
-    writer_schema: Schema,
+    writer_schema: Arc<Schema>,
 }
 
 impl<R: Read> Block<R> {
-    fn new(reader: R) -> Result<Block<R>, Error> {
+    fn new(reader: R, cache: Option<&mut SchemaCache>) -> Result<Block<R>, Error> {
         let mut block = Block {
             reader,
             codec: Codec::Null,
-            writer_schema: Schema::Null,
+            writer_schema: Arc::new(Schema::Null),
             buf: vec![],
             buf_idx: 0,
             message_count: 0,
             marker: [0; 16],
         };
 
-        block.read_header()?;
+        block.read_header(cache)?;
         Ok(block)
     }
 
     /// Try to read the header and to set the writer `Schema`, the `Codec` and the marker based on
     /// its content.
-    fn read_header(&mut self) -> Result<(), Error> {
+    fn read_header(&mut self, cache: Option<&mut SchemaCache>) -> Result<(), Error> {
         let meta_schema = Schema::Map(Box::new(Schema::Bytes));
 
         let mut buf = [0u8; 4];
         self.reader.read_exact(&mut buf)?;
 
-        if buf != [b'O', b'b', b'j', 1u8] {
+        if buf != AVRO_OBJECT_HEADER {
             return Err(DecodeError::new("wrong magic in header").into());
         }
 
         if let Value::Map(meta) = decode(&meta_schema, &mut self.reader)? {
-            // TODO: surface original parse schema errors instead of coalescing them here
-            let schema = meta
-                .get("avro.schema")
-                .and_then(|bytes| {
-                    if let Value::Bytes(ref bytes) = *bytes {
-                        from_slice(bytes.as_ref()).ok()
-                    } else {
-                        None
-                    }
-                })
-                .and_then(|json| Schema::parse(&json).ok());
-            if let Some(schema) = schema {
-                self.writer_schema = schema;
-            } else {
-                return Err(ParseSchemaError::new("unable to parse schema").into());
-            }
-
-            if let Some(codec) = meta
-                .get("avro.codec")
-                .and_then(|codec| {
-                    if let Value::Bytes(ref bytes) = *codec {
-                        from_utf8(bytes.as_ref()).ok()
-                    } else {
-                        None
-                    }
-                })
-                .and_then(|codec| Codec::from_str(codec).ok())
-            {
-                self.codec = codec;
-            }
+            let (writer_schema, codec) = read_header_metadata(&meta, cache)?;
+            self.writer_schema = writer_schema;
+            self.codec = codec;
         } else {
             return Err(DecodeError::new("no metadata in header").into());
         }
@@ -97,14 +76,8 @@ impl<R: Read> Block<R> {
     }
 
     fn fill_buf(&mut self, n: usize) -> Result<(), Error> {
-        // We don't have enough space in the buffer, need to grow it.
-        if n >= self.buf.capacity() {
-            self.buf.reserve(n);
-        }
-
-        unsafe {
-            self.buf.set_len(n);
-        }
+        // The buffer only grows, so that its allocation is reused across blocks.
+        self.buf.resize(n, 0);
         self.reader.read_exact(&mut self.buf[..n])?;
         self.buf_idx = 0;
         Ok(())
@@ -116,9 +89,16 @@ impl<R: Read> Block<R> {
         assert!(self.is_empty(), "Expected self to be empty!");
         match util::read_long(&mut self.reader) {
             Ok(block_len) => {
-                self.message_count = block_len as usize;
                 let block_bytes = util::read_long(&mut self.reader)?;
-                self.fill_buf(block_bytes as usize)?;
+                if block_len < 0 || block_bytes < 0 {
+                    return Err(DecodeError::new(format!(
+                        "invalid block of {} objects and {} bytes",
+                        block_len, block_bytes
+                    ))
+                    .into());
+                }
+                self.message_count = block_len as usize;
+                self.fill_buf(util::safe_len(block_bytes as usize)?)?;
                 let mut marker = [0u8; 16];
                 self.reader.read_exact(&mut marker)?;
 
@@ -136,16 +116,16 @@ impl<R: Read> Block<R> {
                 // into the buffer. But this is fine, for now.
                 self.codec.decompress(&mut self.buf)?;
 
-                return Ok(());
+                Ok(())
             }
-            Err(e) => {
-                if let ErrorKind::UnexpectedEof = e.downcast::<::std::io::Error>()?.kind() {
-                    // to not return any error in case we only finished to read cleanly from the stream
-                    return Ok(());
-                }
-            }
-        };
-        Err(DecodeError::new("unable to read block").into())
+            Err(e) => match e.downcast::<io::Error>() {
+                // to not return any error in case we only finished to read cleanly from the stream
+                Ok(ref e) if e.kind() == ErrorKind::UnexpectedEof => Ok(()),
+                // keep the original `io::Error`, so that callers can inspect its kind
+                Ok(e) => Err(e.into()),
+                Err(e) => Err(e),
+            },
+        }
     }
 
     fn len(&self) -> usize {
@@ -157,6 +137,17 @@ impl<R: Read> Block<R> {
     }
 
     fn read_next(&mut self, read_schema: Option<&Schema>) -> Result<Option<Value>, Error> {
+        self.read_next_with(|writer_schema, block_bytes| {
+            from_avro_datum(writer_schema, block_bytes, read_schema)
+        })
+    }
+
+    /// Read the next item of the block with `read`, given the writer `Schema` and the remaining
+    /// bytes of the block.
+    fn read_next_with<T, F>(&mut self, read: F) -> Result<Option<T>, Error>
+    where
+        F: FnOnce(&Schema, &mut &[u8]) -> Result<T, Error>,
+    {
         if self.is_empty() {
             self.read_block_next()?;
             if self.is_empty() {
@@ -166,13 +157,50 @@ impl<R: Read> Block<R> {
 
         let mut block_bytes = &self.buf[self.buf_idx..];
         let b_original = block_bytes.len();
-        let item = from_avro_datum(&self.writer_schema, &mut block_bytes, read_schema)?;
+        let item = read(&self.writer_schema, &mut block_bytes)?;
         self.buf_idx += b_original - block_bytes.len();
         self.message_count -= 1;
         Ok(Some(item))
     }
 }
 
+/// Read the writer `Schema` and the `Codec` out of the metadata of an Avro header.
+///
+/// The schema is looked up in `cache`, if any, by the CRC-64-AVRO fingerprint of its JSON.
+pub(crate) fn read_header_metadata(
+    meta: &HashMap<String, Value>,
+    cache: Option<&mut SchemaCache>,
+) -> Result<(Arc<Schema>, Codec), Error> {
+    let json = match meta.get("avro.schema") {
+        Some(Value::Bytes(ref bytes)) => bytes,
+        _ => return Err(ParseSchemaError::new("no schema in header").into()),
+    };
+    let schema = match cache {
+        Some(cache) => {
+            // The header holds the schema as written, not its Parsing Canonical Form, whose
+            // fingerprint would only be known after parsing it.
+            let fingerprint = SchemaFingerprint {
+                bytes: Rabin::digest(json).to_vec(),
+            };
+            let json = from_utf8(json)
+                .map_err(|_| ParseSchemaError::new("schema is not a valid utf-8 string"))?;
+            cache.get_or_insert(&fingerprint, json)?
+        }
+        None => Arc::new(Schema::parse(&from_slice(json)?)?),
+    };
+
+    // A missing codec means that the data is not compressed.
+    let codec = match meta.get("avro.codec") {
+        Some(Value::Bytes(ref bytes)) => from_utf8(bytes.as_ref())
+            .map_err(|_| DecodeError::new("codec is not a valid utf-8 string"))
+            .and_then(Codec::from_str)?,
+        Some(_) => return Err(DecodeError::new("codec is not a string").into()),
+        None => Codec::Null,
+    };
+
+    Ok((schema, codec))
+}
+
 /// Main interface for reading Avro formatted values.
 ///
 /// To be used as an iterator:
@@ -193,6 +221,7 @@ pub struct Reader<'a, R> {
     reader_schema: Option<&'a Schema>,
     errored: bool,
     should_resolve_schema: bool,
+    datum_reader: Box<dyn DatumReader>,
 }
 
 impl<'a, R: Read> Reader<'a, R> {
@@ -201,12 +230,13 @@ impl<'a, R: Read> Reader<'a, R> {
     ///
     /// **NOTE** The avro header is going to be read automatically upon creation of the `Reader`.
     pub fn new(reader: R) -> Result<Reader<'a, R>, Error> {
-        let block = Block::new(reader)?;
+        let block = Block::new(reader, None)?;
         let reader = Reader {
             block,
             reader_schema: None,
             errored: false,
             should_resolve_schema: false,
+            datum_reader: Box::new(GenericDatumReader),
         };
         Ok(reader)
     }
@@ -216,28 +246,95 @@ impl<'a, R: Read> Reader<'a, R> {
     ///
     /// **NOTE** The avro header is going to be read automatically upon creation of the `Reader`.
     pub fn with_schema(schema: &'a Schema, reader: R) -> Result<Reader<'a, R>, Error> {
-        let block = Block::new(reader)?;
+        let block = Block::new(reader, None)?;
         let mut reader = Reader {
             block,
             reader_schema: Some(schema),
             errored: false,
             should_resolve_schema: false,
+            datum_reader: Box::new(GenericDatumReader),
         };
         // Check if the reader and writer schemas disagree.
         reader.should_resolve_schema = reader.writer_schema() != schema;
         Ok(reader)
     }
 
+    /// Creates a `Reader` given something implementing the `io::Read` trait to read from, looking
+    /// up the writer `Schema` in a [`SchemaCache`](schema/struct.SchemaCache.html) instead of
+    /// parsing it again if it has already been read.
+    /// No reader `Schema` will be set.
+    ///
+    /// The writer `Schema` is cached by the CRC-64-AVRO fingerprint of its JSON, as written in the
+    /// header: equivalent schemas with a different JSON layout are cached separately.
+    ///
+    /// **NOTE** The avro header is going to be read automatically upon creation of the `Reader`.
+    pub fn new_with_cache(reader: R, cache: &mut SchemaCache) -> Result<Reader<'a, R>, Error> {
+        let block = Block::new(reader, Some(cache))?;
+        let reader = Reader {
+            block,
+            reader_schema: None,
+            errored: false,
+            should_resolve_schema: false,
+            datum_reader: Box::new(GenericDatumReader),
+        };
+        Ok(reader)
+    }
+
     /// Get a reference to the writer `Schema`.
     pub fn writer_schema(&self) -> &Schema {
         &self.block.writer_schema
     }
 
+    /// Get a shared handle on the writer `Schema`, which outlives the `Reader` without being
+    /// cloned.
+    pub fn shared_writer_schema(&self) -> Arc<Schema> {
+        self.block.writer_schema.clone()
+    }
+
     /// Get a reference to the optional reader `Schema`.
     pub fn reader_schema(&self) -> Option<&Schema> {
         self.reader_schema
     }
 
+    /// Set the reader `Schema` used to perform schema resolution on the values read from now on.
+    pub fn set_reader_schema(&mut self, schema: &'a Schema) {
+        self.should_resolve_schema = self.writer_schema() != schema;
+        self.reader_schema = Some(schema);
+    }
+
+    /// Set the [`DatumReader`](trait.DatumReader.html) used by
+    /// [`read_datum`](#method.read_datum). Defaults to
+    /// [`GenericDatumReader`](struct.GenericDatumReader.html).
+    pub fn set_datum_reader(&mut self, datum_reader: Box<dyn DatumReader>) {
+        self.datum_reader = datum_reader;
+    }
+
+    /// Read the next datum with the [`DatumReader`](trait.DatumReader.html) of the `Reader`,
+    /// instead of as a `Value`.
+    ///
+    /// Return `None` once all datums have been read, or after an error, as iterating does.
+    pub fn read_datum(&mut self) -> Option<Result<Box<dyn Any>, Error>> {
+        if self.errored {
+            return None;
+        }
+        let reader_schema = self.reader_schema;
+        let datum_reader = &self.datum_reader;
+        let result = self.block.read_next_with(|writer_schema, block_bytes| {
+            datum_reader.read(
+                writer_schema,
+                reader_schema.unwrap_or(writer_schema),
+                block_bytes,
+            )
+        });
+        match result {
+            Ok(opt) => opt.map(Ok),
+            Err(e) => {
+                self.errored = true;
+                Some(Err(e))
+            }
+        }
+    }
+
     #[inline]
     fn read_next(&mut self) -> Result<Option<Value>, Error> {
         let read_schema = if self.should_resolve_schema {
@@ -288,11 +385,119 @@ pub fn from_avro_datum<R: Read>(
     }
 }
 
+/// Decode a `Value` encoded with the Avro
+/// [single object encoding](https://avro.apache.org/docs/current/spec.html#single_object_encoding)
+/// given its `Schema`.
+///
+/// The two-byte marker and the schema fingerprint are checked before decoding the datum: an error
+/// is returned if `bytes` were not written with `schema`.
+pub fn decode_single_object(schema: &Schema, bytes: &[u8]) -> Result<Value, Error> {
+    if bytes.len() < SINGLE_OBJECT_MAGIC.len() + 8 {
+        return Err(DecodeError::new("single object is too short").into());
+    }
+    let (header, mut datum) = bytes.split_at(SINGLE_OBJECT_MAGIC.len() + 8);
+    let (magic, fingerprint) = header.split_at(SINGLE_OBJECT_MAGIC.len());
+
+    if magic != SINGLE_OBJECT_MAGIC {
+        return Err(DecodeError::new("wrong magic in single object").into());
+    }
+    if fingerprint != &schema.fingerprint_crc64().bytes[..] {
+        return Err(DecodeError::new("single object was not written with this schema").into());
+    }
+
+    decode(schema, &mut datum)
+}
+
+/// Decoder reading a stream of raw Avro datums, as written by a
+/// [`ValueEncoder`](struct.ValueEncoder.html), one at a time.
+///
+/// Unlike [`Reader`](struct.Reader.html), no header, sync marker nor compression is expected. To
+/// be used as an iterator, which stops once the end of the input is reached between two datums:
+///
+/// ```
+/// # use avro_rs::{Schema, ValueDecoder, ValueEncoder};
+/// let schema = Schema::Long;
+/// let mut encoder = ValueEncoder::new(&schema, Vec::new());
+/// encoder.encode_one(1i64).unwrap();
+/// encoder.encode_one(2i64).unwrap();
+///
+/// let input = encoder.into_inner();
+/// let values = ValueDecoder::new(&schema, &input[..])
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(values.len(), 2);
+/// ```
+///
+/// **NOTE** The datums of a schema which are always encoded as zero bytes, such as `"null"`, cannot
+/// be delimited: no value is ever read for such a schema.
+pub struct ValueDecoder<'a, R> {
+    schema: &'a Schema,
+    reader: R,
+    errored: bool,
+}
+
+impl<'a, R: Read> ValueDecoder<'a, R> {
+    /// Creates a `ValueDecoder` given the `Schema` the datums were written with and something
+    /// implementing the `io::Read` trait to read from.
+    pub fn new(schema: &'a Schema, reader: R) -> ValueDecoder<'a, R> {
+        ValueDecoder {
+            schema,
+            reader,
+            errored: false,
+        }
+    }
+
+    /// Get a reference to the `Schema` associated to a `ValueDecoder`.
+    pub fn schema(&self) -> &'a Schema {
+        self.schema
+    }
+
+    /// Return what the `ValueDecoder` is reading from, consuming the `ValueDecoder` itself.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_next(&mut self) -> Result<Option<Value>, Error> {
+        // Read the first byte of the next datum, to tell the end of the input apart from a
+        // truncated datum.
+        let mut first = [0u8; 1];
+        loop {
+            match self.reader.read(&mut first) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let mut reader = (&first[..]).chain(&mut self.reader);
+        decode(self.schema, &mut reader).map(Some)
+    }
+}
+
+impl<'a, R: Read> Iterator for ValueDecoder<'a, R> {
+    type Item = Result<Value, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // to prevent keep on reading after the first error occurs
+        if self.errored {
+            return None;
+        }
+        match self.read_next() {
+            Ok(opt) => opt.map(Ok),
+            Err(e) => {
+                self.errored = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::types::{Record, ToAvro};
-    use crate::Reader;
+    use crate::{Reader, ValueEncoder, Writer};
     use std::io::Cursor;
 
     static SCHEMA: &'static str = r#"
@@ -341,6 +546,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_decode_single_object() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let mut record = Record::new(&schema).unwrap();
+        record.put("a", 27i64);
+        record.put("b", "foo");
+        let expected = record.avro();
+
+        let encoded = crate::encode_single_object(&schema, expected.clone()).unwrap();
+        assert_eq!(decode_single_object(&schema, &encoded).unwrap(), expected);
+
+        let other_schema = Schema::parse_str(UNION_SCHEMA).unwrap();
+        assert!(decode_single_object(&other_schema, &encoded).is_err());
+
+        let mut wrong_magic = encoded.clone();
+        wrong_magic[0] = 0;
+        assert!(decode_single_object(&schema, &wrong_magic).is_err());
+
+        assert!(decode_single_object(&schema, &encoded[..9]).is_err());
+    }
+
     #[test]
     fn test_null_union() {
         let schema = Schema::parse_str(UNION_SCHEMA).unwrap();
@@ -397,6 +623,119 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reader_with_cache() {
+        let mut cache = SchemaCache::new();
+        let reader = Reader::new_with_cache(ENCODED, &mut cache).unwrap();
+        assert_eq!(cache.len(), 1);
+        let values = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            values,
+            Reader::new(ENCODED)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        );
+
+        let first = Reader::new_with_cache(ENCODED, &mut cache).unwrap();
+        let second = Reader::new_with_cache(ENCODED, &mut cache).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert!(std::ptr::eq(first.writer_schema(), second.writer_schema()));
+        assert!(Arc::ptr_eq(
+            &first.shared_writer_schema(),
+            &second.shared_writer_schema()
+        ));
+
+        let schema = Schema::parse_str(UNION_SCHEMA).unwrap();
+        let mut writer = Writer::new(&schema, Vec::new());
+        writer.append(Value::Union(Box::new(Value::Null))).unwrap();
+        writer.flush().unwrap();
+        let input = writer.into_inner();
+        Reader::new_with_cache(&input[..], &mut cache).unwrap();
+        assert_eq!(cache.len(), 2);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_reader_set_reader_schema() {
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "test", "fields": [{"name": "b", "type": "string"}]}"#,
+        )
+        .unwrap();
+        let mut reader = Reader::new(ENCODED).unwrap();
+        assert_eq!(reader.reader_schema(), None);
+
+        reader.set_reader_schema(&schema);
+        assert_eq!(reader.reader_schema(), Some(&schema));
+        assert_eq!(
+            reader.next().unwrap().unwrap(),
+            Value::Record(vec![("b".to_string(), Value::String("foo".to_string()))])
+        );
+    }
+
+    #[test]
+    fn test_reader_io_error() {
+        struct ConnectionReset;
+
+        impl Read for ConnectionReset {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(
+                    ErrorKind::ConnectionReset,
+                    "connection reset",
+                ))
+            }
+        }
+
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let mut writer = crate::Writer::new(&schema, Vec::new());
+        writer.flush().unwrap();
+        let header = writer.into_inner();
+
+        let mut reader = Reader::new(Read::chain(&header[..], ConnectionReset)).unwrap();
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<io::Error>().unwrap().kind(),
+            ErrorKind::ConnectionReset
+        );
+    }
+
+    #[test]
+    fn test_reader_invalid_schema_in_header() {
+        let mut invalid = ENCODED.to_owned();
+        // turn `"type":"record"` into `"type":"recorz"`
+        let position = invalid
+            .windows(6)
+            .position(|window| window == b"record")
+            .unwrap();
+        invalid[position + 5] = b'z';
+
+        let err = Reader::new(&invalid[..]).err().unwrap();
+        assert!(err.downcast_ref::<ParseSchemaError>().is_some());
+        assert!(format!("{}", err).contains("recorz"), "{}", err);
+    }
+
+    #[test]
+    fn test_reader_unknown_codec() {
+        let mut invalid = ENCODED.to_owned();
+        let position = invalid
+            .windows(4)
+            .position(|window| window == b"null")
+            .unwrap();
+        invalid[position..position + 4].copy_from_slice(b"nope");
+
+        assert!(Reader::new(&invalid[..]).is_err());
+    }
+
+    #[test]
+    fn test_reader_truncated_block() {
+        let truncated = &ENCODED[..ENCODED.len() - 20];
+        let mut reader = Reader::new(truncated).unwrap();
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    }
+
     #[test]
     fn test_reader_empty_buffer() {
         let empty = Cursor::new(Vec::new());
@@ -415,4 +754,37 @@ mod tests {
             assert!(value.is_err());
         }
     }
+
+    #[test]
+    fn test_value_decoder() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let values = (0..3)
+            .map(|i| {
+                let mut record = Record::new(&schema).unwrap();
+                record.put("a", i as i64);
+                record.put("b", format!("value {}", i));
+                record.avro()
+            })
+            .collect::<Vec<_>>();
+
+        let mut encoder = ValueEncoder::new(&schema, Vec::new());
+        for value in values.iter() {
+            assert!(encoder.encode_one(value.clone()).unwrap() > 0);
+        }
+        assert!(encoder.encode_one(Value::Long(1)).is_err());
+        let input = encoder.into_inner();
+
+        let decoder = ValueDecoder::new(&schema, &input[..]);
+        assert_eq!(decoder.collect::<Result<Vec<_>, _>>().unwrap(), values);
+
+        let empty: &[u8] = &[];
+        assert!(ValueDecoder::new(&schema, empty).next().is_none());
+
+        // a truncated datum is an error, after which nothing more is read
+        let mut decoder = ValueDecoder::new(&schema, &input[..input.len() - 1]);
+        assert!(decoder.next().unwrap().is_ok());
+        assert!(decoder.next().unwrap().is_ok());
+        assert!(decoder.next().unwrap().is_err());
+        assert!(decoder.next().is_none());
+    }
 }
@@ -1,17 +1,26 @@
 //! Logic for parsing and interacting with schemas in Avro format.
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::Arc;
 
 use digest::Digest;
-use failure::{Error, Fail};
+use failure::{err_msg, Error, Fail};
+use md5::Md5;
 use serde::{
     ser::{SerializeMap, SerializeSeq},
     Serialize, Serializer,
 };
 use serde_json::{self, Map, Value};
+use sha2::Sha256;
 
-use crate::types;
+use crate::decimal;
+use crate::rabin::Rabin;
+use crate::types::{self, SchemaResolutionError};
 use crate::util::MapHelper;
 
 /// Describes errors happened while parsing Avro schemas.
@@ -31,6 +40,7 @@ impl ParseSchemaError {
 /// Represents an Avro schema fingerprint
 /// More information about Avro schema fingerprints can be found in the
 /// [Avro Schema Fingerprint documentation](https://avro.apache.org/docs/current/spec.html#schema_fingerprints)
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SchemaFingerprint {
     pub bytes: Vec<u8>,
 }
@@ -52,7 +62,7 @@ impl fmt::Display for SchemaFingerprint {
 /// Represents any valid Avro schema
 /// More information about Avro schemas can be found in the
 /// [Avro Specification](https://avro.apache.org/docs/current/spec.html#schemas)
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Schema {
     /// A `null` Avro schema.
     Null,
@@ -99,6 +109,43 @@ pub enum Schema {
     },
     /// A `fixed` Avro schema.
     Fixed { name: Name, size: usize },
+    /// Logical type which represents `Decimal` values. The underlying type is serialized and
+    /// deserialized as `Schema::Bytes` or `Schema::Fixed`.
+    ///
+    /// `scale` defaults to 0 and is an integer greater than or equal to 0 and `precision` is an
+    /// integer greater than 0.
+    Decimal {
+        precision: usize,
+        scale: u32,
+        inner: Box<Schema>,
+    },
+    /// A `date` logical type, representing the number of days since the Unix epoch. The
+    /// underlying type is serialized and deserialized as `Schema::Int`.
+    Date,
+    /// A `time-millis` logical type, representing the number of milliseconds after midnight.
+    /// The underlying type is serialized and deserialized as `Schema::Int`.
+    TimeMillis,
+    /// A `time-micros` logical type, representing the number of microseconds after midnight.
+    /// The underlying type is serialized and deserialized as `Schema::Long`.
+    TimeMicros,
+    /// A `timestamp-millis` logical type, representing the number of milliseconds since the Unix
+    /// epoch. The underlying type is serialized and deserialized as `Schema::Long`.
+    TimestampMillis,
+    /// A `timestamp-micros` logical type, representing the number of microseconds since the Unix
+    /// epoch. The underlying type is serialized and deserialized as `Schema::Long`.
+    TimestampMicros,
+    /// A `duration` logical type. The underlying type is serialized and deserialized as a
+    /// `Schema::Fixed` of size 12, named `name`.
+    Duration { name: Name },
+    /// A `uuid` logical type, representing a UUID as defined by RFC 4122. The underlying type is
+    /// serialized and deserialized as `Schema::String`.
+    Uuid,
+    /// A reference to a `record`, by name, as found in recursive schemas such as linked lists or
+    /// trees. The `record` is defined elsewhere in the same schema, usually enclosing the
+    /// reference.
+    ///
+    /// Other references to named types are replaced by the referenced `Schema` when parsing.
+    Ref { name: Name },
 }
 
 /// This type is used to simplify enum variant comparison between `Schema` and `types::Value`.
@@ -125,6 +172,14 @@ pub(crate) enum SchemaKind {
     Record,
     Enum,
     Fixed,
+    Decimal,
+    Date,
+    TimeMillis,
+    TimeMicros,
+    TimestampMillis,
+    TimestampMicros,
+    Duration,
+    Uuid,
 }
 
 impl<'a> From<&'a Schema> for SchemaKind {
@@ -146,6 +201,16 @@ impl<'a> From<&'a Schema> for SchemaKind {
             Schema::Record { .. } => SchemaKind::Record,
             Schema::Enum { .. } => SchemaKind::Enum,
             Schema::Fixed { .. } => SchemaKind::Fixed,
+            Schema::Decimal { .. } => SchemaKind::Decimal,
+            Schema::Date => SchemaKind::Date,
+            Schema::TimeMillis => SchemaKind::TimeMillis,
+            Schema::TimeMicros => SchemaKind::TimeMicros,
+            Schema::TimestampMillis => SchemaKind::TimestampMillis,
+            Schema::TimestampMicros => SchemaKind::TimestampMicros,
+            Schema::Duration { .. } => SchemaKind::Duration,
+            Schema::Uuid => SchemaKind::Uuid,
+            // Only records can be referenced before their definition is complete.
+            Schema::Ref { .. } => SchemaKind::Record,
         }
     }
 }
@@ -168,6 +233,15 @@ impl<'a> From<&'a types::Value> for SchemaKind {
             types::Value::Record(_) => SchemaKind::Record,
             types::Value::Enum(_, _) => SchemaKind::Enum,
             types::Value::Fixed(_, _) => SchemaKind::Fixed,
+            types::Value::Decimal(_) => SchemaKind::Decimal,
+            types::Value::Date(_) => SchemaKind::Date,
+            types::Value::TimeMillis(_) => SchemaKind::TimeMillis,
+            types::Value::TimeMicros(_) => SchemaKind::TimeMicros,
+            types::Value::TimestampMillis(_) => SchemaKind::TimestampMillis,
+            types::Value::TimestampMicros(_) => SchemaKind::TimestampMicros,
+            types::Value::Duration(_) => SchemaKind::Duration,
+            #[cfg(feature = "uuid")]
+            types::Value::Uuid(_) => SchemaKind::Uuid,
         }
     }
 }
@@ -180,9 +254,12 @@ impl<'a> From<&'a types::Value> for SchemaKind {
 ///
 /// `aliases` can also be defined, to facilitate schema evolution.
 ///
+/// Names are identified by their fullname: `aliases` are not taken into account to compare or
+/// hash them, so that they can be used as keys of maps of named types.
+///
 /// More information about schema names can be found in the
 /// [Avro specification](https://avro.apache.org/docs/current/spec.html#names)
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct Name {
     pub name: String,
     pub namespace: Option<String>,
@@ -192,24 +269,51 @@ pub struct Name {
 /// Represents documentation for complex Avro schemas.
 pub type Documentation = Option<String>;
 
+/// Named types defined so far while parsing, shared with the `SchemaStore` they may come from.
+/// A record being parsed is defined as `None` until it is complete.
+type Names = HashMap<Name, Option<Arc<Schema>>>;
+
 impl Name {
     /// Create a new `Name`.
-    /// No `namespace` nor `aliases` will be defined.
+    /// If `name` is a fullname such as `com.example.Foo`, it is split into a name and a
+    /// namespace. No `aliases` will be defined.
     pub fn new(name: &str) -> Name {
-        Name {
-            name: name.to_owned(),
-            namespace: None,
-            aliases: None,
+        match name.rfind('.') {
+            Some(index) => Name {
+                name: name[index + 1..].to_owned(),
+                namespace: Some(name[..index].to_owned()),
+                aliases: None,
+            },
+            None => Name {
+                name: name.to_owned(),
+                namespace: None,
+                aliases: None,
+            },
         }
     }
 
-    /// Parse a `serde_json::Value` into a `Name`.
-    fn parse(complex: &Map<String, Value>) -> Result<Self, Error> {
+    /// Parse a `serde_json::Value` into a `Name`, defined in the `enclosing_namespace`.
+    fn parse(
+        complex: &Map<String, Value>,
+        enclosing_namespace: Option<&str>,
+    ) -> Result<Self, Error> {
         let name = complex
             .name()
             .ok_or_else(|| ParseSchemaError::new("No `name` field"))?;
 
-        let namespace = complex.string("namespace");
+        // A fullname takes precedence over the `namespace` attribute, which takes precedence
+        // over the enclosing namespace. An empty namespace stands for the null namespace.
+        let (name, namespace) = match name.rfind('.') {
+            Some(index) => (name[index + 1..].to_owned(), Some(name[..index].to_owned())),
+            None => {
+                let namespace = match complex.get("namespace").and_then(|v| v.as_str()) {
+                    Some("") => None,
+                    Some(namespace) => Some(namespace.to_owned()),
+                    None => enclosing_namespace.map(|namespace| namespace.to_owned()),
+                };
+                (name, namespace)
+            }
+        };
 
         let aliases: Option<Vec<String>> = complex
             .get("aliases")
@@ -249,10 +353,78 @@ impl Name {
             }
         }
     }
+
+    /// Return the `fullname` of this `Name`, that is `namespace.name`, or `name` if it has no
+    /// namespace.
+    pub fn full_name(&self) -> String {
+        self.fullname(None)
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Name) -> bool {
+        self.name == other.name && self.namespace == other.namespace
+    }
+}
+
+impl Eq for Name {}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.namespace.hash(state);
+        self.name.hash(state);
+    }
+}
+
+/// Names are sorted by namespace, names without a namespace first, and then by name.
+impl Ord for Name {
+    fn cmp(&self, other: &Name) -> Ordering {
+        self.namespace
+            .cmp(&other.namespace)
+            .then_with(|| self.name.cmp(&other.name))
+    }
+}
+
+impl PartialOrd for Name {
+    fn partial_cmp(&self, other: &Name) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Writes the fullname.
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.namespace {
+            Some(ref namespace) => write!(f, "{}.{}", namespace, self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Parses a fullname such as `com.example.Foo` or `Foo`, whose parts must be valid Avro names.
+impl FromStr for Name {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_valid = |part: &str| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+                }
+                _ => false,
+            }
+        };
+        if s.split('.').all(is_valid) {
+            Ok(Name::new(s))
+        } else {
+            Err(ParseSchemaError::new(format!("Invalid name: {:?}", s)).into())
+        }
+    }
 }
 
 /// Represents a `field` in a `record` Avro schema.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RecordField {
     /// Name of the field.
     pub name: String,
@@ -273,7 +445,7 @@ pub struct RecordField {
 }
 
 /// Represents any valid order for a `field` in a `record` Avro schema.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum RecordFieldOrder {
     Ascending,
     Descending,
@@ -282,16 +454,20 @@ pub enum RecordFieldOrder {
 
 impl RecordField {
     /// Parse a `serde_json::Value` into a `RecordField`.
-    fn parse(field: &Map<String, Value>, position: usize) -> Result<Self, Error> {
+    fn parse(
+        field: &Map<String, Value>,
+        position: usize,
+        names: &mut Names,
+        namespace: Option<&str>,
+    ) -> Result<Self, Error> {
         let name = field
             .name()
             .ok_or_else(|| ParseSchemaError::new("No `name` in record field"))?;
 
-        // TODO: "type" = "<record name>"
         let schema = field
             .get("type")
             .ok_or_else(|| ParseSchemaError::new("No `type` in record field").into())
-            .and_then(|type_| Schema::parse(type_))?;
+            .and_then(|type_| Schema::parse_with_names(type_, names, namespace))?;
 
         let default = field.get("default").cloned();
 
@@ -315,6 +491,155 @@ impl RecordField {
             position,
         })
     }
+
+    /// Returns the default value of this field as an Avro `Value`, interpreted
+    /// according to the schema of the field.
+    ///
+    /// As mandated by the Avro specification, the default value of a union
+    /// field is interpreted with the first schema of the union.
+    /// Returns `Ok(None)` if the field has no default value.
+    pub fn default_as_value(&self) -> Result<Option<types::Value>, Error> {
+        match self.default {
+            Some(ref default) => default_to_value(default, &self.schema)
+                .map(Some)
+                .map_err(|err| {
+                    SchemaResolutionError::new(format!(
+                        "invalid default value for field {}: {}",
+                        self.name, err
+                    ))
+                    .into()
+                }),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Converts the JSON `default` of a record field into a `Value` matching `schema`.
+fn default_to_value(default: &Value, schema: &Schema) -> Result<types::Value, Error> {
+    let mismatch = || {
+        SchemaResolutionError::new(format!(
+            "{} is not a valid default for {:?}",
+            default, schema
+        ))
+    };
+
+    let value = match (default, schema) {
+        (&Value::Null, &Schema::Null) => types::Value::Null,
+        (&Value::Bool(b), &Schema::Boolean) => types::Value::Boolean(b),
+        (&Value::Number(ref n), &Schema::Int) => types::Value::Int(default_to_int(n)?),
+        (&Value::Number(ref n), &Schema::Date) => types::Value::Date(default_to_int(n)?),
+        (&Value::Number(ref n), &Schema::TimeMillis) => {
+            types::Value::TimeMillis(default_to_int(n)?)
+        }
+        (&Value::Number(ref n), &Schema::Long) => {
+            types::Value::Long(n.as_i64().ok_or_else(mismatch)?)
+        }
+        (&Value::Number(ref n), &Schema::TimeMicros) => {
+            types::Value::TimeMicros(n.as_i64().ok_or_else(mismatch)?)
+        }
+        (&Value::Number(ref n), &Schema::TimestampMillis) => {
+            types::Value::TimestampMillis(n.as_i64().ok_or_else(mismatch)?)
+        }
+        (&Value::Number(ref n), &Schema::TimestampMicros) => {
+            types::Value::TimestampMicros(n.as_i64().ok_or_else(mismatch)?)
+        }
+        (&Value::Number(ref n), &Schema::Float) => {
+            types::Value::Float(n.as_f64().ok_or_else(mismatch)? as f32)
+        }
+        (&Value::Number(ref n), &Schema::Double) => {
+            types::Value::Double(n.as_f64().ok_or_else(mismatch)?)
+        }
+        (&Value::String(ref s), &Schema::String) => types::Value::String(s.clone()),
+        (&Value::String(ref s), &Schema::Uuid) => types::uuid_value(s.clone())?,
+        (&Value::String(ref s), &Schema::Bytes) => {
+            types::Value::Bytes(default_to_bytes(s).ok_or_else(mismatch)?)
+        }
+        (&Value::String(ref s), &Schema::Fixed { size, .. }) => {
+            let bytes = default_to_bytes(s).ok_or_else(mismatch)?;
+            if bytes.len() != size {
+                return Err(mismatch().into());
+            }
+            types::Value::Fixed(size, bytes)
+        }
+        (&Value::String(_), &Schema::Decimal { ref inner, .. }) => {
+            match default_to_value(default, inner)? {
+                types::Value::Bytes(bytes) | types::Value::Fixed(_, bytes) => {
+                    types::Value::Decimal(decimal::Decimal::from(bytes))
+                }
+                _ => return Err(mismatch().into()),
+            }
+        }
+        (&Value::String(ref s), &Schema::Duration { .. }) => {
+            let bytes = default_to_bytes(s).ok_or_else(mismatch)?;
+            if bytes.len() != 12 {
+                return Err(mismatch().into());
+            }
+            let mut array = [0u8; 12];
+            array.copy_from_slice(&bytes);
+            types::Value::Duration(array.into())
+        }
+        (&Value::String(ref s), &Schema::Enum { ref symbols, .. }) => {
+            let index = symbols.iter().position(|symbol| symbol == s);
+            types::Value::Enum(index.ok_or_else(mismatch)? as i32, s.clone())
+        }
+        (&Value::Array(ref items), &Schema::Array(ref inner)) => types::Value::Array(
+            items
+                .iter()
+                .map(|item| default_to_value(item, inner))
+                .collect::<Result<_, _>>()?,
+        ),
+        (&Value::Object(ref items), &Schema::Map(ref inner)) => types::Value::Map(
+            items
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), default_to_value(value, inner)?)))
+                .collect::<Result<_, Error>>()?,
+        ),
+        (&Value::Object(ref items), &Schema::Record { ref fields, .. }) => types::Value::Record(
+            fields
+                .iter()
+                .map(|field| {
+                    let value = match items.get(&field.name) {
+                        Some(value) => default_to_value(value, &field.schema)?,
+                        None => field.default_as_value()?.ok_or_else(|| {
+                            SchemaResolutionError::new(format!(
+                                "missing field {} in record default",
+                                field.name
+                            ))
+                        })?,
+                    };
+                    Ok((field.name.clone(), value))
+                })
+                .collect::<Result<_, Error>>()?,
+        ),
+        (_, &Schema::Union(ref inner)) => match inner.variants().first() {
+            Some(first) => types::Value::Union(Box::new(default_to_value(default, first)?)),
+            None => return Err(mismatch().into()),
+        },
+        _ => return Err(mismatch().into()),
+    };
+
+    Ok(value)
+}
+
+/// Defaults of `bytes` and `fixed` schemas are strings whose characters are the code points 0-255
+/// of their bytes (ISO-8859-1).
+pub(crate) fn default_to_bytes(s: &str) -> Option<Vec<u8>> {
+    s.chars()
+        .map(|c| {
+            if (c as u32) <= 0xFF {
+                Some(c as u8)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn default_to_int(n: &serde_json::Number) -> Result<i32, Error> {
+    n.as_i64()
+        .filter(|&n| n >= i64::from(i32::MIN) && n <= i64::from(i32::MAX))
+        .map(|n| n as i32)
+        .ok_or_else(|| SchemaResolutionError::new(format!("{} is not a valid int", n)).into())
 }
 
 #[derive(Debug, Clone)]
@@ -349,14 +674,59 @@ impl UnionSchema {
         })
     }
 
+    /// Create the union of `null`, at index 0, and `schema`: the usual way of making a type
+    /// optional.
+    ///
+    /// Fails if `schema` is itself `null` or a union.
+    pub fn with_null(schema: Schema) -> Result<Self, Error> {
+        UnionSchema::new(vec![Schema::Null, schema])
+    }
+
     /// Returns a slice to all variants of this schema.
     pub fn variants(&self) -> &[Schema] {
         &self.schemas
     }
 
-    /// Returns true if the first variant of this `UnionSchema` is `Null`.
+    /// Returns true if this `UnionSchema` is made of `Null` and exactly one other variant, in
+    /// any order.
     pub fn is_nullable(&self) -> bool {
-        !self.schemas.is_empty() && self.schemas[0] == Schema::Null
+        self.schemas.len() == 2 && self.null_index().is_some()
+    }
+
+    /// Returns the variant which is not `Null` if this `UnionSchema` is
+    /// [nullable](#method.is_nullable).
+    pub fn unwrap_nullable(&self) -> Option<&Schema> {
+        if self.is_nullable() {
+            self.schemas.iter().find(|schema| **schema != Schema::Null)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the first variant named `name`, as well as its position within this union.
+    ///
+    /// `name` is either the name of a primitive type, `array` and `map`, or the name or fullname
+    /// of a named type. Logical types are found by the name of their underlying type.
+    pub fn find_variant_by_name(&self, name: &str) -> Option<(usize, &Schema)> {
+        self.schemas
+            .iter()
+            .enumerate()
+            .find(|&(_, schema)| schema.has_type_name(name))
+    }
+
+    /// Returns the position of the variant a `value` belongs to within this union.
+    ///
+    /// A `Value::Union` belongs to the variant of the value it holds.
+    pub fn find_variant_for_value(&self, value: &crate::types::Value) -> Option<usize> {
+        match *value {
+            crate::types::Value::Union(ref inner) => self.find_variant_for_value(inner),
+            ref value => self.find_schema(value).map(|(index, _)| index),
+        }
+    }
+
+    /// Returns the position of the `Null` variant within this `UnionSchema`, if any.
+    pub fn null_index(&self) -> Option<usize> {
+        self.variant_index.get(&SchemaKind::Null).cloned()
     }
 
     /// Optionally returns a reference to the schema matched by this value, as well as its position
@@ -377,609 +747,3671 @@ impl PartialEq for UnionSchema {
     }
 }
 
-impl Schema {
-    /// Create a `Schema` from a string representing a JSON Avro schema.
-    pub fn parse_str(input: &str) -> Result<Self, Error> {
-        let value = serde_json::from_str(input)?;
-        Self::parse(&value)
+impl Eq for UnionSchema {}
+
+/// Definitions of the named types of a schema, by fullname, against which its `Schema::Ref`s
+/// are resolved while walking it.
+///
+/// They are only collected on the first lookup, as most schemas hold no `Schema::Ref`.
+pub(crate) struct Definitions<'s> {
+    root: &'s Schema,
+    named: Option<HashMap<&'s Name, &'s Schema>>,
+}
+
+impl<'s> Definitions<'s> {
+    /// Definitions of the named types nested in `root`.
+    pub(crate) fn new(root: &'s Schema) -> Self {
+        Definitions { root, named: None }
     }
 
-    /// Create a `Schema` from a `serde_json::Value` representing a JSON Avro
-    /// schema.
-    pub fn parse(value: &Value) -> Result<Self, Error> {
-        match *value {
-            Value::String(ref t) => Schema::parse_primitive(t.as_str()),
-            Value::Object(ref data) => Schema::parse_complex(data),
-            Value::Array(ref data) => Schema::parse_union(data),
-            _ => Err(ParseSchemaError::new("Must be a JSON string, object or array").into()),
+    fn collect(schema: &'s Schema, named: &mut HashMap<&'s Name, &'s Schema>) {
+        match *schema {
+            Schema::Array(ref inner) | Schema::Map(ref inner) => Definitions::collect(inner, named),
+            Schema::Union(ref inner) => {
+                for variant in inner.variants() {
+                    Definitions::collect(variant, named);
+                }
+            }
+            Schema::Record {
+                ref name,
+                ref fields,
+                ..
+            } => {
+                if named.contains_key(name) {
+                    return;
+                }
+                named.insert(name, schema);
+                for field in fields {
+                    Definitions::collect(&field.schema, named);
+                }
+            }
+            Schema::Ref { .. } => {}
+            _ => {
+                if let Some(name) = schema.name() {
+                    named.entry(name).or_insert(schema);
+                }
+            }
         }
     }
+}
 
-    /// Converts `self` into its [Parsing Canonical Form].
-    ///
-    /// [Parsing Canonical Form]:
-    /// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
-    pub fn canonical_form(&self) -> String {
-        let json = serde_json::to_value(self).unwrap();
-        parsing_canonical_form(&json)
-    }
+/// Look up the definition of the `Schema::Ref` named `name` among the `definitions` of the
+/// schema being walked.
+///
+/// Every walk over a `Value` and its `Schema` starts from [`Definitions::new`] on the root
+/// schema and hands the same `definitions` down, so that a reference resolves wherever the
+/// referenced type is defined in the root, not only in the records enclosing it.
+pub(crate) fn resolve_ref<'s>(
+    name: &Name,
+    definitions: &mut Definitions<'s>,
+) -> Result<&'s Schema, Error> {
+    let root = definitions.root;
+    definitions
+        .named
+        .get_or_insert_with(|| {
+            let mut named = HashMap::new();
+            Definitions::collect(root, &mut named);
+            named
+        })
+        .get(name)
+        .cloned()
+        .ok_or_else(|| {
+            SchemaResolutionError::new(format!("Unresolved reference to {}", name.full_name()))
+                .into()
+        })
+}
 
-    /// Generate [fingerprint] of Schema's [Parsing Canonical Form].
-    ///
-    /// [Parsing Canonical Form]:
-    /// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
-    /// [fingerprint]:
-    /// https://avro.apache.org/docs/current/spec.html#schema_fingerprints
-    pub fn fingerprint<D: Digest>(&self) -> SchemaFingerprint {
-        let mut d = D::new();
-        d.input(self.canonical_form());
-        SchemaFingerprint {
-            bytes: d.result().to_vec(),
+/// Build the `lookup` of a `Schema::Record`, mapping the name of each of its `fields` to its
+/// position. If two fields have the same name, the last one is kept.
+pub fn rebuild_lookup(fields: &[RecordField]) -> HashMap<String, usize> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(position, field)| (field.name.clone(), position))
+        .collect()
+}
+
+/// Check that the `fields` and `lookup` of the `Schema::Record` named `name` are consistent:
+/// field names are unique, `position` is the actual position of each field, and `lookup` maps
+/// the name of each field to its position, and nothing else.
+pub fn validate_record_schema(
+    name: &Name,
+    fields: &[RecordField],
+    lookup: &HashMap<String, usize>,
+) -> Result<(), Error> {
+    let mut names = HashSet::new();
+    for (position, field) in fields.iter().enumerate() {
+        if !names.insert(field.name.as_str()) {
+            return Err(ParseSchemaError::new(format!(
+                "Duplicate field name {} in record {}",
+                field.name, name
+            ))
+            .into());
+        }
+        if field.position != position {
+            return Err(ParseSchemaError::new(format!(
+                "Field {} of record {} is at position {}, not {}",
+                field.name, name, position, field.position
+            ))
+            .into());
         }
+        if lookup.get(&field.name) != Some(&position) {
+            return Err(ParseSchemaError::new(format!(
+                "Field {} of record {} is not looked up at position {}",
+                field.name, name, position
+            ))
+            .into());
+        }
+    }
+    if lookup.len() != fields.len() {
+        return Err(ParseSchemaError::new(format!(
+            "Lookup of record {} has entries for unknown fields",
+            name
+        ))
+        .into());
     }
+    Ok(())
+}
 
-    /// Parse a `serde_json::Value` representing a primitive Avro type into a
-    /// `Schema`.
-    fn parse_primitive(primitive: &str) -> Result<Self, Error> {
-        match primitive {
-            "null" => Ok(Schema::Null),
-            "boolean" => Ok(Schema::Boolean),
-            "int" => Ok(Schema::Int),
-            "long" => Ok(Schema::Long),
-            "double" => Ok(Schema::Double),
-            "float" => Ok(Schema::Float),
-            "bytes" => Ok(Schema::Bytes),
-            "string" => Ok(Schema::String),
-            other => Err(ParseSchemaError::new(format!("Unknown type: {}", other)).into()),
-        }
+/// Position of the field named `name` among `fields`.
+fn find_field_position(fields: &[RecordField], name: &str) -> Result<usize, Error> {
+    fields
+        .iter()
+        .position(|field| field.name == name)
+        .ok_or_else(|| ParseSchemaError::new(format!("No field named {}", name)).into())
+}
+
+/// Registry of the named types defined by a set of schemas, indexed by fullname.
+///
+/// ```
+/// use avro_rs::{schema::SchemaStore, Schema};
+///
+/// let schema = Schema::parse_str(
+///     r#"{"type": "record", "name": "Hash", "namespace": "org.example", "fields": [
+///         {"name": "md5", "type": {"type": "fixed", "name": "MD5", "size": 16}}
+///     ]}"#,
+/// )
+/// .unwrap();
+///
+/// let mut store = SchemaStore::new();
+/// store.register(&schema).unwrap();
+/// assert!(store.get("org.example.Hash").is_some());
+/// assert!(store.get("org.example.MD5").is_some());
+/// ```
+///
+/// Registered types are held behind an `Arc`, so that cloning a `SchemaStore` or sharing its
+/// types with [`get_shared`](#method.get_shared) does not copy their schema trees.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaStore {
+    schemas: HashMap<Name, Arc<Schema>>,
+}
+
+impl SchemaStore {
+    /// Create an empty `SchemaStore`.
+    pub fn new() -> SchemaStore {
+        SchemaStore::default()
     }
 
-    /// Parse a `serde_json::Value` representing a complex Avro type into a
-    /// `Schema`.
+    /// Register `schema` and all the named types nested in it.
     ///
-    /// Avro supports "recursive" definition of types.
-    /// e.g: {"type": {"type": "string"}}
-    fn parse_complex(complex: &Map<String, Value>) -> Result<Self, Error> {
-        match complex.get("type") {
-            Some(&Value::String(ref t)) => match t.as_str() {
-                "record" => Schema::parse_record(complex),
-                "enum" => Schema::parse_enum(complex),
-                "array" => Schema::parse_array(complex),
-                "map" => Schema::parse_map(complex),
-                "fixed" => Schema::parse_fixed(complex),
-                other => Schema::parse_primitive(other),
-            },
-            Some(&Value::Object(ref data)) => match data.get("type") {
-                Some(ref value) => Schema::parse(value),
-                None => Err(
-                    ParseSchemaError::new(format!("Unknown complex type: {:?}", complex)).into(),
-                ),
+    /// Registering a fullname that is already defined differently is an error.
+    pub fn register(&mut self, schema: &Schema) -> Result<(), Error> {
+        match *schema {
+            Schema::Array(ref inner) | Schema::Map(ref inner) => self.register(inner),
+            Schema::Union(ref inner) => {
+                for variant in inner.variants() {
+                    self.register(variant)?;
+                }
+                Ok(())
+            }
+            Schema::Record {
+                ref name,
+                ref fields,
+                ..
+            } => {
+                self.insert(name, schema)?;
+                for field in fields {
+                    self.register(&field.schema)?;
+                }
+                Ok(())
+            }
+            Schema::Enum { ref name, .. }
+            | Schema::Fixed { ref name, .. }
+            | Schema::Duration { ref name } => self.insert(name, schema),
+            Schema::Decimal { ref inner, .. } => match **inner {
+                Schema::Fixed { ref name, .. } => self.insert(name, schema),
+                _ => Ok(()),
             },
-            _ => Err(ParseSchemaError::new("No `type` in complex type").into()),
+            _ => Ok(()),
         }
     }
 
-    /// Parse a `serde_json::Value` representing a Avro record type into a
-    /// `Schema`.
-    fn parse_record(complex: &Map<String, Value>) -> Result<Self, Error> {
-        let name = Name::parse(complex)?;
-
-        let mut lookup = HashMap::new();
+    fn insert(&mut self, name: &Name, schema: &Schema) -> Result<(), Error> {
+        match self.schemas.get(name) {
+            Some(existing) if !same_definition(existing, schema) => {
+                Err(ParseSchemaError::new(format!("Conflicting definitions of {}", name)).into())
+            }
+            Some(_) => Ok(()),
+            None => {
+                self.schemas.insert(name.clone(), Arc::new(schema.clone()));
+                Ok(())
+            }
+        }
+    }
 
-        let fields: Vec<RecordField> = complex
-            .get("fields")
-            .and_then(|fields| fields.as_array())
-            .ok_or_else(|| ParseSchemaError::new("No `fields` in record").into())
-            .and_then(|fields| {
-                fields
-                    .iter()
-                    .filter_map(|field| field.as_object())
-                    .enumerate()
-                    .map(|(position, field)| RecordField::parse(field, position))
-                    .collect::<Result<_, _>>()
-            })?;
+    /// Get the named type registered under `full_name`.
+    pub fn get(&self, full_name: &str) -> Option<&Schema> {
+        self.schemas
+            .get(&Name::new(full_name))
+            .map(|schema| &**schema)
+    }
 
-        for field in &fields {
-            lookup.insert(field.name.clone(), field.position);
-        }
+    /// Get a shared handle on the named type registered under `full_name`, e.g. to hand it over
+    /// to another thread without cloning it.
+    pub fn get_shared(&self, full_name: &str) -> Option<Arc<Schema>> {
+        self.schemas.get(&Name::new(full_name)).cloned()
+    }
 
-        Ok(Schema::Record {
-            name,
-            doc: complex.doc(),
-            fields,
-            lookup,
-        })
+    /// Get the names of the registered types, sorted as `Name`s are.
+    pub fn names(&self) -> Vec<&Name> {
+        let mut names: Vec<_> = self.schemas.keys().collect();
+        names.sort();
+        names
     }
 
-    /// Parse a `serde_json::Value` representing a Avro enum type into a
-    /// `Schema`.
-    fn parse_enum(complex: &Map<String, Value>) -> Result<Self, Error> {
-        let name = Name::parse(complex)?;
+    /// Replace the `Schema::Ref` nodes of `schema` with the registered definitions.
+    ///
+    /// References to a record enclosing them are kept, as they define recursive schemas.
+    pub fn resolve_references(&self, schema: &Schema) -> Result<Schema, Error> {
+        self.resolve_references_internal(schema, &mut Vec::new())
+    }
 
-        let symbols = complex
-            .get("symbols")
-            .and_then(|v| v.as_array())
-            .ok_or_else(|| ParseSchemaError::new("No `symbols` field in enum"))
-            .and_then(|symbols| {
-                symbols
+    /// `enclosing` holds the fullnames of the records enclosing `schema`.
+    fn resolve_references_internal(
+        &self,
+        schema: &Schema,
+        enclosing: &mut Vec<String>,
+    ) -> Result<Schema, Error> {
+        Ok(match *schema {
+            Schema::Array(ref inner) => Schema::Array(Box::new(
+                self.resolve_references_internal(inner, enclosing)?,
+            )),
+            Schema::Map(ref inner) => Schema::Map(Box::new(
+                self.resolve_references_internal(inner, enclosing)?,
+            )),
+            Schema::Union(ref inner) => Schema::Union(UnionSchema::new(
+                inner
+                    .variants()
                     .iter()
-                    .map(|symbol| symbol.as_str().map(|s| s.to_string()))
-                    .collect::<Option<_>>()
-                    .ok_or_else(|| ParseSchemaError::new("Unable to parse `symbols` in enum"))
-            })?;
-
-        Ok(Schema::Enum {
-            name,
-            doc: complex.doc(),
-            symbols,
+                    .map(|variant| self.resolve_references_internal(variant, enclosing))
+                    .collect::<Result<_, _>>()?,
+            )?),
+            Schema::Record {
+                ref name,
+                ref doc,
+                ref fields,
+                ref lookup,
+            } => {
+                enclosing.push(name.full_name());
+                let fields = fields
+                    .iter()
+                    .map(|field| {
+                        Ok(RecordField {
+                            schema: self.resolve_references_internal(&field.schema, enclosing)?,
+                            ..field.clone()
+                        })
+                    })
+                    .collect::<Result<_, Error>>();
+                enclosing.pop();
+                Schema::Record {
+                    name: name.clone(),
+                    doc: doc.clone(),
+                    fields: fields?,
+                    lookup: lookup.clone(),
+                }
+            }
+            Schema::Ref { ref name } => {
+                let fullname = name.full_name();
+                if enclosing.contains(&fullname) {
+                    schema.clone()
+                } else {
+                    let definition = self.get(&fullname).ok_or_else(|| {
+                        SchemaResolutionError::new(format!("Unresolved reference to {}", fullname))
+                    })?;
+                    self.resolve_references_internal(definition, enclosing)?
+                }
+            }
+            _ => schema.clone(),
         })
     }
 
-    /// Parse a `serde_json::Value` representing a Avro array type into a
-    /// `Schema`.
-    fn parse_array(complex: &Map<String, Value>) -> Result<Self, Error> {
-        complex
-            .get("items")
-            .ok_or_else(|| ParseSchemaError::new("No `items` in array").into())
-            .and_then(|items| Schema::parse(items))
-            .map(|schema| Schema::Array(Box::new(schema)))
+    /// Parse a `serde_json::Value` into a `Schema` that can reference the registered named
+    /// types, and register it.
+    pub(crate) fn parse(&mut self, value: &Value) -> Result<Schema, Error> {
+        let mut names = self
+            .schemas
+            .iter()
+            .map(|(name, schema)| (name.clone(), Some(schema.clone())))
+            .collect();
+        let schema = Schema::parse_with_names(value, &mut names, None)?;
+        let schema = self.define_references(&schema, &mut HashSet::new(), false)?;
+        self.register(&schema)?;
+        Ok(schema)
     }
 
-    /// Parse a `serde_json::Value` representing a Avro map type into a
-    /// `Schema`.
-    fn parse_map(complex: &Map<String, Value>) -> Result<Self, Error> {
-        complex
-            .get("values")
-            .ok_or_else(|| ParseSchemaError::new("No `values` in map").into())
-            .and_then(|items| Schema::parse(items))
-            .map(|schema| Schema::Map(Box::new(schema)))
-    }
-
-    /// Parse a `serde_json::Value` representing a Avro union type into a
-    /// `Schema`.
-    fn parse_union(items: &[Value]) -> Result<Self, Error> {
-        items
-            .iter()
-            .map(Schema::parse)
-            .collect::<Result<Vec<_>, _>>()
-            .and_then(|schemas| Ok(Schema::Union(UnionSchema::new(schemas)?)))
-    }
-
-    /// Parse a `serde_json::Value` representing a Avro fixed type into a
-    /// `Schema`.
-    fn parse_fixed(complex: &Map<String, Value>) -> Result<Self, Error> {
-        let name = Name::parse(complex)?;
-
-        let size = complex
-            .get("size")
-            .and_then(|v| v.as_i64())
-            .ok_or_else(|| ParseSchemaError::new("No `size` in fixed"))?;
-
-        Ok(Schema::Fixed {
-            name,
-            size: size as usize,
-        })
-    }
-}
-
-impl Serialize for Schema {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        match *self {
-            Schema::Null => serializer.serialize_str("null"),
-            Schema::Boolean => serializer.serialize_str("boolean"),
-            Schema::Int => serializer.serialize_str("int"),
-            Schema::Long => serializer.serialize_str("long"),
-            Schema::Float => serializer.serialize_str("float"),
-            Schema::Double => serializer.serialize_str("double"),
-            Schema::Bytes => serializer.serialize_str("bytes"),
-            Schema::String => serializer.serialize_str("string"),
-            Schema::Array(ref inner) => {
-                let mut map = serializer.serialize_map(Some(2))?;
-                map.serialize_entry("type", "array")?;
-                map.serialize_entry("items", &*inner.clone())?;
-                map.end()
-            }
-            Schema::Map(ref inner) => {
-                let mut map = serializer.serialize_map(Some(2))?;
-                map.serialize_entry("type", "map")?;
-                map.serialize_entry("values", &*inner.clone())?;
-                map.end()
-            }
-            Schema::Union(ref inner) => {
-                let variants = inner.variants();
-                let mut seq = serializer.serialize_seq(Some(variants.len()))?;
-                for v in variants {
-                    seq.serialize_element(v)?;
-                }
-                seq.end()
-            }
+    /// Replace the `Schema::Ref` nodes of `schema` to records it does not define with the
+    /// registered definitions, so that it can be used and written out on its own.
+    ///
+    /// A registered type copied into `schema` may refer to records that enclosed it where it was
+    /// first defined. `defined` holds the fullnames of the records met so far, in the order in
+    /// which they are written out: a reference to any of them is kept, and they are referenced
+    /// rather than defined again within the registered definitions added, when `registered`.
+    fn define_references(
+        &self,
+        schema: &Schema,
+        defined: &mut HashSet<Name>,
+        registered: bool,
+    ) -> Result<Schema, Error> {
+        Ok(match *schema {
+            Schema::Array(ref inner) => Schema::Array(Box::new(
+                self.define_references(inner, defined, registered)?,
+            )),
+            Schema::Map(ref inner) => Schema::Map(Box::new(
+                self.define_references(inner, defined, registered)?,
+            )),
+            Schema::Union(ref inner) => Schema::Union(UnionSchema::new(
+                inner
+                    .variants()
+                    .iter()
+                    .map(|variant| self.define_references(variant, defined, registered))
+                    .collect::<Result<_, _>>()?,
+            )?),
             Schema::Record {
                 ref name,
                 ref doc,
                 ref fields,
-                ..
+                ref lookup,
             } => {
-                let mut map = serializer.serialize_map(None)?;
-                map.serialize_entry("type", "record")?;
-                if let Some(ref n) = name.namespace {
-                    map.serialize_entry("namespace", n)?;
-                }
-                map.serialize_entry("name", &name.name)?;
-                if let Some(ref docstr) = doc {
-                    map.serialize_entry("doc", docstr)?;
+                if !defined.insert(name.clone()) && registered {
+                    return Ok(Schema::Ref { name: name.clone() });
                 }
-                if let Some(ref aliases) = name.aliases {
-                    map.serialize_entry("aliases", aliases)?;
+                Schema::Record {
+                    name: name.clone(),
+                    doc: doc.clone(),
+                    fields: fields
+                        .iter()
+                        .map(|field| {
+                            Ok(RecordField {
+                                schema: self.define_references(
+                                    &field.schema,
+                                    defined,
+                                    registered,
+                                )?,
+                                ..field.clone()
+                            })
+                        })
+                        .collect::<Result<_, Error>>()?,
+                    lookup: lookup.clone(),
                 }
-                map.serialize_entry("fields", fields)?;
-                map.end()
             }
-            Schema::Enum {
-                ref name,
-                ref symbols,
+            Schema::Ref { ref name } if !defined.contains(name) => match self.schemas.get(name) {
+                Some(definition) => self.define_references(definition, defined, true)?,
+                None => schema.clone(),
+            },
+            _ => schema.clone(),
+        })
+    }
+}
+
+/// Returns true if `a` and `b` define the same type, a `Schema::Ref` being the same as any
+/// definition of the record it refers to.
+///
+/// The copies of a named type made by `Schema::parse_list` may define the records that the
+/// original only refers to.
+fn same_definition(a: &Schema, b: &Schema) -> bool {
+    match (a, b) {
+        (&Schema::Ref { ref name }, other) | (other, &Schema::Ref { ref name }) => {
+            other.name() == Some(name)
+        }
+        (&Schema::Array(ref a), &Schema::Array(ref b))
+        | (&Schema::Map(ref a), &Schema::Map(ref b)) => same_definition(a, b),
+        (&Schema::Union(ref a), &Schema::Union(ref b)) => {
+            a.variants().len() == b.variants().len()
+                && a.variants()
+                    .iter()
+                    .zip(b.variants())
+                    .all(|(a, b)| same_definition(a, b))
+        }
+        (
+            &Schema::Record {
+                name: ref a_name,
+                doc: ref a_doc,
+                fields: ref a_fields,
                 ..
-            } => {
-                let mut map = serializer.serialize_map(None)?;
-                map.serialize_entry("type", "enum")?;
-                map.serialize_entry("name", &name.name)?;
-                map.serialize_entry("symbols", symbols)?;
-                map.end()
-            }
-            Schema::Fixed { ref name, ref size } => {
-                let mut map = serializer.serialize_map(None)?;
-                map.serialize_entry("type", "fixed")?;
-                map.serialize_entry("name", &name.name)?;
-                map.serialize_entry("size", size)?;
-                map.end()
-            }
+            },
+            &Schema::Record {
+                name: ref b_name,
+                doc: ref b_doc,
+                fields: ref b_fields,
+                ..
+            },
+        ) => {
+            a_name == b_name
+                && a_doc == b_doc
+                && a_fields.len() == b_fields.len()
+                && a_fields.iter().zip(b_fields).all(|(a, b)| {
+                    a.name == b.name
+                        && a.doc == b.doc
+                        && a.default == b.default
+                        && a.order == b.order
+                        && a.position == b.position
+                        && same_definition(&a.schema, &b.schema)
+                })
         }
+        _ => a == b,
     }
 }
 
-impl Serialize for RecordField {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let mut map = serializer.serialize_map(None)?;
-        map.serialize_entry("name", &self.name)?;
-        map.serialize_entry("type", &self.schema)?;
+/// A cache of parsed schemas, keyed by their CRC-64-AVRO fingerprint.
+///
+/// Sharing a `SchemaCache` between [`Reader`](../struct.Reader.html)s created with
+/// [`Reader::new_with_cache`](../struct.Reader.html#method.new_with_cache) avoids parsing the same
+/// writer schema over and over, when reading many Avro containers:
+///
+/// ```
+/// # use avro_rs::schema::SchemaCache;
+/// # use avro_rs::Schema;
+/// let mut cache = SchemaCache::new();
+/// let schema = Schema::parse_str(r#""long""#).unwrap();
+/// let fingerprint = schema.fingerprint_crc64();
+///
+/// let cached = cache.get_or_insert(&fingerprint, r#""long""#).unwrap();
+/// assert_eq!(*cached, schema);
+/// assert!(cache.get(&fingerprint).is_some());
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SchemaCache {
+    schemas: HashMap<[u8; 8], Arc<Schema>>,
+}
 
-        if let Some(ref default) = self.default {
-            map.serialize_entry("default", default)?;
+impl SchemaCache {
+    /// Create an empty `SchemaCache`.
+    pub fn new() -> SchemaCache {
+        SchemaCache::default()
+    }
+
+    /// Get the schema cached for `fingerprint`, if any.
+    pub fn get(&self, fingerprint: &SchemaFingerprint) -> Option<Arc<Schema>> {
+        SchemaCache::key(fingerprint)
+            .ok()
+            .and_then(|key| self.schemas.get(&key).cloned())
+    }
+
+    /// Get the schema cached for `fingerprint`, parsing `json` and caching the result if there is
+    /// none.
+    ///
+    /// `json` is not parsed, nor compared with the cached schema, if `fingerprint` is already
+    /// cached: `fingerprint` must identify `json`, e.g. be the CRC-64-AVRO fingerprint of the
+    /// schema or of `json` itself.
+    pub fn get_or_insert(
+        &mut self,
+        fingerprint: &SchemaFingerprint,
+        json: &str,
+    ) -> Result<Arc<Schema>, Error> {
+        let key = SchemaCache::key(fingerprint)?;
+        if let Some(schema) = self.schemas.get(&key) {
+            return Ok(schema.clone());
         }
+        let schema = Arc::new(Schema::parse_str(json)?);
+        self.schemas.insert(key, schema.clone());
+        Ok(schema)
+    }
 
-        map.end()
+    /// Remove all the cached schemas.
+    pub fn clear(&mut self) {
+        self.schemas.clear();
     }
-}
 
-/// Parses a **valid** avro schema into the Parsing Canonical Form.
-/// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
-fn parsing_canonical_form(schema: &serde_json::Value) -> String {
-    match schema {
-        serde_json::Value::Object(map) => pcf_map(map),
-        serde_json::Value::String(s) => pcf_string(s),
-        serde_json::Value::Array(v) => pcf_array(v),
-        _ => unreachable!(),
+    /// Return the number of cached schemas.
+    pub fn len(&self) -> usize {
+        self.schemas.len()
     }
-}
 
-fn pcf_map(schema: &Map<String, serde_json::Value>) -> String {
-    // Look for the namespace variant up front.
-    let ns = schema.get("namespace").and_then(|v| v.as_str());
-    let mut fields = Vec::new();
-    for (k, v) in schema {
-        // Reduce primitive types to their simple form. ([PRIMITIVE] rule)
-        if schema.len() == 1 && k == "type" {
-            // Invariant: function is only callable from a valid schema, so this is acceptable.
-            if let serde_json::Value::String(s) = v {
-                return pcf_string(s);
-            }
-        }
+    /// Return whether no schema is cached.
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
 
-        // Strip out unused fields ([STRIP] rule)
-        if field_ordering_position(k).is_none() {
-            continue;
+    fn key(fingerprint: &SchemaFingerprint) -> Result<[u8; 8], Error> {
+        if fingerprint.bytes.len() != 8 {
+            return Err(err_msg(format!(
+                "{} is not a CRC-64-AVRO fingerprint",
+                fingerprint
+            )));
         }
+        let mut key = [0u8; 8];
+        key.copy_from_slice(&fingerprint.bytes);
+        Ok(key)
+    }
+}
 
-        // Fully qualify the name, if it isn't already ([FULLNAMES] rule).
-        if k == "name" {
-            // Invariant: Only valid schemas. Must be a string.
-            let name = v.as_str().unwrap();
-            let n = match ns {
-                Some(namespace) if !name.contains('.') => {
-                    Cow::Owned(format!("{}.{}", namespace, name))
-                }
-                _ => Cow::Borrowed(name),
-            };
+/// Entry point of the builders of complex schemas.
+///
+/// ```
+/// use avro_rs::{schema::SchemaBuilder, Schema};
+///
+/// let schema = SchemaBuilder::record("org.example.Foo")
+///     .doc("A Foo record")
+///     .field("x", Schema::Long)
+///     .field("y", SchemaBuilder::union().variant(Schema::Null).variant(Schema::String).build().unwrap())
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(schema.name().unwrap().full_name(), "org.example.Foo");
+/// ```
+pub struct SchemaBuilder;
 
-            fields.push((k, format!("{}:{}", pcf_string(k), pcf_string(&*n))));
-            continue;
-        }
+impl SchemaBuilder {
+    /// Start building a record named `name`, which may be a fullname.
+    pub fn record(name: &str) -> RecordSchemaBuilder {
+        RecordSchemaBuilder::new(name)
+    }
 
-        // Strip off quotes surrounding "size" type, if they exist ([INTEGERS] rule).
-        if k == "size" {
-            let i = match v.as_str() {
-                Some(s) => s.parse::<i64>().expect("Only valid schemas are accepted!"),
-                None => v.as_i64().unwrap(),
-            };
-            fields.push((k, format!("{}:{}", pcf_string(k), i)));
-            continue;
-        }
+    /// Start building an enum named `name`, which may be a fullname.
+    pub fn enumeration<S: Into<String>>(
+        name: &str,
+        symbols: impl IntoIterator<Item = S>,
+    ) -> EnumSchemaBuilder {
+        EnumSchemaBuilder::new(name, symbols)
+    }
 
-        // For anything else, recursively process the result.
-        fields.push((
-            k,
-            format!("{}:{}", pcf_string(k), parsing_canonical_form(v)),
-        ));
+    /// Start building a fixed of `size` bytes named `name`, which may be a fullname.
+    pub fn fixed(name: &str, size: usize) -> FixedSchemaBuilder {
+        FixedSchemaBuilder::new(name, size)
     }
 
-    // Sort the fields by their canonical ordering ([ORDER] rule).
-    fields.sort_unstable_by_key(|(k, _)| field_ordering_position(k).unwrap());
-    let inter = fields
-        .into_iter()
-        .map(|(_, v)| v)
-        .collect::<Vec<_>>()
-        .join(",");
-    format!("{{{}}}", inter)
-}
+    /// Start building a decimal of `precision` digits.
+    pub fn decimal(precision: usize) -> DecimalSchemaBuilder {
+        DecimalSchemaBuilder::new(precision)
+    }
 
-fn pcf_array(arr: &[serde_json::Value]) -> String {
-    let inter = arr
-        .iter()
-        .map(parsing_canonical_form)
-        .collect::<Vec<String>>()
-        .join(",");
-    format!("[{}]", inter)
+    /// Start building a union.
+    pub fn union() -> UnionSchemaBuilder {
+        UnionSchemaBuilder::new()
+    }
 }
 
-fn pcf_string(s: &str) -> String {
-    format!("\"{}\"", s)
+/// Builder of `Schema::Record`, computing the position of each field.
+#[derive(Clone, Debug)]
+pub struct RecordSchemaBuilder {
+    name: Name,
+    doc: Documentation,
+    fields: Vec<RecordField>,
 }
 
-// Used to define the ordering and inclusion of fields.
-fn field_ordering_position(field: &str) -> Option<usize> {
-    let v = match field {
-        "name" => 1,
-        "type" => 2,
-        "fields" => 3,
-        "symbols" => 4,
-        "items" => 5,
-        "values" => 6,
-        "size" => 7,
-        _ => return None,
-    };
+impl RecordSchemaBuilder {
+    /// Create a builder of a record named `name`, which may be a fullname.
+    pub fn new(name: &str) -> RecordSchemaBuilder {
+        RecordSchemaBuilder {
+            name: Name::new(name),
+            doc: None,
+            fields: Vec::new(),
+        }
+    }
 
-    Some(v)
+    /// Set the namespace of the record.
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.name.namespace = Some(namespace.to_owned());
+        self
+    }
+
+    /// Set the aliases of the record.
+    pub fn aliases<S: Into<String>>(mut self, aliases: impl IntoIterator<Item = S>) -> Self {
+        self.name.aliases = Some(aliases.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the documentation of the record.
+    pub fn doc(mut self, doc: &str) -> Self {
+        self.doc = Some(doc.to_owned());
+        self
+    }
+
+    /// Append a field without default value.
+    pub fn field(self, name: &str, schema: Schema) -> Self {
+        self.add_field(name, schema, None)
+    }
+
+    /// Append a field with a `default` value, given in its JSON representation.
+    pub fn field_with_default(self, name: &str, schema: Schema, default: Value) -> Self {
+        self.add_field(name, schema, Some(default))
+    }
+
+    /// Append a field, whose `position` is overwritten with its actual position.
+    pub fn record_field(mut self, field: RecordField) -> Self {
+        let position = self.fields.len();
+        self.fields.push(RecordField { position, ..field });
+        self
+    }
+
+    fn add_field(self, name: &str, schema: Schema, default: Option<Value>) -> Self {
+        self.record_field(RecordField {
+            name: name.to_owned(),
+            doc: None,
+            default,
+            schema,
+            order: RecordFieldOrder::Ascending,
+            position: 0,
+        })
+    }
+
+    /// Build the `Schema::Record`.
+    ///
+    /// Fails if two fields have the same name, or if a default value does not match the
+    /// schema of its field.
+    pub fn build(self) -> Result<Schema, Error> {
+        let lookup = rebuild_lookup(&self.fields);
+        validate_record_schema(&self.name, &self.fields, &lookup)?;
+        for field in &self.fields {
+            field.default_as_value()?;
+        }
+        Ok(Schema::Record {
+            name: self.name,
+            doc: self.doc,
+            fields: self.fields,
+            lookup,
+        })
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Builder of `Schema::Enum`.
+#[derive(Clone, Debug)]
+pub struct EnumSchemaBuilder {
+    name: Name,
+    doc: Documentation,
+    symbols: Vec<String>,
+}
 
-    #[test]
-    fn test_invalid_schema() {
-        assert!(Schema::parse_str("invalid").is_err());
+impl EnumSchemaBuilder {
+    /// Create a builder of an enum named `name`, which may be a fullname.
+    pub fn new<S: Into<String>>(
+        name: &str,
+        symbols: impl IntoIterator<Item = S>,
+    ) -> EnumSchemaBuilder {
+        EnumSchemaBuilder {
+            name: Name::new(name),
+            doc: None,
+            symbols: symbols.into_iter().map(Into::into).collect(),
+        }
     }
 
-    #[test]
-    fn test_primitive_schema() {
-        assert_eq!(Schema::Null, Schema::parse_str("\"null\"").unwrap());
-        assert_eq!(Schema::Int, Schema::parse_str("\"int\"").unwrap());
-        assert_eq!(Schema::Double, Schema::parse_str("\"double\"").unwrap());
+    /// Set the namespace of the enum.
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.name.namespace = Some(namespace.to_owned());
+        self
     }
 
-    #[test]
-    fn test_array_schema() {
-        let schema = Schema::parse_str(r#"{"type": "array", "items": "string"}"#).unwrap();
-        assert_eq!(Schema::Array(Box::new(Schema::String)), schema);
+    /// Set the aliases of the enum.
+    pub fn aliases<S: Into<String>>(mut self, aliases: impl IntoIterator<Item = S>) -> Self {
+        self.name.aliases = Some(aliases.into_iter().map(Into::into).collect());
+        self
     }
 
-    #[test]
-    fn test_map_schema() {
-        let schema = Schema::parse_str(r#"{"type": "map", "values": "double"}"#).unwrap();
-        assert_eq!(Schema::Map(Box::new(Schema::Double)), schema);
+    /// Set the documentation of the enum.
+    pub fn doc(mut self, doc: &str) -> Self {
+        self.doc = Some(doc.to_owned());
+        self
+    }
+
+    /// Build the `Schema::Enum`.
+    ///
+    /// Fails if a symbol is defined twice.
+    pub fn build(self) -> Result<Schema, Error> {
+        for (index, symbol) in self.symbols.iter().enumerate() {
+            if self.symbols[..index].contains(symbol) {
+                Err(ParseSchemaError::new(format!(
+                    "Duplicate enum symbol {}",
+                    symbol
+                )))?;
+            }
+        }
+        Ok(Schema::Enum {
+            name: self.name,
+            doc: self.doc,
+            symbols: self.symbols,
+        })
     }
+}
+
+/// Builder of `Schema::Fixed`.
+#[derive(Clone, Debug)]
+pub struct FixedSchemaBuilder {
+    name: Name,
+    size: usize,
+}
+
+impl FixedSchemaBuilder {
+    /// Create a builder of a fixed of `size` bytes named `name`, which may be a fullname.
+    pub fn new(name: &str, size: usize) -> FixedSchemaBuilder {
+        FixedSchemaBuilder {
+            name: Name::new(name),
+            size,
+        }
+    }
+
+    /// Set the namespace of the fixed.
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.name.namespace = Some(namespace.to_owned());
+        self
+    }
+
+    /// Set the aliases of the fixed.
+    pub fn aliases<S: Into<String>>(mut self, aliases: impl IntoIterator<Item = S>) -> Self {
+        self.name.aliases = Some(aliases.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Build the `Schema::Fixed`.
+    pub fn build(self) -> Result<Schema, Error> {
+        Ok(Schema::Fixed {
+            name: self.name,
+            size: self.size,
+        })
+    }
+}
+
+/// Builder of `Schema::Decimal`, with a scale of 0 and stored as `bytes` unless set otherwise.
+#[derive(Clone, Debug)]
+pub struct DecimalSchemaBuilder {
+    precision: usize,
+    scale: u32,
+    inner: Schema,
+}
+
+impl DecimalSchemaBuilder {
+    /// Create a builder of a decimal of `precision` digits.
+    pub fn new(precision: usize) -> DecimalSchemaBuilder {
+        DecimalSchemaBuilder {
+            precision,
+            scale: 0,
+            inner: Schema::Bytes,
+        }
+    }
+
+    /// Set the scale of the decimal.
+    pub fn scale(mut self, scale: u32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Set the underlying schema of the decimal, which must be `Schema::Bytes` or a
+    /// `Schema::Fixed`.
+    pub fn inner(mut self, inner: Schema) -> Self {
+        self.inner = inner;
+        self
+    }
+
+    /// Build the `Schema::Decimal`.
+    ///
+    /// Fails if the precision is 0, if the scale is greater than the precision, or if the
+    /// underlying schema is neither `bytes` nor a `fixed` large enough to hold `precision`
+    /// digits.
+    pub fn build(self) -> Result<Schema, Error> {
+        let max_precision = match self.inner {
+            Schema::Bytes => usize::MAX,
+            Schema::Fixed { size, .. } => decimal::max_precision_for_len(size),
+            ref other => Err(ParseSchemaError::new(format!(
+                "Decimal cannot be stored as {:?}",
+                other
+            )))?,
+        };
+        if self.precision == 0 || self.precision > max_precision {
+            Err(ParseSchemaError::new(format!(
+                "Invalid decimal precision {}",
+                self.precision
+            )))?;
+        }
+        if self.scale as usize > self.precision {
+            Err(ParseSchemaError::new(format!(
+                "Decimal scale {} is greater than its precision {}",
+                self.scale, self.precision
+            )))?;
+        }
+        Ok(Schema::Decimal {
+            precision: self.precision,
+            scale: self.scale,
+            inner: Box::new(self.inner),
+        })
+    }
+}
+
+/// Builder of `Schema::Union`.
+#[derive(Clone, Debug, Default)]
+pub struct UnionSchemaBuilder {
+    variants: Vec<Schema>,
+}
+
+impl UnionSchemaBuilder {
+    /// Create a builder of an empty union.
+    pub fn new() -> UnionSchemaBuilder {
+        UnionSchemaBuilder::default()
+    }
+
+    /// Append a variant.
+    pub fn variant(mut self, schema: Schema) -> Self {
+        self.variants.push(schema);
+        self
+    }
+
+    /// Build the `Schema::Union`.
+    ///
+    /// Fails if a variant is itself a union, or if two variants have the same type.
+    pub fn build(self) -> Result<Schema, Error> {
+        UnionSchema::new(self.variants).map(Schema::Union)
+    }
+}
+
+/// Visitor of the nodes of a `Schema` tree, as walked by [`walk`](fn.walk.html).
+///
+/// Every method does nothing by default, so that visitors only implement the ones they need.
+pub trait SchemaVisitor {
+    /// Visit a `record`, before its fields.
+    fn visit_record(&mut self, _name: &Name, _fields: &[RecordField]) {}
+
+    /// Visit a field of a `record`, before its schema.
+    fn visit_field(&mut self, _field: &RecordField) {}
+
+    /// Visit an `enum`.
+    fn visit_enum(&mut self, _name: &Name, _symbols: &[String]) {}
+
+    /// Visit a `fixed`, including the one annotated by a `duration`.
+    fn visit_fixed(&mut self, _name: &Name, _size: usize) {}
+
+    /// Visit a `union`, before its variants.
+    fn visit_union(&mut self, _variants: &[Schema]) {}
+
+    /// Visit an `array`, before the schema of its items.
+    fn visit_array(&mut self, _item: &Schema) {}
+
+    /// Visit a `map`, before the schema of its values.
+    fn visit_map(&mut self, _value: &Schema) {}
+
+    /// Visit a reference to a named type, which is not followed.
+    fn visit_ref(&mut self, _name: &Name) {}
+
+    /// Visit a primitive type, or a logical type annotating one. A `decimal` is visited before
+    /// the `bytes` or `fixed` schema it annotates.
+    fn visit_primitive(&mut self, _schema: &Schema) {}
+}
+
+/// Walk the tree of `schema` depth-first, calling `visitor` on every node before its children.
+///
+/// References to named types (`Schema::Ref`) are visited but not followed, so that recursive
+/// schemas are walked only once.
+pub fn walk<V: SchemaVisitor>(schema: &Schema, visitor: &mut V) {
+    match *schema {
+        Schema::Record {
+            ref name,
+            ref fields,
+            ..
+        } => {
+            visitor.visit_record(name, fields);
+            for field in fields {
+                visitor.visit_field(field);
+                walk(&field.schema, visitor);
+            }
+        }
+        Schema::Enum {
+            ref name,
+            ref symbols,
+            ..
+        } => visitor.visit_enum(name, symbols),
+        Schema::Fixed { ref name, size } => visitor.visit_fixed(name, size),
+        Schema::Duration { ref name } => visitor.visit_fixed(name, 12),
+        Schema::Union(ref union) => {
+            visitor.visit_union(union.variants());
+            for variant in union.variants() {
+                walk(variant, visitor);
+            }
+        }
+        Schema::Array(ref item) => {
+            visitor.visit_array(item);
+            walk(item, visitor);
+        }
+        Schema::Map(ref value) => {
+            visitor.visit_map(value);
+            walk(value, visitor);
+        }
+        Schema::Ref { ref name } => visitor.visit_ref(name),
+        Schema::Decimal { ref inner, .. } => {
+            visitor.visit_primitive(schema);
+            walk(inner, visitor);
+        }
+        _ => visitor.visit_primitive(schema),
+    }
+}
+
+/// A [`SchemaVisitor`](trait.SchemaVisitor.html) collecting the names of the named types
+/// defined in a schema, in the order they are walked.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaCollector {
+    names: Vec<Name>,
+}
+
+impl SchemaCollector {
+    /// Create an empty `SchemaCollector`.
+    pub fn new() -> SchemaCollector {
+        SchemaCollector::default()
+    }
+
+    /// Return the names collected so far.
+    pub fn names(&self) -> &[Name] {
+        &self.names
+    }
+}
+
+impl SchemaVisitor for SchemaCollector {
+    fn visit_record(&mut self, name: &Name, _fields: &[RecordField]) {
+        self.names.push(name.clone());
+    }
+
+    fn visit_enum(&mut self, name: &Name, _symbols: &[String]) {
+        self.names.push(name.clone());
+    }
+
+    fn visit_fixed(&mut self, name: &Name, _size: usize) {
+        self.names.push(name.clone());
+    }
+}
+
+/// Types with a statically known Avro `Schema`.
+///
+/// Enable the `derive` feature to derive it for structs with named fields and enums with unit
+/// variants:
+///
+/// ```ignore
+/// use avro_rs::AvroSchema;
+///
+/// #[derive(AvroSchema)]
+/// #[avro(namespace = "org.example", doc = "A user")]
+/// struct User {
+///     name: String,
+///     #[avro(rename = "favorite_number", default = 7)]
+///     number: i32,
+///     emails: Vec<String>,
+///     nickname: Option<String>,
+/// }
+/// ```
+pub trait AvroSchema {
+    /// Returns the schema of this type.
+    fn schema() -> Schema;
+}
+
+macro_rules! impl_avro_schema {
+    ($schema:expr, $($t:ty),+) => {
+        $(
+            impl AvroSchema for $t {
+                fn schema() -> Schema {
+                    $schema
+                }
+            }
+        )+
+    };
+}
+
+impl_avro_schema!(Schema::Null, ());
+impl_avro_schema!(Schema::Boolean, bool);
+impl_avro_schema!(Schema::Int, i8, i16, i32, u8, u16);
+impl_avro_schema!(Schema::Long, i64, u32, u64);
+impl_avro_schema!(Schema::Float, f32);
+impl_avro_schema!(Schema::Double, f64);
+impl_avro_schema!(Schema::String, char, String, str);
+
+impl<T: AvroSchema + ?Sized> AvroSchema for &T {
+    fn schema() -> Schema {
+        T::schema()
+    }
+}
+
+impl<T: AvroSchema + ?Sized> AvroSchema for Box<T> {
+    fn schema() -> Schema {
+        T::schema()
+    }
+}
+
+impl<T: AvroSchema> AvroSchema for Option<T> {
+    /// A union of `null` and the schema of `T`, in this order.
+    fn schema() -> Schema {
+        UnionSchema::new(vec![Schema::Null, T::schema()])
+            .map(Schema::Union)
+            .expect("Option<T> requires T not to be a nullable union")
+    }
+}
+
+impl<T: AvroSchema> AvroSchema for Vec<T> {
+    fn schema() -> Schema {
+        Schema::Array(Box::new(T::schema()))
+    }
+}
+
+impl<T: AvroSchema> AvroSchema for [T] {
+    fn schema() -> Schema {
+        Schema::Array(Box::new(T::schema()))
+    }
+}
+
+impl<T: AvroSchema, S> AvroSchema for HashMap<String, T, S> {
+    fn schema() -> Schema {
+        Schema::Map(Box::new(T::schema()))
+    }
+}
+
+impl<T: AvroSchema> AvroSchema for BTreeMap<String, T> {
+    fn schema() -> Schema {
+        Schema::Map(Box::new(T::schema()))
+    }
+}
+
+impl Schema {
+    /// Create a `Schema` from a string representing a JSON Avro schema.
+    pub fn parse_str(input: &str) -> Result<Self, Error> {
+        let value = serde_json::from_str(input)?;
+        Self::parse(&value)
+    }
+
+    /// Create a list of `Schema`s from a list of strings representing JSON Avro schemas, where
+    /// each schema can reference the named types defined by the schemas before it.
+    ///
+    /// A string holding a JSON array is read as a list of schemas, rather than as a union.
+    ///
+    /// ```
+    /// use avro_rs::Schema;
+    ///
+    /// let schemas = Schema::parse_list(&[
+    ///     r#"{"type": "fixed", "name": "MD5", "size": 16}"#,
+    ///     r#"{"type": "record", "name": "Hash", "fields": [{"name": "md5", "type": "MD5"}]}"#,
+    /// ])
+    /// .unwrap();
+    /// assert_eq!(schemas.len(), 2);
+    /// ```
+    pub fn parse_list(inputs: &[&str]) -> Result<Vec<Self>, Error> {
+        let mut store = SchemaStore::new();
+        let mut schemas = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            match serde_json::from_str(input)? {
+                Value::Array(ref values) => {
+                    for value in values {
+                        schemas.push(store.parse(value)?);
+                    }
+                }
+                ref value => schemas.push(store.parse(value)?),
+            }
+        }
+        Ok(schemas)
+    }
+
+    /// Create a `Schema` from a `serde_json::Value` representing a JSON Avro
+    /// schema.
+    pub fn parse(value: &Value) -> Result<Self, Error> {
+        Schema::parse_with_names(value, &mut HashMap::new(), None)
+    }
+
+    /// Parse a `serde_json::Value` nested in the `namespace` of its enclosing named type, given
+    /// the named types defined so far.
+    fn parse_with_names(
+        value: &Value,
+        names: &mut Names,
+        namespace: Option<&str>,
+    ) -> Result<Self, Error> {
+        match *value {
+            Value::String(ref t) => Schema::parse_reference(t.as_str(), names, namespace),
+            Value::Object(ref data) => Schema::parse_complex(data, names, namespace),
+            Value::Array(ref data) => Schema::parse_union(data, names, namespace),
+            _ => Err(ParseSchemaError::new("Must be a JSON string, object or array").into()),
+        }
+    }
+
+    /// Converts `self` into its [Parsing Canonical Form].
+    ///
+    /// Named types are written out in full on their first occurrence only, and replaced by their
+    /// fullname afterwards.
+    ///
+    /// [Parsing Canonical Form]:
+    /// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
+    pub fn canonical_form(&self) -> String {
+        let json = serde_json::to_value(self).unwrap();
+        parsing_canonical_form(&json)
+    }
+
+    /// Converts `self` into its JSON representation.
+    ///
+    /// Named types are written out in full on their first occurrence only, and replaced by their
+    /// fullname afterwards, so that the result can be parsed back into the same `Schema`.
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).unwrap()
+    }
+
+    /// Converts `self` into its JSON representation, as a string.
+    ///
+    /// See [`to_json`](#method.to_json).
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Returns the name of a `record`, `enum` or `fixed` schema (including the `fixed` underlying
+    /// a logical type), or of the named type referenced by a `Schema::Ref`.
+    pub fn name(&self) -> Option<&Name> {
+        match *self {
+            Schema::Record { ref name, .. }
+            | Schema::Enum { ref name, .. }
+            | Schema::Fixed { ref name, .. }
+            | Schema::Duration { ref name }
+            | Schema::Ref { ref name } => Some(name),
+            Schema::Decimal { ref inner, .. } => inner.name(),
+            _ => None,
+        }
+    }
+
+    /// Returns true if `self` has a [name](#method.name).
+    pub fn is_named(&self) -> bool {
+        self.name().is_some()
+    }
+
+    /// Returns the documentation of a `record` or `enum` schema.
+    pub fn doc(&self) -> Option<&str> {
+        match *self {
+            Schema::Record { ref doc, .. } | Schema::Enum { ref doc, .. } => doc.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns true if `self` is one of the primitive types, or a logical type annotating one.
+    pub fn is_primitive(&self) -> bool {
+        match *self {
+            Schema::Null
+            | Schema::Boolean
+            | Schema::Int
+            | Schema::Long
+            | Schema::Float
+            | Schema::Double
+            | Schema::Bytes
+            | Schema::String
+            | Schema::Date
+            | Schema::TimeMillis
+            | Schema::TimeMicros
+            | Schema::TimestampMillis
+            | Schema::TimestampMicros
+            | Schema::Uuid => true,
+            Schema::Decimal { ref inner, .. } => inner.is_primitive(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if `self` is one of the complex types, a logical type annotating one, or a
+    /// reference to a named type.
+    pub fn is_complex(&self) -> bool {
+        !self.is_primitive()
+    }
+
+    /// Returns true if `self` is a `union` schema.
+    pub fn is_union(&self) -> bool {
+        matches!(*self, Schema::Union(_))
+    }
+
+    /// Walk the tree of this schema with `visitor`, as [`walk`](fn.walk.html) does.
+    pub fn walk<V: SchemaVisitor>(&self, visitor: &mut V) {
+        walk(self, visitor)
+    }
+
+    /// Returns the schema of the items of an `array`, or of the values of a `map`.
+    pub fn item_schema(&self) -> Option<&Schema> {
+        match *self {
+            Schema::Array(ref inner) | Schema::Map(ref inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    /// Returns the fields of a `record` schema.
+    pub fn fields(&self) -> Option<&[RecordField]> {
+        match *self {
+            Schema::Record { ref fields, .. } => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of fields of a `record` schema.
+    pub fn field_count(&self) -> Option<usize> {
+        self.fields().map(|fields| fields.len())
+    }
+
+    /// Returns the field named `name` of a `record` schema.
+    pub fn find_field(&self, name: &str) -> Option<&RecordField> {
+        match *self {
+            Schema::Record {
+                ref fields,
+                ref lookup,
+                ..
+            } => lookup.get(name).map(|&position| &fields[position]),
+            _ => None,
+        }
+    }
+
+    /// Returns a copy of a `record` schema without its field named `name`, e.g. to test schema
+    /// evolution.
+    ///
+    /// Fails if the schema is not a record, or has no such field.
+    pub fn remove_field(&self, name: &str) -> Result<Schema, Error> {
+        self.edit_fields(|fields| {
+            let position = find_field_position(fields, name)?;
+            fields.remove(position);
+            Ok(())
+        })
+    }
+
+    /// Returns a copy of a `record` schema with `field` appended to its fields, whatever its
+    /// `position`.
+    ///
+    /// Fails if the schema is not a record, if it already has a field with the same name, or if
+    /// the default value of `field` does not match its schema.
+    pub fn add_field(&self, field: RecordField) -> Result<Schema, Error> {
+        self.edit_fields(|fields| {
+            fields.push(field);
+            Ok(())
+        })
+    }
+
+    /// Returns a copy of a `record` schema with its field named `old` renamed to `new`.
+    ///
+    /// Fails if the schema is not a record, has no field named `old`, or already has a field
+    /// named `new`.
+    pub fn rename_field(&self, old: &str, new: &str) -> Result<Schema, Error> {
+        self.edit_fields(|fields| {
+            let position = find_field_position(fields, old)?;
+            fields[position].name = new.to_owned();
+            Ok(())
+        })
+    }
+
+    /// Returns a copy of a `record` schema whose fields are modified by `edit`, rebuilding their
+    /// positions and lookup.
+    fn edit_fields<F>(&self, edit: F) -> Result<Schema, Error>
+    where
+        F: FnOnce(&mut Vec<RecordField>) -> Result<(), Error>,
+    {
+        match *self {
+            Schema::Record {
+                ref name,
+                ref doc,
+                ref fields,
+                ..
+            } => {
+                let mut fields = fields.clone();
+                edit(&mut fields)?;
+                fields
+                    .into_iter()
+                    .fold(
+                        RecordSchemaBuilder {
+                            name: name.clone(),
+                            doc: doc.clone(),
+                            fields: Vec::new(),
+                        },
+                        RecordSchemaBuilder::record_field,
+                    )
+                    .build()
+            }
+            _ => Err(ParseSchemaError::new(format!(
+                "Record expected, got {}",
+                types::describe_schema(self)
+            ))
+            .into()),
+        }
+    }
+
+    /// Returns the symbols of an `enum` schema.
+    pub fn enum_symbols(&self) -> Option<&[String]> {
+        match *self {
+            Schema::Enum { ref symbols, .. } => Some(symbols),
+            _ => None,
+        }
+    }
+
+    /// Returns the position of `symbol` among the symbols of an `enum` schema.
+    pub fn symbol_index(&self, symbol: &str) -> Option<usize> {
+        self.enum_symbols()?.iter().position(|s| s == symbol)
+    }
+
+    /// Generate [fingerprint] of Schema's [Parsing Canonical Form].
+    ///
+    /// [Parsing Canonical Form]:
+    /// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
+    /// [fingerprint]:
+    /// https://avro.apache.org/docs/current/spec.html#schema_fingerprints
+    pub fn fingerprint<D: Digest>(&self) -> SchemaFingerprint {
+        let mut d = D::new();
+        d.input(self.canonical_form());
+        SchemaFingerprint {
+            bytes: d.result().to_vec(),
+        }
+    }
+
+    /// Generate the 8-byte CRC-64-AVRO (Rabin) [fingerprint] of Schema's Parsing Canonical Form.
+    ///
+    /// [fingerprint]:
+    /// https://avro.apache.org/docs/current/spec.html#schema_fingerprints
+    pub fn fingerprint_crc64(&self) -> SchemaFingerprint {
+        self.fingerprint::<Rabin>()
+    }
+
+    /// Generate the 16-byte MD5 [fingerprint] of Schema's Parsing Canonical Form.
+    ///
+    /// [fingerprint]:
+    /// https://avro.apache.org/docs/current/spec.html#schema_fingerprints
+    pub fn fingerprint_md5(&self) -> SchemaFingerprint {
+        self.fingerprint::<Md5>()
+    }
+
+    /// Generate the 32-byte SHA-256 [fingerprint] of Schema's Parsing Canonical Form.
+    ///
+    /// [fingerprint]:
+    /// https://avro.apache.org/docs/current/spec.html#schema_fingerprints
+    pub fn fingerprint_sha256(&self) -> SchemaFingerprint {
+        self.fingerprint::<Sha256>()
+    }
+
+    /// Parse a `serde_json::Value` representing a primitive Avro type into a
+    /// `Schema`.
+    fn parse_primitive(primitive: &str) -> Result<Self, Error> {
+        match primitive {
+            "null" => Ok(Schema::Null),
+            "boolean" => Ok(Schema::Boolean),
+            "int" => Ok(Schema::Int),
+            "long" => Ok(Schema::Long),
+            "double" => Ok(Schema::Double),
+            "float" => Ok(Schema::Float),
+            "bytes" => Ok(Schema::Bytes),
+            "string" => Ok(Schema::String),
+            other => Err(ParseSchemaError::new(format!("Unknown type: {}", other)).into()),
+        }
+    }
+
+    /// Parse a name, either of a primitive type or of a named type already defined, into a
+    /// `Schema`.
+    ///
+    /// A reference to a `record` whose definition is not complete yet, i.e. to an enclosing
+    /// `record`, is parsed into a `Schema::Ref`.
+    fn parse_reference(name: &str, names: &Names, namespace: Option<&str>) -> Result<Self, Error> {
+        if is_primitive(name) {
+            return Schema::parse_primitive(name);
+        }
+
+        let fullname = Name::new(&Name::new(name).fullname(namespace));
+        // Unqualified names may also refer to types defined in the null namespace.
+        let (fullname, schema) = match names.get(&fullname) {
+            Some(schema) => (fullname, schema),
+            None => {
+                let name = Name::new(name);
+                match names.get(&name) {
+                    Some(schema) => (name, schema),
+                    None => {
+                        return Err(ParseSchemaError::new(format!("Unknown type: {}", name)).into())
+                    }
+                }
+            }
+        };
+
+        match *schema {
+            Some(ref schema) => Ok(Schema::clone(schema)),
+            None => Ok(Schema::Ref { name: fullname }),
+        }
+    }
+
+    /// Parse a `serde_json::Value` representing a complex Avro type into a
+    /// `Schema`.
+    ///
+    /// Avro supports "recursive" definition of types.
+    /// e.g: {"type": {"type": "string"}}
+    fn parse_complex(
+        complex: &Map<String, Value>,
+        names: &mut Names,
+        namespace: Option<&str>,
+    ) -> Result<Self, Error> {
+        if let Some(logical_type) = complex.get("logicalType").and_then(|v| v.as_str()) {
+            let mut underlying = complex.clone();
+            underlying.remove("logicalType");
+            let inner = Schema::parse_complex(&underlying, names, namespace)?;
+            let schema = Schema::parse_logical(logical_type, complex, inner)?;
+            // References to a named underlying type also carry the logical type.
+            let name = match schema {
+                Schema::Duration { ref name } => Some(name),
+                Schema::Decimal { ref inner, .. } => match **inner {
+                    Schema::Fixed { ref name, .. } => Some(name),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some(name) = name {
+                names.insert(name.clone(), Some(Arc::new(schema.clone())));
+            }
+            return Ok(schema);
+        }
+
+        match complex.get("type") {
+            Some(&Value::String(ref t)) => match t.as_str() {
+                "record" => Schema::parse_record(complex, names, namespace),
+                "enum" => Schema::parse_enum(complex, names, namespace),
+                "array" => Schema::parse_array(complex, names, namespace),
+                "map" => Schema::parse_map(complex, names, namespace),
+                "fixed" => Schema::parse_fixed(complex, names, namespace),
+                other => Schema::parse_reference(other, names, namespace),
+            },
+            Some(&Value::Object(ref data)) => Schema::parse_complex(data, names, namespace),
+            _ => Err(ParseSchemaError::new("No `type` in complex type").into()),
+        }
+    }
+
+    /// Returns true if `name` is the type name of `self`, as used to look up union variants.
+    fn has_type_name(&self, name: &str) -> bool {
+        match *self {
+            Schema::Null => name == "null",
+            Schema::Boolean => name == "boolean",
+            Schema::Int | Schema::Date | Schema::TimeMillis => name == "int",
+            Schema::Long
+            | Schema::TimeMicros
+            | Schema::TimestampMillis
+            | Schema::TimestampMicros => name == "long",
+            Schema::Float => name == "float",
+            Schema::Double => name == "double",
+            Schema::Bytes => name == "bytes",
+            Schema::String | Schema::Uuid => name == "string",
+            Schema::Array(_) => name == "array",
+            Schema::Map(_) => name == "map",
+            Schema::Union(_) => false,
+            Schema::Record { name: ref n, .. }
+            | Schema::Enum { name: ref n, .. }
+            | Schema::Fixed { name: ref n, .. }
+            | Schema::Duration { name: ref n }
+            | Schema::Ref { name: ref n } => n.name == name || n.full_name() == name,
+            Schema::Decimal { ref inner, .. } => inner.has_type_name(name),
+        }
+    }
+
+    /// Register the definition of the named type `name`, which is `None` while the definition
+    /// is being parsed.
+    fn define(names: &mut Names, name: &Name, schema: Option<&Schema>) -> Result<(), Error> {
+        if schema.is_none() && names.contains_key(name) {
+            return Err(ParseSchemaError::new(format!("Duplicate definition of {}", name)).into());
+        }
+        names.insert(name.clone(), schema.cloned().map(Arc::new));
+        Ok(())
+    }
+
+    /// Parse a `serde_json::Value` representing a Avro record type into a
+    /// `Schema`.
+    fn parse_record(
+        complex: &Map<String, Value>,
+        names: &mut Names,
+        namespace: Option<&str>,
+    ) -> Result<Self, Error> {
+        let name = Name::parse(complex, namespace)?;
+        Schema::define(names, &name, None)?;
+
+        let fields: Vec<RecordField> = complex
+            .get("fields")
+            .and_then(|fields| fields.as_array())
+            .ok_or_else(|| ParseSchemaError::new("No `fields` in record").into())
+            .and_then(|fields| {
+                fields
+                    .iter()
+                    .filter_map(|field| field.as_object())
+                    .enumerate()
+                    .map(|(position, field)| {
+                        RecordField::parse(field, position, names, name.namespace.as_deref())
+                    })
+                    .collect::<Result<_, _>>()
+            })?;
+        let lookup = rebuild_lookup(&fields);
+        validate_record_schema(&name, &fields, &lookup)?;
+
+        let schema = Schema::Record {
+            name,
+            doc: complex.doc(),
+            fields,
+            lookup,
+        };
+        if let Schema::Record { ref name, .. } = schema {
+            Schema::define(names, name, Some(&schema))?;
+        }
+        Ok(schema)
+    }
+
+    /// Parse a `serde_json::Value` representing a Avro enum type into a
+    /// `Schema`.
+    fn parse_enum(
+        complex: &Map<String, Value>,
+        names: &mut Names,
+        namespace: Option<&str>,
+    ) -> Result<Self, Error> {
+        let name = Name::parse(complex, namespace)?;
+        Schema::define(names, &name, None)?;
+
+        let symbols = complex
+            .get("symbols")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| ParseSchemaError::new("No `symbols` field in enum"))
+            .and_then(|symbols| {
+                symbols
+                    .iter()
+                    .map(|symbol| symbol.as_str().map(|s| s.to_string()))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| ParseSchemaError::new("Unable to parse `symbols` in enum"))
+            })?;
+
+        let schema = Schema::Enum {
+            name: name.clone(),
+            doc: complex.doc(),
+            symbols,
+        };
+        Schema::define(names, &name, Some(&schema))?;
+        Ok(schema)
+    }
+
+    /// Parse a `serde_json::Value` representing a Avro array type into a
+    /// `Schema`.
+    fn parse_array(
+        complex: &Map<String, Value>,
+        names: &mut Names,
+        namespace: Option<&str>,
+    ) -> Result<Self, Error> {
+        complex
+            .get("items")
+            .ok_or_else(|| ParseSchemaError::new("No `items` in array").into())
+            .and_then(|items| Schema::parse_with_names(items, names, namespace))
+            .map(|schema| Schema::Array(Box::new(schema)))
+    }
+
+    /// Parse a `serde_json::Value` representing a Avro map type into a
+    /// `Schema`.
+    fn parse_map(
+        complex: &Map<String, Value>,
+        names: &mut Names,
+        namespace: Option<&str>,
+    ) -> Result<Self, Error> {
+        complex
+            .get("values")
+            .ok_or_else(|| ParseSchemaError::new("No `values` in map").into())
+            .and_then(|items| Schema::parse_with_names(items, names, namespace))
+            .map(|schema| Schema::Map(Box::new(schema)))
+    }
+
+    /// Parse a `serde_json::Value` representing a Avro union type into a
+    /// `Schema`.
+    fn parse_union(
+        items: &[Value],
+        names: &mut Names,
+        namespace: Option<&str>,
+    ) -> Result<Self, Error> {
+        items
+            .iter()
+            .map(|item| Schema::parse_with_names(item, names, namespace))
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|schemas| Ok(Schema::Union(UnionSchema::new(schemas)?)))
+    }
+
+    /// Parse a `serde_json::Value` representing a Avro fixed type into a
+    /// `Schema`.
+    fn parse_fixed(
+        complex: &Map<String, Value>,
+        names: &mut Names,
+        namespace: Option<&str>,
+    ) -> Result<Self, Error> {
+        let name = Name::parse(complex, namespace)?;
+        Schema::define(names, &name, None)?;
+
+        let size = complex
+            .get("size")
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| ParseSchemaError::new("No `size` in fixed"))?;
+
+        let schema = Schema::Fixed {
+            name: name.clone(),
+            size: size as usize,
+        };
+        Schema::define(names, &name, Some(&schema))?;
+        Ok(schema)
+    }
+
+    /// Parse a `serde_json::Value` annotated with a `logicalType` into a `Schema`, given the
+    /// already parsed underlying `inner` schema.
+    ///
+    /// As mandated by the Avro specification, unknown or invalid logical types are ignored and
+    /// the underlying schema is returned instead.
+    fn parse_logical(
+        logical_type: &str,
+        complex: &Map<String, Value>,
+        inner: Schema,
+    ) -> Result<Self, Error> {
+        match logical_type {
+            "decimal" => Schema::parse_decimal(complex, inner),
+            "date" if inner == Schema::Int => Ok(Schema::Date),
+            "time-millis" if inner == Schema::Int => Ok(Schema::TimeMillis),
+            "time-micros" if inner == Schema::Long => Ok(Schema::TimeMicros),
+            "timestamp-millis" if inner == Schema::Long => Ok(Schema::TimestampMillis),
+            "timestamp-micros" if inner == Schema::Long => Ok(Schema::TimestampMicros),
+            "uuid" if inner == Schema::String => Ok(Schema::Uuid),
+            "duration" => match inner {
+                Schema::Fixed { name, size: 12 } => Ok(Schema::Duration { name }),
+                _ => Ok(inner),
+            },
+            _ => Ok(inner),
+        }
+    }
+
+    /// Parse a `serde_json::Value` representing a Avro decimal logical type into a
+    /// `Schema`.
+    fn parse_decimal(complex: &Map<String, Value>, inner: Schema) -> Result<Self, Error> {
+        let max_precision = match inner {
+            Schema::Bytes => usize::MAX,
+            Schema::Fixed { size, .. } => decimal::max_precision_for_len(size),
+            _ => return Ok(inner),
+        };
+
+        let precision = complex.get("precision").and_then(|v| v.as_u64());
+        let scale = match complex.get("scale") {
+            Some(scale) => scale.as_u64(),
+            None => Some(0),
+        };
+
+        match (precision, scale) {
+            (Some(precision), Some(scale))
+                if precision > 0 && scale <= precision && precision as usize <= max_precision =>
+            {
+                Ok(Schema::Decimal {
+                    precision: precision as usize,
+                    scale: scale as u32,
+                    inner: Box::new(inner),
+                })
+            }
+            _ => Ok(inner),
+        }
+    }
+}
+
+/// Parses a `Schema` from a string representing a JSON Avro schema, as
+/// [`parse_str`](#method.parse_str) does.
+impl FromStr for Schema {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Schema::parse_str(input)
+    }
+}
+
+/// Formats a `Schema` as its [Parsing Canonical Form](#method.canonical_form).
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.canonical_form())
+    }
+}
+
+impl Serialize for Schema {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        SchemaSerializer {
+            schema: self,
+            namespace: None,
+            defined: &RefCell::new(HashSet::new()),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Serializes a `Schema` nested in an enclosing namespace, writing out every named type in full
+/// on its first occurrence only and as its fullname afterwards.
+struct SchemaSerializer<'a> {
+    schema: &'a Schema,
+    namespace: Option<&'a str>,
+    defined: &'a RefCell<HashSet<String>>,
+}
+
+impl<'a> SchemaSerializer<'a> {
+    fn nested(&self, schema: &'a Schema, namespace: Option<&'a str>) -> Self {
+        SchemaSerializer {
+            schema,
+            namespace,
+            defined: self.defined,
+        }
+    }
+
+    /// Returns the fullname of `name` if it was already written out, and marks it as written
+    /// otherwise.
+    fn reference(&self, name: &Name) -> Option<String> {
+        let fullname = name.fullname(self.namespace);
+        if self.defined.borrow_mut().insert(fullname.clone()) {
+            None
+        } else {
+            Some(fullname)
+        }
+    }
+
+    /// Value of the `namespace` attribute of the named type `name`, if it differs from the
+    /// enclosing namespace. The null namespace is written as an empty string.
+    fn namespace_attribute(&self, name: &'a Name) -> Option<&'a str> {
+        match name.namespace.as_deref() {
+            namespace if namespace == self.namespace => None,
+            Some(namespace) => Some(namespace),
+            None => Some(""),
+        }
+    }
+
+    /// Namespace enclosing the types nested in the named type `name`.
+    fn namespace_of(&self, name: &'a Name) -> Option<&'a str> {
+        name.namespace.as_deref().or(self.namespace)
+    }
+}
+
+impl<'a> Serialize for SchemaSerializer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self.schema {
+            Schema::Null => serializer.serialize_str("null"),
+            Schema::Boolean => serializer.serialize_str("boolean"),
+            Schema::Int => serializer.serialize_str("int"),
+            Schema::Long => serializer.serialize_str("long"),
+            Schema::Float => serializer.serialize_str("float"),
+            Schema::Double => serializer.serialize_str("double"),
+            Schema::Bytes => serializer.serialize_str("bytes"),
+            Schema::String => serializer.serialize_str("string"),
+            Schema::Array(ref inner) => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("type", "array")?;
+                map.serialize_entry("items", &self.nested(inner, self.namespace))?;
+                map.end()
+            }
+            Schema::Map(ref inner) => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("type", "map")?;
+                map.serialize_entry("values", &self.nested(inner, self.namespace))?;
+                map.end()
+            }
+            Schema::Union(ref inner) => {
+                let variants = inner.variants();
+                let mut seq = serializer.serialize_seq(Some(variants.len()))?;
+                for v in variants {
+                    seq.serialize_element(&self.nested(v, self.namespace))?;
+                }
+                seq.end()
+            }
+            Schema::Record {
+                ref name,
+                ref doc,
+                ref fields,
+                ..
+            } => {
+                if let Some(fullname) = self.reference(name) {
+                    return serializer.serialize_str(&fullname);
+                }
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "record")?;
+                if let Some(namespace) = self.namespace_attribute(name) {
+                    map.serialize_entry("namespace", namespace)?;
+                }
+                map.serialize_entry("name", &name.name)?;
+                if let Some(ref docstr) = doc {
+                    map.serialize_entry("doc", docstr)?;
+                }
+                if let Some(ref aliases) = name.aliases {
+                    map.serialize_entry("aliases", aliases)?;
+                }
+                let namespace = self.namespace_of(name);
+                let fields = fields
+                    .iter()
+                    .map(|field| FieldSerializer {
+                        field,
+                        schema: self.nested(&field.schema, namespace),
+                    })
+                    .collect::<Vec<_>>();
+                map.serialize_entry("fields", &fields)?;
+                map.end()
+            }
+            Schema::Enum {
+                ref name,
+                ref doc,
+                ref symbols,
+            } => {
+                if let Some(fullname) = self.reference(name) {
+                    return serializer.serialize_str(&fullname);
+                }
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "enum")?;
+                if let Some(namespace) = self.namespace_attribute(name) {
+                    map.serialize_entry("namespace", namespace)?;
+                }
+                map.serialize_entry("name", &name.name)?;
+                if let Some(ref docstr) = doc {
+                    map.serialize_entry("doc", docstr)?;
+                }
+                if let Some(ref aliases) = name.aliases {
+                    map.serialize_entry("aliases", aliases)?;
+                }
+                map.serialize_entry("symbols", symbols)?;
+                map.end()
+            }
+            Schema::Fixed { ref name, ref size } => {
+                if let Some(fullname) = self.reference(name) {
+                    return serializer.serialize_str(&fullname);
+                }
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "fixed")?;
+                if let Some(namespace) = self.namespace_attribute(name) {
+                    map.serialize_entry("namespace", namespace)?;
+                }
+                map.serialize_entry("name", &name.name)?;
+                if let Some(ref aliases) = name.aliases {
+                    map.serialize_entry("aliases", aliases)?;
+                }
+                map.serialize_entry("size", size)?;
+                map.end()
+            }
+            Schema::Decimal {
+                ref precision,
+                ref scale,
+                ref inner,
+            } => {
+                if let Schema::Fixed { ref name, .. } = **inner {
+                    if let Some(fullname) = self.reference(name) {
+                        return serializer.serialize_str(&fullname);
+                    }
+                }
+                let mut map = serializer.serialize_map(None)?;
+                match **inner {
+                    Schema::Fixed { ref name, ref size } => {
+                        map.serialize_entry("type", "fixed")?;
+                        if let Some(namespace) = self.namespace_attribute(name) {
+                            map.serialize_entry("namespace", namespace)?;
+                        }
+                        map.serialize_entry("name", &name.name)?;
+                        map.serialize_entry("size", size)?;
+                    }
+                    _ => map.serialize_entry("type", "bytes")?,
+                }
+                map.serialize_entry("logicalType", "decimal")?;
+                map.serialize_entry("precision", precision)?;
+                map.serialize_entry("scale", scale)?;
+                map.end()
+            }
+            Schema::Date => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "int")?;
+                map.serialize_entry("logicalType", "date")?;
+                map.end()
+            }
+            Schema::TimeMillis => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "int")?;
+                map.serialize_entry("logicalType", "time-millis")?;
+                map.end()
+            }
+            Schema::TimeMicros => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "long")?;
+                map.serialize_entry("logicalType", "time-micros")?;
+                map.end()
+            }
+            Schema::TimestampMillis => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "long")?;
+                map.serialize_entry("logicalType", "timestamp-millis")?;
+                map.end()
+            }
+            Schema::TimestampMicros => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "long")?;
+                map.serialize_entry("logicalType", "timestamp-micros")?;
+                map.end()
+            }
+            Schema::Uuid => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "string")?;
+                map.serialize_entry("logicalType", "uuid")?;
+                map.end()
+            }
+            Schema::Duration { ref name } => {
+                if let Some(fullname) = self.reference(name) {
+                    return serializer.serialize_str(&fullname);
+                }
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "fixed")?;
+                if let Some(namespace) = self.namespace_attribute(name) {
+                    map.serialize_entry("namespace", namespace)?;
+                }
+                map.serialize_entry("name", &name.name)?;
+                map.serialize_entry("size", &12)?;
+                map.serialize_entry("logicalType", "duration")?;
+                map.end()
+            }
+            Schema::Ref { ref name } => serializer.serialize_str(&name.full_name()),
+        }
+    }
+}
+
+impl Serialize for RecordField {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        FieldSerializer {
+            field: self,
+            schema: SchemaSerializer {
+                schema: &self.schema,
+                namespace: None,
+                defined: &RefCell::new(HashSet::new()),
+            },
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Serializes a `RecordField` along with the `SchemaSerializer` of its schema.
+struct FieldSerializer<'a> {
+    field: &'a RecordField,
+    schema: SchemaSerializer<'a>,
+}
+
+impl<'a> Serialize for FieldSerializer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("name", &self.field.name)?;
+        if let Some(ref docstr) = self.field.doc {
+            map.serialize_entry("doc", docstr)?;
+        }
+        map.serialize_entry("type", &self.schema)?;
+
+        if let Some(ref default) = self.field.default {
+            map.serialize_entry("default", default)?;
+        }
+
+        match self.field.order {
+            RecordFieldOrder::Ascending => (),
+            RecordFieldOrder::Descending => map.serialize_entry("order", "descending")?,
+            RecordFieldOrder::Ignore => map.serialize_entry("order", "ignore")?,
+        }
+
+        map.end()
+    }
+}
+
+/// Parses a **valid** avro schema into the Parsing Canonical Form.
+/// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
+fn parsing_canonical_form(schema: &serde_json::Value) -> String {
+    pcf(schema, None, &mut HashSet::new())
+}
+
+/// `namespace` is the enclosing namespace, used to fully qualify names ([FULLNAMES] rule), and
+/// `defined` holds the fullnames of the named types already written out: any later occurrence
+/// of one of them is replaced by its fullname.
+fn pcf(
+    schema: &serde_json::Value,
+    namespace: Option<&str>,
+    defined: &mut HashSet<String>,
+) -> String {
+    match schema {
+        serde_json::Value::Object(map) => pcf_map(map, namespace, defined),
+        serde_json::Value::String(s) => pcf_string(&fullname(s, namespace)),
+        serde_json::Value::Array(v) => pcf_array(v, namespace, defined),
+        _ => unreachable!(),
+    }
+}
+
+fn pcf_map(
+    schema: &Map<String, serde_json::Value>,
+    namespace: Option<&str>,
+    defined: &mut HashSet<String>,
+) -> String {
+    let kind = schema.get("type").and_then(|v| v.as_str());
+    let name = schema.get("name").and_then(|v| v.as_str());
+
+    // Reduce primitive types to their simple form, dropping any other attribute such as
+    // `logicalType` ([PRIMITIVE] rule). Record fields are the only other objects with a `name`.
+    if let (Some(kind), None) = (kind, name) {
+        if is_primitive(kind) {
+            return pcf_string(kind);
+        }
+    }
+
+    // Named types define the namespace of everything they contain.
+    let is_named = matches!(kind, Some("record") | Some("enum") | Some("fixed"));
+    let namespace = match name {
+        Some(name) if is_named => match name.rfind('.') {
+            Some(index) => Some(&name[..index]),
+            None => match schema.get("namespace").and_then(|v| v.as_str()) {
+                Some("") => None,
+                Some(namespace) => Some(namespace),
+                None => namespace,
+            },
+        },
+        _ => namespace,
+    };
+
+    let mut fields = Vec::new();
+    for (k, v) in schema {
+        // Strip out unused fields ([STRIP] rule)
+        if field_ordering_position(k).is_none() {
+            continue;
+        }
+
+        // Fully qualify the name, if it isn't already ([FULLNAMES] rule).
+        if k == "name" {
+            // Invariant: Only valid schemas. Must be a string.
+            let name = v.as_str().unwrap();
+            if !is_named {
+                fields.push((k, format!("{}:{}", pcf_string(k), pcf_string(name))));
+                continue;
+            }
+
+            let n = fullname(name, namespace).into_owned();
+            // A named type is only written out in full on its first occurrence.
+            if defined.contains(&n) {
+                return pcf_string(&n);
+            }
+            fields.push((k, format!("{}:{}", pcf_string(k), pcf_string(&n))));
+            defined.insert(n);
+            continue;
+        }
+
+        // Enum symbols are plain strings, not type names.
+        if k == "symbols" {
+            // Invariant: Only valid schemas. Must be an array of strings.
+            let symbols = v
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|symbol| pcf_string(symbol.as_str().unwrap()))
+                .collect::<Vec<_>>()
+                .join(",");
+            fields.push((k, format!("{}:[{}]", pcf_string(k), symbols)));
+            continue;
+        }
+
+        // Strip off quotes surrounding "size" type, if they exist ([INTEGERS] rule).
+        if k == "size" {
+            let i = match v.as_str() {
+                Some(s) => s.parse::<i64>().expect("Only valid schemas are accepted!"),
+                None => v.as_i64().unwrap(),
+            };
+            fields.push((k, format!("{}:{}", pcf_string(k), i)));
+            continue;
+        }
+
+        // For anything else, recursively process the result.
+        fields.push((
+            k,
+            format!("{}:{}", pcf_string(k), pcf(v, namespace, defined)),
+        ));
+    }
+
+    // Sort the fields by their canonical ordering ([ORDER] rule).
+    fields.sort_unstable_by_key(|(k, _)| field_ordering_position(k).unwrap());
+    let inter = fields
+        .into_iter()
+        .map(|(_, v)| v)
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{}}}", inter)
+}
+
+fn pcf_array(
+    arr: &[serde_json::Value],
+    namespace: Option<&str>,
+    defined: &mut HashSet<String>,
+) -> String {
+    let inter = arr
+        .iter()
+        .map(|v| pcf(v, namespace, defined))
+        .collect::<Vec<String>>()
+        .join(",");
+    format!("[{}]", inter)
+}
+
+/// Qualify `name` with `namespace`, unless it already is or it is a built-in type name.
+fn fullname<'a>(name: &'a str, namespace: Option<&str>) -> Cow<'a, str> {
+    match namespace {
+        Some(namespace) if !name.contains('.') && !is_builtin(name) => {
+            Cow::Owned(format!("{}.{}", namespace, name))
+        }
+        _ => Cow::Borrowed(name),
+    }
+}
+
+fn is_primitive(name: &str) -> bool {
+    matches!(
+        name,
+        "null" | "boolean" | "int" | "long" | "float" | "double" | "bytes" | "string"
+    )
+}
+
+fn is_builtin(name: &str) -> bool {
+    matches!(name, "record" | "enum" | "array" | "map" | "fixed") || is_primitive(name)
+}
+
+fn pcf_string(s: &str) -> String {
+    format!("\"{}\"", s)
+}
+
+// Used to define the ordering and inclusion of fields.
+fn field_ordering_position(field: &str) -> Option<usize> {
+    let v = match field {
+        "name" => 1,
+        "type" => 2,
+        "fields" => 3,
+        "symbols" => 4,
+        "items" => 5,
+        "values" => 6,
+        "size" => 7,
+        _ => return None,
+    };
+
+    Some(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_invalid_schema() {
+        assert!(Schema::parse_str("invalid").is_err());
+    }
+
+    #[test]
+    fn test_primitive_schema() {
+        assert_eq!(Schema::Null, Schema::parse_str("\"null\"").unwrap());
+        assert_eq!(Schema::Int, Schema::parse_str("\"int\"").unwrap());
+        assert_eq!(Schema::Double, Schema::parse_str("\"double\"").unwrap());
+    }
+
+    #[test]
+    fn test_parse_json_values() {
+        let union = UnionSchema::new(vec![Schema::Null, Schema::Long]).unwrap();
+        let cases = vec![
+            (json!("null"), Schema::Null),
+            (json!({"type": "boolean"}), Schema::Boolean),
+            (json!("int"), Schema::Int),
+            (json!("long"), Schema::Long),
+            (json!("float"), Schema::Float),
+            (json!("double"), Schema::Double),
+            (json!("bytes"), Schema::Bytes),
+            (json!("string"), Schema::String),
+            (
+                json!({"type": "array", "items": "int"}),
+                Schema::Array(Box::new(Schema::Int)),
+            ),
+            (
+                json!({"type": "map", "values": "string"}),
+                Schema::Map(Box::new(Schema::String)),
+            ),
+            (json!(["null", "long"]), Schema::Union(union)),
+            (
+                json!({"type": "bytes", "logicalType": "decimal", "precision": 4, "scale": 2}),
+                Schema::Decimal {
+                    precision: 4,
+                    scale: 2,
+                    inner: Box::new(Schema::Bytes),
+                },
+            ),
+            (json!({"type": "int", "logicalType": "date"}), Schema::Date),
+            (
+                json!({"type": "int", "logicalType": "time-millis"}),
+                Schema::TimeMillis,
+            ),
+            (
+                json!({"type": "long", "logicalType": "time-micros"}),
+                Schema::TimeMicros,
+            ),
+            (
+                json!({"type": "long", "logicalType": "timestamp-millis"}),
+                Schema::TimestampMillis,
+            ),
+            (
+                json!({"type": "long", "logicalType": "timestamp-micros"}),
+                Schema::TimestampMicros,
+            ),
+            (
+                json!({"type": "string", "logicalType": "uuid"}),
+                Schema::Uuid,
+            ),
+            (
+                json!({"type": "fixed", "name": "D", "size": 12, "logicalType": "duration"}),
+                Schema::Duration {
+                    name: Name::new("D"),
+                },
+            ),
+            (
+                json!({"type": "fixed", "name": "F", "namespace": "org.example", "size": 16}),
+                Schema::Fixed {
+                    name: Name::new("org.example.F"),
+                    size: 16,
+                },
+            ),
+            (
+                json!({"type": "enum", "name": "E", "symbols": ["A", "B"]}),
+                SchemaBuilder::enumeration("E", vec!["A", "B"])
+                    .build()
+                    .unwrap(),
+            ),
+            (
+                json!({
+                    "type": "record",
+                    "name": "R",
+                    "fields": [
+                        {"name": "a", "type": "long", "default": 42},
+                        {"name": "b", "type": ["null", "string"]}
+                    ]
+                }),
+                SchemaBuilder::record("R")
+                    .field_with_default("a", Schema::Long, json!(42))
+                    .field(
+                        "b",
+                        Schema::Union(
+                            UnionSchema::new(vec![Schema::Null, Schema::String]).unwrap(),
+                        ),
+                    )
+                    .build()
+                    .unwrap(),
+            ),
+        ];
+
+        for (json, expected) in cases {
+            assert_eq!(Schema::parse(&json).unwrap(), expected, "{}", json);
+            assert_eq!(
+                json.to_string().parse::<Schema>().unwrap(),
+                expected,
+                "{}",
+                json
+            );
+        }
+
+        assert!(Schema::parse(&json!({"type": "unknown"})).is_err());
+        assert!("{".parse::<Schema>().is_err());
+    }
+
+    #[test]
+    fn test_array_schema() {
+        let schema = Schema::parse_str(r#"{"type": "array", "items": "string"}"#).unwrap();
+        assert_eq!(Schema::Array(Box::new(Schema::String)), schema);
+    }
+
+    #[test]
+    fn test_map_schema() {
+        let schema = Schema::parse_str(r#"{"type": "map", "values": "double"}"#).unwrap();
+        assert_eq!(Schema::Map(Box::new(Schema::Double)), schema);
+    }
+
+    #[test]
+    fn test_union_schema() {
+        let schema = Schema::parse_str(r#"["null", "int"]"#).unwrap();
+        assert_eq!(
+            Schema::Union(UnionSchema::new(vec![Schema::Null, Schema::Int]).unwrap()),
+            schema
+        );
+    }
+
+    #[test]
+    fn test_union_unsupported_schema() {
+        let schema = Schema::parse_str(r#"["null", ["null", "int"], "string"]"#);
+        assert!(schema.is_err());
+
+        let nested = Schema::Union(UnionSchema::new(vec![Schema::Null, Schema::Int]).unwrap());
+        assert!(UnionSchema::new(vec![Schema::String, nested]).is_err());
+        assert!(UnionSchema::new(vec![Schema::Int, Schema::Null, Schema::Int]).is_err());
+    }
+
+    #[test]
+    fn test_union_with_null() {
+        let union = UnionSchema::with_null(Schema::String).unwrap();
+        assert_eq!(union.variants(), &[Schema::Null, Schema::String]);
+        assert_eq!(union.null_index(), Some(0));
+        assert!(union.is_nullable());
+
+        assert!(UnionSchema::with_null(Schema::Null).is_err());
+        assert!(UnionSchema::with_null(Schema::Union(union)).is_err());
+    }
+
+    #[test]
+    fn test_multi_union_schema() {
+        let schema = Schema::parse_str(r#"["null", "int", "float", "string", "bytes"]"#);
+        assert!(schema.is_ok());
+        let schema = schema.unwrap();
+        assert_eq!(SchemaKind::from(&schema), SchemaKind::Union);
+        let union_schema = match schema {
+            Schema::Union(u) => u,
+            _ => unreachable!(),
+        };
+        assert_eq!(union_schema.variants().len(), 5);
+        let mut variants = union_schema.variants().iter();
+        assert_eq!(SchemaKind::from(variants.next().unwrap()), SchemaKind::Null);
+        assert_eq!(SchemaKind::from(variants.next().unwrap()), SchemaKind::Int);
+        assert_eq!(
+            SchemaKind::from(variants.next().unwrap()),
+            SchemaKind::Float
+        );
+        assert_eq!(
+            SchemaKind::from(variants.next().unwrap()),
+            SchemaKind::String
+        );
+        assert_eq!(
+            SchemaKind::from(variants.next().unwrap()),
+            SchemaKind::Bytes
+        );
+        assert_eq!(variants.next(), None);
+    }
+
+    #[test]
+    fn test_record_schema() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "test",
+                "fields": [
+                    {"name": "a", "type": "long", "default": 42},
+                    {"name": "b", "type": "string"}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+
+        let mut lookup = HashMap::new();
+        lookup.insert("a".to_owned(), 0);
+        lookup.insert("b".to_owned(), 1);
+
+        let expected = Schema::Record {
+            name: Name::new("test"),
+            doc: None,
+            fields: vec![
+                RecordField {
+                    name: "a".to_string(),
+                    doc: None,
+                    default: Some(Value::Number(42i64.into())),
+                    schema: Schema::Long,
+                    order: RecordFieldOrder::Ascending,
+                    position: 0,
+                },
+                RecordField {
+                    name: "b".to_string(),
+                    doc: None,
+                    default: None,
+                    schema: Schema::String,
+                    order: RecordFieldOrder::Ascending,
+                    position: 1,
+                },
+            ],
+            lookup,
+        };
+
+        assert_eq!(expected, schema);
+    }
+
+    #[test]
+    fn test_enum_schema() {
+        let schema = Schema::parse_str(
+            r#"{"type": "enum", "name": "Suit", "symbols": ["diamonds", "spades", "clubs", "hearts"]}"#,
+        ).unwrap();
+
+        let expected = Schema::Enum {
+            name: Name::new("Suit"),
+            doc: None,
+            symbols: vec![
+                "diamonds".to_owned(),
+                "spades".to_owned(),
+                "clubs".to_owned(),
+                "hearts".to_owned(),
+            ],
+        };
+
+        assert_eq!(expected, schema);
+    }
+
+    #[test]
+    fn test_fixed_schema() {
+        let schema = Schema::parse_str(r#"{"type": "fixed", "name": "test", "size": 16}"#).unwrap();
+
+        let expected = Schema::Fixed {
+            name: Name::new("test"),
+            size: 16usize,
+        };
+
+        assert_eq!(expected, schema);
+    }
+
+    #[test]
+    fn test_decimal_schema() {
+        let schema = Schema::parse_str(
+            r#"{"type": "bytes", "logicalType": "decimal", "precision": 18, "scale": 4}"#,
+        )
+        .unwrap();
+
+        let expected = Schema::Decimal {
+            precision: 18,
+            scale: 4,
+            inner: Box::new(Schema::Bytes),
+        };
+
+        assert_eq!(expected, schema);
+
+        let schema = Schema::parse_str(
+            r#"{"type": "fixed", "name": "price", "size": 8, "logicalType": "decimal", "precision": 18}"#,
+        )
+        .unwrap();
+
+        let expected = Schema::Decimal {
+            precision: 18,
+            scale: 0,
+            inner: Box::new(Schema::Fixed {
+                name: Name::new("price"),
+                size: 8,
+            }),
+        };
+
+        assert_eq!(expected, schema);
+    }
+
+    #[test]
+    fn test_invalid_decimal_schema_falls_back_to_inner() {
+        // scale greater than precision
+        let schema = Schema::parse_str(
+            r#"{"type": "bytes", "logicalType": "decimal", "precision": 2, "scale": 4}"#,
+        )
+        .unwrap();
+        assert_eq!(Schema::Bytes, schema);
+
+        // precision too large for the fixed size
+        let schema = Schema::parse_str(
+            r#"{"type": "fixed", "name": "price", "size": 2, "logicalType": "decimal", "precision": 18}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Schema::Fixed {
+                name: Name::new("price"),
+                size: 2,
+            },
+            schema
+        );
+    }
+
+    #[test]
+    fn test_decimal_schema_serialization() {
+        let raw_schema = r#"{"type":"bytes","logicalType":"decimal","precision":18,"scale":4}"#;
+        let schema = Schema::parse_str(raw_schema).unwrap();
+        assert_eq!(raw_schema, serde_json::to_string(&schema).unwrap());
+    }
+
+    #[test]
+    fn test_date_schema() {
+        let raw_schema = r#"{"type":"int","logicalType":"date"}"#;
+        let schema = Schema::parse_str(raw_schema).unwrap();
+        assert_eq!(schema, Schema::Date);
+        assert_eq!(raw_schema, serde_json::to_string(&schema).unwrap());
+
+        // `date` can only annotate an `int`
+        let schema = Schema::parse_str(r#"{"type":"long","logicalType":"date"}"#).unwrap();
+        assert_eq!(schema, Schema::Long);
+    }
+
+    #[test]
+    fn test_time_schemas() {
+        let schemas = vec![
+            (
+                r#"{"type":"int","logicalType":"time-millis"}"#,
+                Schema::TimeMillis,
+            ),
+            (
+                r#"{"type":"long","logicalType":"time-micros"}"#,
+                Schema::TimeMicros,
+            ),
+            (
+                r#"{"type":"long","logicalType":"timestamp-millis"}"#,
+                Schema::TimestampMillis,
+            ),
+            (
+                r#"{"type":"long","logicalType":"timestamp-micros"}"#,
+                Schema::TimestampMicros,
+            ),
+        ];
+
+        for (raw_schema, expected) in schemas {
+            let schema = Schema::parse_str(raw_schema).unwrap();
+            assert_eq!(schema, expected);
+            assert_eq!(raw_schema, serde_json::to_string(&schema).unwrap());
+        }
+
+        let schema =
+            Schema::parse_str(r#"{"type":"int","logicalType":"timestamp-millis"}"#).unwrap();
+        assert_eq!(schema, Schema::Int);
+    }
+
+    #[test]
+    fn test_no_documentation() {
+        let schema =
+            Schema::parse_str(r#"{"type": "enum", "name": "Coin", "symbols": ["heads", "tails"]}"#)
+                .unwrap();
+
+        let doc = match schema {
+            Schema::Enum { doc, .. } => doc,
+            _ => return assert!(false),
+        };
+
+        assert!(doc.is_none());
+    }
+
+    #[test]
+    fn test_documentation() {
+        let schema = Schema::parse_str(
+            r#"{"type": "enum", "name": "Coin", "doc": "Some documentation", "symbols": ["heads", "tails"]}"#
+        ).unwrap();
+
+        let doc = match schema {
+            Schema::Enum { doc, .. } => doc,
+            _ => None,
+        };
+
+        assert_eq!("Some documentation".to_owned(), doc.unwrap());
+    }
+
+    // Tests to ensure Schema is Send + Sync. These tests don't need to _do_ anything, if they can
+    // compile, they pass.
+    #[test]
+    fn test_schema_is_send() {
+        fn send<S: Send>(_s: S) {}
+
+        let schema = Schema::Null;
+        send(schema);
+    }
+
+    #[test]
+    fn test_schema_is_sync() {
+        fn sync<S: Sync>(_s: S) {}
+
+        let schema = Schema::Null;
+        sync(&schema);
+        sync(schema);
+    }
+
+    #[test]
+    fn test_schema_is_eq() {
+        fn eq<S: Eq>(_s: S) {}
+
+        eq(Schema::Null);
+        eq(Name::new("test"));
+        eq(RecordFieldOrder::Ascending);
+        eq(UnionSchema::new(vec![Schema::Null, Schema::Long]).unwrap());
+    }
+
+    #[test]
+    fn test_schema_json_round_trip() {
+        let raw_schemas = [
+            r#""string""#,
+            r#"{"type": "array", "items": ["null", {"type": "map", "values": "bytes"}]}"#,
+            r#"["int", "string", {"type": "enum", "name": "E", "symbols": ["A", "B"]}]"#,
+            r#"{"type": "fixed", "name": "F", "namespace": "org.example", "size": 4}"#,
+            r#"{"type": "bytes", "logicalType": "decimal", "precision": 9, "scale": 3}"#,
+            r#"{"type": "fixed", "name": "D", "size": 12, "logicalType": "duration"}"#,
+            r#"{
+                "type": "record",
+                "name": "R",
+                "namespace": "org.example",
+                "doc": "A record",
+                "fields": [
+                    {"name": "a", "type": "long", "default": 42, "doc": "The a field"},
+                    {"name": "b", "type": "string", "order": "descending"},
+                    {"name": "c", "type": ["null", "R"], "default": null, "order": "ignore"},
+                    {"name": "d", "type": {"type": "long", "logicalType": "timestamp-micros"}}
+                ]
+            }"#,
+        ];
+
+        for raw_schema in raw_schemas.iter() {
+            let schema = Schema::parse_str(raw_schema).unwrap();
+            assert_eq!(Schema::parse(&schema.to_json()).unwrap(), schema);
+        }
+
+        // union variants are compared in order
+        assert_ne!(
+            Schema::parse_str(r#"["null", "long"]"#).unwrap(),
+            Schema::parse_str(r#"["long", "null"]"#).unwrap()
+        );
+        // so are record fields, including their position
+        assert_ne!(
+            Schema::parse_str(
+                r#"{"type": "record", "name": "R", "fields": [
+                    {"name": "a", "type": "long"}, {"name": "b", "type": "long"}
+                ]}"#
+            )
+            .unwrap(),
+            Schema::parse_str(
+                r#"{"type": "record", "name": "R", "fields": [
+                    {"name": "b", "type": "long"}, {"name": "a", "type": "long"}
+                ]}"#
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        // Test vectors from the Avro specification test suite (share/test/data/schema-tests.txt)
+        let vectors = vec![
+            (r#""null""#, r#""null""#),
+            (r#"{"type":"null"}"#, r#""null""#),
+            (r#"{"type":"int","logicalType":"date"}"#, r#""int""#),
+            (
+                r#"{"type":"bytes","logicalType":"decimal","precision":4,"scale":2}"#,
+                r#""bytes""#,
+            ),
+            (
+                r#"{"type":"fixed","name":"Test","size":1}"#,
+                r#"{"name":"Test","type":"fixed","size":1}"#,
+            ),
+            (
+                r#"{"type":"fixed","name":"MyFixed","namespace":"org.apache.hadoop.avro","size":1}"#,
+                r#"{"name":"org.apache.hadoop.avro.MyFixed","type":"fixed","size":1}"#,
+            ),
+            (
+                r#"{"type":"enum","name":"Test","symbols":["A","B"]}"#,
+                r#"{"name":"Test","type":"enum","symbols":["A","B"]}"#,
+            ),
+            (
+                r#"{"type":"array","items":"long"}"#,
+                r#"{"type":"array","items":"long"}"#,
+            ),
+            (
+                r#"{"type":"map","values":{"type":"enum","name":"Test","symbols":["A","B"]}}"#,
+                r#"{"type":"map","values":{"name":"Test","type":"enum","symbols":["A","B"]}}"#,
+            ),
+            (r#"["string","null","long"]"#, r#"["string","null","long"]"#),
+            (
+                r#"{"type":"record","name":"Test","doc":"A record","fields":[{"name":"f","type":"long","default":42}]}"#,
+                r#"{"name":"Test","type":"record","fields":[{"name":"f","type":"long"}]}"#,
+            ),
+            (
+                r#"{"type":"record","name":"HandshakeRequest","namespace":"org.apache.avro.ipc","fields":[{"name":"clientHash","type":{"type":"fixed","name":"MD5","size":16}},{"name":"meta","type":["null",{"type":"map","values":"bytes"}]}]}"#,
+                r#"{"name":"org.apache.avro.ipc.HandshakeRequest","type":"record","fields":[{"name":"clientHash","type":{"name":"org.apache.avro.ipc.MD5","type":"fixed","size":16}},{"name":"meta","type":["null",{"type":"map","values":"bytes"}]}]}"#,
+            ),
+        ];
+
+        for (raw_schema, expected) in vectors {
+            let schema = Schema::parse_str(raw_schema).unwrap();
+            assert_eq!(schema.canonical_form(), expected, "{}", raw_schema);
+        }
+    }
+
+    #[test]
+    fn test_canonical_form_named_type_references() {
+        let raw_schema = r#"
+        {
+            "type": "record",
+            "name": "HandshakeResponse",
+            "namespace": "org.apache.avro.ipc",
+            "fields": [
+                {"name": "serverHash", "type": ["null", {"type": "fixed", "name": "MD5", "size": 16}]},
+                {"name": "clientHash", "type": "MD5"},
+                {"name": "otherHash", "type": {"type": "fixed", "name": "MD5", "size": 16}}
+            ]
+        }
+        "#;
+
+        let json = serde_json::from_str(raw_schema).unwrap();
+        assert_eq!(
+            parsing_canonical_form(&json),
+            r#"{"name":"org.apache.avro.ipc.HandshakeResponse","type":"record","fields":[{"name":"serverHash","type":["null",{"name":"org.apache.avro.ipc.MD5","type":"fixed","size":16}]},{"name":"clientHash","type":"org.apache.avro.ipc.MD5"},{"name":"otherHash","type":"org.apache.avro.ipc.MD5"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_parse_list() {
+        let md5 = r#"{"type": "fixed", "name": "MD5", "namespace": "org.example", "size": 16}"#;
+        let hash = r#"
+        {
+            "type": "record",
+            "name": "Hash",
+            "namespace": "org.example",
+            "fields": [{"name": "md5", "type": "MD5"}]
+        }
+        "#;
+        let expected = Schema::Fixed {
+            name: Name {
+                name: "MD5".to_owned(),
+                namespace: Some("org.example".to_owned()),
+                aliases: None,
+            },
+            size: 16,
+        };
+
+        let schemas = Schema::parse_list(&[md5, hash]).unwrap();
+        assert_eq!(schemas.len(), 2);
+        assert_eq!(schemas[0], expected);
+        match schemas[1] {
+            Schema::Record { ref fields, .. } => assert_eq!(fields[0].schema, expected),
+            ref other => panic!("expected a record, got {:?}", other),
+        }
+
+        // a single JSON array holding all the schemas
+        let array = format!("[{}, {}]", md5, hash);
+        assert_eq!(Schema::parse_list(&[&array]).unwrap(), schemas);
+
+        // named types must be defined before they are referenced
+        assert!(Schema::parse_list(&[hash, md5]).is_err());
+        assert!(Schema::parse_list(&[md5, md5]).is_err());
+    }
+
+    #[test]
+    fn test_parse_list_reusing_recursive_type() {
+        // `Child` refers back to `Parent`, which the second schema does not define itself.
+        let parent = r#"
+        {
+            "type": "record",
+            "name": "Parent",
+            "fields": [
+                {"name": "child", "type": {
+                    "type": "record",
+                    "name": "Child",
+                    "fields": [{"name": "parent", "type": ["null", "Parent"]}]
+                }}
+            ]
+        }
+        "#;
+        let holder = r#"
+        {"type": "record", "name": "Holder", "fields": [{"name": "child", "type": "Child"}]}
+        "#;
+        let schemas = Schema::parse_list(&[parent, holder]).unwrap();
+        let holder = &schemas[1];
+
+        let child = |parent: types::Value| {
+            types::Value::Record(vec![(
+                "parent".to_owned(),
+                types::Value::Union(Box::new(parent)),
+            )])
+        };
+        let parent = |child: types::Value| types::Value::Record(vec![("child".to_owned(), child)]);
+        let value = types::Value::Record(vec![(
+            "child".to_owned(),
+            child(parent(child(types::Value::Null))),
+        )]);
+        assert!(value.validate(holder));
+        let encoded = crate::to_avro_datum(holder, value.clone()).unwrap();
+        assert_eq!(
+            crate::from_avro_datum(holder, &mut &encoded[..], None).unwrap(),
+            value
+        );
+
+        // the second schema can be written out and parsed back on its own
+        assert_eq!(
+            Schema::parse_str(&holder.canonical_form()).unwrap(),
+            *holder
+        );
+        assert_eq!(Schema::parse(&holder.to_json()).unwrap(), *holder);
+    }
+
+    #[test]
+    fn test_schema_store() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "Tree",
+                "namespace": "com.example",
+                "fields": [
+                    {"name": "label", "type": {"type": "enum", "name": "Label", "symbols": ["A", "B"]}},
+                    {"name": "children", "type": {"type": "array", "items": "Tree"}}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+        let mut store = SchemaStore::new();
+        store.register(&schema).unwrap();
+        // registering the same definitions again is fine
+        store.register(&schema).unwrap();
+
+        assert_eq!(store.get("com.example.Tree"), Some(&schema));
+        assert_eq!(
+            store.get("com.example.Label"),
+            Some(&Schema::Enum {
+                name: Name::new("com.example.Label"),
+                doc: None,
+                symbols: vec!["A".to_owned(), "B".to_owned()],
+            })
+        );
+        assert_eq!(store.get("Label"), None);
+        assert_eq!(
+            store.names(),
+            vec![
+                &Name::new("com.example.Label"),
+                &Name::new("com.example.Tree")
+            ]
+        );
+        assert_eq!(
+            store.get_shared("com.example.Tree").as_deref(),
+            Some(&schema)
+        );
+        // clones of the store share the registered types
+        assert!(Arc::ptr_eq(
+            &store.clone().get_shared("com.example.Tree").unwrap(),
+            &store.get_shared("com.example.Tree").unwrap()
+        ));
+
+        let conflicting = Schema::parse_str(
+            r#"{"type": "enum", "name": "Label", "namespace": "com.example", "symbols": ["C"]}"#,
+        )
+        .unwrap();
+        assert!(store.register(&conflicting).is_err());
+
+        let children = match schema {
+            Schema::Record { ref fields, .. } => &fields[1].schema,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            children,
+            &Schema::Array(Box::new(Schema::Ref {
+                name: Name::new("com.example.Tree")
+            }))
+        );
+        assert_eq!(
+            store.resolve_references(children).unwrap(),
+            Schema::Array(Box::new(schema.clone()))
+        );
+        assert_eq!(store.resolve_references(&schema).unwrap(), schema);
+        assert!(SchemaStore::new().resolve_references(children).is_err());
+    }
+
+    #[test]
+    fn test_nullable_union() {
+        let union = |raw: &str| match Schema::parse_str(raw).unwrap() {
+            Schema::Union(union) => union,
+            other => panic!("expected a union, got {:?}", other),
+        };
+
+        let null_first = union(r#"["null", "string"]"#);
+        assert!(null_first.is_nullable());
+        assert_eq!(null_first.unwrap_nullable(), Some(&Schema::String));
+        assert_eq!(null_first.null_index(), Some(0));
+
+        let null_last = union(r#"["long", "null"]"#);
+        assert!(null_last.is_nullable());
+        assert_eq!(null_last.unwrap_nullable(), Some(&Schema::Long));
+        assert_eq!(null_last.null_index(), Some(1));
+
+        let multi = union(r#"["null", "long", "string"]"#);
+        assert!(!multi.is_nullable());
+        assert_eq!(multi.unwrap_nullable(), None);
+        assert_eq!(multi.null_index(), Some(0));
+
+        let not_null = union(r#"["long", "string"]"#);
+        assert!(!not_null.is_nullable());
+        assert_eq!(not_null.unwrap_nullable(), None);
+        assert_eq!(not_null.null_index(), None);
+    }
+
+    #[test]
+    fn test_find_variant() {
+        use crate::types::Value as AvroValue;
+
+        let schema = Schema::parse_str(
+            r#"[
+                "null",
+                {"type": "long", "logicalType": "timestamp-millis"},
+                {"type": "record", "name": "Foo", "namespace": "com.example", "fields": []},
+                {"type": "array", "items": "int"}
+            ]"#,
+        )
+        .unwrap();
+        let union = match schema {
+            Schema::Union(ref union) => union,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(union.find_variant_by_name("null"), Some((0, &Schema::Null)));
+        assert_eq!(
+            union.find_variant_by_name("long"),
+            Some((1, &Schema::TimestampMillis))
+        );
+        assert_eq!(union.find_variant_by_name("Foo").map(|(i, _)| i), Some(2));
+        assert_eq!(
+            union
+                .find_variant_by_name("com.example.Foo")
+                .map(|(i, _)| i),
+            Some(2)
+        );
+        assert_eq!(union.find_variant_by_name("array").map(|(i, _)| i), Some(3));
+        assert_eq!(union.find_variant_by_name("string"), None);
+        assert_eq!(union.find_variant_by_name("com.other.Foo"), None);
+
+        assert_eq!(union.find_variant_for_value(&AvroValue::Null), Some(0));
+        assert_eq!(
+            union.find_variant_for_value(&AvroValue::TimestampMillis(0)),
+            Some(1)
+        );
+        assert_eq!(
+            union.find_variant_for_value(&AvroValue::Union(Box::new(AvroValue::Record(vec![])))),
+            Some(2)
+        );
+        assert_eq!(union.find_variant_for_value(&AvroValue::Int(1)), None);
+    }
+
+    #[test]
+    fn test_schema_accessors() {
+        let record = Schema::parse_str(
+            r#"{"type": "record", "name": "Foo", "namespace": "com.example", "doc": "A Foo", "fields": [
+                {"name": "a", "type": "long"},
+                {"name": "b", "type": "string"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(record.field_count(), Some(2));
+        assert_eq!(record.fields().unwrap()[1].name, "b");
+        assert_eq!(record.find_field("b").unwrap().schema, Schema::String);
+        assert_eq!(record.find_field("b").unwrap().position, 1);
+        assert!(record.find_field("c").is_none());
+        assert!(record.is_named());
+        assert_eq!(record.name(), Some(&Name::new("com.example.Foo")));
+        assert_eq!(record.doc(), Some("A Foo"));
+        assert!(record.is_complex());
+        assert!(!record.is_primitive());
+
+        let decimal = Schema::parse_str(
+            r#"{"type": "fixed", "name": "Amount", "size": 8, "logicalType": "decimal", "precision": 10}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            decimal.name().map(|name| name.name.as_str()),
+            Some("Amount")
+        );
+        assert!(decimal.is_complex());
+
+        assert!(!Schema::Long.is_named());
+        assert_eq!(Schema::Long.doc(), None);
+        assert!(Schema::Long.is_primitive());
+        assert!(Schema::Date.is_primitive());
+        assert!(!Schema::Long.is_union());
+
+        let union = Schema::parse_str(r#"["null", "long"]"#).unwrap();
+        assert!(union.is_union());
+        assert!(union.is_complex());
+
+        let array = Schema::Array(Box::new(Schema::Long));
+        assert_eq!(array.item_schema(), Some(&Schema::Long));
+        let map = Schema::Map(Box::new(Schema::String));
+        assert_eq!(map.item_schema(), Some(&Schema::String));
+        assert_eq!(union.item_schema(), None);
+        assert_eq!(union.fields(), None);
+        assert_eq!(union.field_count(), None);
+        assert!(union.find_field("a").is_none());
+
+        let suit = SchemaBuilder::enumeration("Suit", vec!["spades", "hearts"])
+            .build()
+            .unwrap();
+        assert_eq!(
+            suit.enum_symbols(),
+            Some(&["spades".to_owned(), "hearts".to_owned()][..])
+        );
+        assert_eq!(suit.symbol_index("hearts"), Some(1));
+        assert_eq!(suit.symbol_index("clubs"), None);
+        assert_eq!(Schema::String.enum_symbols(), None);
+        assert_eq!(Schema::String.symbol_index("hearts"), None);
+    }
+
+    #[test]
+    fn test_record_schema_consistency() {
+        let record = Schema::parse_str(
+            r#"{"type": "record", "name": "Foo", "fields": [
+                {"name": "a", "type": "long"},
+                {"name": "b", "type": "string"}
+            ]}"#,
+        )
+        .unwrap();
+        let (name, fields, lookup) = match record {
+            Schema::Record {
+                ref name,
+                ref fields,
+                ref lookup,
+                ..
+            } => (name, fields, lookup),
+            _ => unreachable!(),
+        };
+        assert_eq!(&rebuild_lookup(fields), lookup);
+        assert!(validate_record_schema(name, fields, lookup).is_ok());
+
+        let mut wrong_position = lookup.clone();
+        wrong_position.insert("a".to_owned(), 1);
+        assert!(validate_record_schema(name, fields, &wrong_position).is_err());
+        let mut missing = lookup.clone();
+        missing.remove("b");
+        assert!(validate_record_schema(name, fields, &missing).is_err());
+        let mut unknown = lookup.clone();
+        unknown.insert("c".to_owned(), 0);
+        assert!(validate_record_schema(name, fields, &unknown).is_err());
+
+        let mut misplaced = fields.clone();
+        misplaced.swap(0, 1);
+        assert!(validate_record_schema(name, &misplaced, &rebuild_lookup(&misplaced)).is_err());
+        let mut duplicate = fields.clone();
+        duplicate[1].name = "a".to_owned();
+        assert!(validate_record_schema(name, &duplicate, &rebuild_lookup(&duplicate)).is_err());
+
+        // duplicate field names are rejected by the parser too
+        assert!(Schema::parse_str(
+            r#"{"type": "record", "name": "Foo", "fields": [
+                {"name": "a", "type": "long"},
+                {"name": "a", "type": "string"}
+            ]}"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_schema_field_edition() {
+        let record = Schema::parse_str(
+            r#"{"type": "record", "name": "Foo", "doc": "A Foo", "fields": [
+                {"name": "a", "type": "long"},
+                {"name": "b", "type": "string"},
+                {"name": "c", "type": "int"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let removed = record.remove_field("a").unwrap();
+        assert_eq!(
+            removed,
+            Schema::parse_str(
+                r#"{"type": "record", "name": "Foo", "doc": "A Foo", "fields": [
+                    {"name": "b", "type": "string"},
+                    {"name": "c", "type": "int"}
+                ]}"#,
+            )
+            .unwrap()
+        );
+        assert_eq!(removed.find_field("c").unwrap().position, 1);
+        assert!(record.remove_field("d").is_err());
+
+        let field = RecordField {
+            name: "d".to_owned(),
+            doc: None,
+            default: Some(serde_json::json!(null)),
+            schema: Schema::parse_str(r#"["null", "long"]"#).unwrap(),
+            order: RecordFieldOrder::Ascending,
+            position: 0,
+        };
+        let added = record.add_field(field.clone()).unwrap();
+        assert_eq!(added.field_count(), Some(4));
+        assert_eq!(added.find_field("d").unwrap().position, 3);
+        assert!(added.add_field(field.clone()).is_err());
+        let invalid_default = RecordField {
+            name: "e".to_owned(),
+            default: Some(serde_json::json!("foo")),
+            ..field
+        };
+        assert!(record.add_field(invalid_default).is_err());
+
+        let renamed = record.rename_field("b", "bb").unwrap();
+        assert_eq!(renamed.find_field("bb").unwrap().position, 1);
+        assert!(renamed.find_field("b").is_none());
+        assert!(record.rename_field("b", "c").is_err());
+        assert!(record.rename_field("d", "e").is_err());
+
+        assert!(Schema::Long.remove_field("a").is_err());
+        assert!(Schema::Long.rename_field("a", "b").is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let schema = Schema::parse_str(
+            r#"{"type": "enum", "name": "Suit", "doc": "card suits", "symbols": ["SPADES", "HEARTS"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            schema.to_string(),
+            r#"{"name":"Suit","type":"enum","symbols":["SPADES","HEARTS"]}"#
+        );
+        assert_eq!(Schema::Long.to_string(), r#""long""#);
+    }
+
+    #[test]
+    fn test_schema_fingerprint() {
+        let raw_schema = r#"
+    {
+        "type": "record",
+        "name": "test",
+        "fields": [
+            {"name": "a", "type": "long", "default": 42},
+            {"name": "b", "type": "string"}
+        ]
+    }
+"#;
+
+        let schema = Schema::parse_str(raw_schema).unwrap();
+        assert_eq!(
+            "c4d97949770866dec733ae7afa3046757e901d0cfea32eb92a8faeadcc4de153",
+            format!("{}", schema.fingerprint::<Sha256>())
+        );
+
+        assert_eq!(
+            "7bce8188f28e66480a45ffbdc3615b7d",
+            format!("{}", schema.fingerprint::<Md5>())
+        );
 
-    #[test]
-    fn test_union_schema() {
-        let schema = Schema::parse_str(r#"["null", "int"]"#).unwrap();
+        assert_eq!(schema.fingerprint_sha256(), schema.fingerprint::<Sha256>());
+        assert_eq!(schema.fingerprint_md5(), schema.fingerprint::<Md5>());
+        assert_eq!(schema.fingerprint_crc64().bytes.len(), 8);
         assert_eq!(
-            Schema::Union(UnionSchema::new(vec![Schema::Null, Schema::Int]).unwrap()),
-            schema
+            "8a8f25cce724dd63",
+            format!("{}", Schema::Null.fingerprint_crc64())
         );
     }
 
     #[test]
-    fn test_union_unsupported_schema() {
-        let schema = Schema::parse_str(r#"["null", ["null", "int"], "string"]"#);
-        assert!(schema.is_err());
+    fn test_name_new() {
+        let name = Name::new("com.example.Foo");
+        assert_eq!(name.name, "Foo");
+        assert_eq!(name.namespace, Some("com.example".to_owned()));
+        assert_eq!(name.full_name(), "com.example.Foo");
+        assert_eq!(Name::new("Foo").full_name(), "Foo");
     }
 
     #[test]
-    fn test_multi_union_schema() {
-        let schema = Schema::parse_str(r#"["null", "int", "float", "string", "bytes"]"#);
-        assert!(schema.is_ok());
-        let schema = schema.unwrap();
-        assert_eq!(SchemaKind::from(&schema), SchemaKind::Union);
-        let union_schema = match schema {
-            Schema::Union(u) => u,
-            _ => unreachable!(),
+    fn test_name_traits() {
+        let name: Name = "com.example.Foo".parse().unwrap();
+        assert_eq!(name, Name::new("com.example.Foo"));
+        assert_eq!(name.to_string(), "com.example.Foo");
+        assert_eq!("_Foo1".parse::<Name>().unwrap().to_string(), "_Foo1");
+        for invalid in &["", "1Foo", "com..Foo", "com.example.", "Foo-Bar"] {
+            assert!(invalid.parse::<Name>().is_err(), "{:?}", invalid);
+        }
+
+        // aliases are not part of the identity of a name
+        let aliased = Name {
+            aliases: Some(vec!["Bar".to_owned()]),
+            ..name.clone()
         };
-        assert_eq!(union_schema.variants().len(), 5);
-        let mut variants = union_schema.variants().iter();
-        assert_eq!(SchemaKind::from(variants.next().unwrap()), SchemaKind::Null);
-        assert_eq!(SchemaKind::from(variants.next().unwrap()), SchemaKind::Int);
-        assert_eq!(
-            SchemaKind::from(variants.next().unwrap()),
-            SchemaKind::Float
-        );
+        assert_eq!(aliased, name);
+        let mut names = HashMap::new();
+        names.insert(aliased, 1);
+        assert_eq!(names.get(&name), Some(&1));
+
+        let mut sorted = vec![
+            Name::new("org.Foo"),
+            Name::new("com.Foo"),
+            Name::new("Foo"),
+            Name::new("com.Bar"),
+        ];
+        sorted.sort();
         assert_eq!(
-            SchemaKind::from(variants.next().unwrap()),
-            SchemaKind::String
+            sorted.iter().map(Name::to_string).collect::<Vec<_>>(),
+            vec!["Foo", "com.Bar", "com.Foo", "org.Foo"]
         );
+    }
+
+    #[test]
+    fn test_namespace_propagation() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "Outer",
+                "namespace": "com.example",
+                "fields": [
+                    {"name": "a", "type": {"type": "enum", "name": "Foo", "symbols": ["X"]}},
+                    {"name": "b", "type": {
+                        "type": "record",
+                        "name": "org.other.Bar",
+                        "fields": [
+                            {"name": "c", "type": {"type": "fixed", "name": "Baz", "size": 1}}
+                        ]
+                    }},
+                    {"name": "d", "type": {"type": "fixed", "name": "Qux", "namespace": "", "size": 1}}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+
+        let fields = match schema {
+            Schema::Record {
+                ref name,
+                ref fields,
+                ..
+            } => {
+                assert_eq!(name.full_name(), "com.example.Outer");
+                fields
+            }
+            _ => panic!("expected a record"),
+        };
+        let name = |schema: &Schema| match *schema {
+            Schema::Record { ref name, .. }
+            | Schema::Enum { ref name, .. }
+            | Schema::Fixed { ref name, .. } => name.clone(),
+            _ => panic!("expected a named type"),
+        };
+
         assert_eq!(
-            SchemaKind::from(variants.next().unwrap()),
-            SchemaKind::Bytes
+            name(&fields[0].schema),
+            Name {
+                name: "Foo".to_owned(),
+                namespace: Some("com.example".to_owned()),
+                aliases: None,
+            }
         );
-        assert_eq!(variants.next(), None);
+        assert_eq!(name(&fields[1].schema).full_name(), "org.other.Bar");
+        match fields[1].schema {
+            Schema::Record { ref fields, .. } => {
+                assert_eq!(name(&fields[0].schema).full_name(), "org.other.Baz")
+            }
+            _ => panic!("expected a record"),
+        }
+        assert_eq!(name(&fields[2].schema).full_name(), "Qux");
+
+        assert_eq!(Schema::parse_str(&schema.to_json_string()).unwrap(), schema);
     }
 
     #[test]
-    fn test_record_schema() {
+    fn test_named_type_references() {
         let schema = Schema::parse_str(
             r#"
             {
                 "type": "record",
                 "name": "test",
+                "namespace": "com.example",
                 "fields": [
-                    {"name": "a", "type": "long", "default": 42},
-                    {"name": "b", "type": "string"}
+                    {"name": "a", "type": {"type": "fixed", "name": "md5", "size": 16}},
+                    {"name": "b", "type": "md5"},
+                    {"name": "c", "type": "com.example.md5"},
+                    {"name": "d", "type": {
+                        "type": "fixed", "name": "price", "size": 4,
+                        "logicalType": "decimal", "precision": 5
+                    }},
+                    {"name": "e", "type": ["null", "price"]}
                 ]
             }
         "#,
         )
         .unwrap();
 
-        let mut lookup = HashMap::new();
-        lookup.insert("a".to_owned(), 0);
-        lookup.insert("b".to_owned(), 1);
-
-        let expected = Schema::Record {
-            name: Name::new("test"),
-            doc: None,
-            fields: vec![
-                RecordField {
-                    name: "a".to_string(),
-                    doc: None,
-                    default: Some(Value::Number(42i64.into())),
-                    schema: Schema::Long,
-                    order: RecordFieldOrder::Ascending,
-                    position: 0,
-                },
-                RecordField {
-                    name: "b".to_string(),
-                    doc: None,
-                    default: None,
-                    schema: Schema::String,
-                    order: RecordFieldOrder::Ascending,
-                    position: 1,
-                },
-            ],
-            lookup,
+        let fields = match schema {
+            Schema::Record { ref fields, .. } => fields,
+            _ => panic!("expected a record"),
         };
+        assert_eq!(fields[0].schema, fields[1].schema);
+        assert_eq!(fields[0].schema, fields[2].schema);
+        assert_eq!(
+            fields[4].schema,
+            Schema::Union(UnionSchema::new(vec![Schema::Null, fields[3].schema.clone()]).unwrap())
+        );
 
-        assert_eq!(expected, schema);
+        assert!(Schema::parse_str(r#"{"type": "array", "items": "unknown"}"#).is_err());
+        assert!(Schema::parse_str(
+            r#"[{"type": "fixed", "name": "a", "size": 1}, {"type": "fixed", "name": "a", "size": 2}]"#
+        )
+        .is_err());
     }
 
     #[test]
-    fn test_enum_schema() {
+    fn test_recursive_schema() {
         let schema = Schema::parse_str(
-            r#"{"type": "enum", "name": "Suit", "symbols": ["diamonds", "spades", "clubs", "hearts"]}"#,
-        ).unwrap();
-
-        let expected = Schema::Enum {
-            name: Name::new("Suit"),
-            doc: None,
-            symbols: vec![
-                "diamonds".to_owned(),
-                "spades".to_owned(),
-                "clubs".to_owned(),
-                "hearts".to_owned(),
-            ],
-        };
+            r#"
+            {
+                "type": "record",
+                "name": "LinkedList",
+                "namespace": "com.example",
+                "fields": [
+                    {"name": "value", "type": "int"},
+                    {"name": "next", "type": ["null", "LinkedList"]}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
 
-        assert_eq!(expected, schema);
+        match schema {
+            Schema::Record { ref fields, .. } => assert_eq!(
+                fields[1].schema,
+                Schema::Union(
+                    UnionSchema::new(vec![
+                        Schema::Null,
+                        Schema::Ref {
+                            name: Name::new("com.example.LinkedList")
+                        }
+                    ])
+                    .unwrap()
+                )
+            ),
+            _ => panic!("expected a record"),
+        }
+        assert_eq!(
+            schema.canonical_form(),
+            r#"{"name":"com.example.LinkedList","type":"record","fields":[{"name":"value","type":"int"},{"name":"next","type":["null","com.example.LinkedList"]}]}"#
+        );
+        assert_eq!(Schema::parse_str(&schema.to_json_string()).unwrap(), schema);
     }
 
     #[test]
-    fn test_fixed_schema() {
-        let schema = Schema::parse_str(r#"{"type": "fixed", "name": "test", "size": 16}"#).unwrap();
+    fn test_to_json_round_trip() {
+        let raw_schema = r#"
+            {
+                "type": "record",
+                "name": "test",
+                "namespace": "com.example",
+                "doc": "A test record",
+                "aliases": ["old_test"],
+                "fields": [
+                    {"name": "a", "type": "long", "default": 42, "doc": "The a field"},
+                    {"name": "b", "type": "string", "order": "descending"},
+                    {"name": "c", "type": {"type": "array", "items": ["null", "int"]}, "order": "ignore"},
+                    {"name": "d", "type": {"type": "map", "values": "double"}},
+                    {"name": "e", "type": {"type": "enum", "name": "suit", "doc": "A suit", "symbols": ["HEARTS", "SPADES"]}},
+                    {"name": "f", "type": {"type": "fixed", "name": "md5", "aliases": ["hash"], "size": 16}},
+                    {"name": "g", "type": {"type": "int", "logicalType": "date"}}
+                ]
+            }
+        "#;
+        let schema = Schema::parse_str(raw_schema).unwrap();
 
-        let expected = Schema::Fixed {
+        assert_eq!(
+            schema.to_json(),
+            serde_json::from_str::<Value>(raw_schema).unwrap()
+        );
+        assert_eq!(Schema::parse_str(&schema.to_json_string()).unwrap(), schema);
+        assert_eq!(Schema::Null.to_json(), Value::String("null".to_owned()));
+    }
+
+    #[test]
+    fn test_to_json_named_type_references() {
+        let md5 = Schema::Fixed {
+            name: Name {
+                name: "md5".to_owned(),
+                namespace: Some("com.example".to_owned()),
+                aliases: None,
+            },
+            size: 16,
+        };
+        let field = |name: &str, position: usize| RecordField {
+            name: name.to_owned(),
+            doc: None,
+            default: None,
+            schema: md5.clone(),
+            order: RecordFieldOrder::Ascending,
+            position,
+        };
+        let schema = Schema::Record {
             name: Name::new("test"),
-            size: 16usize,
+            doc: None,
+            fields: vec![field("a", 0), field("b", 1)],
+            lookup: HashMap::new(),
         };
 
-        assert_eq!(expected, schema);
+        assert_eq!(
+            schema.to_json_string(),
+            r#"{"type":"record","name":"test","fields":[{"name":"a","type":{"type":"fixed","namespace":"com.example","name":"md5","size":16}},{"name":"b","type":"com.example.md5"}]}"#
+        );
     }
 
     #[test]
-    fn test_no_documentation() {
-        let schema =
-            Schema::parse_str(r#"{"type": "enum", "name": "Coin", "symbols": ["heads", "tails"]}"#)
-                .unwrap();
+    fn test_record_field_default_as_value() {
+        use crate::types::Value as AvroValue;
 
-        let doc = match schema {
-            Schema::Enum { doc, .. } => doc,
-            _ => return assert!(false),
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "test",
+                "fields": [
+                    {"name": "a", "type": "long", "default": 42},
+                    {"name": "b", "type": ["null", "string"], "default": null},
+                    {"name": "c", "type": ["string", "null"], "default": "foo"},
+                    {"name": "d", "type": {"type": "enum", "name": "e", "symbols": ["X", "Y"]}, "default": "Y"},
+                    {"name": "e", "type": {"type": "array", "items": "int"}, "default": [1, 2]},
+                    {"name": "f", "type": {"type": "map", "values": "double"}, "default": {"x": 1}},
+                    {"name": "g", "type": {
+                        "type": "record",
+                        "name": "inner",
+                        "fields": [
+                            {"name": "h", "type": "int"},
+                            {"name": "i", "type": "boolean", "default": true}
+                        ]
+                    }, "default": {"h": 3}},
+                    {"name": "j", "type": "string"}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+
+        let fields = match schema {
+            Schema::Record { fields, .. } => fields,
+            _ => panic!("expected a record"),
         };
+        let defaults = fields
+            .iter()
+            .map(|field| field.default_as_value().unwrap())
+            .collect::<Vec<_>>();
 
-        assert!(doc.is_none());
+        let mut map = HashMap::new();
+        map.insert("x".to_owned(), AvroValue::Double(1.0));
+
+        assert_eq!(
+            defaults,
+            vec![
+                Some(AvroValue::Long(42)),
+                Some(AvroValue::Union(Box::new(AvroValue::Null))),
+                Some(AvroValue::Union(Box::new(AvroValue::String(
+                    "foo".to_owned()
+                )))),
+                Some(AvroValue::Enum(1, "Y".to_owned())),
+                Some(AvroValue::Array(vec![AvroValue::Int(1), AvroValue::Int(2)])),
+                Some(AvroValue::Map(map)),
+                Some(AvroValue::Record(vec![
+                    ("h".to_owned(), AvroValue::Int(3)),
+                    ("i".to_owned(), AvroValue::Boolean(true)),
+                ])),
+                None,
+            ]
+        );
     }
 
     #[test]
-    fn test_documentation() {
-        let schema = Schema::parse_str(
-            r#"{"type": "enum", "name": "Coin", "doc": "Some documentation", "symbols": ["heads", "tails"]}"#
-        ).unwrap();
+    fn test_record_field_bytes_default() {
+        use crate::types::Value as AvroValue;
 
-        let doc = match schema {
-            Schema::Enum { doc, .. } => doc,
-            _ => None,
-        };
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "test",
+                "fields": [
+                    {"name": "a", "type": "bytes", "default": "\u00ff\u0000a"},
+                    {"name": "b", "type": {"type": "fixed", "name": "f", "size": 2}, "default": "\u00e9a"}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
 
-        assert_eq!("Some documentation".to_owned(), doc.unwrap());
+        if let Schema::Record { fields, .. } = schema {
+            assert_eq!(
+                fields[0].default_as_value().unwrap(),
+                Some(AvroValue::Bytes(vec![0xFF, 0x00, 0x61]))
+            );
+            assert_eq!(
+                fields[1].default_as_value().unwrap(),
+                Some(AvroValue::Fixed(2, vec![0xE9, 0x61]))
+            );
+        }
     }
 
-    // Tests to ensure Schema is Send + Sync. These tests don't need to _do_ anything, if they can
-    // compile, they pass.
     #[test]
-    fn test_schema_is_send() {
-        fn send<S: Send>(_s: S) {}
+    fn test_record_field_invalid_default() {
+        let invalid = vec![
+            r#"{"name": "a", "type": "int", "default": "foo"}"#,
+            r#"{"name": "a", "type": "int", "default": 3000000000}"#,
+            r#"{"name": "a", "type": ["null", "int"], "default": 1}"#,
+            r#"{"name": "a", "type": {"type": "enum", "name": "e", "symbols": ["X"]}, "default": "Z"}"#,
+            r#"{"name": "a", "type": {"type": "fixed", "name": "f", "size": 2}, "default": "abc"}"#,
+            r#"{"name": "a", "type": "bytes", "default": "\u0100"}"#,
+        ];
 
-        let schema = Schema::Null;
-        send(schema);
+        for field in invalid {
+            let schema = Schema::parse_str(&format!(
+                r#"{{"type": "record", "name": "test", "fields": [{}]}}"#,
+                field
+            ))
+            .unwrap();
+            if let Schema::Record { fields, .. } = schema {
+                assert!(fields[0].default_as_value().is_err(), "{}", field);
+            }
+        }
     }
 
     #[test]
-    fn test_schema_is_sync() {
-        fn sync<S: Sync>(_s: S) {}
+    fn test_schema_builder() {
+        let built = SchemaBuilder::record("test")
+            .doc("A test record")
+            .field("a", Schema::Long)
+            .field_with_default("b", Schema::String, Value::String("foo".into()))
+            .field(
+                "c",
+                SchemaBuilder::union()
+                    .variant(Schema::Null)
+                    .variant(
+                        SchemaBuilder::enumeration("Suit", vec!["spades", "hearts"])
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+                    .unwrap(),
+            )
+            .field(
+                "d",
+                SchemaBuilder::fixed("md5", 16)
+                    .namespace("org.example")
+                    .build()
+                    .unwrap(),
+            )
+            .field("e", SchemaBuilder::decimal(5).scale(2).build().unwrap())
+            .field(
+                "f",
+                SchemaBuilder::decimal(9)
+                    .inner(SchemaBuilder::fixed("amount", 4).build().unwrap())
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
 
-        let schema = Schema::Null;
-        sync(&schema);
-        sync(schema);
+        let parsed = Schema::parse_str(
+            r#"{"type": "record", "name": "test", "doc": "A test record", "fields": [
+                {"name": "a", "type": "long"},
+                {"name": "b", "type": "string", "default": "foo"},
+                {"name": "c", "type": ["null", {"type": "enum", "name": "Suit", "symbols": ["spades", "hearts"]}]},
+                {"name": "d", "type": {"type": "fixed", "name": "md5", "namespace": "org.example", "size": 16}},
+                {"name": "e", "type": {"type": "bytes", "logicalType": "decimal", "precision": 5, "scale": 2}},
+                {"name": "f", "type": {"type": "fixed", "name": "amount", "size": 4, "logicalType": "decimal", "precision": 9}}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(built, parsed);
+
+        match built {
+            Schema::Record { fields, lookup, .. } => {
+                assert_eq!(fields[3].position, 3);
+                assert_eq!(lookup["c"], 2);
+            }
+            _ => panic!("Expected a record"),
+        }
     }
 
     #[test]
-    fn test_schema_fingerprint() {
-        use md5::Md5;
-        use sha2::Sha256;
-
-        let raw_schema = r#"
-    {
-        "type": "record",
-        "name": "test",
-        "fields": [
-            {"name": "a", "type": "long", "default": 42},
-            {"name": "b", "type": "string"}
-        ]
+    fn test_schema_builder_errors() {
+        assert!(SchemaBuilder::record("test")
+            .field("a", Schema::Long)
+            .field("a", Schema::Int)
+            .build()
+            .is_err());
+        assert!(SchemaBuilder::record("test")
+            .field_with_default("a", Schema::Long, Value::String("foo".into()))
+            .build()
+            .is_err());
+        assert!(SchemaBuilder::enumeration("Suit", vec!["spades", "spades"])
+            .build()
+            .is_err());
+        assert!(SchemaBuilder::union()
+            .variant(Schema::Int)
+            .variant(Schema::Int)
+            .build()
+            .is_err());
+        assert!(SchemaBuilder::decimal(0).build().is_err());
+        assert!(SchemaBuilder::decimal(2).scale(3).build().is_err());
+        assert!(SchemaBuilder::decimal(2)
+            .inner(Schema::String)
+            .build()
+            .is_err());
+        assert!(SchemaBuilder::decimal(10)
+            .inner(SchemaBuilder::fixed("amount", 4).build().unwrap())
+            .build()
+            .is_err());
     }
-"#;
 
-        let schema = Schema::parse_str(raw_schema).unwrap();
+    #[test]
+    fn test_walk() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "Node",
+                "namespace": "org.example",
+                "fields": [
+                    {"name": "suit", "type": {"type": "enum", "name": "Suit", "symbols": ["A"]}},
+                    {"name": "ids", "type": {"type": "array", "items": "long"}},
+                    {"name": "hashes", "type": {"type": "map", "values": {
+                        "type": "fixed", "name": "MD5", "size": 16
+                    }}},
+                    {"name": "amount", "type": {
+                        "type": "bytes", "logicalType": "decimal", "precision": 4
+                    }},
+                    {"name": "next", "type": ["null", "Node"]}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+
+        let mut collector = SchemaCollector::new();
+        schema.walk(&mut collector);
+        let names = collector
+            .names()
+            .iter()
+            .map(Name::full_name)
+            .collect::<Vec<_>>();
         assert_eq!(
-            "c4d97949770866dec733ae7afa3046757e901d0cfea32eb92a8faeadcc4de153",
-            format!("{}", schema.fingerprint::<Sha256>())
+            names,
+            vec!["org.example.Node", "org.example.Suit", "org.example.MD5"]
         );
 
+        /// Records the visited nodes.
+        #[derive(Default)]
+        struct Trace(Vec<String>);
+
+        impl SchemaVisitor for Trace {
+            fn visit_field(&mut self, field: &RecordField) {
+                self.0.push(format!("field {}", field.name));
+            }
+
+            fn visit_union(&mut self, variants: &[Schema]) {
+                self.0.push(format!("union of {}", variants.len()));
+            }
+
+            fn visit_array(&mut self, _item: &Schema) {
+                self.0.push("array".to_string());
+            }
+
+            fn visit_map(&mut self, _value: &Schema) {
+                self.0.push("map".to_string());
+            }
+
+            fn visit_ref(&mut self, name: &Name) {
+                self.0.push(format!("ref {}", name.name));
+            }
+
+            fn visit_primitive(&mut self, schema: &Schema) {
+                self.0.push(schema.canonical_form());
+            }
+        }
+
+        let mut trace = Trace::default();
+        walk(&schema, &mut trace);
         assert_eq!(
-            "7bce8188f28e66480a45ffbdc3615b7d",
-            format!("{}", schema.fingerprint::<Md5>())
+            trace.0,
+            vec![
+                "field suit",
+                "field ids",
+                "array",
+                "\"long\"",
+                "field hashes",
+                "map",
+                "field amount",
+                "\"bytes\"",
+                "\"bytes\"",
+                "field next",
+                "union of 2",
+                "\"null\"",
+                "ref Node",
+            ]
         );
     }
-
 }
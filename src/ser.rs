@@ -9,6 +9,7 @@ use serde::{
     Serialize,
 };
 
+use crate::schema::Schema;
 use crate::types::{ToAvro, Value};
 
 #[derive(Clone, Default)]
@@ -19,8 +20,9 @@ pub struct SeqSerializer {
 }
 
 pub struct MapSerializer {
-    indices: HashMap<String, usize>,
-    values: Vec<Value>,
+    items: HashMap<String, Value>,
+    // Key of the entry whose value is serialized next.
+    key: Option<String>,
 }
 
 pub struct StructSerializer {
@@ -65,12 +67,12 @@ impl SeqSerializer {
 
 impl MapSerializer {
     pub fn new(len: Option<usize>) -> MapSerializer {
-        let (indices, values) = match len {
-            Some(len) => (HashMap::with_capacity(len), Vec::with_capacity(len)),
-            None => (HashMap::new(), Vec::new()),
+        let items = match len {
+            Some(len) => HashMap::with_capacity(len),
+            None => HashMap::new(),
         };
 
-        MapSerializer { indices, values }
+        MapSerializer { items, key: None }
     }
 }
 
@@ -121,6 +123,7 @@ impl<'b> ser::Serializer for &'b mut Serializer {
         self.serialize_i32(i32::from(v))
     }
 
+    /// Values that do not fit into an `int` are serialized as a `long`.
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
         if v <= i32::max_value() as u32 {
             self.serialize_i32(v as i32)
@@ -129,6 +132,7 @@ impl<'b> ser::Serializer for &'b mut Serializer {
         }
     }
 
+    /// Values that do not fit into a `long` are rejected.
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
         if v <= i64::max_value() as u64 {
             self.serialize_i64(v as i64)
@@ -137,6 +141,18 @@ impl<'b> ser::Serializer for &'b mut Serializer {
         }
     }
 
+    fn serialize_i128(self, _: i128) -> Result<Self::Ok, Self::Error> {
+        Err(Error::custom(
+            "i128 is not supported, Avro has no 128-bit integer",
+        ))
+    }
+
+    fn serialize_u128(self, _: u128) -> Result<Self::Ok, Self::Error> {
+        Err(Error::custom(
+            "u128 is not supported, Avro has no 128-bit integer",
+        ))
+    }
+
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
         Ok(Value::Float(v))
     }
@@ -186,15 +202,20 @@ impl<'b> ser::Serializer for &'b mut Serializer {
         Ok(Value::Enum(index as i32, variant.to_string()))
     }
 
+    #[cfg_attr(not(feature = "chrono"), allow(unused_variables))]
     fn serialize_newtype_struct<T: ?Sized>(
         self,
-        _: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize,
     {
-        value.serialize(self)
+        match value.serialize(self)? {
+            #[cfg(feature = "chrono")]
+            Value::Int(days) if name == crate::avro_date::NAME => Ok(Value::Date(days)),
+            value => Ok(value),
+        }
     }
 
     fn serialize_newtype_variant<T: ?Sized>(
@@ -231,9 +252,10 @@ impl<'b> ser::Serializer for &'b mut Serializer {
         _: &'static str,
         _: u32,
         _: &'static str,
-        _: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        unimplemented!() // TODO ?
+        // Like newtype variants, tuple variants are serialized as their content.
+        self.serialize_seq(Some(len))
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
@@ -253,9 +275,11 @@ impl<'b> ser::Serializer for &'b mut Serializer {
         _: &'static str,
         _: u32,
         _: &'static str,
-        _: usize,
+        len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        unimplemented!() // TODO ?
+        // Like newtype variants, struct variants are serialized as their content, e.g. a record
+        // picked among the branches of a union.
+        Ok(StructSerializer::new(len))
     }
 }
 
@@ -313,15 +337,15 @@ impl ser::SerializeTupleVariant for SeqSerializer {
     type Ok = Value;
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, _: &T) -> Result<(), Self::Error>
+    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: Serialize,
     {
-        unimplemented!()
+        ser::SerializeSeq::serialize_element(self, value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        unimplemented!()
+        ser::SerializeSeq::end(self)
     }
 }
 
@@ -336,7 +360,7 @@ impl ser::SerializeMap for MapSerializer {
         let key = key.serialize(&mut Serializer::default())?;
 
         if let Value::String(key) = key {
-            self.indices.insert(key, self.values.len());
+            self.key = Some(key);
             Ok(())
         } else {
             Err(Error::custom("map key is not a string"))
@@ -347,20 +371,17 @@ impl ser::SerializeMap for MapSerializer {
     where
         T: Serialize,
     {
-        self.values
-            .push(value.serialize(&mut Serializer::default())?);
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::custom("map value serialized before its key"))?;
+        self.items
+            .insert(key, value.serialize(&mut Serializer::default())?);
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        let mut items = HashMap::new();
-        for (key, index) in self.indices {
-            if let Some(value) = self.values.get(index) {
-                items.insert(key, value.clone());
-            }
-        }
-
-        Ok(Value::Map(items))
+        Ok(Value::Map(self.items))
     }
 }
 
@@ -384,6 +405,7 @@ impl ser::SerializeStruct for StructSerializer {
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        // The serialized fields are moved into the record, without copying them.
         Ok(Value::Record(self.fields))
     }
 }
@@ -392,15 +414,351 @@ impl ser::SerializeStructVariant for StructSerializer {
     type Ok = Value;
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, _: &'static str, _: &T) -> Result<(), Self::Error>
+    fn serialize_field<T: ?Sized>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        ser::SerializeStruct::serialize_field(self, name, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        ser::SerializeStruct::end(self)
+    }
+}
+
+/// A serializer producing values that conform to a given schema.
+///
+/// Values are first serialized like [`to_value`](fn.to_value.html) does, and then resolved
+/// against the schema: union branches are picked according to the schema rather than the
+/// position of the Rust enum variant, enum indices are looked up by symbol and numbers are
+/// promoted when needed. Values matching none of the union branches are reported as errors.
+///
+/// ```
+/// use avro_rs::{types::Value, Schema, SchemaGuidedSerializer};
+/// use serde::Serialize;
+///
+/// let schema = Schema::parse_str(r#"["null", "long"]"#).unwrap();
+/// let value = 27.serialize(&mut SchemaGuidedSerializer::new(&schema)).unwrap();
+/// assert_eq!(value, Value::Union(Box::new(Value::Long(27))));
+/// ```
+pub struct SchemaGuidedSerializer<'s> {
+    schema: &'s Schema,
+}
+
+/// Compound serializer of a [`SchemaGuidedSerializer`](struct.SchemaGuidedSerializer.html),
+/// resolving the value produced by `inner` once it is complete.
+pub struct SchemaGuidedCompound<'s, C> {
+    inner: C,
+    schema: &'s Schema,
+}
+
+impl<'s> SchemaGuidedSerializer<'s> {
+    pub fn new(schema: &'s Schema) -> SchemaGuidedSerializer<'s> {
+        SchemaGuidedSerializer { schema }
+    }
+
+    fn compound<C>(&self, inner: C) -> SchemaGuidedCompound<'s, C> {
+        SchemaGuidedCompound {
+            inner,
+            schema: self.schema,
+        }
+    }
+}
+
+fn resolve_to_schema(value: Value, schema: &Schema) -> Result<Value, Error> {
+    value
+        .resolve(schema)
+        .map_err(|e| Error::custom(format!("value does not match the schema: {}", e)))
+}
+
+macro_rules! guided_serialize(
+    ($method:ident, $t:ty) => (
+        fn $method(self, v: $t) -> Result<Self::Ok, Self::Error> {
+            resolve_to_schema(Serializer::default().$method(v)?, self.schema)
+        }
+    );
+);
+
+impl<'a, 's> ser::Serializer for &'a mut SchemaGuidedSerializer<'s> {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SchemaGuidedCompound<'s, SeqSerializer>;
+    type SerializeTuple = SchemaGuidedCompound<'s, SeqSerializer>;
+    type SerializeTupleStruct = SchemaGuidedCompound<'s, SeqSerializer>;
+    type SerializeTupleVariant = SchemaGuidedCompound<'s, SeqSerializer>;
+    type SerializeMap = SchemaGuidedCompound<'s, MapSerializer>;
+    type SerializeStruct = SchemaGuidedCompound<'s, StructSerializer>;
+    type SerializeStructVariant = SchemaGuidedCompound<'s, StructSerializer>;
+
+    guided_serialize!(serialize_bool, bool);
+    guided_serialize!(serialize_i8, i8);
+    guided_serialize!(serialize_i16, i16);
+    guided_serialize!(serialize_i32, i32);
+    guided_serialize!(serialize_i64, i64);
+    guided_serialize!(serialize_u8, u8);
+    guided_serialize!(serialize_u16, u16);
+    guided_serialize!(serialize_u32, u32);
+    guided_serialize!(serialize_u64, u64);
+    guided_serialize!(serialize_i128, i128);
+    guided_serialize!(serialize_u128, u128);
+    guided_serialize!(serialize_f32, f32);
+    guided_serialize!(serialize_f64, f64);
+    guided_serialize!(serialize_char, char);
+    guided_serialize!(serialize_str, &str);
+    guided_serialize!(serialize_bytes, &[u8]);
+    guided_serialize!(serialize_unit_struct, &'static str);
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        resolve_to_schema(Serializer::default().serialize_none()?, self.schema)
+    }
+
+    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize,
+    {
+        resolve_to_schema(Serializer::default().serialize_some(value)?, self.schema)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        resolve_to_schema(Serializer::default().serialize_unit()?, self.schema)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        let value = Serializer::default().serialize_unit_variant(name, index, variant)?;
+        resolve_to_schema(value, self.schema)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize,
+    {
+        let value = Serializer::default().serialize_newtype_struct(name, value)?;
+        resolve_to_schema(value, self.schema)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize,
+    {
+        let value = Serializer::default().serialize_newtype_variant(name, index, variant, value)?;
+        resolve_to_schema(value, self.schema)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(self.compound(Serializer::default().serialize_seq(len)?))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(self.compound(Serializer::default().serialize_tuple(len)?))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(self.compound(Serializer::default().serialize_tuple_struct(name, len)?))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        let inner = Serializer::default().serialize_tuple_variant(name, index, variant, len)?;
+        Ok(self.compound(inner))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(self.compound(Serializer::default().serialize_map(len)?))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(self.compound(Serializer::default().serialize_struct(name, len)?))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        let inner = Serializer::default().serialize_struct_variant(name, index, variant, len)?;
+        Ok(self.compound(inner))
+    }
+}
+
+impl<'s, C> ser::SerializeSeq for SchemaGuidedCompound<'s, C>
+where
+    C: ser::SerializeSeq<Ok = Value, Error = Error>,
+{
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.inner.serialize_element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        resolve_to_schema(self.inner.end()?, self.schema)
+    }
+}
+
+impl<'s, C> ser::SerializeTuple for SchemaGuidedCompound<'s, C>
+where
+    C: ser::SerializeTuple<Ok = Value, Error = Error>,
+{
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.inner.serialize_element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        resolve_to_schema(self.inner.end()?, self.schema)
+    }
+}
+
+impl<'s, C> ser::SerializeTupleStruct for SchemaGuidedCompound<'s, C>
+where
+    C: ser::SerializeTupleStruct<Ok = Value, Error = Error>,
+{
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.inner.serialize_field(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        resolve_to_schema(self.inner.end()?, self.schema)
+    }
+}
+
+impl<'s, C> ser::SerializeTupleVariant for SchemaGuidedCompound<'s, C>
+where
+    C: ser::SerializeTupleVariant<Ok = Value, Error = Error>,
+{
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.inner.serialize_field(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        resolve_to_schema(self.inner.end()?, self.schema)
+    }
+}
+
+impl<'s, C> ser::SerializeMap for SchemaGuidedCompound<'s, C>
+where
+    C: ser::SerializeMap<Ok = Value, Error = Error>,
+{
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.inner.serialize_key(key)
+    }
+
+    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.inner.serialize_value(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        resolve_to_schema(self.inner.end()?, self.schema)
+    }
+}
+
+impl<'s, C> ser::SerializeStruct for SchemaGuidedCompound<'s, C>
+where
+    C: ser::SerializeStruct<Ok = Value, Error = Error>,
+{
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error>
     where
         T: Serialize,
     {
-        unimplemented!()
+        self.inner.serialize_field(name, value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        unimplemented!()
+        resolve_to_schema(self.inner.end()?, self.schema)
+    }
+}
+
+impl<'s, C> ser::SerializeStructVariant for SchemaGuidedCompound<'s, C>
+where
+    C: ser::SerializeStructVariant<Ok = Value, Error = Error>,
+{
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.inner.serialize_field(name, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        resolve_to_schema(self.inner.end()?, self.schema)
     }
 }
 
@@ -408,15 +766,32 @@ impl ser::SerializeStructVariant for StructSerializer {
 ///
 /// This conversion can fail if the value is not valid as per the Avro specification.
 /// e.g: HashMap with non-string keys
+///
+/// Unsigned integers are serialized as an `int` if they fit, and as a `long` otherwise: `u64`
+/// values greater than `i64::MAX`, as well as any 128-bit integer, are rejected.
 pub fn to_value<S: Serialize>(value: S) -> Result<Value, Error> {
     let mut serializer = Serializer::default();
     value.serialize(&mut serializer)
 }
 
+/// Interpret a serializeable instance as a `Value` conforming to `schema`.
+///
+/// Unlike [`to_value`](fn.to_value.html), the schema guides the serialization (see
+/// [`SchemaGuidedSerializer`](struct.SchemaGuidedSerializer.html)) and the resulting value is
+/// validated against it.
+pub fn to_value_with_schema<S: Serialize>(value: S, schema: &Schema) -> Result<Value, Error> {
+    let value = value.serialize(&mut SchemaGuidedSerializer::new(schema))?;
+    match value.validate_detailed(schema) {
+        Ok(()) => Ok(value),
+        Err(errors) => Err(Error::custom(&errors[0])),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
 
     #[derive(Debug, Deserialize, Serialize)]
     struct Test {
@@ -424,6 +799,19 @@ mod tests {
         b: String,
     }
 
+    #[derive(Debug, Serialize)]
+    enum Suit {
+        Hearts,
+        Spades,
+    }
+
+    #[derive(Debug, Serialize)]
+    struct Card {
+        suit: Suit,
+        rank: Option<i32>,
+        note: String,
+    }
+
     #[test]
     fn test_to_value() {
         let test = Test {
@@ -437,4 +825,220 @@ mod tests {
 
         assert_eq!(to_value(test).unwrap(), expected);
     }
+
+    #[test]
+    fn test_to_value_unsigned_integers() {
+        assert_eq!(to_value(0u8).unwrap(), Value::Int(0));
+        assert_eq!(to_value(u8::max_value()).unwrap(), Value::Int(255));
+        assert_eq!(to_value(0u16).unwrap(), Value::Int(0));
+        assert_eq!(to_value(u16::max_value()).unwrap(), Value::Int(65_535));
+
+        assert_eq!(to_value(0u32).unwrap(), Value::Int(0));
+        assert_eq!(
+            to_value(i32::max_value() as u32).unwrap(),
+            Value::Int(i32::max_value())
+        );
+        assert_eq!(
+            to_value(i32::max_value() as u32 + 1).unwrap(),
+            Value::Long(i64::from(i32::max_value()) + 1)
+        );
+        assert_eq!(
+            to_value(u32::max_value()).unwrap(),
+            Value::Long(i64::from(u32::max_value()))
+        );
+
+        assert_eq!(to_value(0u64).unwrap(), Value::Long(0));
+        assert_eq!(
+            to_value(i64::max_value() as u64).unwrap(),
+            Value::Long(i64::max_value())
+        );
+        assert!(to_value(i64::max_value() as u64 + 1).is_err());
+        assert!(to_value(u64::max_value()).is_err());
+
+        for value in &[0u128, 1, u128::max_value()] {
+            assert!(to_value(value).is_err());
+        }
+        assert!(to_value(0i128).is_err());
+        assert!(to_value_with_schema(0u128, &Schema::Long).is_err());
+        assert_eq!(
+            to_value_with_schema(u32::max_value(), &Schema::Long).unwrap(),
+            Value::Long(i64::from(u32::max_value()))
+        );
+    }
+
+    #[test]
+    fn test_to_value_map() {
+        let mut map = BTreeMap::new();
+        map.insert("a", 1i64);
+        map.insert("b", 2i64);
+        let mut expected = HashMap::new();
+        expected.insert("a".to_owned(), Value::Long(1));
+        expected.insert("b".to_owned(), Value::Long(2));
+        assert_eq!(to_value(&map).unwrap(), Value::Map(expected));
+
+        let mut map = BTreeMap::new();
+        map.insert(1, 1i64);
+        assert!(to_value(&map).is_err());
+    }
+
+    #[test]
+    fn test_schema_guided_serializer() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "Card",
+                "fields": [
+                    {"name": "suit", "type": {"type": "enum", "name": "Suit", "symbols": ["SPADES", "Hearts", "Spades"]}},
+                    {"name": "rank", "type": ["long", "null"]},
+                    {"name": "note", "type": ["null", "string"]}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+        let card = Card {
+            suit: Suit::Hearts,
+            rank: Some(12),
+            note: "foo".to_owned(),
+        };
+
+        let value = card
+            .serialize(&mut SchemaGuidedSerializer::new(&schema))
+            .unwrap();
+        assert_eq!(
+            value,
+            Value::Record(vec![
+                ("suit".to_owned(), Value::Enum(1, "Hearts".to_owned())),
+                ("rank".to_owned(), Value::Union(Box::new(Value::Long(12)))),
+                (
+                    "note".to_owned(),
+                    Value::Union(Box::new(Value::String("foo".to_owned())))
+                ),
+            ])
+        );
+        assert!(value.validate(&schema));
+
+        // The Rust variant index of `Spades` is 1, but its symbol is at position 2.
+        let enum_schema = match schema {
+            Schema::Record { ref fields, .. } => &fields[0].schema,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            Suit::Spades
+                .serialize(&mut SchemaGuidedSerializer::new(enum_schema))
+                .unwrap(),
+            Value::Enum(2, "Spades".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_to_value_variants() {
+        #[derive(Serialize)]
+        enum Shape {
+            Point(i32, i32),
+            Circle { radius: f64, label: String },
+        }
+
+        assert_eq!(
+            to_value(Shape::Point(1, 2)).unwrap(),
+            Value::Array(vec![Value::Int(1), Value::Int(2)])
+        );
+        assert_eq!(
+            to_value(Shape::Circle {
+                radius: 1.5,
+                label: "foo".to_owned(),
+            })
+            .unwrap(),
+            Value::Record(vec![
+                ("radius".to_owned(), Value::Double(1.5)),
+                ("label".to_owned(), Value::String("foo".to_owned())),
+            ])
+        );
+
+        // struct variants pick their record among the branches of a union
+        let schema = Schema::parse_str(
+            r#"
+            [
+                {"type": "array", "items": "int"},
+                {
+                    "type": "record",
+                    "name": "Circle",
+                    "fields": [
+                        {"name": "radius", "type": "double"},
+                        {"name": "label", "type": "string"}
+                    ]
+                }
+            ]
+        "#,
+        )
+        .unwrap();
+        let circle = Shape::Circle {
+            radius: 1.5,
+            label: "foo".to_owned(),
+        };
+        let union = match schema {
+            Schema::Union(ref inner) => inner,
+            _ => unreachable!(),
+        };
+        let value = to_value_with_schema(circle, &schema).unwrap();
+        assert!(value.validate(&schema));
+        assert_eq!(value.union_index(union), Some(1));
+        let value = to_value_with_schema(Shape::Point(1, 2), &schema).unwrap();
+        assert_eq!(value.union_index(union), Some(0));
+    }
+
+    #[test]
+    fn test_schema_guided_serializer_no_matching_branch() {
+        let schema = Schema::parse_str(r#"["null", "int"]"#).unwrap();
+        assert!("foo"
+            .serialize(&mut SchemaGuidedSerializer::new(&schema))
+            .is_err());
+        assert!(vec![1, 2]
+            .serialize(&mut SchemaGuidedSerializer::new(&schema))
+            .is_err());
+    }
+
+    #[test]
+    fn test_to_value_with_schema() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "Test",
+                "fields": [
+                    {"name": "a", "type": "long"},
+                    {"name": "b", "type": ["null", "string"]}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+        let test = || Test {
+            a: 27,
+            b: "foo".to_owned(),
+        };
+
+        // Without the schema, `b` is not wrapped in a union.
+        assert!(!to_value(test()).unwrap().validate(&schema));
+
+        let value = to_value_with_schema(test(), &schema).unwrap();
+        assert_eq!(
+            value,
+            Value::Record(vec![
+                ("a".to_owned(), Value::Long(27)),
+                (
+                    "b".to_owned(),
+                    Value::Union(Box::new(Value::String("foo".to_owned())))
+                ),
+            ])
+        );
+
+        let schema = Schema::parse_str(r#"["string", "null"]"#).unwrap();
+        assert_eq!(
+            to_value_with_schema(None::<String>, &schema).unwrap(),
+            Value::Union(Box::new(Value::Null))
+        );
+        assert!(to_value_with_schema(27, &schema).is_err());
+    }
 }
@@ -1,30 +1,156 @@
 //! Logic handling the intermediate representation of Avro values.
-use std::collections::HashMap;
-use std::hash::BuildHasher;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::mem;
 use std::u8;
 
 use failure::{Error, Fail};
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
 use serde_json::Value as JsonValue;
 
-use crate::schema::{RecordField, Schema, SchemaKind, UnionSchema};
+use crate::decimal::Decimal;
+use crate::duration::Duration;
+use crate::schema::{
+    resolve_ref, Definitions, RecordField, RecordFieldOrder, Schema, SchemaKind, UnionSchema,
+};
+
+#[cfg(feature = "chrono")]
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, NaiveDate, NaiveTime, TimeZone, Timelike, Utc,
+};
+
+#[cfg(feature = "uuid")]
+use uuid::Uuid;
+
+#[cfg(feature = "derive")]
+pub use avro_derive::ToAvro;
+
+/// Number of days between 0001-01-01 (day 1 of the Common Era) and the Unix epoch.
+#[cfg(feature = "chrono")]
+pub(crate) const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// Number of microseconds in a day, which bounds the values of the `time-*` logical types.
+const MICROS_PER_DAY: i64 = 86_400_000_000;
 
 /// Describes errors happened while performing schema resolution on Avro data.
 #[derive(Fail, Debug)]
-#[fail(display = "Schema resoulution error: {}", _0)]
-pub struct SchemaResolutionError(pub String);
+pub struct SchemaResolutionError {
+    /// Description of the error.
+    pub message: String,
+    /// Location of the value that could not be resolved, starting from the resolved value.
+    pub path: ValuePath,
+}
 
 impl SchemaResolutionError {
     pub fn new<S>(msg: S) -> SchemaResolutionError
     where
         S: Into<String>,
     {
-        SchemaResolutionError(msg.into())
+        SchemaResolutionError {
+            message: msg.into(),
+            path: ValuePath::default(),
+        }
+    }
+}
+
+impl fmt::Display for SchemaResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.0.is_empty() {
+            write!(f, "Schema resoulution error: {}", self.message)
+        } else {
+            write!(
+                f,
+                "Schema resoulution error at {}: {}",
+                self.path, self.message
+            )
+        }
+    }
+}
+
+/// Prefix the path of `err` with `segment` if it is a `SchemaResolutionError`, as it is
+/// propagated from a nested value to its parent.
+fn resolution_error_at(segment: PathSegment, err: Error) -> Error {
+    match err.downcast::<SchemaResolutionError>() {
+        Ok(mut err) => {
+            err.path.0.insert(0, segment);
+            err.into()
+        }
+        Err(err) => err,
+    }
+}
+
+/// A single step of a [`ValuePath`](struct.ValuePath.html).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /// A field of a record.
+    Field(String),
+    /// An item of an array.
+    Index(usize),
+    /// A value of a map.
+    Key(String),
+}
+
+/// Location of a nested value, starting from the validated value.
+///
+/// It is displayed as `$` followed by its segments, e.g. `$.items[2]["key"]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValuePath(pub Vec<PathSegment>);
+
+impl fmt::Display for ValuePath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "$")?;
+        for segment in &self.0 {
+            match *segment {
+                PathSegment::Field(ref name) => write!(f, ".{}", name)?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+                PathSegment::Key(ref key) => write!(f, "[{:?}]", key)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Describes errors happened while validating Avro data.
+#[derive(Fail, Clone, Debug, PartialEq)]
+#[fail(
+    display = "Invalid value at {}: expected {}, got {}",
+    path, expected, got
+)]
+pub struct ValidationError {
+    /// Location of the invalid value.
+    pub path: ValuePath,
+    /// Description of the schema the value was validated against.
+    pub expected: String,
+    /// Description of the invalid value.
+    pub got: String,
+}
+
+impl ValidationError {
+    pub fn new<E, G>(path: ValuePath, expected: E, got: G) -> ValidationError
+    where
+        E: Into<String>,
+        G: Into<String>,
+    {
+        ValidationError {
+            path,
+            expected: expected.into(),
+            got: got.into(),
+        }
     }
 }
 
 /// Represents any valid Avro value
 /// More information about Avro values can be found in the
 /// [Avro Specification](https://avro.apache.org/docs/current/spec.html#schemas)
+///
+/// Values are compared structurally: the items of a `Value::Array` and the fields of a
+/// `Value::Record` must be in the same order, while the entries of a `Value::Map` are compared
+/// by key, whatever their order. Use [`semantic_eq`](#method.semantic_eq) to compare records by
+/// field name instead.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
     /// A `null` Avro value.
@@ -66,6 +192,27 @@ pub enum Value {
     ///
     /// See [Record](types.Record) for a more user-friendly support.
     Record(Vec<(String, Value)>),
+    /// A `decimal` Avro value.
+    ///
+    /// Only the unscaled value is stored: the scale is defined by the corresponding schema.
+    Decimal(Decimal),
+    /// A `date` Avro value.
+    ///
+    /// A Date is represented by the number of days since the Unix epoch.
+    Date(i32),
+    /// A `time-millis` Avro value, in milliseconds after midnight.
+    TimeMillis(i32),
+    /// A `time-micros` Avro value, in microseconds after midnight.
+    TimeMicros(i64),
+    /// A `timestamp-millis` Avro value, in milliseconds since the Unix epoch.
+    TimestampMillis(i64),
+    /// A `timestamp-micros` Avro value, in microseconds since the Unix epoch.
+    TimestampMicros(i64),
+    /// A `duration` Avro value.
+    Duration(Duration),
+    /// A `uuid` Avro value.
+    #[cfg(feature = "uuid")]
+    Uuid(Uuid),
 }
 
 /// Any structure implementing the [ToAvro](trait.ToAvro.html) trait will be usable
@@ -77,6 +224,12 @@ pub trait ToAvro {
 
 macro_rules! to_avro(
     ($t:ty, $v:expr) => (
+        impl From<$t> for Value {
+            fn from(value: $t) -> Self {
+                $v(value)
+            }
+        }
+
         impl ToAvro for $t {
             fn avro(self) -> Value {
                 $v(self)
@@ -91,6 +244,108 @@ to_avro!(i64, Value::Long);
 to_avro!(f32, Value::Float);
 to_avro!(f64, Value::Double);
 to_avro!(String, Value::String);
+to_avro!(Decimal, Value::Decimal);
+to_avro!(Duration, Value::Duration);
+
+impl<'a> From<&'a str> for Value {
+    fn from(value: &'a str) -> Self {
+        Value::String(value.to_owned())
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Value::Bytes(value)
+    }
+}
+
+/// Converts an optional value into a `Value::Union`, holding `Value::Null` for `None`.
+impl From<Option<Value>> for Value {
+    fn from(value: Option<Value>) -> Self {
+        Value::Union(Box::new(value.unwrap_or(Value::Null)))
+    }
+}
+
+macro_rules! try_from_value(
+    ($t:ty, $accessor:ident, $expected:expr) => (
+        impl TryFrom<Value> for $t {
+            type Error = Error;
+
+            fn try_from(value: Value) -> Result<Self, Self::Error> {
+                value.$accessor().ok_or_else(|| {
+                    ValidationError::new(ValuePath::default(), $expected, describe_value(&value))
+                        .into()
+                })
+            }
+        }
+    );
+);
+
+try_from_value!(bool, as_bool, "boolean");
+try_from_value!(i32, as_i32, "int");
+try_from_value!(i64, as_i64, "long");
+try_from_value!(f32, as_f32, "float");
+try_from_value!(f64, as_f64, "double");
+
+impl TryFrom<Value> for String {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::String(s) => Ok(s),
+            other => {
+                Err(
+                    ValidationError::new(ValuePath::default(), "string", describe_value(&other))
+                        .into(),
+                )
+            }
+        }
+    }
+}
+
+impl TryFrom<Value> for Vec<u8> {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Bytes(bytes) | Value::Fixed(_, bytes) => Ok(bytes),
+            other => {
+                Err(
+                    ValidationError::new(ValuePath::default(), "bytes", describe_value(&other))
+                        .into(),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl ToAvro for NaiveDate {
+    fn avro(self) -> Value {
+        Value::from(self)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl ToAvro for NaiveTime {
+    fn avro(self) -> Value {
+        Value::from(self)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl ToAvro for DateTime<Utc> {
+    fn avro(self) -> Value {
+        Value::from(self)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl ToAvro for Uuid {
+    fn avro(self) -> Value {
+        Value::Uuid(self)
+    }
+}
 
 impl ToAvro for () {
     fn avro(self) -> Value {
@@ -104,6 +359,20 @@ impl ToAvro for usize {
     }
 }
 
+macro_rules! to_avro_int(
+    ($($t:ty),*) => (
+        $(
+            impl ToAvro for $t {
+                fn avro(self) -> Value {
+                    Value::Int(i32::from(self))
+                }
+            }
+        )*
+    );
+);
+
+to_avro_int!(i8, i16, u8, u16);
+
 impl<'a> ToAvro for &'a str {
     fn avro(self) -> Value {
         Value::String(self.to_owned())
@@ -129,6 +398,50 @@ where
     }
 }
 
+impl<T> ToAvro for Vec<T>
+where
+    T: ToAvro,
+{
+    fn avro(self) -> Value {
+        Value::Array(self.into_iter().map(ToAvro::avro).collect())
+    }
+}
+
+/// Tuples are converted into a `Value::Array`, so all their items should convert into values of
+/// the same schema.
+macro_rules! to_avro_tuple(
+    ($($name:ident),+) => (
+        impl<$($name: ToAvro),+> ToAvro for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn avro(self) -> Value {
+                let ($($name,)+) = self;
+                Value::Array(vec![$($name.avro()),+])
+            }
+        }
+    );
+);
+
+to_avro_tuple!(A, B);
+to_avro_tuple!(A, B, C);
+to_avro_tuple!(A, B, C, D);
+to_avro_tuple!(A, B, C, D, E);
+to_avro_tuple!(A, B, C, D, E, F);
+to_avro_tuple!(A, B, C, D, E, F, G);
+to_avro_tuple!(A, B, C, D, E, F, G, H);
+
+impl<T> ToAvro for BTreeMap<String, T>
+where
+    T: ToAvro,
+{
+    fn avro(self) -> Value {
+        Value::Map(
+            self.into_iter()
+                .map(|(key, value)| (key, value.avro()))
+                .collect::<_>(),
+        )
+    }
+}
+
 impl<T, S: BuildHasher> ToAvro for HashMap<String, T, S>
 where
     T: ToAvro,
@@ -172,18 +485,25 @@ impl<S: Serialize> ToAvro for S {
 */
 
 /// Utility interface to build `Value::Record` objects.
-#[derive(Debug, Clone)]
+///
+/// The default `Record` has no schema and no fields, and ignores any field put in it: it is only
+/// useful as a placeholder.
+#[derive(Debug, Clone, Default)]
 pub struct Record<'a> {
     /// List of fields contained in the record.
     /// Ordered according to the fields in the schema given to create this
-    /// `Record` object. Any unset field defaults to `Value::Null`.
+    /// `Record` object. Any unset field holds the default value of the field in the schema, or
+    /// `Value::Null` if it has none.
     pub fields: Vec<(String, Value)>,
-    schema_lookup: &'a HashMap<String, usize>,
+    schema_lookup: Option<&'a HashMap<String, usize>>,
 }
 
 impl<'a> Record<'a> {
     /// Create a `Record` given a `Schema`.
     ///
+    /// Fields are initialized with their default value, or `Value::Null` if they have no
+    /// (valid) default.
+    ///
     /// If the `Schema` is not a `Schema::Record` variant, `None` will be returned.
     pub fn new(schema: &Schema) -> Option<Record> {
         match *schema {
@@ -194,18 +514,56 @@ impl<'a> Record<'a> {
             } => {
                 let mut fields = Vec::with_capacity(schema_fields.len());
                 for schema_field in schema_fields.iter() {
-                    fields.push((schema_field.name.clone(), Value::Null));
+                    let default = schema_field.default_as_value().ok().and_then(|v| v);
+                    fields.push((schema_field.name.clone(), default.unwrap_or(Value::Null)));
                 }
 
                 Some(Record {
                     fields,
-                    schema_lookup,
+                    schema_lookup: Some(schema_lookup),
                 })
             }
             _ => None,
         }
     }
 
+    /// Create a `Record` given a `Schema`, with every field initialized with its default value.
+    ///
+    /// Unlike [`new`](#method.new), this fails if the `Schema` is not a `Schema::Record`
+    /// variant, or if any of its fields has no valid default.
+    pub fn new_strict(schema: &Schema) -> Result<Record<'_>, Error> {
+        match *schema {
+            Schema::Record {
+                fields: ref schema_fields,
+                lookup: ref schema_lookup,
+                ..
+            } => {
+                let fields = schema_fields
+                    .iter()
+                    .map(|schema_field| {
+                        let default = schema_field.default_as_value()?.ok_or_else(|| {
+                            SchemaResolutionError::new(format!(
+                                "missing default for field {}",
+                                schema_field.name
+                            ))
+                        })?;
+                        Ok((schema_field.name.clone(), default))
+                    })
+                    .collect::<Result<_, Error>>()?;
+
+                Ok(Record {
+                    fields,
+                    schema_lookup: Some(schema_lookup),
+                })
+            }
+            _ => Err(SchemaResolutionError::new(format!(
+                "Record expected, got {}",
+                describe_schema(schema)
+            ))
+            .into()),
+        }
+    }
+
     /// Put a compatible value (implementing the `ToAvro` trait) in the
     /// `Record` for a given `field` name.
     ///
@@ -215,7 +573,7 @@ impl<'a> Record<'a> {
     where
         V: ToAvro,
     {
-        if let Some(&position) = self.schema_lookup.get(field) {
+        if let Some(&position) = self.schema_lookup.and_then(|lookup| lookup.get(field)) {
             self.fields[position].1 = value.avro()
         }
     }
@@ -227,6 +585,129 @@ impl<'a> ToAvro for Record<'a> {
     }
 }
 
+/// A record value owning the index of its fields by name, for O(1) field access.
+///
+/// Unlike [`Record`](struct.Record.html), a `GenericRecord` does not borrow its `Schema`, can be
+/// read from, and reports unknown fields as errors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericRecord {
+    fields: Vec<(String, Value)>,
+    lookup: HashMap<String, usize>,
+}
+
+impl GenericRecord {
+    /// Create a `GenericRecord` given a `Schema`.
+    ///
+    /// Fields are initialized with their default value, or `Value::Null` if they have no
+    /// (valid) default.
+    ///
+    /// If the `Schema` is not a `Schema::Record` variant, an error will be returned.
+    pub fn new(schema: &Schema) -> Result<GenericRecord, Error> {
+        match *schema {
+            Schema::Record {
+                fields: ref schema_fields,
+                ref lookup,
+                ..
+            } => {
+                let fields = schema_fields
+                    .iter()
+                    .map(|schema_field| {
+                        let default = schema_field.default_as_value().ok().and_then(|v| v);
+                        (schema_field.name.clone(), default.unwrap_or(Value::Null))
+                    })
+                    .collect();
+
+                Ok(GenericRecord {
+                    fields,
+                    lookup: lookup.clone(),
+                })
+            }
+            _ => Err(SchemaResolutionError::new(format!(
+                "Record expected, got {}",
+                describe_schema(schema)
+            ))
+            .into()),
+        }
+    }
+
+    /// Put a compatible value (implementing the `ToAvro` trait) in the `GenericRecord` for a
+    /// given `field` name.
+    ///
+    /// Return an error if the record has no such field. Does not perform any schema validation.
+    pub fn put<V>(&mut self, field: &str, value: V) -> Result<(), Error>
+    where
+        V: ToAvro,
+    {
+        match self.lookup.get(field) {
+            Some(&position) => {
+                self.fields[position].1 = value.avro();
+                Ok(())
+            }
+            None => Err(SchemaResolutionError::new(format!("No field named {}", field)).into()),
+        }
+    }
+
+    /// Get the value of a given `field`, if the record has such a field.
+    pub fn get(&self, field: &str) -> Option<&Value> {
+        self.lookup
+            .get(field)
+            .map(|&position| &self.fields[position].1)
+    }
+
+    /// Get a mutable reference to the value of a given `field`, if the record has such a field.
+    pub fn get_mut(&mut self, field: &str) -> Option<&mut Value> {
+        match self.lookup.get(field) {
+            Some(&position) => Some(&mut self.fields[position].1),
+            None => None,
+        }
+    }
+
+    /// Return the fields of the record, in order.
+    pub fn fields(&self) -> &[(String, Value)] {
+        &self.fields
+    }
+
+    /// Convert the `GenericRecord` into a `Value::Record`.
+    pub fn into_value(self) -> Value {
+        Value::Record(self.fields)
+    }
+}
+
+impl ToAvro for GenericRecord {
+    fn avro(self) -> Value {
+        self.into_value()
+    }
+}
+
+impl From<GenericRecord> for Value {
+    fn from(record: GenericRecord) -> Self {
+        record.into_value()
+    }
+}
+
+impl TryFrom<Value> for GenericRecord {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Record(fields) => {
+                let lookup = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(position, &(ref name, _))| (name.clone(), position))
+                    .collect();
+                Ok(GenericRecord { fields, lookup })
+            }
+            other => {
+                Err(
+                    ValidationError::new(ValuePath::default(), "record", describe_value(&other))
+                        .into(),
+                )
+            }
+        }
+    }
+}
+
 impl ToAvro for JsonValue {
     fn avro(self) -> Value {
         match self {
@@ -249,329 +730,3581 @@ impl ToAvro for JsonValue {
     }
 }
 
-impl Value {
-    /// Validate the value against the given [Schema](../schema/enum.Schema.html).
-    ///
-    /// See the [Avro specification](https://avro.apache.org/docs/current/spec.html)
-    /// for the full set of rules of schema validation.
-    pub fn validate(&self, schema: &Schema) -> bool {
-        match (self, schema) {
-            (&Value::Null, &Schema::Null) => true,
-            (&Value::Boolean(_), &Schema::Boolean) => true,
-            (&Value::Int(_), &Schema::Int) => true,
-            (&Value::Long(_), &Schema::Long) => true,
-            (&Value::Float(_), &Schema::Float) => true,
-            (&Value::Double(_), &Schema::Double) => true,
-            (&Value::Bytes(_), &Schema::Bytes) => true,
-            (&Value::String(_), &Schema::String) => true,
-            (&Value::Fixed(n, _), &Schema::Fixed { size, .. }) => n == size,
-            (&Value::String(ref s), &Schema::Enum { ref symbols, .. }) => symbols.contains(s),
-            (&Value::Enum(i, ref s), &Schema::Enum { ref symbols, .. }) => symbols
-                .get(i as usize)
-                .map(|ref symbol| symbol == &s)
-                .unwrap_or(false),
-            // (&Value::Union(None), &Schema::Union(_)) => true,
-            (&Value::Union(ref value), &Schema::Union(ref inner)) => {
-                inner.find_schema(value).is_some()
+/// The default `Value` is `Value::Null`, e.g. to take a value out of a mutable reference with
+/// `std::mem::take`.
+impl Default for Value {
+    fn default() -> Self {
+        Value::Null
+    }
+}
+
+/// `Value` is only an equivalence relation as long as it holds no `NaN` float or double: in
+/// `Ord`, `NaN` is equal to itself and greater than any other number.
+impl Eq for Value {}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders values following the
+/// [sort order](https://avro.apache.org/docs/current/spec.html#order) of Avro data.
+///
+/// As `Value` does not hold its schema, all record fields are compared in ascending order and
+/// enums by their index: see [`Value::cmp_with_schema`](enum.Value.html#method.cmp_with_schema)
+/// to honor the `order` of record fields. Maps, which Avro does not order, are compared by their
+/// entries sorted by key. Values of different types are ordered by the declaration order of their
+/// variants.
+impl Ord for Value {
+    fn cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (&Value::Null, &Value::Null) => Ordering::Equal,
+            (&Value::Boolean(a), &Value::Boolean(b)) => a.cmp(&b),
+            (&Value::Int(a), &Value::Int(b))
+            | (&Value::Date(a), &Value::Date(b))
+            | (&Value::TimeMillis(a), &Value::TimeMillis(b)) => a.cmp(&b),
+            (&Value::Long(a), &Value::Long(b))
+            | (&Value::TimeMicros(a), &Value::TimeMicros(b))
+            | (&Value::TimestampMillis(a), &Value::TimestampMillis(b))
+            | (&Value::TimestampMicros(a), &Value::TimestampMicros(b)) => a.cmp(&b),
+            (&Value::Float(a), &Value::Float(b)) => cmp_float(f64::from(a), f64::from(b)),
+            (&Value::Double(a), &Value::Double(b)) => cmp_float(a, b),
+            (&Value::Bytes(ref a), &Value::Bytes(ref b)) => a.cmp(b),
+            (&Value::String(ref a), &Value::String(ref b)) => a.cmp(b),
+            (&Value::Fixed(_, ref a), &Value::Fixed(_, ref b)) => a.cmp(b),
+            (&Value::Enum(i, ref a), &Value::Enum(j, ref b)) => i.cmp(&j).then_with(|| a.cmp(b)),
+            (&Value::Union(ref a), &Value::Union(ref b)) => a.cmp(b),
+            (&Value::Array(ref a), &Value::Array(ref b)) => a.cmp(b),
+            (&Value::Map(ref a), &Value::Map(ref b)) => {
+                let mut a = a.iter().collect::<Vec<_>>();
+                let mut b = b.iter().collect::<Vec<_>>();
+                a.sort();
+                b.sort();
+                a.cmp(&b)
             }
-            (&Value::Array(ref items), &Schema::Array(ref inner)) => {
-                items.iter().all(|item| item.validate(inner))
+            (&Value::Record(ref a), &Value::Record(ref b)) => a.cmp(b),
+            (&Value::Decimal(ref a), &Value::Decimal(ref b)) => a.cmp(b),
+            (&Value::Duration(a), &Value::Duration(b)) => {
+                (a.months, a.days, a.millis).cmp(&(b.months, b.days, b.millis))
             }
-            (&Value::Map(ref items), &Schema::Map(ref inner)) => {
-                items.iter().all(|(_, value)| value.validate(inner))
+            #[cfg(feature = "uuid")]
+            (&Value::Uuid(a), &Value::Uuid(b)) => a.cmp(&b),
+            _ => self.variant_rank().cmp(&other.variant_rank()),
+        }
+    }
+}
+
+/// Total order of floating point numbers, where `NaN` is greater than any other number.
+fn cmp_float(a: f64, b: f64) -> Ordering {
+    a.partial_cmp(&b)
+        .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()))
+}
+
+/// Hashes values consistently with their equality: map entries are hashed in key order, and
+/// floats and doubles by their bit pattern (with `-0.0` hashed as `0.0`).
+///
+/// Note that `NaN` floats and doubles are not equal to themselves, so that a value holding one
+/// cannot be found back as a key of a `HashMap`.
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match *self {
+            Value::Null => {}
+            Value::Boolean(b) => b.hash(state),
+            Value::Int(i) | Value::Date(i) | Value::TimeMillis(i) => i.hash(state),
+            Value::Long(i)
+            | Value::TimeMicros(i)
+            | Value::TimestampMillis(i)
+            | Value::TimestampMicros(i) => i.hash(state),
+            Value::Float(x) => (x + 0.0).to_bits().hash(state),
+            Value::Double(x) => (x + 0.0).to_bits().hash(state),
+            Value::Bytes(ref bytes) => bytes.hash(state),
+            Value::String(ref s) => s.hash(state),
+            Value::Fixed(size, ref bytes) => {
+                size.hash(state);
+                bytes.hash(state);
             }
-            (&Value::Record(ref record_fields), &Schema::Record { ref fields, .. }) => {
-                fields.len() == record_fields.len()
-                    && fields.iter().zip(record_fields.iter()).all(
-                        |(field, &(ref name, ref value))| {
-                            field.name == *name && value.validate(&field.schema)
-                        },
-                    )
+            Value::Enum(i, ref symbol) => {
+                i.hash(state);
+                symbol.hash(state);
             }
-            _ => false,
+            Value::Union(ref inner) => inner.hash(state),
+            Value::Array(ref items) => items.hash(state),
+            Value::Map(ref items) => {
+                let mut entries = items.iter().collect::<Vec<_>>();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                entries.hash(state);
+            }
+            Value::Record(ref fields) => fields.hash(state),
+            Value::Decimal(ref decimal) => decimal.hash(state),
+            Value::Duration(duration) => duration.hash(state),
+            #[cfg(feature = "uuid")]
+            Value::Uuid(uuid) => uuid.hash(state),
         }
     }
+}
 
-    /// Attempt to perform schema resolution on the value, with the given
-    /// [Schema](../schema/enum.Schema.html).
-    ///
-    /// See [Schema Resolution](https://avro.apache.org/docs/current/spec.html#Schema+Resolution)
-    /// in the Avro specification for the full set of rules of schema
-    /// resolution.
-    pub fn resolve(mut self, schema: &Schema) -> Result<Self, Error> {
-        // Check if this schema is a union, and if the reader schema is not.
-        if SchemaKind::from(&self) == SchemaKind::Union
-            && SchemaKind::from(schema) != SchemaKind::Union
-        {
-            // Pull out the Union, and attempt to resolve against it.
-            let v = match self {
-                Value::Union(b) => *b,
-                _ => unreachable!(),
-            };
-            self = v;
+/// Formats a `Value` in a concise, human-readable way, e.g. `{name: "foo", tags: ["a", "b"]}`.
+///
+/// Bytes are written as `b"..."` with non-printable bytes escaped in hexadecimal, and map
+/// entries are sorted by key.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Null => write!(f, "null"),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Int(i) | Value::Date(i) | Value::TimeMillis(i) => write!(f, "{}", i),
+            Value::Long(i)
+            | Value::TimeMicros(i)
+            | Value::TimestampMillis(i)
+            | Value::TimestampMicros(i) => write!(f, "{}", i),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Double(x) => write!(f, "{}", x),
+            Value::Bytes(ref bytes) => write_bytes(f, bytes),
+            Value::String(ref s) => write!(f, "{:?}", s),
+            Value::Fixed(size, ref bytes) => {
+                write!(f, "fixed[{}](", size)?;
+                write_bytes(f, bytes)?;
+                write!(f, ")")
+            }
+            Value::Enum(_, ref symbol) => write!(f, "{}", symbol),
+            Value::Union(ref inner) => write!(f, "Union({})", inner),
+            Value::Array(ref items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Value::Map(ref items) => {
+                let mut keys = items.keys().collect::<Vec<_>>();
+                keys.sort();
+                write!(f, "{{")?;
+                for (i, key) in keys.into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}: {}", key, items[key])?;
+                }
+                write!(f, "}}")
+            }
+            Value::Record(ref fields) => {
+                write!(f, "{{")?;
+                for (i, &(ref name, ref value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", name, value)?;
+                }
+                write!(f, "}}")
+            }
+            Value::Decimal(ref decimal) => write!(f, "{}", decimal),
+            Value::Duration(duration) => write!(
+                f,
+                "duration({} months, {} days, {} millis)",
+                duration.months, duration.days, duration.millis
+            ),
+            #[cfg(feature = "uuid")]
+            Value::Uuid(uuid) => write!(f, "{}", uuid),
         }
-        match *schema {
-            Schema::Null => self.resolve_null(),
-            Schema::Boolean => self.resolve_boolean(),
-            Schema::Int => self.resolve_int(),
-            Schema::Long => self.resolve_long(),
-            Schema::Float => self.resolve_float(),
-            Schema::Double => self.resolve_double(),
-            Schema::Bytes => self.resolve_bytes(),
-            Schema::String => self.resolve_string(),
-            Schema::Fixed { size, .. } => self.resolve_fixed(size),
-            Schema::Union(ref inner) => self.resolve_union(inner),
-            Schema::Enum { ref symbols, .. } => self.resolve_enum(symbols),
-            Schema::Array(ref inner) => self.resolve_array(inner),
-            Schema::Map(ref inner) => self.resolve_map(inner),
-            Schema::Record { ref fields, .. } => self.resolve_record(fields),
+    }
+}
+
+fn write_bytes(f: &mut fmt::Formatter, bytes: &[u8]) -> fmt::Result {
+    write!(f, "b\"")?;
+    for &byte in bytes {
+        match byte {
+            b'"' | b'\\' => write!(f, "\\{}", char::from(byte))?,
+            0x20..=0x7E => write!(f, "{}", char::from(byte))?,
+            _ => write!(f, "\\x{:02x}", byte)?,
         }
     }
+    write!(f, "\"")
+}
 
-    fn resolve_null(self) -> Result<Self, Error> {
-        match self {
-            Value::Null => Ok(Value::Null),
-            other => {
-                Err(SchemaResolutionError::new(format!("Null expected, got {:?}", other)).into())
+/// Serializes a `Value` following the
+/// [JSON encoding](https://avro.apache.org/docs/current/spec.html#json_encoding) of Avro data.
+///
+/// Bytes, fixed and decimal values are written as strings whose characters are the code points
+/// 0-255 of their bytes (ISO-8859-1), enums as their symbol and non-null unions as a
+/// single-entry object keyed by the type of the branch. As `Value` does not keep the names of
+/// named types, records, enums and fixed branches are keyed by `"record"`, `"enum"` and `"fixed"`.
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Boolean(b) => serializer.serialize_bool(b),
+            Value::Int(i) | Value::Date(i) | Value::TimeMillis(i) => serializer.serialize_i32(i),
+            Value::Long(i)
+            | Value::TimeMicros(i)
+            | Value::TimestampMillis(i)
+            | Value::TimestampMicros(i) => serializer.serialize_i64(i),
+            Value::Float(f) => serializer.serialize_f32(f),
+            Value::Double(d) => serializer.serialize_f64(d),
+            Value::Bytes(ref bytes) | Value::Fixed(_, ref bytes) => {
+                serializer.serialize_str(&latin1_string(bytes))
+            }
+            Value::Decimal(ref decimal) => {
+                serializer.serialize_str(&latin1_string(&decimal.to_vec()))
+            }
+            Value::Duration(duration) => {
+                let bytes: [u8; 12] = duration.into();
+                serializer.serialize_str(&latin1_string(&bytes))
+            }
+            Value::String(ref s) | Value::Enum(_, ref s) => serializer.serialize_str(s),
+            #[cfg(feature = "uuid")]
+            Value::Uuid(uuid) => serializer.collect_str(&uuid),
+            Value::Union(ref inner) => match **inner {
+                Value::Null => serializer.serialize_unit(),
+                ref inner => {
+                    let mut map = serializer.serialize_map(Some(1))?;
+                    map.serialize_entry(union_branch_name(inner), inner)?;
+                    map.end()
+                }
+            },
+            Value::Array(ref items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            Value::Map(ref items) => {
+                let mut map = serializer.serialize_map(Some(items.len()))?;
+                for (key, value) in items {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            Value::Record(ref fields) => {
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for &(ref name, ref value) in fields {
+                    map.serialize_entry(name, value)?;
+                }
+                map.end()
             }
         }
     }
+}
 
-    fn resolve_boolean(self) -> Result<Self, Error> {
-        match self {
-            Value::Boolean(b) => Ok(Value::Boolean(b)),
-            other => {
-                Err(SchemaResolutionError::new(format!("Boolean expected, got {:?}", other)).into())
+/// Deserializes a `Value` out of any self-describing format, such as JSON.
+///
+/// Integers become `Value::Long`, floating point numbers `Value::Double`, strings
+/// `Value::String`, sequences `Value::Array` and maps `Value::Map`.
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("any valid Avro value")
+            }
+
+            fn visit_bool<E: de::Error>(self, b: bool) -> Result<Value, E> {
+                Ok(Value::Boolean(b))
+            }
+
+            fn visit_i64<E: de::Error>(self, i: i64) -> Result<Value, E> {
+                Ok(Value::Long(i))
+            }
+
+            fn visit_u64<E: de::Error>(self, u: u64) -> Result<Value, E> {
+                match i64::try_from(u) {
+                    Ok(i) => Ok(Value::Long(i)),
+                    Err(_) => Ok(Value::Double(u as f64)),
+                }
+            }
+
+            fn visit_f64<E: de::Error>(self, d: f64) -> Result<Value, E> {
+                Ok(Value::Double(d))
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Value, E> {
+                Ok(Value::String(s.to_owned()))
+            }
+
+            fn visit_string<E: de::Error>(self, s: String) -> Result<Value, E> {
+                Ok(Value::String(s))
+            }
+
+            fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Value, E> {
+                Ok(Value::Bytes(bytes.to_owned()))
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<Value, E> {
+                Ok(Value::Bytes(bytes))
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+                Ok(Value::Null)
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+                Ok(Value::Null)
+            }
+
+            fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                Value::deserialize(deserializer)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(item) = seq.next_element()? {
+                    items.push(item);
+                }
+                Ok(Value::Array(items))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut items = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some((key, value)) = map.next_entry()? {
+                    items.insert(key, value);
+                }
+                Ok(Value::Map(items))
             }
         }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// Maps every byte to the character with the same code point, as done by the JSON encoding of
+/// `bytes` and `fixed` values.
+pub(crate) fn latin1_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|&byte| char::from(byte)).collect()
+}
+
+/// Type name keying a non-null union branch in the JSON encoding.
+fn union_branch_name(value: &Value) -> &'static str {
+    match *value {
+        Value::Null => "null",
+        Value::Boolean(_) => "boolean",
+        Value::Int(_) | Value::Date(_) | Value::TimeMillis(_) => "int",
+        Value::Long(_)
+        | Value::TimeMicros(_)
+        | Value::TimestampMillis(_)
+        | Value::TimestampMicros(_) => "long",
+        Value::Float(_) => "float",
+        Value::Double(_) => "double",
+        Value::Bytes(_) | Value::Decimal(_) => "bytes",
+        Value::String(_) => "string",
+        #[cfg(feature = "uuid")]
+        Value::Uuid(_) => "string",
+        Value::Fixed(..) | Value::Duration(_) => "fixed",
+        Value::Enum(..) => "enum",
+        Value::Union(ref inner) => union_branch_name(inner),
+        Value::Array(_) => "array",
+        Value::Map(_) => "map",
+        Value::Record(_) => "record",
     }
+}
 
-    fn resolve_int(self) -> Result<Self, Error> {
-        match self {
-            Value::Int(n) => Ok(Value::Int(n)),
-            Value::Long(n) => Ok(Value::Int(n as i32)),
+#[cfg(feature = "chrono")]
+impl From<NaiveDate> for Value {
+    fn from(date: NaiveDate) -> Self {
+        Value::Date(date.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<Value> for NaiveDate {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Date(days) => NaiveDate::from_ymd_opt(1970, 1, 1)
+                .and_then(|epoch| epoch.checked_add_signed(ChronoDuration::days(i64::from(days))))
+                .ok_or_else(|| {
+                    SchemaResolutionError::new(format!("Date out of range: {} days", days)).into()
+                }),
             other => {
-                Err(SchemaResolutionError::new(format!("Int expected, got {:?}", other)).into())
+                Err(SchemaResolutionError::new(format!("Date expected, got {:?}", other)).into())
             }
         }
     }
+}
 
-    fn resolve_long(self) -> Result<Self, Error> {
-        match self {
-            Value::Int(n) => Ok(Value::Long(i64::from(n))),
-            Value::Long(n) => Ok(Value::Long(n)),
+/// Converts a `NaiveTime` into a `Value::TimeMicros`, which represents it without loss of
+/// precision.
+#[cfg(feature = "chrono")]
+impl From<NaiveTime> for Value {
+    fn from(time: NaiveTime) -> Self {
+        let micros = i64::from(time.num_seconds_from_midnight()) * 1_000_000
+            + i64::from(time.nanosecond() / 1_000);
+        Value::TimeMicros(micros)
+    }
+}
+
+/// Converts a `Value::TimeMillis` or `Value::TimeMicros` into a `NaiveTime`.
+#[cfg(feature = "chrono")]
+impl TryFrom<Value> for NaiveTime {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let micros = match value {
+            Value::TimeMillis(millis) => i64::from(millis) * 1_000,
+            Value::TimeMicros(micros) => micros,
             other => {
-                Err(SchemaResolutionError::new(format!("Long expected, got {:?}", other)).into())
+                return Err(SchemaResolutionError::new(format!(
+                    "TimeMillis or TimeMicros expected, got {:?}",
+                    other
+                ))
+                .into());
             }
+        };
+
+        // Checked before casting the seconds to `u32`, which would wrap around.
+        if !(0..MICROS_PER_DAY).contains(&micros) {
+            return Err(SchemaResolutionError::new(format!(
+                "Time out of range: {} microseconds",
+                micros
+            ))
+            .into());
         }
+
+        NaiveTime::from_num_seconds_from_midnight_opt(
+            (micros / 1_000_000) as u32,
+            (micros % 1_000_000) as u32 * 1_000,
+        )
+        .ok_or_else(|| {
+            SchemaResolutionError::new(format!("Time out of range: {} microseconds", micros)).into()
+        })
     }
+}
 
-    fn resolve_float(self) -> Result<Self, Error> {
-        match self {
-            Value::Int(n) => Ok(Value::Float(n as f32)),
-            Value::Long(n) => Ok(Value::Float(n as f32)),
-            Value::Float(x) => Ok(Value::Float(x)),
-            Value::Double(x) => Ok(Value::Float(x as f32)),
+/// Converts a `DateTime<Utc>` into a `Value::TimestampMicros`, which represents it without loss
+/// of precision.
+#[cfg(feature = "chrono")]
+impl From<DateTime<Utc>> for Value {
+    fn from(datetime: DateTime<Utc>) -> Self {
+        Value::TimestampMicros(
+            datetime.timestamp() * 1_000_000 + i64::from(datetime.timestamp_subsec_micros()),
+        )
+    }
+}
+
+/// Converts a `Value::TimestampMillis` or `Value::TimestampMicros` into a `DateTime<Utc>`.
+#[cfg(feature = "chrono")]
+impl TryFrom<Value> for DateTime<Utc> {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let micros = match value {
+            Value::TimestampMillis(millis) => millis.checked_mul(1_000),
+            Value::TimestampMicros(micros) => Some(micros),
             other => {
-                Err(SchemaResolutionError::new(format!("Float expected, got {:?}", other)).into())
+                return Err(SchemaResolutionError::new(format!(
+                    "TimestampMillis or TimestampMicros expected, got {:?}",
+                    other
+                ))
+                .into());
             }
-        }
+        };
+
+        micros
+            .and_then(|micros| {
+                let secs = micros.div_euclid(1_000_000);
+                let nanos = micros.rem_euclid(1_000_000) as u32 * 1_000;
+                Utc.timestamp_opt(secs, nanos).single()
+            })
+            .ok_or_else(|| SchemaResolutionError::new("Timestamp out of range").into())
     }
+}
 
-    fn resolve_double(self) -> Result<Self, Error> {
-        match self {
-            Value::Int(n) => Ok(Value::Double(f64::from(n))),
-            Value::Long(n) => Ok(Value::Double(n as f64)),
-            Value::Float(x) => Ok(Value::Double(f64::from(x))),
-            Value::Double(x) => Ok(Value::Double(x)),
+#[cfg(feature = "uuid")]
+impl From<Uuid> for Value {
+    fn from(uuid: Uuid) -> Self {
+        Value::Uuid(uuid)
+    }
+}
+
+/// Converts a `Value::Uuid`, or a `Value::String` holding a UUID, into a `Uuid`.
+#[cfg(feature = "uuid")]
+impl TryFrom<Value> for Uuid {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Uuid(uuid) => Ok(uuid),
+            Value::String(s) => parse_uuid(&s),
             other => {
-                Err(SchemaResolutionError::new(format!("Double expected, got {:?}", other)).into())
+                Err(SchemaResolutionError::new(format!("Uuid expected, got {:?}", other)).into())
             }
         }
     }
+}
 
-    fn resolve_bytes(self) -> Result<Self, Error> {
-        match self {
-            Value::Bytes(bytes) => Ok(Value::Bytes(bytes)),
-            Value::String(s) => Ok(Value::Bytes(s.into_bytes())),
-            Value::Array(items) => Ok(Value::Bytes(
-                items
-                    .into_iter()
-                    .map(Value::try_u8)
-                    .collect::<Result<Vec<_>, _>>()?,
-            )),
-            other => {
-                Err(SchemaResolutionError::new(format!("Bytes expected, got {:?}", other)).into())
-            }
+#[cfg(feature = "uuid")]
+fn parse_uuid(s: &str) -> Result<Uuid, Error> {
+    Uuid::parse_str(s)
+        .map_err(|err| SchemaResolutionError::new(format!("invalid UUID {:?}: {}", s, err)).into())
+}
+
+/// Reads the string representation of a `uuid` value, as a `Value::Uuid` if the `uuid` feature
+/// is enabled, or else as a `Value::String`.
+#[cfg(feature = "uuid")]
+pub(crate) fn uuid_value(s: String) -> Result<Value, Error> {
+    parse_uuid(&s).map(Value::Uuid)
+}
+
+/// Reads the string representation of a `uuid` value, as a `Value::Uuid` if the `uuid` feature
+/// is enabled, or else as a `Value::String`.
+#[cfg(not(feature = "uuid"))]
+pub(crate) fn uuid_value(s: String) -> Result<Value, Error> {
+    Ok(Value::String(s))
+}
+
+impl Value {
+    /// Returns the boolean of a `Value::Boolean`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Boolean(b) => Some(b),
+            _ => None,
         }
     }
 
-    fn resolve_string(self) -> Result<Self, Error> {
-        match self {
-            Value::String(s) => Ok(Value::String(s)),
-            Value::Bytes(bytes) => Ok(Value::String(String::from_utf8(bytes)?)),
-            other => {
-                Err(SchemaResolutionError::new(format!("String expected, got {:?}", other)).into())
-            }
+    /// Returns the integer of a `Value::Int`, or of a `date` or `time-millis` value.
+    pub fn as_i32(&self) -> Option<i32> {
+        match *self {
+            Value::Int(i) | Value::Date(i) | Value::TimeMillis(i) => Some(i),
+            _ => None,
         }
     }
 
-    fn resolve_fixed(self, size: usize) -> Result<Self, Error> {
-        match self {
-            Value::Fixed(n, bytes) => {
-                if n == size {
-                    Ok(Value::Fixed(n, bytes))
-                } else {
-                    Err(SchemaResolutionError::new(format!(
-                        "Fixed size mismatch, {} expected, got {}",
-                        size, n
-                    ))
-                    .into())
-                }
-            }
-            other => {
-                Err(SchemaResolutionError::new(format!("String expected, got {:?}", other)).into())
-            }
+    /// Returns the integer of a `Value::Long`, or of a `time-micros` or timestamp value.
+    ///
+    /// Values holding an `i32` are widened.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Long(i)
+            | Value::TimeMicros(i)
+            | Value::TimestampMillis(i)
+            | Value::TimestampMicros(i) => Some(i),
+            _ => self.as_i32().map(i64::from),
         }
     }
 
-    fn resolve_enum(self, symbols: &[String]) -> Result<Self, Error> {
-        let validate_symbol = |symbol: String, symbols: &[String]| {
-            if let Some(index) = symbols.iter().position(|ref item| item == &&symbol) {
-                Ok(Value::Enum(index as i32, symbol))
-            } else {
-                Err(SchemaResolutionError::new(format!(
-                    "Enum default {} is not among allowed symbols {:?}",
-                    symbol, symbols,
-                ))
-                .into())
-            }
-        };
+    /// Returns the number of a `Value::Float`.
+    pub fn as_f32(&self) -> Option<f32> {
+        match *self {
+            Value::Float(x) => Some(x),
+            _ => None,
+        }
+    }
 
-        match self {
-            Value::Enum(i, s) => {
-                if i >= 0 && i < symbols.len() as i32 {
-                    validate_symbol(s, symbols)
-                } else {
-                    Err(SchemaResolutionError::new(format!(
-                        "Enum value {} is out of bound {}",
-                        i,
-                        symbols.len() as i32
-                    ))
-                    .into())
-                }
-            }
-            Value::String(s) => validate_symbol(s, symbols),
-            other => Err(SchemaResolutionError::new(format!(
-                "Enum({:?}) expected, got {:?}",
-                symbols, other
-            ))
-            .into()),
+    /// Returns the number of a `Value::Double`, or of a widened `Value::Float`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Double(x) => Some(x),
+            Value::Float(x) => Some(f64::from(x)),
+            _ => None,
         }
     }
 
-    fn resolve_union(self, schema: &UnionSchema) -> Result<Self, Error> {
-        let v = match self {
-            // Both are unions case.
-            Value::Union(v) => *v,
-            // Reader is a union, but writer is not.
-            v => v,
-        };
-        // Find the first match in the reader schema.
-        let (_, inner) = schema
-            .find_schema(&v)
-            .ok_or_else(|| SchemaResolutionError::new("Could not find matching type in union"))?;
-        v.resolve(inner)
+    /// Returns the string of a `Value::String`.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref s) => Some(s),
+            _ => None,
+        }
     }
 
-    fn resolve_array(self, schema: &Schema) -> Result<Self, Error> {
-        match self {
-            Value::Array(items) => Ok(Value::Array(
-                items
-                    .into_iter()
-                    .map(|item| item.resolve(schema))
-                    .collect::<Result<Vec<_>, _>>()?,
-            )),
-            other => Err(SchemaResolutionError::new(format!(
-                "Array({:?}) expected, got {:?}",
-                schema, other
-            ))
-            .into()),
+    /// Returns the bytes of a `Value::Bytes` or a `Value::Fixed`.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match *self {
+            Value::Bytes(ref bytes) | Value::Fixed(_, ref bytes) => Some(bytes),
+            _ => None,
         }
     }
 
-    fn resolve_map(self, schema: &Schema) -> Result<Self, Error> {
-        match self {
-            Value::Map(items) => Ok(Value::Map(
-                items
-                    .into_iter()
-                    .map(|(key, value)| value.resolve(schema).map(|value| (key, value)))
-                    .collect::<Result<HashMap<_, _>, _>>()?,
-            )),
-            other => Err(SchemaResolutionError::new(format!(
-                "Map({:?}) expected, got {:?}",
-                schema, other
-            ))
-            .into()),
+    /// Returns the symbol of a `Value::Enum`.
+    pub fn enum_symbol(&self) -> Option<&str> {
+        match *self {
+            Value::Enum(_, ref symbol) => Some(symbol),
+            _ => None,
+        }
+    }
+
+    /// Returns `Some(())` if the value is `Value::Null`.
+    pub fn as_null(&self) -> Option<()> {
+        match *self {
+            Value::Null => Some(()),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the value is `Value::Null`.
+    pub fn is_null(&self) -> bool {
+        self.as_null().is_some()
+    }
+
+    /// Returns the value held by a `Value::Union`.
+    pub fn unwrap_union(&self) -> Option<&Value> {
+        match *self {
+            Value::Union(ref inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the value is a `Value::Union` holding `Value::Null`.
+    pub fn is_null_union(&self) -> bool {
+        match *self {
+            Value::Union(ref inner) => inner.is_null(),
+            _ => false,
+        }
+    }
+
+    /// Returns the value held by a `Value::Union`, along with the position of its variant
+    /// within `schema`.
+    ///
+    /// As a `Value::Union` does not record the variant it was built for, the variant is looked up
+    /// in `schema` from the held value.
+    pub fn strip_union_wrapper(&self, schema: &UnionSchema) -> Option<(usize, &Value)> {
+        let inner = self.unwrap_union()?;
+        schema
+            .find_variant_for_value(inner)
+            .map(|index| (index, inner))
+    }
+
+    /// Returns the position within `schema` of the variant of a `Value::Union`.
+    pub fn union_index(&self, schema: &UnionSchema) -> Option<usize> {
+        self.strip_union_wrapper(schema).map(|(index, _)| index)
+    }
+
+    /// Walk the tree of this value along with its `schema` with `visitor`, as
+    /// [`walk`](fn.walk.html) does.
+    pub fn walk<V: ValueVisitor>(&self, schema: &Schema, visitor: &mut V) {
+        walk(self, schema, visitor)
+    }
+
+    /// Serializes the value to a JSON string, without any schema.
+    ///
+    /// The value is written as by its `Serialize` implementation: see there for the limits of
+    /// this schema-less representation. Use
+    /// [`value_to_avro_json`](../fn.value_to_avro_json.html) to follow a schema instead.
+    pub fn to_json_string(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Parses a JSON string into a value, without any schema.
+    ///
+    /// The value is read as by its `Deserialize` implementation, e.g. JSON objects are read as
+    /// `Value::Map` and integers as `Value::Long`. Use
+    /// [`value_from_avro_json`](../fn.value_from_avro_json.html) to follow a schema instead.
+    pub fn from_json_str(s: &str) -> Result<Value, Error> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    /// Returns the items of a `Value::Array`.
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match *self {
+            Value::Array(ref items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries of a `Value::Map`.
+    pub fn as_map(&self) -> Option<&HashMap<String, Value>> {
+        match *self {
+            Value::Map(ref items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Returns the fields of a `Value::Record`, in order.
+    pub fn as_record(&self) -> Option<&[(String, Value)]> {
+        match *self {
+            Value::Record(ref fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// Returns the name and value of the field at position `index` of a `Value::Record`, or
+    /// `None` if it has fewer fields.
+    pub fn field_by_index(&self, index: usize) -> Option<(&str, &Value)> {
+        self.as_record()?
+            .get(index)
+            .map(|&(ref name, ref value)| (name.as_str(), value))
+    }
+
+    /// Returns the value of the first field named `name` of a `Value::Record`.
+    ///
+    /// Fields are scanned in order: use a [`GenericRecord`](struct.GenericRecord.html) to look
+    /// up many fields by name.
+    pub fn field_by_name(&self, name: &str) -> Option<&Value> {
+        self.as_record()?
+            .iter()
+            .find(|&&(ref field, _)| field == name)
+            .map(|&(_, ref value)| value)
+    }
+
+    /// Replace the value of the field at position `index` of a `Value::Record`.
+    ///
+    /// Fails if the value is not a record, or has fewer fields.
+    pub fn set_field_by_index(&mut self, index: usize, value: Value) -> Result<(), Error> {
+        match *self {
+            Value::Record(ref mut fields) => {
+                let count = fields.len();
+                match fields.get_mut(index) {
+                    Some(&mut (_, ref mut current)) => {
+                        *current = value;
+                        Ok(())
+                    }
+                    None => Err(SchemaResolutionError::new(format!(
+                        "No field at position {} of a record of {} fields",
+                        index, count
+                    ))
+                    .into()),
+                }
+            }
+            ref other => Err(not_a_record(other)),
+        }
+    }
+
+    /// Returns the value nested at `path`, such as `user.addresses[0].street`.
+    ///
+    /// Dot-separated segments are looked up as record fields or map keys, and `[n]` suffixes as
+    /// array items. Unions along the way are looked through. `None` is returned if any segment
+    /// is missing or `path` is malformed; an empty `path` designates the value itself.
+    ///
+    /// ```
+    /// use avro_rs::types::Value;
+    ///
+    /// let value = Value::Record(vec![(
+    ///     "tags".to_string(),
+    ///     Value::Array(vec![Value::String("foo".to_string())]),
+    /// )]);
+    /// assert_eq!(value.get_by_path("tags[0]").and_then(Value::as_str), Some("foo"));
+    /// assert_eq!(value.get_by_path("tags[1]"), None);
+    /// ```
+    pub fn get_by_path(&self, path: &str) -> Option<&Value> {
+        let mut value = self;
+        for segment in parse_value_path(path)? {
+            value = value.get_segment(&segment)?;
+        }
+        Some(value)
+    }
+
+    /// Returns a mutable reference to the value nested at `path`.
+    ///
+    /// See [`get_by_path`](#method.get_by_path) for the syntax of `path`.
+    pub fn get_by_path_mut(&mut self, path: &str) -> Option<&mut Value> {
+        let mut value = self;
+        for segment in parse_value_path(path)? {
+            value = value.get_segment_mut(&segment)?;
+        }
+        Some(value)
+    }
+
+    fn get_segment(&self, segment: &PathSegment) -> Option<&Value> {
+        match (self, segment) {
+            (&Value::Union(ref inner), _) => inner.get_segment(segment),
+            (&Value::Record(ref fields), &PathSegment::Field(ref name)) => fields
+                .iter()
+                .find(|&&(ref field, _)| field == name)
+                .map(|&(_, ref value)| value),
+            (&Value::Map(ref items), &PathSegment::Field(ref key)) => items.get(key),
+            (&Value::Array(ref items), &PathSegment::Index(index)) => items.get(index),
+            _ => None,
+        }
+    }
+
+    fn get_segment_mut(&mut self, segment: &PathSegment) -> Option<&mut Value> {
+        match (self, segment) {
+            (&mut Value::Union(ref mut inner), _) => inner.get_segment_mut(segment),
+            (&mut Value::Record(ref mut fields), &PathSegment::Field(ref name)) => fields
+                .iter_mut()
+                .find(|&&mut (ref field, _)| field == name)
+                .map(|&mut (_, ref mut value)| value),
+            (&mut Value::Map(ref mut items), &PathSegment::Field(ref key)) => items.get_mut(key),
+            (&mut Value::Array(ref mut items), &PathSegment::Index(index)) => items.get_mut(index),
+            _ => None,
+        }
+    }
+
+    /// Validate the value against the given [Schema](../schema/enum.Schema.html).
+    ///
+    /// See the [Avro specification](https://avro.apache.org/docs/current/spec.html)
+    /// for the full set of rules of schema validation.
+    pub fn validate(&self, schema: &Schema) -> bool {
+        self.validate_internal(schema, &mut Definitions::new(schema))
+    }
+
+    pub(crate) fn validate_internal<'s>(
+        &self,
+        schema: &'s Schema,
+        definitions: &mut Definitions<'s>,
+    ) -> bool {
+        match (self, schema) {
+            (_, &Schema::Ref { ref name }) => match resolve_ref(name, definitions) {
+                Ok(definition) => self.validate_internal(definition, definitions),
+                Err(_) => false,
+            },
+            (&Value::Null, &Schema::Null) => true,
+            (&Value::Boolean(_), &Schema::Boolean) => true,
+            (&Value::Int(_), &Schema::Int) => true,
+            (&Value::Long(_), &Schema::Long) => true,
+            (&Value::Float(_), &Schema::Float) => true,
+            (&Value::Double(_), &Schema::Double) => true,
+            (&Value::Bytes(_), &Schema::Bytes) => true,
+            (&Value::String(_), &Schema::String) => true,
+            (&Value::Fixed(n, ref bytes), &Schema::Fixed { size, .. }) => {
+                n == size && bytes.len() == n
+            }
+            (&Value::Date(_), &Schema::Date) => true,
+            (&Value::Int(_), &Schema::Date) => true,
+            (&Value::TimeMillis(millis), &Schema::TimeMillis)
+            | (&Value::Int(millis), &Schema::TimeMillis) => {
+                (0..MICROS_PER_DAY / 1_000).contains(&i64::from(millis))
+            }
+            (&Value::TimeMicros(micros), &Schema::TimeMicros)
+            | (&Value::Long(micros), &Schema::TimeMicros) => (0..MICROS_PER_DAY).contains(&micros),
+            (&Value::TimestampMillis(_), &Schema::TimestampMillis) => true,
+            (&Value::Long(_), &Schema::TimestampMillis) => true,
+            (&Value::TimestampMicros(_), &Schema::TimestampMicros) => true,
+            (&Value::Long(_), &Schema::TimestampMicros) => true,
+            (&Value::Duration(_), &Schema::Duration { .. }) => true,
+            (&Value::Fixed(n, ref bytes), &Schema::Duration { .. }) => n == 12 && bytes.len() == n,
+            #[cfg(feature = "uuid")]
+            (&Value::Uuid(_), &Schema::Uuid) => true,
+            #[cfg(feature = "uuid")]
+            (&Value::String(ref s), &Schema::Uuid) => parse_uuid(s).is_ok(),
+            #[cfg(not(feature = "uuid"))]
+            (&Value::String(_), &Schema::Uuid) => true,
+            (
+                &Value::Decimal(ref decimal),
+                &Schema::Decimal {
+                    precision,
+                    ref inner,
+                    ..
+                },
+            ) => {
+                decimal.num_digits() <= precision
+                    && match **inner {
+                        Schema::Fixed { size, .. } => decimal.to_sign_extended_vec(size).is_some(),
+                        _ => true,
+                    }
+            }
+            // Bytes are written with a length, which a decimal backed by a fixed does not have.
+            (&Value::Bytes(ref bytes), &Schema::Decimal { ref inner, .. }) => match **inner {
+                Schema::Fixed { .. } => false,
+                _ => Value::Decimal(Decimal::from(&bytes[..])).validate(schema),
+            },
+            (&Value::Fixed(n, ref bytes), &Schema::Decimal { ref inner, .. }) => {
+                let fits = match **inner {
+                    Schema::Fixed { size, .. } => n == size && bytes.len() == n,
+                    _ => false,
+                };
+                fits && Value::Decimal(Decimal::from(&bytes[..])).validate(schema)
+            }
+            (&Value::String(ref s), &Schema::Enum { ref symbols, .. }) => symbols.contains(s),
+            (&Value::Enum(i, ref s), &Schema::Enum { ref symbols, .. }) => symbols
+                .get(i as usize)
+                .map(|ref symbol| symbol == &s)
+                .unwrap_or(false),
+            // (&Value::Union(None), &Schema::Union(_)) => true,
+            (&Value::Union(ref value), &Schema::Union(ref inner)) => {
+                match inner.find_schema(value) {
+                    Some((_, variant)) => value.validate_internal(variant, definitions),
+                    None => false,
+                }
+            }
+            (&Value::Array(ref items), &Schema::Array(ref inner)) => items
+                .iter()
+                .all(|item| item.validate_internal(inner, definitions)),
+            (&Value::Map(ref items), &Schema::Map(ref inner)) => items
+                .iter()
+                .all(|(_, value)| value.validate_internal(inner, definitions)),
+            (&Value::Record(ref record_fields), &Schema::Record { ref fields, .. }) => {
+                fields.len() == record_fields.len()
+                    && fields.iter().zip(record_fields.iter()).all(
+                        |(field, &(ref name, ref value))| {
+                            field.name == *name
+                                && value.validate_internal(&field.schema, definitions)
+                        },
+                    )
+            }
+            _ => false,
+        }
+    }
+
+    /// Validate the value against the given [Schema](../schema/enum.Schema.html), reporting
+    /// every invalid nested value instead of a single `bool`.
+    ///
+    /// Validation does not stop at the first error: all the items, values and fields of arrays,
+    /// maps and records are checked, and each error carries the
+    /// [`ValuePath`](struct.ValuePath.html) of the offending value.
+    ///
+    /// ```
+    /// use avro_rs::{types::Value, Schema};
+    ///
+    /// let schema = Schema::parse_str(r#"{"type": "array", "items": "int"}"#).unwrap();
+    /// let value = Value::Array(vec![Value::Int(1), Value::Long(2), Value::Null]);
+    ///
+    /// let errors = value.validate_detailed(&schema).unwrap_err();
+    /// assert_eq!(errors.len(), 2);
+    /// assert_eq!(
+    ///     errors[0].to_string(),
+    ///     "Invalid value at $[1]: expected int, got Long(2)"
+    /// );
+    /// ```
+    pub fn validate_detailed(&self, schema: &Schema) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        self.collect_validation_errors(
+            schema,
+            &mut Definitions::new(schema),
+            &mut Vec::new(),
+            &mut errors,
+        );
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Check whether the value conforms to `schema`: an alias of [`validate`](#method.validate),
+    /// which does not modify the value either.
+    pub fn is_compatible_with(&self, schema: &Schema) -> bool {
+        self.validate(schema)
+    }
+
+    /// Check that the value conforms to `schema`, returning the first
+    /// [`ValidationError`](struct.ValidationError.html) reported by
+    /// [`validate_detailed`](#method.validate_detailed) otherwise.
+    ///
+    /// ```
+    /// use avro_rs::{to_avro_datum, types::Value, Schema};
+    /// # use failure::Error;
+    ///
+    /// fn encode(value: Value, schema: &Schema) -> Result<Vec<u8>, Error> {
+    ///     value.assert_compatible_with(schema)?;
+    ///     to_avro_datum(schema, value)
+    /// }
+    ///
+    /// assert!(encode(Value::Long(1), &Schema::Long).is_ok());
+    /// assert!(encode(Value::Null, &Schema::Long).is_err());
+    /// ```
+    pub fn assert_compatible_with(&self, schema: &Schema) -> Result<(), Error> {
+        self.validate_detailed(schema)
+            .map_err(|mut errors| errors.swap_remove(0).into())
+    }
+
+    fn collect_validation_errors<'s>(
+        &self,
+        schema: &'s Schema,
+        definitions: &mut Definitions<'s>,
+        path: &mut Vec<PathSegment>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        match (self, schema) {
+            (_, &Schema::Ref { ref name }) => match resolve_ref(name, definitions) {
+                Ok(definition) => {
+                    self.collect_validation_errors(definition, definitions, path, errors)
+                }
+                Err(_) => errors.push(ValidationError::new(
+                    ValuePath(path.clone()),
+                    describe_schema(schema),
+                    describe_value(self),
+                )),
+            },
+            (&Value::Union(ref value), &Schema::Union(ref inner)) => {
+                match inner.find_schema(value) {
+                    Some((_, schema)) => {
+                        value.collect_validation_errors(schema, definitions, path, errors)
+                    }
+                    None => errors.push(ValidationError::new(
+                        ValuePath(path.clone()),
+                        describe_schema(schema),
+                        describe_value(value),
+                    )),
+                }
+            }
+            (&Value::Array(ref items), &Schema::Array(ref inner)) => {
+                for (index, item) in items.iter().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    item.collect_validation_errors(inner, definitions, path, errors);
+                    path.pop();
+                }
+            }
+            (&Value::Map(ref items), &Schema::Map(ref inner)) => {
+                for (key, value) in items {
+                    path.push(PathSegment::Key(key.clone()));
+                    value.collect_validation_errors(inner, definitions, path, errors);
+                    path.pop();
+                }
+            }
+            (&Value::Record(ref record_fields), &Schema::Record { ref fields, .. }) => {
+                for (position, field) in fields.iter().enumerate() {
+                    path.push(PathSegment::Field(field.name.clone()));
+                    match record_fields.get(position) {
+                        Some(&(ref name, ref value)) if *name == field.name => value
+                            .collect_validation_errors(&field.schema, definitions, path, errors),
+                        Some(&(ref name, _)) => errors.push(ValidationError::new(
+                            ValuePath(path.clone()),
+                            format!("field {}", field.name),
+                            format!("field {}", name),
+                        )),
+                        None => errors.push(ValidationError::new(
+                            ValuePath(path.clone()),
+                            describe_schema(&field.schema),
+                            "missing field",
+                        )),
+                    }
+                    path.pop();
+                }
+                for &(ref name, _) in record_fields.iter().skip(fields.len()) {
+                    path.push(PathSegment::Field(name.clone()));
+                    errors.push(ValidationError::new(
+                        ValuePath(path.clone()),
+                        "no field",
+                        format!("field {}", name),
+                    ));
+                    path.pop();
+                }
+            }
+            _ => {
+                if !self.validate(schema) {
+                    errors.push(ValidationError::new(
+                        ValuePath(path.clone()),
+                        describe_schema(schema),
+                        describe_value(self),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Compare the value with `other` following the
+    /// [sort order](https://avro.apache.org/docs/current/spec.html#order) of the given
+    /// [Schema](../schema/enum.Schema.html).
+    ///
+    /// Unlike the `Ord` implementation of `Value`, record fields are compared according to their
+    /// `order` attribute, enum values by the position of their symbol in the schema, and union
+    /// values by the position of their branch in the schema first.
+    pub fn cmp_with_schema(&self, other: &Value, schema: &Schema) -> Ordering {
+        self.cmp_internal(other, schema, &mut Definitions::new(schema))
+    }
+
+    fn cmp_internal<'s>(
+        &self,
+        other: &Value,
+        schema: &'s Schema,
+        definitions: &mut Definitions<'s>,
+    ) -> Ordering {
+        match (self, other, schema) {
+            (_, _, &Schema::Ref { ref name }) => match resolve_ref(name, definitions) {
+                Ok(schema) => self.cmp_internal(other, schema, definitions),
+                Err(_) => self.cmp(other),
+            },
+            (&Value::Union(ref a), &Value::Union(ref b), &Schema::Union(ref inner)) => {
+                match (inner.find_schema(a), inner.find_schema(b)) {
+                    (Some((i, variant)), Some((j, _))) if i == j => {
+                        a.cmp_internal(b, variant, definitions)
+                    }
+                    (Some((i, _)), Some((j, _))) => i.cmp(&j),
+                    _ => self.cmp(other),
+                }
+            }
+            (_, _, &Schema::Enum { .. }) => {
+                match (self.enum_position(schema), other.enum_position(schema)) {
+                    (Some(i), Some(j)) => i.cmp(&j),
+                    _ => self.cmp(other),
+                }
+            }
+            (&Value::Array(ref a), &Value::Array(ref b), &Schema::Array(ref inner)) => a
+                .iter()
+                .zip(b.iter())
+                .map(|(a, b)| a.cmp_internal(b, inner, definitions))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            (&Value::Record(ref a), &Value::Record(ref b), &Schema::Record { ref fields, .. }) => {
+                let mut ordering = Ordering::Equal;
+                for (field, (&(_, ref a), &(_, ref b))) in fields.iter().zip(a.iter().zip(b)) {
+                    ordering = match field.order {
+                        RecordFieldOrder::Ascending => {
+                            a.cmp_internal(b, &field.schema, definitions)
+                        }
+                        RecordFieldOrder::Descending => {
+                            a.cmp_internal(b, &field.schema, definitions).reverse()
+                        }
+                        RecordFieldOrder::Ignore => Ordering::Equal,
+                    };
+                    if ordering != Ordering::Equal {
+                        break;
+                    }
+                }
+                ordering
+            }
+            _ => self.cmp(other),
+        }
+    }
+
+    /// Check whether the value is equal to `other` as values of the given
+    /// [Schema](../schema/enum.Schema.html), e.g. to compare the result of schema resolution with
+    /// an expected value.
+    ///
+    /// Unlike the `PartialEq` implementation of `Value`, record fields are compared by name
+    /// rather than by position, and a value held by a `Value::Union` is equal to the same value
+    /// outside of any `Value::Union` if `schema` is a union.
+    ///
+    /// ```
+    /// use avro_rs::{types::Value, Schema};
+    ///
+    /// let schema = Schema::parse_str(
+    ///     r#"{"type": "record", "name": "test", "fields": [
+    ///         {"name": "a", "type": "long"},
+    ///         {"name": "b", "type": "string"}
+    ///     ]}"#,
+    /// )
+    /// .unwrap();
+    /// let a = Value::Record(vec![
+    ///     ("a".to_string(), Value::Long(1)),
+    ///     ("b".to_string(), Value::String("foo".to_string())),
+    /// ]);
+    /// let b = Value::Record(vec![
+    ///     ("b".to_string(), Value::String("foo".to_string())),
+    ///     ("a".to_string(), Value::Long(1)),
+    /// ]);
+    /// assert!(a != b);
+    /// assert!(a.semantic_eq(&b, &schema));
+    /// ```
+    pub fn semantic_eq(&self, other: &Value, schema: &Schema) -> bool {
+        self.semantic_eq_internal(other, schema, &mut Definitions::new(schema))
+    }
+
+    fn semantic_eq_internal<'s>(
+        &self,
+        other: &Value,
+        schema: &'s Schema,
+        definitions: &mut Definitions<'s>,
+    ) -> bool {
+        match (self, other, schema) {
+            (_, _, &Schema::Ref { ref name }) => match resolve_ref(name, definitions) {
+                Ok(schema) => self.semantic_eq_internal(other, schema, definitions),
+                Err(_) => self == other,
+            },
+            (&Value::Union(ref a), b, &Schema::Union(ref inner))
+            | (b, &Value::Union(ref a), &Schema::Union(ref inner)) => {
+                let b = match *b {
+                    Value::Union(ref b) => b,
+                    ref b => b,
+                };
+                match inner.find_schema(a) {
+                    Some((_, variant)) => a.semantic_eq_internal(b, variant, definitions),
+                    None => **a == *b,
+                }
+            }
+            (&Value::Array(ref a), &Value::Array(ref b), &Schema::Array(ref inner)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b)
+                        .all(|(a, b)| a.semantic_eq_internal(b, inner, definitions))
+            }
+            (&Value::Map(ref a), &Value::Map(ref b), &Schema::Map(ref inner)) => {
+                a.len() == b.len()
+                    && a.iter().all(|(key, a)| match b.get(key) {
+                        Some(b) => a.semantic_eq_internal(b, inner, definitions),
+                        None => false,
+                    })
+            }
+            (
+                &Value::Record(ref a),
+                &Value::Record(ref b),
+                &Schema::Record {
+                    ref fields,
+                    ref lookup,
+                    ..
+                },
+            ) => {
+                if a.len() != b.len() {
+                    return false;
+                }
+                a.iter().all(|&(ref name, ref a)| {
+                    match b.iter().find(|&&(ref field, _)| field == name) {
+                        Some(&(_, ref b)) => match lookup.get(name) {
+                            Some(&position) => {
+                                a.semantic_eq_internal(b, &fields[position].schema, definitions)
+                            }
+                            None => a == b,
+                        },
+                        None => false,
+                    }
+                })
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Position of the symbol of an enum value, or of a string, among the symbols of the enum
+    /// `schema`.
+    fn enum_position(&self, schema: &Schema) -> Option<usize> {
+        match *self {
+            Value::Enum(_, ref symbol) | Value::String(ref symbol) => schema.symbol_index(symbol),
+            _ => None,
+        }
+    }
+
+    /// Position of the variant in the declaration of `Value`, used to order values of different
+    /// types.
+    fn variant_rank(&self) -> u8 {
+        match *self {
+            Value::Null => 0,
+            Value::Boolean(_) => 1,
+            Value::Int(_) => 2,
+            Value::Long(_) => 3,
+            Value::Float(_) => 4,
+            Value::Double(_) => 5,
+            Value::Bytes(_) => 6,
+            Value::String(_) => 7,
+            Value::Fixed(..) => 8,
+            Value::Enum(..) => 9,
+            Value::Union(_) => 10,
+            Value::Array(_) => 11,
+            Value::Map(_) => 12,
+            Value::Record(_) => 13,
+            Value::Decimal(_) => 14,
+            Value::Date(_) => 15,
+            Value::TimeMillis(_) => 16,
+            Value::TimeMicros(_) => 17,
+            Value::TimestampMillis(_) => 18,
+            Value::TimestampMicros(_) => 19,
+            Value::Duration(_) => 20,
+            #[cfg(feature = "uuid")]
+            Value::Uuid(_) => 21,
+        }
+    }
+
+    /// Attempt to perform schema resolution on the value, with the given
+    /// [Schema](../schema/enum.Schema.html).
+    ///
+    /// The value is expected to have been read with a writer schema, and `schema` is the reader
+    /// schema it has to be converted to. The following transformations are applied:
+    ///
+    /// * numeric promotions: `int` to `long`, `float` or `double`, `long` to `float` or `double`,
+    ///   and `float` to `double`;
+    /// * `string` and `bytes` are interchangeable;
+    /// * a value is resolved against the reader union branch of the same type, or else the first
+    ///   branch it can be promoted to, and a union value read with a non-union reader schema is
+    ///   unwrapped;
+    /// * record fields are matched by name: fields missing from the reader schema are dropped, and
+    ///   fields missing from the value take the default of the reader schema;
+    /// * enum symbols are looked up by name in the reader symbols.
+    ///
+    /// Values do not carry the names of the types they were written with, so the names and
+    /// aliases of named types are not compared: use
+    /// [`check_compatibility`](../fn.check_compatibility.html) to check them.
+    ///
+    /// See [Schema Resolution](https://avro.apache.org/docs/current/spec.html#Schema+Resolution)
+    /// in the Avro specification for the full set of rules of schema
+    /// resolution.
+    ///
+    /// ```
+    /// use avro_rs::{types::Value, Schema};
+    ///
+    /// let schema = Schema::parse_str(r#"{"type": "array", "items": "double"}"#).unwrap();
+    /// assert_eq!(
+    ///     Value::Array(vec![Value::Int(42)]).resolve(&schema).unwrap(),
+    ///     Value::Array(vec![Value::Double(42.0)])
+    /// );
+    /// ```
+    pub fn resolve(self, schema: &Schema) -> Result<Self, Error> {
+        self.resolve_internal(schema, &mut Definitions::new(schema))
+    }
+
+    /// Convert a copy of the value to the given [Schema](../schema/enum.Schema.html), on a
+    /// best-effort basis.
+    ///
+    /// This applies the conversions of [`resolve`](#method.resolve), e.g. an `int` into a
+    /// `long`, a `long` into an `int` if it fits, a `string` into `bytes` and back, or a number
+    /// into a `float` or `double`. Values of incompatible types, such as a `boolean` for an
+    /// `int`, are an error.
+    pub fn coerce(&self, schema: &Schema) -> Result<Self, Error> {
+        self.clone().resolve(schema)
+    }
+
+    /// Build the zero value of `schema`, e.g. to fill in data that is not available.
+    ///
+    /// Numbers are 0, and strings, bytes, arrays and maps are empty. Fixed values are filled
+    /// with zeros, enums take their first symbol and unions the zero value of their first
+    /// variant that has one. Record fields take their default value, or else the zero value of
+    /// their schema.
+    ///
+    /// Fails for enums without symbols, invalid field defaults, and recursive records that would
+    /// hold themselves forever, such as a record with a non-nullable field of its own type.
+    ///
+    /// ```
+    /// use avro_rs::{types::Value, Schema};
+    ///
+    /// let schema = Schema::parse_str(
+    ///     r#"{"type": "record", "name": "Node", "fields": [
+    ///         {"name": "id", "type": "long", "default": 1},
+    ///         {"name": "label", "type": "string"},
+    ///         {"name": "next", "type": ["null", "Node"]}
+    ///     ]}"#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     Value::default_for_schema(&schema).unwrap(),
+    ///     Value::Record(vec![
+    ///         ("id".to_string(), Value::Long(1)),
+    ///         ("label".to_string(), Value::String(String::new())),
+    ///         ("next".to_string(), Value::Union(Box::new(Value::Null))),
+    ///     ])
+    /// );
+    /// ```
+    pub fn default_for_schema(schema: &Schema) -> Result<Value, Error> {
+        Ok(match *schema {
+            Schema::Null => Value::Null,
+            Schema::Boolean => Value::Boolean(false),
+            Schema::Int => Value::Int(0),
+            Schema::Long => Value::Long(0),
+            Schema::Float => Value::Float(0.0),
+            Schema::Double => Value::Double(0.0),
+            Schema::Bytes => Value::Bytes(Vec::new()),
+            Schema::String => Value::String(String::new()),
+            Schema::Array(_) => Value::Array(Vec::new()),
+            Schema::Map(_) => Value::Map(HashMap::new()),
+            Schema::Union(ref inner) => {
+                let mut error = None;
+                for variant in inner.variants() {
+                    match Value::default_for_schema(variant) {
+                        Ok(value) => return Ok(Value::Union(Box::new(value))),
+                        Err(err) => error = Some(err),
+                    }
+                }
+                return Err(error.unwrap_or_else(|| {
+                    SchemaResolutionError::new("Empty union has no default value").into()
+                }));
+            }
+            Schema::Record { ref fields, .. } => Value::Record(
+                fields
+                    .iter()
+                    .map(|field| {
+                        let value = match field.default_as_value()? {
+                            Some(value) => value,
+                            None => Value::default_for_schema(&field.schema)?,
+                        };
+                        Ok((field.name.clone(), value))
+                    })
+                    .collect::<Result<_, Error>>()?,
+            ),
+            Schema::Enum {
+                ref name,
+                ref symbols,
+                ..
+            } => match symbols.first() {
+                Some(symbol) => Value::Enum(0, symbol.clone()),
+                None => {
+                    return Err(SchemaResolutionError::new(format!(
+                        "Enum {} has no symbols",
+                        name.full_name()
+                    ))
+                    .into())
+                }
+            },
+            Schema::Fixed { size, .. } => Value::Fixed(size, vec![0; size]),
+            Schema::Decimal { .. } => Value::Decimal(Decimal::from(vec![0])),
+            Schema::Date => Value::Date(0),
+            Schema::TimeMillis => Value::TimeMillis(0),
+            Schema::TimeMicros => Value::TimeMicros(0),
+            Schema::TimestampMillis => Value::TimestampMillis(0),
+            Schema::TimestampMicros => Value::TimestampMicros(0),
+            Schema::Duration { .. } => Value::Duration(Duration::new(0, 0, 0)),
+            #[cfg(feature = "uuid")]
+            Schema::Uuid => Value::Uuid(Uuid::nil()),
+            #[cfg(not(feature = "uuid"))]
+            Schema::Uuid => Value::String("00000000-0000-0000-0000-000000000000".to_owned()),
+            // References only point to recursive records, which would then hold themselves.
+            Schema::Ref { ref name } => {
+                return Err(SchemaResolutionError::new(format!(
+                    "Recursive record {} has no finite default value",
+                    name.full_name()
+                ))
+                .into())
+            }
+        })
+    }
+
+    fn resolve_internal<'s>(
+        mut self,
+        schema: &'s Schema,
+        definitions: &mut Definitions<'s>,
+    ) -> Result<Self, Error> {
+        // Check if this schema is a union, and if the reader schema is not.
+        if SchemaKind::from(&self) == SchemaKind::Union
+            && SchemaKind::from(schema) != SchemaKind::Union
+        {
+            // Pull out the Union, and attempt to resolve against it.
+            let v = match self {
+                Value::Union(b) => *b,
+                _ => unreachable!(),
+            };
+            self = v;
+        }
+        match *schema {
+            Schema::Null => self.resolve_null(),
+            Schema::Boolean => self.resolve_boolean(),
+            Schema::Int => self.resolve_int(),
+            Schema::Long => self.resolve_long(),
+            Schema::Float => self.resolve_float(),
+            Schema::Double => self.resolve_double(),
+            Schema::Bytes => self.resolve_bytes(),
+            Schema::String => self.resolve_string(),
+            Schema::Fixed { size, .. } => self.resolve_fixed(size),
+            Schema::Union(ref inner) => self.resolve_union(inner, definitions),
+            Schema::Enum { ref symbols, .. } => self.resolve_enum(symbols),
+            Schema::Array(ref inner) => self.resolve_array(inner, definitions),
+            Schema::Map(ref inner) => self.resolve_map(inner, definitions),
+            Schema::Record { ref fields, .. } => self.resolve_record(fields, definitions),
+            Schema::Ref { ref name } => {
+                let definition = resolve_ref(name, definitions)?;
+                self.resolve_internal(definition, definitions)
+            }
+            Schema::Decimal { .. } => self.resolve_decimal(schema),
+            Schema::Date => self.resolve_date(),
+            Schema::TimeMillis => self.resolve_time_millis(),
+            Schema::TimeMicros => self.resolve_time_micros(),
+            Schema::TimestampMillis => self.resolve_timestamp_millis(),
+            Schema::TimestampMicros => self.resolve_timestamp_micros(),
+            Schema::Duration { .. } => self.resolve_duration(),
+            Schema::Uuid => self.resolve_uuid(),
+        }
+    }
+
+    fn resolve_null(self) -> Result<Self, Error> {
+        match self {
+            Value::Null => Ok(Value::Null),
+            other => {
+                Err(SchemaResolutionError::new(format!("Null expected, got {:?}", other)).into())
+            }
+        }
+    }
+
+    fn resolve_boolean(self) -> Result<Self, Error> {
+        match self {
+            Value::Boolean(b) => Ok(Value::Boolean(b)),
+            other => {
+                Err(SchemaResolutionError::new(format!("Boolean expected, got {:?}", other)).into())
+            }
+        }
+    }
+
+    fn resolve_int(self) -> Result<Self, Error> {
+        match self {
+            Value::Int(n) => Ok(Value::Int(n)),
+            // Only needed for JSON defaults, which are always read as longs.
+            Value::Long(n) if n >= i64::from(i32::MIN) && n <= i64::from(i32::MAX) => {
+                Ok(Value::Int(n as i32))
+            }
+            other => {
+                Err(SchemaResolutionError::new(format!("Int expected, got {:?}", other)).into())
+            }
+        }
+    }
+
+    fn resolve_date(self) -> Result<Self, Error> {
+        match self {
+            Value::Date(n) | Value::Int(n) => Ok(Value::Date(n)),
+            Value::Long(n) => i32::try_from(n).map(Value::Date).map_err(|_| {
+                SchemaResolutionError::new(format!("Date out of range: {} days", n)).into()
+            }),
+            other => {
+                Err(SchemaResolutionError::new(format!("Date expected, got {:?}", other)).into())
+            }
+        }
+    }
+
+    fn resolve_time_millis(self) -> Result<Self, Error> {
+        let millis = match self {
+            Value::TimeMillis(n) | Value::Int(n) => i64::from(n),
+            Value::Long(n) => n,
+            other => {
+                return Err(SchemaResolutionError::new(format!(
+                    "TimeMillis expected, got {:?}",
+                    other
+                ))
+                .into())
+            }
+        };
+        if !(0..MICROS_PER_DAY / 1_000).contains(&millis) {
+            return Err(SchemaResolutionError::new(format!(
+                "Time out of range: {} milliseconds",
+                millis
+            ))
+            .into());
+        }
+        Ok(Value::TimeMillis(millis as i32))
+    }
+
+    fn resolve_time_micros(self) -> Result<Self, Error> {
+        let micros = match self {
+            Value::TimeMicros(n) | Value::Long(n) => n,
+            Value::Int(n) => i64::from(n),
+            other => {
+                return Err(SchemaResolutionError::new(format!(
+                    "TimeMicros expected, got {:?}",
+                    other
+                ))
+                .into())
+            }
+        };
+        if !(0..MICROS_PER_DAY).contains(&micros) {
+            return Err(SchemaResolutionError::new(format!(
+                "Time out of range: {} microseconds",
+                micros
+            ))
+            .into());
+        }
+        Ok(Value::TimeMicros(micros))
+    }
+
+    fn resolve_timestamp_millis(self) -> Result<Self, Error> {
+        match self {
+            Value::TimestampMillis(n) | Value::Long(n) => Ok(Value::TimestampMillis(n)),
+            Value::Int(n) => Ok(Value::TimestampMillis(i64::from(n))),
+            other => Err(SchemaResolutionError::new(format!(
+                "TimestampMillis expected, got {:?}",
+                other
+            ))
+            .into()),
+        }
+    }
+
+    fn resolve_timestamp_micros(self) -> Result<Self, Error> {
+        match self {
+            Value::TimestampMicros(n) | Value::Long(n) => Ok(Value::TimestampMicros(n)),
+            Value::Int(n) => Ok(Value::TimestampMicros(i64::from(n))),
+            other => Err(SchemaResolutionError::new(format!(
+                "TimestampMicros expected, got {:?}",
+                other
+            ))
+            .into()),
+        }
+    }
+
+    fn resolve_duration(self) -> Result<Self, Error> {
+        match self {
+            Value::Duration(duration) => Ok(Value::Duration(duration)),
+            Value::Fixed(12, ref bytes) if bytes.len() == 12 => {
+                let mut buf = [0u8; 12];
+                buf.copy_from_slice(&bytes);
+                Ok(Value::Duration(Duration::from(buf)))
+            }
+            other => Err(
+                SchemaResolutionError::new(format!("Duration expected, got {:?}", other)).into(),
+            ),
+        }
+    }
+
+    fn resolve_long(self) -> Result<Self, Error> {
+        match self {
+            Value::Int(n) => Ok(Value::Long(i64::from(n))),
+            Value::Long(n) => Ok(Value::Long(n)),
+            other => {
+                Err(SchemaResolutionError::new(format!("Long expected, got {:?}", other)).into())
+            }
+        }
+    }
+
+    fn resolve_float(self) -> Result<Self, Error> {
+        match self {
+            Value::Int(n) => Ok(Value::Float(n as f32)),
+            Value::Long(n) => Ok(Value::Float(n as f32)),
+            Value::Float(x) => Ok(Value::Float(x)),
+            Value::Double(x) => Ok(Value::Float(x as f32)),
+            other => {
+                Err(SchemaResolutionError::new(format!("Float expected, got {:?}", other)).into())
+            }
+        }
+    }
+
+    fn resolve_double(self) -> Result<Self, Error> {
+        match self {
+            Value::Int(n) => Ok(Value::Double(f64::from(n))),
+            Value::Long(n) => Ok(Value::Double(n as f64)),
+            Value::Float(x) => Ok(Value::Double(f64::from(x))),
+            Value::Double(x) => Ok(Value::Double(x)),
+            other => {
+                Err(SchemaResolutionError::new(format!("Double expected, got {:?}", other)).into())
+            }
+        }
+    }
+
+    fn resolve_bytes(self) -> Result<Self, Error> {
+        match self {
+            Value::Bytes(bytes) => Ok(Value::Bytes(bytes)),
+            Value::String(s) => Ok(Value::Bytes(s.into_bytes())),
+            Value::Array(items) => Ok(Value::Bytes(
+                items
+                    .into_iter()
+                    .map(Value::try_u8)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            other => {
+                Err(SchemaResolutionError::new(format!("Bytes expected, got {:?}", other)).into())
+            }
+        }
+    }
+
+    fn resolve_string(self) -> Result<Self, Error> {
+        match self {
+            Value::String(s) => Ok(Value::String(s)),
+            Value::Bytes(bytes) => Ok(Value::String(String::from_utf8(bytes)?)),
+            #[cfg(feature = "uuid")]
+            Value::Uuid(uuid) => Ok(Value::String(uuid.to_string())),
+            other => {
+                Err(SchemaResolutionError::new(format!("String expected, got {:?}", other)).into())
+            }
+        }
+    }
+
+    fn resolve_uuid(self) -> Result<Self, Error> {
+        match self {
+            #[cfg(feature = "uuid")]
+            Value::Uuid(uuid) => Ok(Value::Uuid(uuid)),
+            Value::String(s) => uuid_value(s),
+            other => {
+                Err(SchemaResolutionError::new(format!("Uuid expected, got {:?}", other)).into())
+            }
+        }
+    }
+
+    fn resolve_fixed(self, size: usize) -> Result<Self, Error> {
+        match self {
+            Value::Fixed(n, bytes) => {
+                if n != size {
+                    Err(SchemaResolutionError::new(format!(
+                        "Fixed size mismatch, {} expected, got {}",
+                        size, n
+                    ))
+                    .into())
+                } else if bytes.len() != n {
+                    Err(SchemaResolutionError::new(format!(
+                        "Fixed of size {} holds {} bytes",
+                        n,
+                        bytes.len()
+                    ))
+                    .into())
+                } else {
+                    Ok(Value::Fixed(n, bytes))
+                }
+            }
+            other => {
+                Err(SchemaResolutionError::new(format!("String expected, got {:?}", other)).into())
+            }
+        }
+    }
+
+    fn resolve_decimal(self, schema: &Schema) -> Result<Self, Error> {
+        let decimal = match self {
+            Value::Decimal(decimal) => decimal,
+            Value::Bytes(bytes) | Value::Fixed(_, bytes) => Decimal::from(bytes),
+            other => {
+                return Err(SchemaResolutionError::new(format!(
+                    "Decimal expected, got {:?}",
+                    other
+                ))
+                .into());
+            }
+        };
+
+        let value = Value::Decimal(decimal);
+        if value.validate(schema) {
+            Ok(value)
+        } else {
+            Err(SchemaResolutionError::new(format!(
+                "Decimal {:?} does not fit in {:?}",
+                value, schema
+            ))
+            .into())
+        }
+    }
+
+    fn resolve_enum(self, symbols: &[String]) -> Result<Self, Error> {
+        let validate_symbol = |symbol: String, symbols: &[String]| {
+            if let Some(index) = symbols.iter().position(|ref item| item == &&symbol) {
+                Ok(Value::Enum(index as i32, symbol))
+            } else {
+                Err(SchemaResolutionError::new(format!(
+                    "Enum default {} is not among allowed symbols {:?}",
+                    symbol, symbols,
+                ))
+                .into())
+            }
+        };
+
+        match self {
+            Value::Enum(i, s) => {
+                if i >= 0 && i < symbols.len() as i32 {
+                    validate_symbol(s, symbols)
+                } else {
+                    Err(SchemaResolutionError::new(format!(
+                        "Enum value {} is out of bound {}",
+                        i,
+                        symbols.len() as i32
+                    ))
+                    .into())
+                }
+            }
+            Value::String(s) => validate_symbol(s, symbols),
+            other => Err(SchemaResolutionError::new(format!(
+                "Enum({:?}) expected, got {:?}",
+                symbols, other
+            ))
+            .into()),
+        }
+    }
+
+    fn resolve_union<'s>(
+        self,
+        schema: &'s UnionSchema,
+        definitions: &mut Definitions<'s>,
+    ) -> Result<Self, Error> {
+        let v = match self {
+            // Both are unions case.
+            Value::Union(v) => *v,
+            // Reader is a union, but writer is not.
+            v => v,
+        };
+        // Find the first match in the reader schema, or else the first variant the value can be
+        // promoted to.
+        let variant = match schema.find_schema(&v) {
+            Some((_, inner)) => inner,
+            None => schema
+                .variants()
+                .iter()
+                .find(|variant| v.promotes_to(variant))
+                .ok_or_else(|| {
+                    SchemaResolutionError::new(format!(
+                        "Could not find matching type in union for {:?}",
+                        v
+                    ))
+                })?,
+        };
+        Ok(Value::Union(Box::new(
+            v.resolve_internal(variant, definitions)?,
+        )))
+    }
+
+    /// Returns true if the value can be promoted to `schema`, following the Avro specification:
+    /// an `int` to a `long`, `float` or `double`, a `long` to a `float` or `double`, a `float` to
+    /// a `double`, and `string` and `bytes` to each other. A value of the underlying type of a
+    /// logical type, or promoted to it, is also read as the logical type.
+    fn promotes_to(&self, schema: &Schema) -> bool {
+        match (self, schema) {
+            (&Value::Int(_), &Schema::Long)
+            | (&Value::Int(_), &Schema::Float)
+            | (&Value::Int(_), &Schema::Double)
+            | (&Value::Long(_), &Schema::Float)
+            | (&Value::Long(_), &Schema::Double)
+            | (&Value::Float(_), &Schema::Double)
+            | (&Value::String(_), &Schema::Bytes)
+            | (&Value::Bytes(_), &Schema::String)
+            | (&Value::Int(_), &Schema::Date)
+            | (&Value::Int(_), &Schema::TimeMillis)
+            | (&Value::Int(_), &Schema::TimeMicros)
+            | (&Value::Int(_), &Schema::TimestampMillis)
+            | (&Value::Int(_), &Schema::TimestampMicros)
+            | (&Value::Long(_), &Schema::TimeMicros)
+            | (&Value::Long(_), &Schema::TimestampMillis)
+            | (&Value::Long(_), &Schema::TimestampMicros)
+            | (&Value::String(_), &Schema::Uuid)
+            | (&Value::Fixed(12, _), &Schema::Duration { .. }) => true,
+            (&Value::Bytes(_), &Schema::Decimal { ref inner, .. }) => **inner == Schema::Bytes,
+            (&Value::Fixed(n, _), &Schema::Decimal { ref inner, .. }) => match **inner {
+                Schema::Fixed { size, .. } => n == size,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn resolve_array<'s>(
+        self,
+        schema: &'s Schema,
+        definitions: &mut Definitions<'s>,
+    ) -> Result<Self, Error> {
+        match self {
+            Value::Array(items) => Ok(Value::Array(
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        item.resolve_internal(schema, definitions)
+                            .map_err(|err| resolution_error_at(PathSegment::Index(index), err))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            other => Err(SchemaResolutionError::new(format!(
+                "Array({:?}) expected, got {:?}",
+                schema, other
+            ))
+            .into()),
+        }
+    }
+
+    fn resolve_map<'s>(
+        self,
+        schema: &'s Schema,
+        definitions: &mut Definitions<'s>,
+    ) -> Result<Self, Error> {
+        match self {
+            Value::Map(items) => Ok(Value::Map(
+                items
+                    .into_iter()
+                    .map(
+                        |(key, value)| match value.resolve_internal(schema, definitions) {
+                            Ok(value) => Ok((key, value)),
+                            Err(err) => Err(resolution_error_at(PathSegment::Key(key), err)),
+                        },
+                    )
+                    .collect::<Result<HashMap<_, _>, _>>()?,
+            )),
+            other => Err(SchemaResolutionError::new(format!(
+                "Map({:?}) expected, got {:?}",
+                schema, other
+            ))
+            .into()),
+        }
+    }
+
+    fn resolve_record<'s>(
+        self,
+        fields: &'s [RecordField],
+        definitions: &mut Definitions<'s>,
+    ) -> Result<Self, Error> {
+        let mut items = match self {
+            Value::Map(items) => Ok(items),
+            Value::Record(fields) => Ok(fields.into_iter().collect::<HashMap<_, _>>()),
+            other => Err(Error::from(SchemaResolutionError::new(format!(
+                "Record({:?}) expected, got {:?}",
+                fields, other
+            )))),
+        }?;
+
+        let new_fields = fields
+            .iter()
+            .map(|field| {
+                let value = match items.remove(&field.name) {
+                    Some(value) => value,
+                    None => field.default_as_value()?.ok_or_else(|| {
+                        SchemaResolutionError::new(format!(
+                            "missing field {} in record",
+                            field.name
+                        ))
+                    })?,
+                };
+                value
+                    .resolve_internal(&field.schema, definitions)
+                    .map(|value| (field.name.clone(), value))
+                    .map_err(|err| resolution_error_at(PathSegment::Field(field.name.clone()), err))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Value::Record(new_fields))
+    }
+
+    fn try_u8(self) -> Result<u8, Error> {
+        let int = self.resolve(&Schema::Int)?;
+        if let Value::Int(n) = int {
+            if n >= 0 && n <= i32::from(u8::MAX) {
+                return Ok(n as u8);
+            }
+        }
+
+        Err(SchemaResolutionError::new(format!("Unable to convert to u8, got {:?}", int)).into())
+    }
+}
+
+/// Wrap `value` into a `Value::Union` of the given union schema.
+///
+/// The value must match one of the variants of the union: a `Value::Union` is unwrapped first.
+///
+/// ```
+/// use avro_rs::{types::{make_union, Value}, Schema};
+///
+/// let schema = Schema::parse_str(r#"["null", "string"]"#).unwrap();
+/// if let Schema::Union(ref union) = schema {
+///     let value = make_union(union, Value::String("foo".to_string())).unwrap();
+///     assert!(value.validate(&schema));
+///     assert!(make_union(union, Value::Int(42)).is_err());
+/// }
+/// ```
+pub fn make_union(schema: &UnionSchema, value: Value) -> Result<Value, Error> {
+    let value = match value {
+        Value::Union(inner) => *inner,
+        value => value,
+    };
+    let compatible = match schema.find_schema(&value) {
+        Some((_, &Schema::Ref { .. })) => true,
+        Some((_, variant)) => value.validate(variant),
+        None => false,
+    };
+    if compatible {
+        Ok(Value::Union(Box::new(value)))
+    } else {
+        Err(ValidationError::new(
+            ValuePath::default(),
+            describe_union(schema),
+            describe_value(&value),
+        )
+        .into())
+    }
+}
+
+/// Wrap `value` into a `Value::Union`, as expected by a nullable union schema such as
+/// `["null", "string"]`.
+///
+/// `Value::Null` becomes the null branch of the union. As `Value::Union` does not record the
+/// position of its variant, the result fits nullable unions whatever the position of `null`.
+pub fn make_nullable(value: Value) -> Value {
+    match value {
+        Value::Union(_) => value,
+        value => Value::Union(Box::new(value)),
+    }
+}
+
+/// Build the `Value::Enum` of `symbol`, looking up its position in the `enum` schema.
+///
+/// ```
+/// use avro_rs::{types::{make_enum, Value}, Schema};
+///
+/// let schema =
+///     Schema::parse_str(r#"{"type": "enum", "name": "Suit", "symbols": ["spades", "hearts"]}"#)
+///         .unwrap();
+/// assert_eq!(
+///     make_enum(&schema, "hearts").unwrap(),
+///     Value::Enum(1, "hearts".to_string())
+/// );
+/// assert!(make_enum(&schema, "clubs").is_err());
+/// ```
+pub fn make_enum(schema: &Schema, symbol: &str) -> Result<Value, Error> {
+    match schema.symbol_index(symbol) {
+        Some(index) => Ok(Value::Enum(index as i32, symbol.to_owned())),
+        None => Err(ValidationError::new(
+            ValuePath::default(),
+            describe_schema(schema),
+            format!("symbol {:?}", symbol),
+        )
+        .into()),
+    }
+}
+
+/// Merge the fields of the `patch` record into the `base` record, such as a partial update into
+/// the current state of a record.
+///
+/// Fields of `patch` overwrite the fields of `base` with the same name, unless they are null
+/// (`Value::Null`, or a `Value::Union` holding it). Fields of `base` missing from `patch` are
+/// kept, and fields of `patch` missing from `base` are appended.
+///
+/// ```
+/// use avro_rs::types::{merge_record, Value};
+///
+/// let base = Value::Record(vec![
+///     ("a".to_string(), Value::Long(1)),
+///     ("b".to_string(), Value::String("foo".to_string())),
+/// ]);
+/// let patch = Value::Record(vec![
+///     ("b".to_string(), Value::String("bar".to_string())),
+///     ("c".to_string(), Value::Null),
+/// ]);
+/// assert_eq!(
+///     merge_record(base, patch).unwrap(),
+///     Value::Record(vec![
+///         ("a".to_string(), Value::Long(1)),
+///         ("b".to_string(), Value::String("bar".to_string())),
+///         ("c".to_string(), Value::Null),
+///     ])
+/// );
+/// ```
+pub fn merge_record(base: Value, patch: Value) -> Result<Value, Error> {
+    let mut fields = match base {
+        Value::Record(fields) => fields,
+        base => return Err(not_a_record(&base)),
+    };
+    let patch = match patch {
+        Value::Record(fields) => fields,
+        patch => return Err(not_a_record(&patch)),
+    };
+
+    for (name, value) in patch {
+        match fields
+            .iter_mut()
+            .find(|&&mut (ref field, _)| *field == name)
+        {
+            Some(&mut (_, ref mut current)) => {
+                let is_null = match value {
+                    Value::Union(ref inner) => inner.is_null(),
+                    ref value => value.is_null(),
+                };
+                if !is_null {
+                    *current = value;
+                }
+            }
+            None => fields.push((name, value)),
+        }
+    }
+    Ok(Value::Record(fields))
+}
+
+/// Compare `a` and `b` following the
+/// [sort order](https://avro.apache.org/docs/current/spec.html#order) of `schema`, as
+/// [`Value::cmp_with_schema`](enum.Value.html#method.cmp_with_schema) does, e.g. to sort records
+/// before merging them.
+///
+/// ```
+/// use std::cmp::Ordering;
+///
+/// use avro_rs::types::{compare_avro, Value};
+/// use avro_rs::Schema;
+///
+/// let schema = Schema::parse_str(
+///     r#"{"type": "record", "name": "test", "fields": [
+///         {"name": "a", "type": "long", "order": "descending"}
+///     ]}"#,
+/// )
+/// .unwrap();
+/// let record = |a| Value::Record(vec![("a".to_string(), Value::Long(a))]);
+/// assert_eq!(compare_avro(&record(1), &record(2), &schema), Ordering::Greater);
+/// ```
+pub fn compare_avro(a: &Value, b: &Value, schema: &Schema) -> Ordering {
+    a.cmp_with_schema(b, schema)
+}
+
+/// Resolve a `value` written with `writer_schema` into `reader_schema`, following the rules of
+/// [schema resolution](https://avro.apache.org/docs/current/spec.html#Schema+Resolution), as
+/// [`Value::resolve`](enum.Value.html#method.resolve) does.
+///
+/// The value is first checked against `writer_schema`, returning the first
+/// [`ValidationError`](struct.ValidationError.html) if it does not conform to it.
+///
+/// ```
+/// use avro_rs::{resolve, types::Value, Schema};
+///
+/// let writer_schema = Schema::parse_str(
+///     r#"{"type": "record", "name": "user", "fields": [{"name": "id", "type": "int"}]}"#,
+/// )
+/// .unwrap();
+/// let reader_schema = Schema::parse_str(
+///     r#"{"type": "record", "name": "user", "fields": [
+///         {"name": "id", "type": "long"},
+///         {"name": "name", "type": "string", "default": "anonymous"}
+///     ]}"#,
+/// )
+/// .unwrap();
+///
+/// let value = Value::Record(vec![("id".to_owned(), Value::Int(1))]);
+/// assert_eq!(
+///     resolve(value, &writer_schema, &reader_schema).unwrap(),
+///     Value::Record(vec![
+///         ("id".to_owned(), Value::Long(1)),
+///         ("name".to_owned(), Value::String("anonymous".to_owned())),
+///     ])
+/// );
+/// ```
+pub fn resolve(
+    value: Value,
+    writer_schema: &Schema,
+    reader_schema: &Schema,
+) -> Result<Value, Error> {
+    value.assert_compatible_with(writer_schema)?;
+    value.resolve(reader_schema)
+}
+
+/// Compute the patch turning the `before` record into the `after` record: a record holding the
+/// fields of `after` which are missing from `before` or hold a different value.
+///
+/// [`merge_record`](fn.merge_record.html) applies such a patch, except for the fields whose new
+/// value is null. Fields removed from `before` are not represented in the patch.
+pub fn diff_record(before: &Value, after: &Value) -> Result<Value, Error> {
+    let before = match *before {
+        Value::Record(ref fields) => fields,
+        ref before => return Err(not_a_record(before)),
+    };
+    let after = match *after {
+        Value::Record(ref fields) => fields,
+        ref after => return Err(not_a_record(after)),
+    };
+
+    let changed = after
+        .iter()
+        .filter(|&&(ref name, ref value)| {
+            !before
+                .iter()
+                .any(|&(ref field, ref previous)| field == name && previous == value)
+        })
+        .cloned()
+        .collect();
+    Ok(Value::Record(changed))
+}
+
+/// Copy the fields of a `Value::Record` into a map of their values by name.
+///
+/// If a field appears several times, its last value is kept.
+pub fn record_to_map(record: &Value) -> Result<HashMap<String, Value>, Error> {
+    match *record {
+        Value::Record(ref fields) => Ok(fields.iter().cloned().collect()),
+        ref other => Err(not_a_record(other)),
+    }
+}
+
+/// Build a `Value::Record` out of a map of field values by name, following the field order of
+/// the record `schema`.
+///
+/// Fields missing from `map` take their default value. An error is returned if such a field
+/// has no (valid) default, or if `map` holds a field which is not in `schema`.
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use avro_rs::types::{map_to_record, Value};
+/// use avro_rs::Schema;
+///
+/// let schema = Schema::parse_str(
+///     r#"{"type": "record", "name": "test", "fields": [
+///         {"name": "a", "type": "long", "default": 42},
+///         {"name": "b", "type": "string"}
+///     ]}"#,
+/// )
+/// .unwrap();
+/// let mut map = HashMap::new();
+/// map.insert("b".to_string(), Value::String("foo".to_string()));
+/// assert_eq!(
+///     map_to_record(map, &schema).unwrap(),
+///     Value::Record(vec![
+///         ("a".to_string(), Value::Long(42)),
+///         ("b".to_string(), Value::String("foo".to_string())),
+///     ])
+/// );
+/// ```
+pub fn map_to_record(mut map: HashMap<String, Value>, schema: &Schema) -> Result<Value, Error> {
+    let (name, schema_fields) = match *schema {
+        Schema::Record {
+            ref name,
+            ref fields,
+            ..
+        } => (name, fields),
+        _ => {
+            return Err(SchemaResolutionError::new(format!(
+                "Record expected, got {}",
+                describe_schema(schema)
+            ))
+            .into())
+        }
+    };
+
+    let fields = schema_fields
+        .iter()
+        .map(|field| {
+            let value = match map.remove(&field.name) {
+                Some(value) => value,
+                None => field.default_as_value()?.ok_or_else(|| {
+                    SchemaResolutionError::new(format!(
+                        "Missing field {} of record {}, which has no default value",
+                        field.name,
+                        name.full_name()
+                    ))
+                })?,
+            };
+            Ok((field.name.clone(), value))
+        })
+        .collect::<Result<_, Error>>()?;
+
+    let mut unknown: Vec<_> = map.keys().map(String::as_str).collect();
+    if !unknown.is_empty() {
+        unknown.sort();
+        return Err(SchemaResolutionError::new(format!(
+            "Unknown fields {} of record {}",
+            unknown.join(", "),
+            name.full_name()
+        ))
+        .into());
+    }
+    Ok(Value::Record(fields))
+}
+
+/// Panics if `value` is not a `Value::Record`: use [`record_to_map`](fn.record_to_map.html)
+/// to handle any value.
+impl From<Value> for HashMap<String, Value> {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Record(fields) => fields.into_iter().collect(),
+            other => panic!("{}", not_a_record(&other)),
+        }
+    }
+}
+
+/// Builds a `Value::Record` with its fields sorted by name: use
+/// [`map_to_record`](fn.map_to_record.html) to follow the field order of a schema instead.
+///
+/// Unlike `ToAvro`, which turns maps into `Value::Map`.
+impl From<HashMap<String, Value>> for Value {
+    fn from(map: HashMap<String, Value>) -> Self {
+        let sorted: BTreeMap<_, _> = map.into_iter().collect();
+        Value::Record(sorted.into_iter().collect())
+    }
+}
+
+/// Visitor of the nodes of a `Value` tree along with their `Schema`, as walked by
+/// [`walk`](fn.walk.html).
+///
+/// Every method does nothing by default, so that visitors only implement the ones they need.
+pub trait ValueVisitor {
+    /// Visit a `Value::Record`, before its fields.
+    fn visit_record(&mut self, _fields: &[(String, Value)], _schema: &Schema) {}
+
+    /// Visit the value of a record field, before walking it.
+    fn visit_field(&mut self, _value: &Value, _field: &RecordField) {}
+
+    /// Visit a `Value::Array`, before its items.
+    fn visit_array(&mut self, _items: &[Value], _schema: &Schema) {}
+
+    /// Visit a `Value::Map`, before its values.
+    fn visit_map(&mut self, _items: &HashMap<String, Value>, _schema: &Schema) {}
+
+    /// Visit a `Value::Union`, before the value it holds.
+    fn visit_union(&mut self, _inner: &Value, _schema: &Schema) {}
+
+    /// Visit any other value, which holds no nested values.
+    fn visit_leaf(&mut self, _value: &Value, _schema: &Schema) {}
+}
+
+/// Walk the tree of `value` depth-first along with its `schema`, calling `visitor` on every
+/// node before its children.
+///
+/// References to named types are followed. A value which does not match its schema, e.g. a
+/// `Value::Array` for a `Schema::Map` or a record field missing from the schema, is visited as
+/// a leaf, if at all.
+pub fn walk<V: ValueVisitor>(value: &Value, schema: &Schema, visitor: &mut V) {
+    walk_internal(value, schema, &mut Definitions::new(schema), visitor)
+}
+
+fn walk_internal<'s, V: ValueVisitor>(
+    value: &Value,
+    schema: &'s Schema,
+    definitions: &mut Definitions<'s>,
+    visitor: &mut V,
+) {
+    if let Schema::Ref { ref name } = *schema {
+        if let Ok(definition) = resolve_ref(name, definitions) {
+            walk_internal(value, definition, definitions, visitor);
+        }
+        return;
+    }
+
+    match (value, schema) {
+        (
+            Value::Record(fields),
+            Schema::Record {
+                fields: schema_fields,
+                lookup,
+                ..
+            },
+        ) => {
+            visitor.visit_record(fields, schema);
+            for (name, value) in fields {
+                if let Some(&position) = lookup.get(name) {
+                    let field = &schema_fields[position];
+                    visitor.visit_field(value, field);
+                    walk_internal(value, &field.schema, definitions, visitor);
+                }
+            }
+        }
+        (Value::Array(items), Schema::Array(inner)) => {
+            visitor.visit_array(items, schema);
+            for item in items {
+                walk_internal(item, inner, definitions, visitor);
+            }
+        }
+        (Value::Map(items), Schema::Map(inner)) => {
+            visitor.visit_map(items, schema);
+            for value in items.values() {
+                walk_internal(value, inner, definitions, visitor);
+            }
+        }
+        (Value::Union(inner), Schema::Union(union)) => {
+            visitor.visit_union(inner, schema);
+            if let Some((_, variant)) = union.find_schema(inner) {
+                walk_internal(inner, variant, definitions, visitor);
+            }
+        }
+        _ => visitor.visit_leaf(value, schema),
+    }
+}
+
+/// Transform the tree of `value` along with its `schema`, replacing every node for which `f`
+/// returns a new value.
+///
+/// Nodes are transformed top-down: the nodes nested in a replaced node are not visited. Nested
+/// values are matched to their schema as done by [`walk`](fn.walk.html).
+///
+/// ```
+/// use avro_rs::{types::{transform, Value}, Schema};
+///
+/// // Redact every social security number.
+/// let schema = Schema::parse_str(r#"
+///     {
+///         "type": "record",
+///         "name": "User",
+///         "fields": [
+///             {"name": "name", "type": "string"},
+///             {"name": "ssn", "type": {"type": "fixed", "name": "SSN", "size": 4}}
+///         ]
+///     }
+/// "#).unwrap();
+/// let user = Value::Record(vec![
+///     ("name".to_string(), Value::String("Jane".to_string())),
+///     ("ssn".to_string(), Value::Fixed(4, vec![1, 2, 3, 4])),
+/// ]);
+///
+/// let redacted = transform(user, &schema, |_, schema| match schema.name() {
+///     Some(name) if name.name == "SSN" => Some(Value::Fixed(4, vec![0; 4])),
+///     _ => None,
+/// });
+/// assert_eq!(
+///     redacted,
+///     Value::Record(vec![
+///         ("name".to_string(), Value::String("Jane".to_string())),
+///         ("ssn".to_string(), Value::Fixed(4, vec![0; 4])),
+///     ])
+/// );
+/// ```
+pub fn transform<F>(value: Value, schema: &Schema, f: F) -> Value
+where
+    F: Fn(&Value, &Schema) -> Option<Value>,
+{
+    transform_internal(value, schema, &mut Definitions::new(schema), &f)
+}
+
+fn transform_internal<'s, F>(
+    value: Value,
+    schema: &'s Schema,
+    definitions: &mut Definitions<'s>,
+    f: &F,
+) -> Value
+where
+    F: Fn(&Value, &Schema) -> Option<Value>,
+{
+    if let Schema::Ref { ref name } = *schema {
+        return match resolve_ref(name, definitions) {
+            Ok(definition) => transform_internal(value, definition, definitions, f),
+            Err(_) => value,
+        };
+    }
+    if let Some(replacement) = f(&value, schema) {
+        return replacement;
+    }
+
+    match (value, schema) {
+        (
+            Value::Record(fields),
+            Schema::Record {
+                fields: schema_fields,
+                lookup,
+                ..
+            },
+        ) => {
+            let fields = fields
+                .into_iter()
+                .map(|(name, value)| {
+                    let value = match lookup.get(&name) {
+                        Some(&position) => transform_internal(
+                            value,
+                            &schema_fields[position].schema,
+                            definitions,
+                            f,
+                        ),
+                        None => value,
+                    };
+                    (name, value)
+                })
+                .collect();
+            Value::Record(fields)
+        }
+        (Value::Array(items), Schema::Array(inner)) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| transform_internal(item, inner, definitions, f))
+                .collect(),
+        ),
+        (Value::Map(items), Schema::Map(inner)) => Value::Map(
+            items
+                .into_iter()
+                .map(|(key, value)| (key, transform_internal(value, inner, definitions, f)))
+                .collect(),
+        ),
+        (Value::Union(inner), Schema::Union(union)) => match union.find_schema(&inner) {
+            Some((_, variant)) => Value::Union(Box::new(transform_internal(
+                *inner,
+                variant,
+                definitions,
+                f,
+            ))),
+            None => Value::Union(inner),
+        },
+        (value, _) => value,
+    }
+}
+
+fn not_a_record(value: &Value) -> Error {
+    ValidationError::new(ValuePath::default(), "record", describe_value(value)).into()
+}
+
+/// Splits a path such as `a.b[0][1]` into its segments, record fields and map keys being both
+/// represented by `PathSegment::Field`.
+fn parse_value_path(path: &str) -> Option<Vec<PathSegment>> {
+    let mut segments = Vec::new();
+    if path.is_empty() {
+        return Some(segments);
+    }
+    for part in path.split('.') {
+        let (name, mut indices) = match part.find('[') {
+            Some(position) => (&part[..position], &part[position..]),
+            None => (part, ""),
+        };
+        if !name.is_empty() {
+            segments.push(PathSegment::Field(name.to_owned()));
+        } else if indices.is_empty() {
+            return None;
+        }
+        while !indices.is_empty() {
+            let end = indices.find(']')?;
+            if !indices.starts_with('[') {
+                return None;
+            }
+            segments.push(PathSegment::Index(indices[1..end].parse().ok()?));
+            indices = &indices[end + 1..];
+        }
+    }
+    Some(segments)
+}
+
+/// Short description of a schema, used in validation errors.
+pub(crate) fn describe_schema(schema: &Schema) -> String {
+    match *schema {
+        Schema::Null => "null".to_owned(),
+        Schema::Boolean => "boolean".to_owned(),
+        Schema::Int => "int".to_owned(),
+        Schema::Long => "long".to_owned(),
+        Schema::Float => "float".to_owned(),
+        Schema::Double => "double".to_owned(),
+        Schema::Bytes => "bytes".to_owned(),
+        Schema::String => "string".to_owned(),
+        Schema::Array(_) => "array".to_owned(),
+        Schema::Map(_) => "map".to_owned(),
+        Schema::Union(ref inner) => describe_union(inner),
+        Schema::Record { ref name, .. } => format!("record {}", name.name),
+        Schema::Enum {
+            ref name,
+            ref symbols,
+            ..
+        } => format!("enum {} with symbols {:?}", name.name, symbols),
+        Schema::Fixed { ref name, size } => format!("fixed {} of size {}", name.name, size),
+        Schema::Decimal {
+            precision, scale, ..
+        } => format!("decimal({}, {})", precision, scale),
+        Schema::Date => "date".to_owned(),
+        Schema::TimeMillis => "time-millis".to_owned(),
+        Schema::TimeMicros => "time-micros".to_owned(),
+        Schema::TimestampMillis => "timestamp-millis".to_owned(),
+        Schema::TimestampMicros => "timestamp-micros".to_owned(),
+        Schema::Duration { .. } => "duration".to_owned(),
+        Schema::Uuid => "uuid".to_owned(),
+        Schema::Ref { ref name } => format!("record {}", name.name),
+    }
+}
+
+fn describe_union(union: &UnionSchema) -> String {
+    format!(
+        "union of [{}]",
+        union
+            .variants()
+            .iter()
+            .map(describe_schema)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// Short description of a value, used in validation errors.
+///
+/// Complex values are summarized instead of being printed in full.
+pub(crate) fn describe_value(value: &Value) -> String {
+    match *value {
+        Value::Bytes(ref bytes) => format!("bytes of length {}", bytes.len()),
+        Value::Fixed(size, ref bytes) if bytes.len() != size => {
+            format!("fixed of size {} holding {} bytes", size, bytes.len())
+        }
+        Value::Fixed(size, _) => format!("fixed of size {}", size),
+        Value::Union(ref inner) => format!("union value {}", describe_value(inner)),
+        Value::Array(ref items) => format!("array of {} items", items.len()),
+        Value::Map(ref items) => format!("map of {} entries", items.len()),
+        Value::Record(ref fields) => format!(
+            "record with fields [{}]",
+            fields
+                .iter()
+                .map(|&(ref name, _)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        ref other => format!("{:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{Name, RecordField, RecordFieldOrder, UnionSchema};
+    use crate::{from_avro_datum, to_avro_datum};
+
+    #[test]
+    fn value_accessors() {
+        assert_eq!(Value::Boolean(true).as_bool(), Some(true));
+        assert_eq!(Value::Int(1).as_i32(), Some(1));
+        assert_eq!(Value::Date(1).as_i32(), Some(1));
+        assert_eq!(Value::Long(1).as_i32(), None);
+        assert_eq!(Value::Long(1).as_i64(), Some(1));
+        assert_eq!(Value::Int(1).as_i64(), Some(1));
+        assert_eq!(Value::TimestampMillis(1).as_i64(), Some(1));
+        assert_eq!(Value::Float(1.5).as_f32(), Some(1.5));
+        assert_eq!(Value::Float(1.5).as_f64(), Some(1.5));
+        assert_eq!(Value::Double(1.5).as_f32(), None);
+        assert_eq!(Value::String("foo".to_owned()).as_str(), Some("foo"));
+        assert_eq!(Value::Bytes(vec![1]).as_bytes(), Some(&[1u8][..]));
+        assert_eq!(Value::Fixed(1, vec![1]).as_bytes(), Some(&[1u8][..]));
+        assert_eq!(Value::Null.as_null(), Some(()));
+        assert!(Value::Null.is_null());
+        assert!(!Value::Union(Box::new(Value::Null)).is_null());
+        assert_eq!(
+            Value::Array(vec![Value::Null]).as_array(),
+            Some(&[Value::Null][..])
+        );
+        assert_eq!(Value::Map(HashMap::new()).as_map(), Some(&HashMap::new()));
+        assert_eq!(
+            Value::Record(vec![("a".to_owned(), Value::Null)]).as_record(),
+            Some(&[("a".to_owned(), Value::Null)][..])
+        );
+        assert_eq!(Value::Null.as_record(), None);
+        assert_eq!(Value::Null.as_str(), None);
+    }
+
+    #[test]
+    fn union_accessors() {
+        let schema = UnionSchema::new(vec![Schema::Null, Schema::Long, Schema::String]).unwrap();
+        let null = Value::Union(Box::new(Value::Null));
+        let long = Value::Union(Box::new(Value::Long(1)));
+
+        assert_eq!(null.unwrap_union(), Some(&Value::Null));
+        assert_eq!(long.unwrap_union(), Some(&Value::Long(1)));
+        assert_eq!(Value::Long(1).unwrap_union(), None);
+
+        assert!(null.is_null_union());
+        assert!(!long.is_null_union());
+        assert!(!Value::Null.is_null_union());
+
+        assert_eq!(null.strip_union_wrapper(&schema), Some((0, &Value::Null)));
+        assert_eq!(
+            long.strip_union_wrapper(&schema),
+            Some((1, &Value::Long(1)))
+        );
+        assert_eq!(Value::Long(1).strip_union_wrapper(&schema), None);
+        assert_eq!(
+            Value::Union(Box::new(Value::Boolean(true))).strip_union_wrapper(&schema),
+            None
+        );
+
+        assert_eq!(
+            Value::Union(Box::new(Value::String("foo".to_owned()))).union_index(&schema),
+            Some(2)
+        );
+        assert_eq!(Value::Null.union_index(&schema), None);
+    }
+
+    #[test]
+    fn generic_record() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "test",
+                "fields": [
+                    {"name": "a", "type": "long", "default": 42},
+                    {"name": "b", "type": "string"}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+
+        let mut record = GenericRecord::new(&schema).unwrap();
+        assert_eq!(record.get("a"), Some(&Value::Long(42)));
+        assert_eq!(record.get("b"), Some(&Value::Null));
+        assert_eq!(record.get("c"), None);
+
+        record.put("b", "foo").unwrap();
+        record.put("a", Value::Long(1)).unwrap();
+        assert!(record.put("c", 1i64).is_err());
+        *record.get_mut("a").unwrap() = Value::Long(2);
+        assert!(record.get_mut("c").is_none());
+
+        let value = Value::Record(vec![
+            ("a".to_string(), Value::Long(2)),
+            ("b".to_string(), Value::String("foo".to_string())),
+        ]);
+        assert_eq!(record.fields(), value.as_record().unwrap());
+        assert!(value.validate(&schema));
+        assert_eq!(record.clone().into_value(), value);
+        assert_eq!(Value::from(record.clone()), value);
+        assert_eq!(GenericRecord::try_from(value).unwrap(), record);
+
+        assert!(GenericRecord::new(&Schema::Long).is_err());
+        assert!(GenericRecord::try_from(Value::Long(1)).is_err());
+    }
+
+    #[test]
+    fn value_conversions() {
+        assert_eq!(Value::from(true), Value::Boolean(true));
+        assert_eq!(Value::from(1i32), Value::Int(1));
+        assert_eq!(Value::from(1i64), Value::Long(1));
+        assert_eq!(Value::from(1.5f32), Value::Float(1.5));
+        assert_eq!(Value::from(1.5f64), Value::Double(1.5));
+        assert_eq!(Value::from("foo"), Value::String("foo".to_owned()));
+        assert_eq!(
+            Value::from("foo".to_owned()),
+            Value::String("foo".to_owned())
+        );
+        assert_eq!(Value::from(vec![1u8]), Value::Bytes(vec![1]));
+        assert_eq!(
+            Value::from(Some(Value::Int(1))),
+            Value::Union(Box::new(Value::Int(1)))
+        );
+        assert_eq!(Value::from(None), Value::Union(Box::new(Value::Null)));
+
+        assert_eq!(bool::try_from(Value::Boolean(true)).unwrap(), true);
+        assert_eq!(i32::try_from(Value::Int(1)).unwrap(), 1);
+        assert_eq!(i64::try_from(Value::Long(1)).unwrap(), 1);
+        assert_eq!(i64::try_from(Value::Int(1)).unwrap(), 1);
+        assert_eq!(f32::try_from(Value::Float(1.5)).unwrap(), 1.5);
+        assert_eq!(f64::try_from(Value::Double(1.5)).unwrap(), 1.5);
+        assert_eq!(
+            String::try_from(Value::String("foo".to_owned())).unwrap(),
+            "foo"
+        );
+        assert_eq!(Vec::<u8>::try_from(Value::Bytes(vec![1])).unwrap(), vec![1]);
+
+        let error = i32::try_from(Value::Long(1)).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ValidationError>(),
+            Some(&ValidationError::new(
+                ValuePath::default(),
+                "int",
+                "Long(1)"
+            ))
+        );
+        assert!(String::try_from(Value::Null).is_err());
+        assert!(Vec::<u8>::try_from(Value::String("foo".to_owned())).is_err());
+    }
+
+    #[test]
+    fn to_avro_collections() {
+        assert_eq!(5u8.avro(), Value::Int(5));
+        assert_eq!((-5i8).avro(), Value::Int(-5));
+        assert_eq!(500u16.avro(), Value::Int(500));
+        assert_eq!((-500i16).avro(), Value::Int(-500));
+
+        assert_eq!(
+            vec![1i64, 2].avro(),
+            Value::Array(vec![Value::Long(1), Value::Long(2)])
+        );
+        assert_eq!(
+            (1i32, 2i32, 3i32).avro(),
+            Value::Array(vec![Value::Int(1), Value::Int(2), Value::Int(3)])
+        );
+        assert_eq!(
+            (1i32, 2i32, 3i32, 4i32, 5i32, 6i32, 7i32, 8i32).avro(),
+            Value::Array((1..=8).map(Value::Int).collect())
+        );
+
+        let mut map = BTreeMap::new();
+        map.insert("a".to_owned(), "b");
+        let mut expected = HashMap::new();
+        expected.insert("a".to_owned(), Value::String("b".to_owned()));
+        assert_eq!(map.avro(), Value::Map(expected));
+
+        // bytes given as integers are still valid bytes
+        let bytes = vec![1u8, 2].avro().resolve(&Schema::Bytes).unwrap();
+        assert_eq!(bytes, Value::Bytes(vec![1, 2]));
+    }
+
+    #[test]
+    fn value_display() {
+        let mut map = HashMap::new();
+        map.insert("b".to_string(), Value::Long(2));
+        map.insert("a".to_string(), Value::Null);
+        let value = Value::Record(vec![
+            ("name".to_string(), Value::String("foo".to_string())),
+            ("flag".to_string(), Value::Boolean(true)),
+            ("bytes".to_string(), Value::Bytes(vec![0x61, 0x00, 0xFF])),
+            ("fixed".to_string(), Value::Fixed(2, vec![0x01, 0x62])),
+            ("suit".to_string(), Value::Enum(1, "HEARTS".to_string())),
+            (
+                "opt".to_string(),
+                Value::Union(Box::new(Value::Double(1.5))),
+            ),
+            (
+                "items".to_string(),
+                Value::Array(vec![Value::Int(1), Value::Int(2)]),
+            ),
+            ("map".to_string(), Value::Map(map)),
+        ]);
+
+        assert_eq!(
+            value.to_string(),
+            r#"{name: "foo", flag: true, bytes: b"a\x00\xff", fixed: fixed[2](b"\x01b"), suit: HEARTS, opt: Union(1.5), items: [1, 2], map: {"a": null, "b": 2}}"#
+        );
+    }
+
+    #[test]
+    fn value_ord() {
+        assert!(Value::Null < Value::Boolean(false));
+        assert!(Value::Boolean(false) < Value::Boolean(true));
+        assert!(Value::Int(-3) < Value::Int(2));
+        assert!(Value::Double(1.5) < Value::Double(f64::NAN));
+        assert_eq!(
+            Value::Double(f64::NAN).cmp(&Value::Double(f64::NAN)),
+            Ordering::Equal
+        );
+        assert!(Value::String("abc".to_string()) < Value::String("abd".to_string()));
+        assert!(Value::Bytes(vec![1, 2]) < Value::Bytes(vec![1, 2, 0]));
+        assert!(
+            Value::Array(vec![Value::Int(1), Value::Int(9)]) < Value::Array(vec![Value::Int(2)])
+        );
+        assert!(Value::Enum(0, "SPADES".to_string()) < Value::Enum(1, "HEARTS".to_string()));
+        // values of different types are ordered consistently
+        assert!(Value::Int(9) < Value::Long(1));
+        assert!(Value::Long(1) > Value::Int(9));
+
+        let mut values = vec![
+            Value::String("b".to_string()),
+            Value::Null,
+            Value::String("a".to_string()),
+        ];
+        values.sort();
+        assert_eq!(
+            values,
+            vec![
+                Value::Null,
+                Value::String("a".to_string()),
+                Value::String("b".to_string()),
+            ]
+        );
+
+        let mut map = BTreeMap::new();
+        map.insert(Value::Long(2), "two");
+        map.insert(Value::Long(1), "one");
+        assert_eq!(
+            map.values().cloned().collect::<Vec<_>>(),
+            vec!["one", "two"]
+        );
+    }
+
+    #[test]
+    fn value_cmp_with_schema() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "test",
+                "fields": [
+                    {"name": "a", "type": "long", "order": "ignore"},
+                    {"name": "b", "type": "string", "order": "descending"},
+                    {"name": "c", "type": ["null", "int", "string"]}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+        let record = |a: i64, b: &str, c: Value| {
+            Value::Record(vec![
+                ("a".to_string(), Value::Long(a)),
+                ("b".to_string(), Value::String(b.to_string())),
+                ("c".to_string(), Value::Union(Box::new(c))),
+            ])
+        };
+
+        assert_eq!(
+            record(1, "foo", Value::Null).cmp_with_schema(&record(2, "foo", Value::Null), &schema),
+            Ordering::Equal
+        );
+        assert_eq!(
+            record(1, "a", Value::Null).cmp_with_schema(&record(1, "b", Value::Null), &schema),
+            Ordering::Greater
+        );
+        // union values are ordered by branch first
+        assert_eq!(
+            record(1, "a", Value::String("a".to_string()))
+                .cmp_with_schema(&record(1, "a", Value::Int(42)), &schema),
+            Ordering::Greater
+        );
+        assert_eq!(
+            record(1, "a", Value::Int(1)).cmp_with_schema(&record(1, "a", Value::Int(2)), &schema),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn record_field_access() {
+        let mut record = Value::Record(vec![
+            ("a".to_string(), Value::Long(1)),
+            ("b".to_string(), Value::String("foo".to_string())),
+        ]);
+        assert_eq!(
+            record.field_by_index(1),
+            Some(("b", &Value::String("foo".to_string())))
+        );
+        assert_eq!(record.field_by_index(2), None);
+        assert_eq!(record.field_by_name("a"), Some(&Value::Long(1)));
+        assert_eq!(record.field_by_name("c"), None);
+
+        record.set_field_by_index(0, Value::Long(2)).unwrap();
+        assert_eq!(record.field_by_name("a"), Some(&Value::Long(2)));
+        assert!(record.set_field_by_index(2, Value::Null).is_err());
+
+        let mut long = Value::Long(1);
+        assert_eq!(long.field_by_index(0), None);
+        assert_eq!(long.field_by_name("a"), None);
+        assert!(long.set_field_by_index(0, Value::Null).is_err());
+    }
+
+    #[test]
+    fn compare_avro_enums_and_nested_records() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "outer",
+                "fields": [
+                    {"name": "suit", "type": {"type": "enum", "name": "Suit", "symbols": ["SPADES", "HEARTS"]}},
+                    {"name": "inner", "type": {"type": "array", "items": {
+                        "type": "record",
+                        "name": "inner",
+                        "fields": [{"name": "x", "type": "int", "order": "descending"}]
+                    }}}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+        let record = |suit: Value, xs: &[i32]| {
+            Value::Record(vec![
+                ("suit".to_string(), suit),
+                (
+                    "inner".to_string(),
+                    Value::Array(
+                        xs.iter()
+                            .map(|&x| Value::Record(vec![("x".to_string(), Value::Int(x))]))
+                            .collect(),
+                    ),
+                ),
+            ])
+        };
+        let spades = || Value::Enum(0, "SPADES".to_string());
+        let hearts = || Value::Enum(1, "HEARTS".to_string());
+
+        // enums are ordered by the position of their symbol, not alphabetically
+        assert_eq!(
+            compare_avro(&record(spades(), &[]), &record(hearts(), &[]), &schema),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_avro(
+                &record(Value::String("HEARTS".to_string()), &[]),
+                &record(spades(), &[]),
+                &schema
+            ),
+            Ordering::Greater
+        );
+        // the order of nested record fields applies to array items
+        assert_eq!(
+            compare_avro(
+                &record(spades(), &[1, 2]),
+                &record(spades(), &[1, 3]),
+                &schema
+            ),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_avro(&record(spades(), &[1]), &record(spades(), &[1, 0]), &schema),
+            Ordering::Less
+        );
+
+        let mut records = vec![
+            record(hearts(), &[1]),
+            record(spades(), &[1]),
+            record(spades(), &[2]),
+        ];
+        records.sort_by(|a, b| compare_avro(a, b, &schema));
+        assert_eq!(
+            records,
+            vec![
+                record(spades(), &[2]),
+                record(spades(), &[1]),
+                record(hearts(), &[1]),
+            ]
+        );
+    }
+
+    #[test]
+    fn value_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::HashSet;
+
+        fn hash(value: &Value) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let mut a = HashMap::new();
+        let mut b = HashMap::new();
+        for i in 0..32 {
+            a.insert(i.to_string(), Value::Int(i));
+            b.insert((31 - i).to_string(), Value::Int(31 - i));
+        }
+        assert_eq!(Value::Map(a.clone()), Value::Map(b.clone()));
+        assert_eq!(hash(&Value::Map(a)), hash(&Value::Map(b)));
+
+        assert_eq!(hash(&Value::Double(0.0)), hash(&Value::Double(-0.0)));
+        assert_ne!(hash(&Value::Int(1)), hash(&Value::Long(1)));
+
+        let mut set = HashSet::new();
+        set.insert(Value::Array(vec![Value::String("foo".to_string())]));
+        assert!(set.contains(&Value::Array(vec![Value::String("foo".to_string())])));
+        assert!(!set.contains(&Value::Array(vec![])));
+    }
+
+    #[test]
+    fn record_defaults() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "test",
+                "fields": [
+                    {"name": "a", "type": "long", "default": 42},
+                    {"name": "b", "type": ["null", "string"], "default": null},
+                    {"name": "c", "type": "string"}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+
+        let mut record = Record::new(&schema).unwrap();
+        assert_eq!(
+            record.fields,
+            vec![
+                ("a".to_string(), Value::Long(42)),
+                ("b".to_string(), Value::Union(Box::new(Value::Null))),
+                ("c".to_string(), Value::Null),
+            ]
+        );
+        record.put("c", "foo");
+        assert!(record.avro().validate(&schema));
+
+        assert!(Record::new_strict(&schema).is_err());
+        assert!(Record::new_strict(&Schema::Long).is_err());
+
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "test", "fields": [{"name": "a", "type": "int", "default": 1}]}"#,
+        )
+        .unwrap();
+        let record = Record::new_strict(&schema).unwrap();
+        assert_eq!(
+            record.avro(),
+            Value::Record(vec![("a".to_string(), Value::Int(1))])
+        );
+    }
+
+    #[test]
+    fn union_constructors() {
+        let union = UnionSchema::new(vec![
+            Schema::Null,
+            Schema::Array(Box::new(Schema::Int)),
+            Schema::String,
+        ])
+        .unwrap();
+
+        assert_eq!(
+            make_union(&union, Value::Null).unwrap(),
+            Value::Union(Box::new(Value::Null))
+        );
+        assert_eq!(
+            make_union(&union, Value::Array(vec![Value::Int(1)])).unwrap(),
+            Value::Union(Box::new(Value::Array(vec![Value::Int(1)])))
+        );
+        assert_eq!(
+            make_union(
+                &union,
+                Value::Union(Box::new(Value::String("foo".to_string())))
+            )
+            .unwrap(),
+            Value::Union(Box::new(Value::String("foo".to_string())))
+        );
+        assert!(make_union(&union, Value::Long(1)).is_err());
+        assert_eq!(
+            make_union(&union, Value::Array(vec![Value::Long(1)]))
+                .unwrap_err()
+                .to_string(),
+            "Invalid value at $: expected union of [null, array, string], got array of 1 items"
+        );
+
+        assert_eq!(
+            make_nullable(Value::Null),
+            Value::Union(Box::new(Value::Null))
+        );
+        assert_eq!(
+            make_nullable(Value::Long(1)),
+            Value::Union(Box::new(Value::Long(1)))
+        );
+        assert_eq!(
+            make_nullable(Value::Union(Box::new(Value::Long(1)))),
+            Value::Union(Box::new(Value::Long(1)))
+        );
+    }
+
+    #[test]
+    fn semantic_equality() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "Node",
+                "fields": [
+                    {"name": "id", "type": "long"},
+                    {"name": "attrs", "type": {"type": "map", "values": "Node"}},
+                    {"name": "next", "type": ["null", "Node"]}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+        let leaf = |id| {
+            Value::Record(vec![
+                ("next".to_string(), Value::Union(Box::new(Value::Null))),
+                ("attrs".to_string(), Value::Map(HashMap::new())),
+                ("id".to_string(), Value::Long(id)),
+            ])
+        };
+        let node = |next: Value| {
+            Value::Record(vec![
+                ("id".to_string(), Value::Long(0)),
+                (
+                    "attrs".to_string(),
+                    Value::Map(vec![("a".to_string(), leaf(1))].into_iter().collect()),
+                ),
+                ("next".to_string(), next),
+            ])
+        };
+        let ordered_leaf = Value::Record(vec![
+            ("id".to_string(), Value::Long(2)),
+            ("attrs".to_string(), Value::Map(HashMap::new())),
+            ("next".to_string(), Value::Null),
+        ]);
+
+        let a = node(Value::Union(Box::new(leaf(2))));
+        let b = node(ordered_leaf.clone());
+        assert_ne!(a, b);
+        assert!(a.semantic_eq(&b, &schema));
+        assert!(b.semantic_eq(&a, &schema));
+        assert!(!a.semantic_eq(&node(Value::Union(Box::new(leaf(3)))), &schema));
+        assert!(!a.semantic_eq(&node(Value::Null), &schema));
+        assert!(!leaf(1).semantic_eq(&Value::Record(vec![]), &schema));
+
+        let array = Schema::Array(Box::new(schema.clone()));
+        assert!(Value::Array(vec![leaf(2)]).semantic_eq(&Value::Array(vec![ordered_leaf]), &array));
+        assert!(!Value::Array(vec![leaf(2)]).semantic_eq(&Value::Array(vec![]), &array));
+    }
+
+    #[test]
+    fn compatibility_assertions() {
+        let schema = Schema::parse_str(r#"{"type": "array", "items": "int"}"#).unwrap();
+        let valid = Value::Array(vec![Value::Int(1)]);
+        assert!(valid.is_compatible_with(&schema));
+        assert!(valid.assert_compatible_with(&schema).is_ok());
+
+        let invalid = Value::Array(vec![Value::Int(1), Value::Null]);
+        assert!(!invalid.is_compatible_with(&schema));
+        let error = invalid.assert_compatible_with(&schema).unwrap_err();
+        assert_eq!(
+            error.downcast::<ValidationError>().unwrap().to_string(),
+            "Invalid value at $[1]: expected int, got Null"
+        );
+    }
+
+    #[test]
+    fn default_value_and_record() {
+        let mut value = Value::Long(1);
+        assert_eq!(std::mem::take(&mut value), Value::Long(1));
+        assert_eq!(value, Value::Null);
+
+        let mut record = Record::default();
+        record.put("a", 1i64);
+        assert_eq!(record.avro(), Value::Record(vec![]));
+    }
+
+    #[test]
+    fn record_map_conversions() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "test",
+                "fields": [
+                    {"name": "b", "type": "long"},
+                    {"name": "a", "type": ["null", "string"], "default": null},
+                    {"name": "c", "type": "string"}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+        let record = Value::Record(vec![
+            ("b".to_string(), Value::Long(1)),
+            ("a".to_string(), Value::Union(Box::new(Value::Null))),
+            ("c".to_string(), Value::String("foo".to_string())),
+        ]);
+
+        let map = record_to_map(&record).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["b"], Value::Long(1));
+        assert!(record_to_map(&Value::Long(1)).is_err());
+        assert_eq!(map_to_record(map.clone(), &schema).unwrap(), record);
+        assert_eq!(HashMap::from(record.clone()), map);
+
+        // fields are sorted by name without a schema
+        assert_eq!(
+            Value::from(map.clone()),
+            Value::Record(vec![
+                ("a".to_string(), Value::Union(Box::new(Value::Null))),
+                ("b".to_string(), Value::Long(1)),
+                ("c".to_string(), Value::String("foo".to_string())),
+            ])
+        );
+
+        let mut partial = map.clone();
+        partial.remove("a");
+        assert_eq!(map_to_record(partial.clone(), &schema).unwrap(), record);
+        partial.remove("c");
+        assert!(map_to_record(partial, &schema).is_err());
+
+        let mut unknown = map.clone();
+        unknown.insert("d".to_string(), Value::Null);
+        assert!(map_to_record(unknown, &schema).is_err());
+        assert!(map_to_record(map, &Schema::Long).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn record_map_conversion_of_non_record() {
+        let _ = HashMap::from(Value::Long(1));
+    }
+
+    #[test]
+    fn default_values() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "Node",
+                "fields": [
+                    {"name": "b", "type": "boolean"},
+                    {"name": "f", "type": "float"},
+                    {"name": "bytes", "type": "bytes"},
+                    {"name": "tags", "type": {"type": "array", "items": "string"}},
+                    {"name": "attrs", "type": {"type": "map", "values": "long"}},
+                    {"name": "suit", "type": {"type": "enum", "name": "Suit", "symbols": ["A", "B"]}},
+                    {"name": "suit2", "type": "Suit", "default": "B"},
+                    {"name": "md5", "type": {"type": "fixed", "name": "MD5", "size": 4}},
+                    {"name": "amount", "type": {
+                        "type": "bytes", "logicalType": "decimal", "precision": 4, "scale": 2
+                    }},
+                    {"name": "date", "type": {"type": "int", "logicalType": "date"}},
+                    {"name": "next", "type": ["Node", "null"]}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+
+        let value = Value::default_for_schema(&schema).unwrap();
+        assert!(value.validate(&schema));
+        assert_eq!(
+            value,
+            Value::Record(vec![
+                ("b".to_owned(), Value::Boolean(false)),
+                ("f".to_owned(), Value::Float(0.0)),
+                ("bytes".to_owned(), Value::Bytes(vec![])),
+                ("tags".to_owned(), Value::Array(vec![])),
+                ("attrs".to_owned(), Value::Map(HashMap::new())),
+                ("suit".to_owned(), Value::Enum(0, "A".to_owned())),
+                ("suit2".to_owned(), Value::Enum(1, "B".to_owned())),
+                ("md5".to_owned(), Value::Fixed(4, vec![0; 4])),
+                ("amount".to_owned(), Value::Decimal(Decimal::from(vec![0]))),
+                ("date".to_owned(), Value::Date(0)),
+                // the recursive variant is skipped
+                ("next".to_owned(), Value::Union(Box::new(Value::Null))),
+            ])
+        );
+
+        let recursive = Schema::parse_str(
+            r#"{"type": "record", "name": "Loop", "fields": [{"name": "next", "type": "Loop"}]}"#,
+        )
+        .unwrap();
+        assert!(Value::default_for_schema(&recursive).is_err());
+        let empty_enum = Schema::Enum {
+            name: Name::new("Empty"),
+            doc: None,
+            symbols: vec![],
+        };
+        assert!(Value::default_for_schema(&empty_enum).is_err());
+    }
+
+    #[test]
+    fn enum_constructor() {
+        let schema = Schema::Enum {
+            name: Name::new("Suit"),
+            doc: None,
+            symbols: vec!["spades".to_owned(), "hearts".to_owned()],
+        };
+
+        let value = make_enum(&schema, "hearts").unwrap();
+        assert_eq!(value, Value::Enum(1, "hearts".to_owned()));
+        assert_eq!(value.enum_symbol(), Some("hearts"));
+        assert!(value.validate(&schema));
+        assert_eq!(Value::String("hearts".to_owned()).enum_symbol(), None);
+
+        assert_eq!(
+            make_enum(&schema, "clubs").unwrap_err().to_string(),
+            "Invalid value at $: expected enum Suit with symbols [\"spades\", \"hearts\"], \
+             got symbol \"clubs\""
+        );
+        assert!(make_enum(&Schema::String, "hearts").is_err());
+    }
+
+    #[test]
+    fn walk_and_transform_values() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "Node",
+                "fields": [
+                    {"name": "id", "type": "long"},
+                    {"name": "tags", "type": {"type": "array", "items": "string"}},
+                    {"name": "attrs", "type": {"type": "map", "values": "long"}},
+                    {"name": "next", "type": ["null", "Node"]}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+        let node = |id: i64, size: i64, tags: Vec<&str>, next: Value| {
+            let mut attrs = HashMap::new();
+            attrs.insert("size".to_string(), Value::Long(size));
+            Value::Record(vec![
+                ("id".to_string(), Value::Long(id)),
+                (
+                    "tags".to_string(),
+                    Value::Array(tags.into_iter().map(Value::from).collect()),
+                ),
+                ("attrs".to_string(), Value::Map(attrs)),
+                ("next".to_string(), Value::Union(Box::new(next))),
+            ])
+        };
+        let value = node(1, 10, vec!["a", "b"], node(2, 20, vec!["c"], Value::Null));
+        assert!(value.validate(&schema));
+
+        /// Records the visited nodes.
+        #[derive(Default)]
+        struct Trace(Vec<String>);
+
+        impl ValueVisitor for Trace {
+            fn visit_record(&mut self, fields: &[(String, Value)], _schema: &Schema) {
+                self.0.push(format!("record of {}", fields.len()));
+            }
+
+            fn visit_field(&mut self, _value: &Value, field: &RecordField) {
+                self.0.push(format!("field {}", field.name));
+            }
+
+            fn visit_array(&mut self, items: &[Value], _schema: &Schema) {
+                self.0.push(format!("array of {}", items.len()));
+            }
+
+            fn visit_map(&mut self, items: &HashMap<String, Value>, _schema: &Schema) {
+                self.0.push(format!("map of {}", items.len()));
+            }
+
+            fn visit_union(&mut self, _inner: &Value, schema: &Schema) {
+                self.0.push(format!("union {}", schema.canonical_form()));
+            }
+
+            fn visit_leaf(&mut self, value: &Value, schema: &Schema) {
+                self.0
+                    .push(format!("{} {}", value, schema.canonical_form()));
+            }
+        }
+
+        let mut trace = Trace::default();
+        value.walk(&schema, &mut trace);
+        let mut expected = Vec::new();
+        for &(id, tags) in &[(1, &["a", "b"][..]), (2, &["c"][..])] {
+            expected.extend(vec![
+                "record of 4".to_string(),
+                "field id".to_string(),
+                format!("{} \"long\"", id),
+                "field tags".to_string(),
+                format!("array of {}", tags.len()),
+            ]);
+            expected.extend(tags.iter().map(|tag| format!("\"{}\" \"string\"", tag)));
+            expected.extend(vec![
+                "field attrs".to_string(),
+                "map of 1".to_string(),
+                format!("{} \"long\"", id * 10),
+                "field next".to_string(),
+                "union [\"null\",\"Node\"]".to_string(),
+            ]);
+        }
+        expected.push("null \"null\"".to_string());
+        assert_eq!(trace.0, expected);
+
+        // increment every long, and empty the arrays of a single item
+        let transformed = transform(value, &schema, |value, _| match *value {
+            Value::Long(i) => Some(Value::Long(i + 1)),
+            Value::Array(ref items) if items.len() == 1 => Some(Value::Array(vec![])),
+            _ => None,
+        });
+        assert_eq!(
+            transformed,
+            node(2, 11, vec!["a", "b"], node(3, 21, vec![], Value::Null))
+        );
+        assert!(transformed.validate(&schema));
+    }
+
+    #[test]
+    fn merge_and_diff_records() {
+        let record = |fields: Vec<(&str, Value)>| {
+            Value::Record(
+                fields
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect(),
+            )
+        };
+        let base = record(vec![
+            ("a", Value::Long(1)),
+            ("b", Value::String("foo".to_string())),
+            ("c", Value::Union(Box::new(Value::Int(1)))),
+        ]);
+        let patch = record(vec![
+            ("c", Value::Union(Box::new(Value::Null))),
+            ("b", Value::String("bar".to_string())),
+            ("d", Value::Boolean(true)),
+        ]);
+        let merged = merge_record(base.clone(), patch).unwrap();
+        assert_eq!(
+            merged,
+            record(vec![
+                ("a", Value::Long(1)),
+                ("b", Value::String("bar".to_string())),
+                ("c", Value::Union(Box::new(Value::Int(1)))),
+                ("d", Value::Boolean(true)),
+            ])
+        );
+
+        let diff = diff_record(&base, &merged).unwrap();
+        assert_eq!(
+            diff,
+            record(vec![
+                ("b", Value::String("bar".to_string())),
+                ("d", Value::Boolean(true)),
+            ])
+        );
+        assert_eq!(merge_record(base.clone(), diff).unwrap(), merged);
+        assert_eq!(diff_record(&base, &base).unwrap(), record(vec![]));
+
+        assert!(merge_record(Value::Long(1), base.clone()).is_err());
+        assert!(merge_record(base.clone(), Value::Null).is_err());
+        assert!(diff_record(&base, &Value::Long(1)).is_err());
+    }
+
+    #[test]
+    fn value_get_by_path() {
+        let mut address = HashMap::new();
+        address.insert("street".to_string(), Value::String("Main St".to_string()));
+        let mut value = Value::Record(vec![(
+            "user".to_string(),
+            Value::Union(Box::new(Value::Record(vec![
+                ("name".to_string(), Value::String("foo".to_string())),
+                (
+                    "addresses".to_string(),
+                    Value::Array(vec![Value::Map(address)]),
+                ),
+                (
+                    "matrix".to_string(),
+                    Value::Array(vec![Value::Array(vec![Value::Int(1), Value::Int(2)])]),
+                ),
+            ]))),
+        )]);
+
+        assert_eq!(
+            value.get_by_path("user.addresses[0].street"),
+            Some(&Value::String("Main St".to_string()))
+        );
+        assert_eq!(value.get_by_path("user.matrix[0][1]"), Some(&Value::Int(2)));
+        assert_eq!(value.get_by_path(""), Some(&value));
+        assert!(value.get_by_path("user").unwrap().as_record().is_none());
+        for missing in &[
+            "user.age",
+            "user.addresses[1]",
+            "user.addresses[0].city",
+            "user.name[0]",
+            "user..name",
+            "user.addresses[x]",
+            "user.addresses[0",
+            "user.addresses[0]x",
+        ] {
+            assert_eq!(value.get_by_path(missing), None, "{}", missing);
         }
+
+        *value.get_by_path_mut("user.matrix[0][0]").unwrap() = Value::Int(42);
+        assert_eq!(
+            value.get_by_path("user.matrix[0][0]"),
+            Some(&Value::Int(42))
+        );
+        assert_eq!(value.get_by_path_mut("user.age"), None);
     }
 
-    fn resolve_record(self, fields: &[RecordField]) -> Result<Self, Error> {
-        let mut items = match self {
-            Value::Map(items) => Ok(items),
-            Value::Record(fields) => Ok(fields.into_iter().collect::<HashMap<_, _>>()),
-            other => Err(Error::from(SchemaResolutionError::new(format!(
-                "Record({:?}) expected, got {:?}",
-                fields, other
-            )))),
-        }?;
+    #[test]
+    fn value_json_serialization() {
+        let value = Value::Record(vec![
+            ("a".to_string(), Value::Long(27)),
+            ("b".to_string(), Value::Bytes(vec![0x00, 0x41, 0xFF])),
+            ("c".to_string(), Value::Enum(1, "spades".to_string())),
+            ("d".to_string(), Value::Union(Box::new(Value::Null))),
+            (
+                "e".to_string(),
+                Value::Union(Box::new(Value::String("foo".to_string()))),
+            ),
+            (
+                "f".to_string(),
+                Value::Array(vec![Value::Int(1), Value::Double(2.5)]),
+            ),
+        ]);
 
-        let new_fields = fields
-            .iter()
-            .map(|field| {
-                let value = match items.remove(&field.name) {
-                    Some(value) => value,
-                    None => match field.default {
-                        Some(ref value) => match field.schema {
-                            Schema::Enum { ref symbols, .. } => {
-                                value.clone().avro().resolve_enum(symbols)?
-                            }
-                            _ => value.clone().avro(),
-                        },
-                        _ => {
-                            return Err(SchemaResolutionError::new(format!(
-                                "missing field {} in record",
-                                field.name
-                            ))
-                            .into());
-                        }
-                    },
-                };
-                value
-                    .resolve(&field.schema)
-                    .map(|value| (field.name.clone(), value))
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            "{\"a\":27,\"b\":\"\\u0000A\u{FF}\",\"c\":\"spades\",\"d\":null,\
+             \"e\":{\"string\":\"foo\"},\"f\":[1,2.5]}",
+        );
+        assert_eq!(
+            serde_json::to_value(&Value::Fixed(2, vec![0x61, 0xE9])).unwrap(),
+            JsonValue::String("a\u{E9}".to_string()),
+        );
+        assert_eq!(
+            serde_json::to_value(&Value::Union(Box::new(Value::Record(vec![])))).unwrap(),
+            serde_json::json!({ "record": {} }),
+        );
+    }
 
-        Ok(Value::Record(new_fields))
+    #[test]
+    fn value_json_deserialization() {
+        let value: Value =
+            serde_json::from_str(r#"{"a": [1, 2.5, "x", null, true], "b": {"c": -3}}"#).unwrap();
+
+        let mut inner = HashMap::new();
+        inner.insert("c".to_string(), Value::Long(-3));
+        let mut expected = HashMap::new();
+        expected.insert(
+            "a".to_string(),
+            Value::Array(vec![
+                Value::Long(1),
+                Value::Double(2.5),
+                Value::String("x".to_string()),
+                Value::Null,
+                Value::Boolean(true),
+            ]),
+        );
+        expected.insert("b".to_string(), Value::Map(inner));
+
+        assert_eq!(value, Value::Map(expected));
     }
 
-    fn try_u8(self) -> Result<u8, Error> {
-        let int = self.resolve(&Schema::Int)?;
-        if let Value::Int(n) = int {
-            if n >= 0 && n <= i32::from(u8::MAX) {
-                return Ok(n as u8);
-            }
-        }
+    #[test]
+    fn value_json_string_round_trip() {
+        let mut tags = HashMap::new();
+        tags.insert("empty".to_string(), Value::Array(vec![]));
+        tags.insert(
+            "nested".to_string(),
+            Value::Array(vec![
+                Value::Map(HashMap::new()),
+                Value::Array(vec![Value::Null, Value::Boolean(false)]),
+            ]),
+        );
+        let mut expected = HashMap::new();
+        expected.insert("id".to_string(), Value::Long(-42));
+        expected.insert("score".to_string(), Value::Double(0.5));
+        expected.insert("name".to_string(), Value::String("caf\u{E9}".to_string()));
+        expected.insert("tags".to_string(), Value::Map(tags));
+        let value = Value::Map(expected);
 
-        Err(SchemaResolutionError::new(format!("Unable to convert to u8, got {:?}", int)).into())
+        let json = value.to_json_string().unwrap();
+        assert_eq!(Value::from_json_str(&json).unwrap(), value);
+
+        let record = Value::Record(vec![
+            ("a".to_string(), Value::Int(1)),
+            (
+                "b".to_string(),
+                Value::Union(Box::new(Value::String("x".to_string()))),
+            ),
+        ]);
+        assert_eq!(
+            record.to_json_string().unwrap(),
+            r#"{"a":1,"b":{"string":"x"}}"#
+        );
+        assert!(Value::from_json_str("{\"a\": ").is_err());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::schema::{Name, RecordField, RecordFieldOrder, UnionSchema};
+    #[test]
+    fn value_is_send_and_sync() {
+        // `Value` and `Schema` only hold owned data, so that they can be shared across threads.
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Value>();
+        assert_send_sync::<Schema>();
+    }
 
     #[test]
     fn validate() {
@@ -633,6 +4366,33 @@ mod tests {
 
         assert!(Value::Fixed(4, vec![0, 0, 0, 0]).validate(&schema));
         assert!(!Value::Fixed(5, vec![0, 0, 0, 0, 0]).validate(&schema));
+        // the size must match the actual number of bytes
+        assert!(!Value::Fixed(4, vec![0; 8]).validate(&schema));
+        assert!(!Value::Fixed(4, vec![0; 3]).validate(&schema));
+        assert_eq!(
+            Value::Fixed(4, vec![0; 8])
+                .validate_detailed(&schema)
+                .unwrap_err()[0]
+                .to_string(),
+            "Invalid value at $: expected fixed some_fixed of size 4, got fixed of size 4 holding 8 bytes"
+        );
+        assert!(Value::Fixed(4, vec![0; 8]).resolve(&schema).is_err());
+        assert!(to_avro_datum(&schema, Value::Fixed(4, vec![0; 8])).is_err());
+
+        let duration = Schema::Duration {
+            name: Name::new("duration"),
+        };
+        assert!(Value::Fixed(12, vec![0; 12]).validate(&duration));
+        assert!(!Value::Fixed(12, vec![0; 4]).validate(&duration));
+        assert!(Value::Fixed(12, vec![0; 4]).resolve(&duration).is_err());
+
+        let decimal = Schema::Decimal {
+            precision: 4,
+            scale: 0,
+            inner: Box::new(schema.clone()),
+        };
+        assert!(Value::Fixed(4, vec![0, 0, 0x04, 0xD2]).validate(&decimal));
+        assert!(!Value::Fixed(4, vec![0x04, 0xD2]).validate(&decimal));
     }
 
     #[test]
@@ -733,6 +4493,621 @@ mod tests {
         .validate(&schema));
     }
 
+    #[test]
+    fn validate_detailed() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "outer",
+                "fields": [
+                    {"name": "a", "type": "long"},
+                    {"name": "b", "type": {"type": "array", "items": "int"}},
+                    {"name": "c", "type": {"type": "map", "values": ["null", "string"]}},
+                    {"name": "d", "type": {
+                        "type": "record",
+                        "name": "inner",
+                        "fields": [{"name": "e", "type": "boolean"}]
+                    }}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+
+        let mut map = HashMap::new();
+        map.insert("k".to_owned(), Value::Union(Box::new(Value::Int(1))));
+        let value = Value::Record(vec![
+            ("a".to_owned(), Value::String("foo".to_owned())),
+            (
+                "b".to_owned(),
+                Value::Array(vec![Value::Int(1), Value::Null]),
+            ),
+            ("c".to_owned(), Value::Map(map)),
+            (
+                "d".to_owned(),
+                Value::Record(vec![("f".to_owned(), Value::Boolean(true))]),
+            ),
+        ]);
+        assert!(!value.validate(&schema));
+
+        let field = |name: &str| PathSegment::Field(name.to_owned());
+        let errors = value.validate_detailed(&schema).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError::new(ValuePath(vec![field("a")]), "long", "String(\"foo\")"),
+                ValidationError::new(
+                    ValuePath(vec![field("b"), PathSegment::Index(1)]),
+                    "int",
+                    "Null"
+                ),
+                ValidationError::new(
+                    ValuePath(vec![field("c"), PathSegment::Key("k".to_owned())]),
+                    "union of [null, string]",
+                    "Int(1)"
+                ),
+                ValidationError::new(
+                    ValuePath(vec![field("d"), field("e")]),
+                    "field e",
+                    "field f"
+                ),
+            ]
+        );
+        assert_eq!(
+            errors[2].to_string(),
+            "Invalid value at $.c[\"k\"]: expected union of [null, string], got Int(1)"
+        );
+
+        let valid = Value::Record(vec![
+            ("a".to_owned(), Value::Long(1)),
+            ("b".to_owned(), Value::Array(vec![])),
+            ("c".to_owned(), Value::Map(HashMap::new())),
+            (
+                "d".to_owned(),
+                Value::Record(vec![("e".to_owned(), Value::Boolean(true))]),
+            ),
+        ]);
+        assert!(valid.validate_detailed(&schema).is_ok());
+    }
+
+    #[test]
+    fn validate_detailed_record_fields_count() {
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "r", "fields": [
+                {"name": "a", "type": "int"},
+                {"name": "b", "type": "string"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let errors = Value::Record(vec![("a".to_owned(), Value::Int(1))])
+            .validate_detailed(&schema)
+            .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![ValidationError::new(
+                ValuePath(vec![PathSegment::Field("b".to_owned())]),
+                "string",
+                "missing field"
+            )]
+        );
+
+        let errors = Value::Record(vec![
+            ("a".to_owned(), Value::Int(1)),
+            ("b".to_owned(), Value::String("foo".to_owned())),
+            ("c".to_owned(), Value::Null),
+        ])
+        .validate_detailed(&schema)
+        .unwrap_err();
+        assert_eq!(errors[0].path.to_string(), "$.c");
+    }
+
+    #[test]
+    fn validate_decimal() {
+        use num_bigint::BigInt;
+
+        let bytes_schema = Schema::Decimal {
+            precision: 4,
+            scale: 2,
+            inner: Box::new(Schema::Bytes),
+        };
+        let fixed_schema = Schema::Decimal {
+            precision: 4,
+            scale: 2,
+            inner: Box::new(Schema::Fixed {
+                name: Name::new("decimal"),
+                size: 2,
+            }),
+        };
+
+        let decimal = Value::Decimal(Decimal::from(BigInt::from(-9999)));
+        assert!(decimal.validate(&bytes_schema));
+        assert!(decimal.validate(&fixed_schema));
+        assert!(!decimal.validate(&Schema::Bytes));
+
+        let too_precise = Value::Decimal(Decimal::from(BigInt::from(12345)));
+        assert!(!too_precise.validate(&bytes_schema));
+        assert!(!too_precise.validate(&fixed_schema));
+
+        assert!(Value::Bytes(vec![0x04, 0xD2]).validate(&bytes_schema));
+        assert!(Value::Fixed(2, vec![0x04, 0xD2]).validate(&fixed_schema));
+        assert!(!Value::Fixed(2, vec![0x04, 0xD2]).validate(&bytes_schema));
+        // bytes would be written with their length, which a fixed does not have
+        assert!(!Value::Bytes(vec![0x04, 0xD2]).validate(&fixed_schema));
+    }
+
+    #[test]
+    fn validate_logical_types() {
+        use num_bigint::BigInt;
+
+        let schemas = vec![
+            Schema::Int,
+            Schema::Long,
+            Schema::Bytes,
+            Schema::Fixed {
+                name: Name::new("twelve"),
+                size: 12,
+            },
+            Schema::Date,
+            Schema::TimeMillis,
+            Schema::TimeMicros,
+            Schema::TimestampMillis,
+            Schema::TimestampMicros,
+            Schema::Decimal {
+                precision: 4,
+                scale: 2,
+                inner: Box::new(Schema::Bytes),
+            },
+            Schema::Duration {
+                name: Name::new("duration"),
+            },
+        ];
+        // Each value, along with the positions of the schemas it is valid against.
+        let cases = vec![
+            (Value::Int(1), vec![0, 4, 5]),
+            (Value::Long(1), vec![1, 6, 7, 8]),
+            (Value::Bytes(vec![0x04, 0xD2]), vec![2, 9]),
+            (Value::Fixed(12, vec![0; 12]), vec![3, 10]),
+            (Value::Date(1), vec![4]),
+            (Value::TimeMillis(1), vec![5]),
+            (Value::TimeMicros(1), vec![6]),
+            (Value::TimestampMillis(1), vec![7]),
+            (Value::TimestampMicros(1), vec![8]),
+            (Value::Decimal(Decimal::from(BigInt::from(-9999))), vec![9]),
+            (Value::Decimal(Decimal::from(BigInt::from(12345))), vec![]),
+            (Value::Duration(Duration::from([0; 12])), vec![10]),
+        ];
+
+        for (value, valid) in cases {
+            for (position, schema) in schemas.iter().enumerate() {
+                let union =
+                    Schema::Union(UnionSchema::new(vec![Schema::Null, schema.clone()]).unwrap());
+                let expected = valid.contains(&position);
+                assert_eq!(
+                    value.validate(schema),
+                    expected,
+                    "{:?} against {:?}",
+                    value,
+                    schema
+                );
+                assert_eq!(
+                    Value::Union(Box::new(value.clone())).validate(&union),
+                    expected && SchemaKind::from(&value) == SchemaKind::from(schema),
+                    "{:?} against {:?}",
+                    value,
+                    union
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn resolve_decimal() {
+        let schema = Schema::Decimal {
+            precision: 4,
+            scale: 2,
+            inner: Box::new(Schema::Bytes),
+        };
+
+        assert_eq!(
+            Value::Bytes(vec![0x04, 0xD2]).resolve(&schema).unwrap(),
+            Value::Decimal(Decimal::from(vec![0x04, 0xD2]))
+        );
+        assert!(Value::Bytes(vec![0x7F, 0xFF, 0xFF])
+            .resolve(&schema)
+            .is_err());
+        assert!(Value::String("1234".to_string()).resolve(&schema).is_err());
+    }
+
+    #[test]
+    fn validate_and_resolve_date() {
+        assert!(Value::Date(17_000).validate(&Schema::Date));
+        assert!(Value::Int(17_000).validate(&Schema::Date));
+        assert!(!Value::Date(17_000).validate(&Schema::Int));
+        assert!(!Value::Long(17_000).validate(&Schema::Date));
+
+        assert_eq!(
+            Value::Int(17_000).resolve(&Schema::Date).unwrap(),
+            Value::Date(17_000)
+        );
+        assert_eq!(
+            Value::Long(17_000).resolve(&Schema::Date).unwrap(),
+            Value::Date(17_000)
+        );
+        assert!(Value::Long(1 << 40).resolve(&Schema::Date).is_err());
+        assert!(Value::String("2016-07-18".to_string())
+            .resolve(&Schema::Date)
+            .is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn date_chrono_conversion() {
+        let date = NaiveDate::from_ymd_opt(2016, 7, 18).unwrap();
+        assert_eq!(Value::from(date), Value::Date(17_000));
+        assert_eq!(NaiveDate::try_from(Value::Date(17_000)).unwrap(), date);
+
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+        assert_eq!(Value::from(epoch), Value::Date(0));
+        assert_eq!(
+            NaiveDate::try_from(Value::Date(-1)).unwrap(),
+            NaiveDate::from_ymd_opt(1969, 12, 31).unwrap()
+        );
+
+        assert!(NaiveDate::try_from(Value::Int(17_000)).is_err());
+        assert!(NaiveDate::try_from(Value::Date(i32::MAX)).is_err());
+    }
+
+    #[test]
+    fn validate_and_resolve_uuid() {
+        let uuid = "936da01f-9abd-4d9d-80c7-02af85c822a8";
+        assert!(Value::String(uuid.to_owned()).validate(&Schema::Uuid));
+        assert!(!Value::Bytes(uuid.into()).validate(&Schema::Uuid));
+        assert!(Value::Long(1).resolve(&Schema::Uuid).is_err());
+
+        let schema = Schema::parse_str(r#"{"type": "string", "logicalType": "uuid"}"#).unwrap();
+        let encoded = to_avro_datum(&schema, uuid).unwrap();
+        assert_eq!(encoded[0], 72);
+        assert_eq!(&encoded[1..], uuid.as_bytes());
+
+        let decoded = from_avro_datum(&schema, &mut &encoded[..], None).unwrap();
+        #[cfg(feature = "uuid")]
+        {
+            let expected = Uuid::parse_str(uuid).unwrap();
+            assert_eq!(decoded, Value::Uuid(expected));
+            assert_eq!(decoded.to_string(), uuid);
+            assert_eq!(Uuid::try_from(decoded).unwrap(), expected);
+            assert_eq!(to_avro_datum(&schema, expected).unwrap(), encoded);
+            assert_eq!(Value::from(expected), expected.avro());
+
+            assert!(Value::Uuid(expected).validate(&Schema::Uuid));
+            assert!(!Value::Uuid(expected).validate(&Schema::String));
+            assert_eq!(
+                Value::Uuid(expected).resolve(&Schema::String).unwrap(),
+                Value::String(uuid.to_owned())
+            );
+            assert!(Value::String("foo".to_owned())
+                .resolve(&Schema::Uuid)
+                .is_err());
+            assert!(Uuid::try_from(Value::String("foo".to_owned())).is_err());
+
+            // The reader would reject it, so it is not written either
+            assert!(!Value::String("not-a-uuid".to_owned()).validate(&Schema::Uuid));
+            assert!(to_avro_datum(&schema, Value::String("not-a-uuid".to_owned())).is_err());
+        }
+        #[cfg(not(feature = "uuid"))]
+        assert_eq!(decoded, Value::String(uuid.to_owned()));
+    }
+
+    #[test]
+    fn validate_and_resolve_time() {
+        assert!(Value::TimeMillis(1_000).validate(&Schema::TimeMillis));
+        assert!(Value::Int(1_000).validate(&Schema::TimeMillis));
+        assert!(!Value::Long(1_000).validate(&Schema::TimeMillis));
+        assert!(Value::TimeMicros(1_000).validate(&Schema::TimeMicros));
+        assert!(Value::TimestampMillis(1_000).validate(&Schema::TimestampMillis));
+        assert!(Value::Long(1_000).validate(&Schema::TimestampMicros));
+        assert!(!Value::TimestampMillis(1_000).validate(&Schema::TimestampMicros));
+
+        // Times are within a day, as in resolution
+        assert!(Value::TimeMillis(86_399_999).validate(&Schema::TimeMillis));
+        assert!(!Value::TimeMillis(86_400_000).validate(&Schema::TimeMillis));
+        assert!(!Value::Int(-1).validate(&Schema::TimeMillis));
+        assert!(Value::Long(86_399_999_999).validate(&Schema::TimeMicros));
+        assert!(!Value::TimeMicros(86_400_000_000).validate(&Schema::TimeMicros));
+        assert!(!Value::Long(-1).validate(&Schema::TimeMicros));
+        assert!(to_avro_datum(&Schema::TimeMillis, Value::Int(-1)).is_err());
+
+        assert_eq!(
+            Value::Int(1_000).resolve(&Schema::TimeMicros).unwrap(),
+            Value::TimeMicros(1_000)
+        );
+        assert_eq!(
+            Value::Long(1_000)
+                .resolve(&Schema::TimestampMillis)
+                .unwrap(),
+            Value::TimestampMillis(1_000)
+        );
+        assert!(Value::Boolean(true)
+            .resolve(&Schema::TimestampMicros)
+            .is_err());
+
+        // times are within a day, and longs are not truncated into millis
+        assert_eq!(
+            Value::Long(86_399_999)
+                .resolve(&Schema::TimeMillis)
+                .unwrap(),
+            Value::TimeMillis(86_399_999)
+        );
+        assert!(Value::Long(1 << 40).resolve(&Schema::TimeMillis).is_err());
+        assert!(Value::Int(86_400_000).resolve(&Schema::TimeMillis).is_err());
+        assert!(Value::Int(-1).resolve(&Schema::TimeMillis).is_err());
+        assert!(Value::Long(86_400_000_000)
+            .resolve(&Schema::TimeMicros)
+            .is_err());
+        assert!(Value::TimeMicros(-1).resolve(&Schema::TimeMicros).is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn time_chrono_conversion() {
+        let time = NaiveTime::from_hms_micro_opt(13, 37, 42, 123_456).unwrap();
+        let micros = ((13 * 60 + 37) * 60 + 42) * 1_000_000 + 123_456;
+        assert_eq!(Value::from(time), Value::TimeMicros(micros));
+        assert_eq!(
+            NaiveTime::try_from(Value::TimeMicros(micros)).unwrap(),
+            time
+        );
+        assert_eq!(
+            NaiveTime::try_from(Value::TimeMillis(1_500)).unwrap(),
+            NaiveTime::from_hms_milli_opt(0, 0, 1, 500).unwrap()
+        );
+
+        assert!(NaiveTime::try_from(Value::TimeMillis(-1)).is_err());
+        assert!(NaiveTime::try_from(Value::TimeMicros(86_400_000_000)).is_err());
+        // would be 10 seconds after midnight, if the seconds wrapped around as `u32`
+        assert!(NaiveTime::try_from(Value::TimeMicros(((1 << 32) + 10) * 1_000_000)).is_err());
+        assert!(NaiveTime::try_from(Value::Long(0)).is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn timestamp_chrono_conversion() {
+        let datetime = Utc
+            .timestamp_opt(1_500_000_000, 123_456_000)
+            .single()
+            .unwrap();
+        assert_eq!(
+            Value::from(datetime),
+            Value::TimestampMicros(1_500_000_000_123_456)
+        );
+        assert_eq!(
+            DateTime::<Utc>::try_from(Value::TimestampMicros(1_500_000_000_123_456)).unwrap(),
+            datetime
+        );
+        assert_eq!(
+            DateTime::<Utc>::try_from(Value::TimestampMillis(-1)).unwrap(),
+            Utc.timestamp_opt(-1, 999_000_000).single().unwrap()
+        );
+
+        assert!(DateTime::<Utc>::try_from(Value::TimestampMillis(i64::MAX)).is_err());
+        assert!(DateTime::<Utc>::try_from(Value::Long(0)).is_err());
+    }
+
+    #[test]
+    fn validate_and_resolve_duration() {
+        let schema = Schema::Duration {
+            name: Name::new("duration"),
+        };
+        let duration = Duration::new(1, 2, 3);
+
+        assert!(Value::Duration(duration).validate(&schema));
+        assert!(Value::Fixed(12, vec![0; 12]).validate(&schema));
+        assert!(!Value::Fixed(8, vec![0; 8]).validate(&schema));
+        assert!(!Value::Duration(duration).validate(&Schema::Fixed {
+            name: Name::new("duration"),
+            size: 12
+        }));
+
+        assert_eq!(
+            Value::Fixed(12, vec![1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0])
+                .resolve(&schema)
+                .unwrap(),
+            Value::Duration(duration)
+        );
+        assert!(Value::Fixed(8, vec![0; 8]).resolve(&schema).is_err());
+    }
+
+    #[test]
+    fn resolve_numeric_promotions() {
+        let promotions = vec![
+            (Value::Int(42), Schema::Long, Value::Long(42)),
+            (Value::Int(42), Schema::Float, Value::Float(42.0)),
+            (Value::Int(42), Schema::Double, Value::Double(42.0)),
+            (Value::Long(42), Schema::Float, Value::Float(42.0)),
+            (Value::Long(42), Schema::Double, Value::Double(42.0)),
+            (Value::Float(1.5), Schema::Double, Value::Double(1.5)),
+            // precision is lost for longs that do not fit in a float mantissa
+            (
+                Value::Long((1 << 24) + 1),
+                Schema::Float,
+                Value::Float((1 << 24) as f32),
+            ),
+        ];
+
+        for (value, schema, expected) in promotions {
+            let resolved = value.clone().resolve(&schema).unwrap();
+            assert_eq!(resolved, expected, "{:?} as {:?}", value, schema);
+            assert!(resolved.validate(&schema));
+        }
+    }
+
+    #[test]
+    fn coerce() {
+        let coercions = vec![
+            (Value::Int(42), Schema::Long, Some(Value::Long(42))),
+            (Value::Long(42), Schema::Int, Some(Value::Int(42))),
+            (Value::Long(i64::MAX), Schema::Int, None),
+            (
+                Value::String("foo".to_string()),
+                Schema::Bytes,
+                Some(Value::Bytes(b"foo".to_vec())),
+            ),
+            (
+                Value::Bytes(b"foo".to_vec()),
+                Schema::String,
+                Some(Value::String("foo".to_string())),
+            ),
+            (Value::Bytes(vec![0xFF]), Schema::String, None),
+            (Value::Int(1), Schema::Float, Some(Value::Float(1.0))),
+            (Value::Long(1), Schema::Double, Some(Value::Double(1.0))),
+            (Value::Boolean(true), Schema::Int, None),
+        ];
+
+        for (value, schema, expected) in coercions {
+            assert_eq!(
+                value.coerce(&schema).ok(),
+                expected,
+                "{:?} as {:?}",
+                value,
+                schema
+            );
+        }
+    }
+
+    #[test]
+    fn resolve_int_out_of_range() {
+        assert_eq!(
+            Value::Long(42).resolve(&Schema::Int).unwrap(),
+            Value::Int(42)
+        );
+        assert!(Value::Long(i64::from(i32::MAX) + 1)
+            .resolve(&Schema::Int)
+            .is_err());
+        assert!(Value::Double(1.0).resolve(&Schema::Long).is_err());
+    }
+
+    #[test]
+    fn resolve_union() {
+        let writer_schema = Schema::parse_str(r#"["null", "string", "int"]"#).unwrap();
+        let reader_schema = Schema::parse_str(r#"["null", "int", "string"]"#).unwrap();
+        let value = Value::Union(Box::new(Value::Int(5)));
+        assert!(value.validate(&writer_schema));
+
+        let resolved = value.resolve(&reader_schema).unwrap();
+        assert_eq!(resolved, Value::Union(Box::new(Value::Int(5))));
+        assert!(resolved.validate(&reader_schema));
+
+        // promotion within the union
+        let reader_schema = Schema::parse_str(r#"["null", "string", "double"]"#).unwrap();
+        let resolved = Value::Union(Box::new(Value::Int(5)))
+            .resolve(&reader_schema)
+            .unwrap();
+        assert_eq!(resolved, Value::Union(Box::new(Value::Double(5.0))));
+        assert!(resolved.validate(&reader_schema));
+
+        // writer is not a union
+        let resolved = Value::Long(5).resolve(&reader_schema).unwrap();
+        assert_eq!(resolved, Value::Union(Box::new(Value::Double(5.0))));
+
+        // reader is not a union
+        assert_eq!(
+            Value::Union(Box::new(Value::Int(5)))
+                .resolve(&Schema::Long)
+                .unwrap(),
+            Value::Long(5)
+        );
+
+        // no compatible variant
+        let reader_schema = Schema::parse_str(r#"["null", "string"]"#).unwrap();
+        assert!(Value::Union(Box::new(Value::Int(5)))
+            .resolve(&reader_schema)
+            .is_err());
+
+        // a long is not narrowed to an int, even if it fits
+        let reader_schema = Schema::parse_str(r#"["null", "int"]"#).unwrap();
+        assert!(Value::Long(5).resolve(&reader_schema).is_err());
+        assert!(Value::Union(Box::new(Value::Long(5)))
+            .resolve(&reader_schema)
+            .is_err());
+
+        // strings and bytes are promoted to each other
+        let reader_schema = Schema::parse_str(r#"["null", "bytes"]"#).unwrap();
+        assert_eq!(
+            Value::String("foo".to_owned())
+                .resolve(&reader_schema)
+                .unwrap(),
+            Value::Union(Box::new(Value::Bytes(b"foo".to_vec())))
+        );
+    }
+
+    #[test]
+    fn resolve_record_added_fields() {
+        let writer_schema = Schema::parse_str(
+            r#"{"type": "record", "name": "r", "fields": [{"name": "a", "type": "int"}]}"#,
+        )
+        .unwrap();
+        let reader_schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "r",
+                "fields": [
+                    {"name": "a", "type": "int"},
+                    {"name": "b", "type": ["null", "long"], "default": null},
+                    {"name": "c", "type": "double", "default": 1}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+        let value = Value::Record(vec![("a".to_owned(), Value::Int(1))]);
+        assert!(value.validate(&writer_schema));
+
+        let resolved = value.clone().resolve(&reader_schema).unwrap();
+        assert_eq!(
+            resolved,
+            Value::Record(vec![
+                ("a".to_owned(), Value::Int(1)),
+                ("b".to_owned(), Value::Union(Box::new(Value::Null))),
+                ("c".to_owned(), Value::Double(1.0)),
+            ])
+        );
+        assert!(resolved.validate(&reader_schema));
+
+        // a field without default that is missing from the writer cannot be resolved
+        let reader_schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "r",
+                "fields": [
+                    {"name": "a", "type": "int"},
+                    {"name": "b", "type": "long"}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+        assert!(value.resolve(&reader_schema).is_err());
+    }
+
+    #[test]
+    fn resolve_with_writer_schema() {
+        let writer_schema = Schema::parse_str(r#"["null", "int"]"#).unwrap();
+        let reader_schema = Schema::parse_str(r#"["null", "long"]"#).unwrap();
+        let value = Value::Union(Box::new(Value::Int(5)));
+        assert_eq!(
+            resolve(value, &writer_schema, &reader_schema).unwrap(),
+            Value::Union(Box::new(Value::Long(5)))
+        );
+
+        // the value must conform to the writer schema, even if it resolves into the reader one
+        let error = resolve(Value::Long(5), &writer_schema, &reader_schema).unwrap_err();
+        assert!(error.downcast_ref::<ValidationError>().is_some());
+    }
+
     #[test]
     fn resolve_bytes_ok() {
         let value = Value::Array(vec![Value::Int(0), Value::Int(42)]);
@@ -747,4 +5122,52 @@ mod tests {
         let value = Value::Array(vec![Value::Int(2000), Value::Int(-42)]);
         assert!(value.resolve(&Schema::Bytes).is_err());
     }
+
+    #[test]
+    fn resolve_error_path() {
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "outer", "fields": [
+                {"name": "items", "type": {"type": "array", "items": {
+                    "type": "record", "name": "inner", "fields": [
+                        {"name": "tags", "type": {"type": "map", "values": "int"}}
+                    ]
+                }}}
+            ]}"#,
+        )
+        .unwrap();
+        let inner = |value: Value| {
+            Value::Record(vec![(
+                "tags".to_string(),
+                Value::Map(vec![("a".to_string(), value)].into_iter().collect()),
+            )])
+        };
+        let value = Value::Record(vec![(
+            "items".to_string(),
+            Value::Array(vec![inner(Value::Int(1)), inner(Value::Long(1 << 40))]),
+        )]);
+
+        let err = value.resolve(&schema).unwrap_err();
+        let err = err.downcast_ref::<SchemaResolutionError>().unwrap();
+        assert_eq!(
+            err.path,
+            ValuePath(vec![
+                PathSegment::Field("items".to_string()),
+                PathSegment::Index(1),
+                PathSegment::Field("tags".to_string()),
+                PathSegment::Key("a".to_string()),
+            ])
+        );
+        assert!(
+            err.to_string()
+                .starts_with(r#"Schema resoulution error at $.items[1].tags["a"]: "#),
+            "{}",
+            err
+        );
+
+        let err = Value::Boolean(true).resolve(&Schema::Int).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Schema resoulution error: Int expected, got Boolean(true)"
+        );
+    }
 }
@@ -72,7 +72,7 @@ pub fn zig_i64(n: i64, buffer: &mut Vec<u8>) {
     encode_variable(((n << 1) ^ (n >> 63)) as u64, buffer)
 }
 
-pub fn zag_i32<R: Read>(reader: &mut R) -> Result<i32, Error> {
+pub fn zag_i32<R: Read + ?Sized>(reader: &mut R) -> Result<i32, Error> {
     let i = zag_i64(reader)?;
     if i < i64::from(i32::min_value()) || i > i64::from(i32::max_value()) {
         Err(DecodeError::new("int out of range").into())
@@ -81,7 +81,7 @@ pub fn zag_i32<R: Read>(reader: &mut R) -> Result<i32, Error> {
     }
 }
 
-pub fn zag_i64<R: Read>(reader: &mut R) -> Result<i64, Error> {
+pub fn zag_i64<R: Read + ?Sized>(reader: &mut R) -> Result<i64, Error> {
     let z = decode_variable(reader)?;
     Ok(if z & 0x1 == 0 {
         (z >> 1) as i64
@@ -102,7 +102,7 @@ fn encode_variable(mut z: u64, buffer: &mut Vec<u8>) {
     }
 }
 
-fn decode_variable<R: Read>(reader: &mut R) -> Result<u64, Error> {
+fn decode_variable<R: Read + ?Sized>(reader: &mut R) -> Result<u64, Error> {
     let mut i = 0u64;
     let mut buf = [0u8; 1];
 
@@ -140,8 +140,14 @@ pub fn max_allocation_bytes(num_bytes: usize) -> usize {
     }
 }
 
+/// Maximum number of bytes that can be allocated when decoding data, as set by
+/// `max_allocation_bytes`.
+pub(crate) fn allocation_limit() -> usize {
+    max_allocation_bytes(512 * 1024 * 1024)
+}
+
 pub fn safe_len(len: usize) -> Result<usize, Error> {
-    let max_bytes = max_allocation_bytes(512 * 1024 * 1024);
+    let max_bytes = allocation_limit();
 
     if len <= max_bytes {
         Ok(len)
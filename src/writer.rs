@@ -1,36 +1,24 @@
 //! Logic handling writing in Avro format at user level.
+use std::any::Any;
 use std::collections::HashMap;
 use std::io::Write;
 
-use failure::{Error, Fail};
+use failure::Error;
 use rand::random;
 use serde::Serialize;
 use serde_json;
 
-use crate::encode::{encode, encode_ref, encode_to_vec};
+use crate::datum::{DatumWriter, GenericDatumWriter};
+use crate::encode::{encode, encode_ref, encode_to_vec, estimate_encoded_size};
 use crate::schema::Schema;
 use crate::ser::Serializer;
 use crate::types::{ToAvro, Value};
 use crate::Codec;
 
 const SYNC_SIZE: usize = 16;
-const SYNC_INTERVAL: usize = 1000 * SYNC_SIZE; // TODO: parametrize in Writer
+pub(crate) const SYNC_INTERVAL: usize = 1000 * SYNC_SIZE; // TODO: parametrize in Writer
 
-const AVRO_OBJECT_HEADER: &[u8] = &[b'O', b'b', b'j', 1u8];
-
-/// Describes errors happened while validating Avro data.
-#[derive(Fail, Debug)]
-#[fail(display = "Validation error: {}", _0)]
-pub struct ValidationError(String);
-
-impl ValidationError {
-    pub fn new<S>(msg: S) -> ValidationError
-    where
-        S: Into<String>,
-    {
-        ValidationError(msg.into())
-    }
-}
+pub(crate) const AVRO_OBJECT_HEADER: &[u8] = &[b'O', b'b', b'j', 1u8];
 
 /// Main interface for writing Avro formatted values.
 pub struct Writer<'a, W> {
@@ -42,6 +30,7 @@ pub struct Writer<'a, W> {
     codec: Codec,
     marker: Vec<u8>,
     has_header: bool,
+    datum_writer: Box<dyn DatumWriter>,
 }
 
 impl<'a, W: Write> Writer<'a, W> {
@@ -55,11 +44,6 @@ impl<'a, W: Write> Writer<'a, W> {
     /// Creates a `Writer` with a specific `Codec` given a `Schema` and something implementing the
     /// `io::Write` trait to write to.
     pub fn with_codec(schema: &'a Schema, writer: W, codec: Codec) -> Writer<'a, W> {
-        let mut marker = Vec::with_capacity(16);
-        for _ in 0..16 {
-            marker.push(random::<u8>());
-        }
-
         Writer {
             schema,
             serializer: Serializer::default(),
@@ -67,8 +51,9 @@ impl<'a, W: Write> Writer<'a, W> {
             buffer: Vec::with_capacity(SYNC_INTERVAL),
             num_values: 0,
             codec,
-            marker,
+            marker: generate_marker(),
             has_header: false,
+            datum_writer: Box::new(GenericDatumWriter),
         }
     }
 
@@ -86,14 +71,7 @@ impl<'a, W: Write> Writer<'a, W> {
     /// internal buffering for performance reasons. If you want to be sure the value has been
     /// written, then call [`flush`](struct.Writer.html#method.flush).
     pub fn append<T: ToAvro>(&mut self, value: T) -> Result<usize, Error> {
-        let n = if !self.has_header {
-            let header = self.header()?;
-            let n = self.append_bytes(header.as_ref())?;
-            self.has_header = true;
-            n
-        } else {
-            0
-        };
+        let n = self.maybe_write_header()?;
 
         let avro = value.avro();
         write_value_ref(self.schema, &avro, &mut self.buffer)?;
@@ -115,14 +93,7 @@ impl<'a, W: Write> Writer<'a, W> {
     /// internal buffering for performance reasons. If you want to be sure the value has been
     /// written, then call [`flush`](struct.Writer.html#method.flush).
     pub fn append_value_ref(&mut self, value: &Value) -> Result<usize, Error> {
-        let n = if !self.has_header {
-            let header = self.header()?;
-            let n = self.append_bytes(header.as_ref())?;
-            self.has_header = true;
-            n
-        } else {
-            0
-        };
+        let n = self.maybe_write_header()?;
 
         write_value_ref(self.schema, value, &mut self.buffer)?;
 
@@ -135,6 +106,43 @@ impl<'a, W: Write> Writer<'a, W> {
         Ok(n)
     }
 
+    /// Set the [`DatumWriter`](trait.DatumWriter.html) used by
+    /// [`append_datum`](#method.append_datum). Defaults to
+    /// [`GenericDatumWriter`](struct.GenericDatumWriter.html).
+    pub fn set_datum_writer(&mut self, datum_writer: Box<dyn DatumWriter>) {
+        self.datum_writer = datum_writer;
+    }
+
+    /// Append a datum of any type to a `Writer`, as written by its
+    /// [`DatumWriter`](trait.DatumWriter.html).
+    ///
+    /// Return the number of bytes written (it might be 0, see below).
+    ///
+    /// **NOTE** This function is not guaranteed to perform any actual write, since it relies on
+    /// internal buffering for performance reasons. If you want to be sure the value has been
+    /// written, then call [`flush`](struct.Writer.html#method.flush).
+    pub fn append_datum(&mut self, datum: &dyn Any) -> Result<usize, Error> {
+        let n = self.maybe_write_header()?;
+
+        let len = self.buffer.len();
+        if let Err(e) = self
+            .datum_writer
+            .write(self.schema, datum, &mut self.buffer)
+        {
+            // Drop whatever the datum writer wrote before failing.
+            self.buffer.truncate(len);
+            return Err(e);
+        }
+
+        self.num_values += 1;
+
+        if self.buffer.len() >= SYNC_INTERVAL {
+            return self.flush().map(|b| b + n);
+        }
+
+        Ok(n)
+    }
+
     /// Append anything implementing the `Serialize` trait to a `Writer` for
     /// [`serde`](https://docs.serde.rs/serde/index.html) compatibility, also performing schema
     /// validation.
@@ -239,23 +247,28 @@ impl<'a, W: Write> Writer<'a, W> {
     /// has been written before releasing the `Writer`.
     ///
     /// Return the number of bytes written.
+    ///
+    /// The header is written even if no value has been appended, so that the output is always a
+    /// valid Avro container.
     pub fn flush(&mut self) -> Result<usize, Error> {
-        if self.num_values == 0 {
-            return Ok(0);
-        }
+        let mut num_bytes = self.maybe_write_header()?;
 
-        self.codec.compress(&mut self.buffer)?;
+        if self.num_values > 0 {
+            self.codec.compress(&mut self.buffer)?;
 
-        let num_values = self.num_values;
-        let stream_len = self.buffer.len();
+            let num_values = self.num_values;
+            let stream_len = self.buffer.len();
 
-        let num_bytes = self.append_raw(&num_values.avro(), &Schema::Long)?
-            + self.append_raw(&stream_len.avro(), &Schema::Long)?
-            + self.writer.write(self.buffer.as_ref())?
-            + self.append_marker()?;
+            num_bytes += self.append_raw(&num_values.avro(), &Schema::Long)?
+                + self.append_raw(&stream_len.avro(), &Schema::Long)?;
+            self.writer.write_all(self.buffer.as_ref())?;
+            num_bytes += stream_len + self.append_marker()?;
 
-        self.buffer.clear();
-        self.num_values = 0;
+            self.buffer.clear();
+            self.num_values = 0;
+        }
+
+        self.writer.flush()?;
 
         Ok(num_bytes)
     }
@@ -268,42 +281,70 @@ impl<'a, W: Write> Writer<'a, W> {
         self.writer
     }
 
+    /// Write the Avro header, unless it has already been written.
+    fn maybe_write_header(&mut self) -> Result<usize, Error> {
+        if self.has_header {
+            return Ok(0);
+        }
+
+        let header = self.header()?;
+        let n = self.append_bytes(header.as_ref())?;
+        self.has_header = true;
+        Ok(n)
+    }
+
     /// Generate and append synchronization marker to the payload.
     fn append_marker(&mut self) -> Result<usize, Error> {
-        // using .writer.write directly to avoid mutable borrow of self
+        // using .writer.write_all directly to avoid mutable borrow of self
         // with ref borrowing of self.marker
-        Ok(self.writer.write(&self.marker)?)
+        self.writer.write_all(&self.marker)?;
+        Ok(self.marker.len())
     }
 
     /// Append a raw Avro Value to the payload avoiding to encode it again.
     fn append_raw(&mut self, value: &Value, schema: &Schema) -> Result<usize, Error> {
-        self.append_bytes(encode_to_vec(&value, schema).as_ref())
+        self.append_bytes(encode_to_vec(&value, schema)?.as_ref())
     }
 
     /// Append pure bytes to the payload.
     fn append_bytes(&mut self, bytes: &[u8]) -> Result<usize, Error> {
-        Ok(self.writer.write(bytes)?)
+        self.writer.write_all(bytes)?;
+        Ok(bytes.len())
     }
 
     /// Create an Avro header based on schema, codec and sync marker.
     fn header(&self) -> Result<Vec<u8>, Error> {
-        let schema_bytes = serde_json::to_string(self.schema)?.into_bytes();
+        header(self.schema, self.codec, &self.marker)
+    }
+}
+
+/// Generate a random synchronization marker.
+pub(crate) fn generate_marker() -> Vec<u8> {
+    let mut marker = Vec::with_capacity(SYNC_SIZE);
+    for _ in 0..SYNC_SIZE {
+        marker.push(random::<u8>());
+    }
+    marker
+}
 
-        let mut metadata = HashMap::with_capacity(2);
-        metadata.insert("avro.schema", Value::Bytes(schema_bytes));
-        metadata.insert("avro.codec", self.codec.avro());
+/// Create an Avro header based on schema, codec and sync marker.
+pub(crate) fn header(schema: &Schema, codec: Codec, marker: &[u8]) -> Result<Vec<u8>, Error> {
+    let schema_bytes = serde_json::to_string(schema)?.into_bytes();
 
-        let mut header = Vec::new();
-        header.extend_from_slice(AVRO_OBJECT_HEADER);
-        encode(
-            &metadata.avro(),
-            &Schema::Map(Box::new(Schema::Bytes)),
-            &mut header,
-        );
-        header.extend_from_slice(&self.marker);
+    let mut metadata = HashMap::with_capacity(2);
+    metadata.insert("avro.schema", Value::Bytes(schema_bytes));
+    metadata.insert("avro.codec", codec.avro());
 
-        Ok(header)
-    }
+    let mut header = Vec::new();
+    header.extend_from_slice(AVRO_OBJECT_HEADER);
+    encode(
+        &metadata.avro(),
+        &Schema::Map(Box::new(Schema::Bytes)),
+        &mut header,
+    )?;
+    header.extend_from_slice(marker);
+
+    Ok(header)
 }
 
 /// Encode a compatible value (implementing the `ToAvro` trait) into Avro format, also performing
@@ -317,19 +358,29 @@ fn write_avro_datum<T: ToAvro>(
     buffer: &mut Vec<u8>,
 ) -> Result<(), Error> {
     let avro = value.avro();
-    if !avro.validate(schema) {
-        return Err(ValidationError::new("value does not match schema").into());
-    }
-    encode(&avro, schema, buffer);
-    Ok(())
+    validate(&avro, schema)?;
+    buffer.reserve(estimate_encoded_size(&avro, schema));
+    encode(&avro, schema, buffer)
 }
 
-fn write_value_ref(schema: &Schema, value: &Value, buffer: &mut Vec<u8>) -> Result<(), Error> {
-    if !value.validate(schema) {
-        return Err(ValidationError::new("value does not match schema").into());
+/// Validate `value` against `schema`, returning the first invalid nested value as error.
+fn validate(value: &Value, schema: &Schema) -> Result<(), Error> {
+    if value.validate(schema) {
+        return Ok(());
     }
-    encode_ref(value, schema, buffer);
-    Ok(())
+    value
+        .validate_detailed(schema)
+        .map_err(|mut errors| errors.remove(0).into())
+}
+
+pub(crate) fn write_value_ref(
+    schema: &Schema,
+    value: &Value,
+    buffer: &mut Vec<u8>,
+) -> Result<(), Error> {
+    validate(value, schema)?;
+    buffer.reserve(estimate_encoded_size(value, schema));
+    encode_ref(value, schema, buffer)
 }
 
 /// Encode a compatible value (implementing the `ToAvro` trait) into Avro format, also
@@ -344,11 +395,73 @@ pub fn to_avro_datum<T: ToAvro>(schema: &Schema, value: T) -> Result<Vec<u8>, Er
     Ok(buffer)
 }
 
+/// Two-byte marker prefixing any value written with the single object encoding.
+pub(crate) const SINGLE_OBJECT_MAGIC: [u8; 2] = [0xC3, 0x01];
+
+/// Encode a compatible value (implementing the `ToAvro` trait) using the Avro
+/// [single object encoding](https://avro.apache.org/docs/current/spec.html#single_object_encoding),
+/// also performing schema validation.
+///
+/// The encoded datum is prefixed by a two-byte marker and by the CRC-64-AVRO fingerprint of
+/// `schema`, allowing readers to identify the schema it was written with.
+pub fn encode_single_object<T: ToAvro>(schema: &Schema, value: T) -> Result<Vec<u8>, Error> {
+    let mut buffer = SINGLE_OBJECT_MAGIC.to_vec();
+    buffer.extend_from_slice(&schema.fingerprint_crc64().bytes);
+    write_avro_datum(schema, value, &mut buffer)?;
+    Ok(buffer)
+}
+
+/// Encoder writing a stream of raw Avro datums, one at a time.
+///
+/// Unlike [`Writer`](struct.Writer.html), no header, sync marker nor compression is written: each
+/// datum is written right away, as by [`to_avro_datum`](fn.to_avro_datum.html). Read the stream
+/// back with a [`ValueDecoder`](struct.ValueDecoder.html).
+pub struct ValueEncoder<'a, W> {
+    schema: &'a Schema,
+    writer: W,
+    // Reused across datums to reduce allocation.
+    buffer: Vec<u8>,
+}
+
+impl<'a, W: Write> ValueEncoder<'a, W> {
+    /// Creates a `ValueEncoder` given a `Schema` and something implementing the `io::Write` trait
+    /// to write to.
+    pub fn new(schema: &'a Schema, writer: W) -> ValueEncoder<'a, W> {
+        ValueEncoder {
+            schema,
+            writer,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Get a reference to the `Schema` associated to a `ValueEncoder`.
+    pub fn schema(&self) -> &'a Schema {
+        self.schema
+    }
+
+    /// Encode a compatible value (implementing the `ToAvro` trait) and write it, also performing
+    /// schema validation.
+    ///
+    /// Return the number of bytes written.
+    pub fn encode_one<T: ToAvro>(&mut self, value: T) -> Result<usize, Error> {
+        self.buffer.clear();
+        write_avro_datum(self.schema, value, &mut self.buffer)?;
+        self.writer.write_all(&self.buffer)?;
+        Ok(self.buffer.len())
+    }
+
+    /// Return what the `ValueEncoder` is writing to, consuming the `ValueEncoder` itself.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::types::Record;
     use crate::util::zig_i64;
+    use crate::Reader;
     use serde::{Deserialize, Serialize};
 
     static SCHEMA: &'static str = r#"
@@ -365,6 +478,17 @@ mod tests {
             ["null", "long"]
         "#;
 
+    #[test]
+    fn test_encode_single_object() {
+        let schema = Schema::parse_str(r#""long""#).unwrap();
+        let mut expected = vec![0xC3, 0x01];
+        expected.extend_from_slice(&schema.fingerprint_crc64().bytes);
+        zig_i64(27, &mut expected);
+
+        assert_eq!(encode_single_object(&schema, 27i64).unwrap(), expected);
+        assert!(encode_single_object(&schema, "foo").is_err());
+    }
+
     #[test]
     fn test_to_avro_datum() {
         let schema = Schema::parse_str(SCHEMA).unwrap();
@@ -654,4 +778,60 @@ mod tests {
             data
         );
     }
+
+    /// A sink accepting at most one byte per `write` call.
+    struct ByteByByte(Vec<u8>);
+
+    impl Write for ByteByByte {
+        fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+            match buf.first() {
+                Some(byte) => {
+                    self.0.push(*byte);
+                    Ok(1)
+                }
+                None => Ok(0),
+            }
+        }
+
+        fn flush(&mut self) -> ::std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_writer_partial_writes() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let mut writer = Writer::new(&schema, ByteByByte(Vec::new()));
+
+        let mut record = Record::new(&schema).unwrap();
+        record.put("a", 27i64);
+        record.put("b", "foo");
+
+        let n1 = writer.append(record.clone()).unwrap();
+        let n2 = writer.flush().unwrap();
+        let result = writer.into_inner().0;
+
+        assert_eq!(n1 + n2, result.len());
+        let values = Reader::new(&result[..])
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, vec![record.avro()]);
+    }
+
+    #[test]
+    fn test_writer_flush_without_values() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let mut writer = Writer::new(&schema, Vec::new());
+
+        let n = writer.flush().unwrap();
+        assert_eq!(writer.flush().unwrap(), 0);
+        let result = writer.into_inner();
+
+        assert!(n > 0);
+        assert_eq!(n, result.len());
+        let mut reader = Reader::new(&result[..]).unwrap();
+        assert_eq!(reader.writer_schema(), &schema);
+        assert!(reader.next().is_none());
+    }
 }
@@ -0,0 +1,137 @@
+#![cfg(feature = "async")]
+
+use avro_rs::{
+    types::{Record, ToAvro, Value},
+    AsyncReader, AsyncWriter, Codec, Reader, Schema, Writer,
+};
+
+static SCHEMA: &str = r#"
+    {
+        "type": "record",
+        "name": "test",
+        "fields": [
+            {"name": "a", "type": "long"},
+            {"name": "b", "type": "string"}
+        ]
+    }
+"#;
+
+fn records(schema: &Schema, n: i64) -> Vec<Value> {
+    (0..n)
+        .map(|i| {
+            let mut record = Record::new(schema).unwrap();
+            record.put("a", i);
+            record.put("b", format!("value {}", i));
+            record.avro()
+        })
+        .collect()
+}
+
+async fn write(schema: &Schema, codec: Codec, values: &[Value]) -> Vec<u8> {
+    let mut writer = AsyncWriter::with_codec(schema, Vec::new(), codec);
+    for value in values {
+        writer.append_value_ref(value).await.unwrap();
+    }
+    writer.flush().await.unwrap();
+    writer.into_inner()
+}
+
+async fn read(input: &[u8]) -> Vec<Value> {
+    let mut reader = AsyncReader::new(input).await.unwrap();
+    let mut values = Vec::new();
+    while let Some(value) = reader.next().await {
+        values.push(value.unwrap());
+    }
+    values
+}
+
+#[tokio::test]
+async fn test_async_round_trip() {
+    let schema = Schema::parse_str(SCHEMA).unwrap();
+    // enough values to span several blocks
+    let values = records(&schema, 5_000);
+
+    let mut codecs = vec![Codec::Null, Codec::Deflate];
+    if cfg!(feature = "snappy") {
+        codecs.push("snappy".parse().unwrap());
+    }
+    for codec in codecs {
+        let input = write(&schema, codec, &values).await;
+        assert_eq!(read(&input).await, values, "{:?}", codec);
+    }
+}
+
+#[tokio::test]
+async fn test_async_empty() {
+    let schema = Schema::parse_str(SCHEMA).unwrap();
+    let input = write(&schema, Codec::Null, &[]).await;
+
+    let mut reader = AsyncReader::new(&input[..]).await.unwrap();
+    assert_eq!(reader.writer_schema(), &schema);
+    assert!(reader.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_async_interoperability() {
+    let schema = Schema::parse_str(SCHEMA).unwrap();
+    let values = records(&schema, 10);
+
+    let input = write(&schema, Codec::Deflate, &values).await;
+    let read_values = Reader::new(&input[..])
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(read_values, values);
+
+    let mut writer = Writer::with_codec(&schema, Vec::new(), Codec::Deflate);
+    writer.extend_from_slice(&values).unwrap();
+    let input = writer.into_inner();
+    assert_eq!(read(&input).await, values);
+}
+
+#[tokio::test]
+async fn test_async_reader_schema() {
+    let schema = Schema::parse_str(SCHEMA).unwrap();
+    let reader_schema = Schema::parse_str(
+        r#"
+        {
+            "type": "record",
+            "name": "test",
+            "fields": [
+                {"name": "a", "type": "long"},
+                {"name": "c", "type": "int", "default": 42}
+            ]
+        }
+    "#,
+    )
+    .unwrap();
+    let input = write(&schema, Codec::Null, &records(&schema, 1)).await;
+
+    let mut reader = AsyncReader::with_schema(&reader_schema, &input[..])
+        .await
+        .unwrap();
+    assert_eq!(reader.reader_schema(), Some(&reader_schema));
+    assert_eq!(
+        reader.next().await.unwrap().unwrap(),
+        Value::Record(vec![
+            ("a".to_string(), Value::Long(0)),
+            ("c".to_string(), Value::Int(42)),
+        ])
+    );
+    assert!(reader.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_async_invalid_input() {
+    assert!(AsyncReader::new(&b"Obj\x02"[..]).await.is_err());
+
+    let schema = Schema::parse_str(SCHEMA).unwrap();
+    let mut input = write(&schema, Codec::Null, &records(&schema, 3)).await;
+    // corrupt the marker of the block
+    let last = input.len() - 1;
+    input[last] ^= 0xff;
+
+    let mut reader = AsyncReader::new(&input[..]).await.unwrap();
+    assert!(reader.next().await.unwrap().is_err());
+    assert!(reader.next().await.is_none());
+}
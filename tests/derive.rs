@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use avro_derive::{AvroSchema, ToAvro};
+use avro_rs::{
+    from_value, to_value,
+    types::{ToAvro as _, Value},
+    AvroSchema as _, Schema,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(AvroSchema, Debug, Deserialize, PartialEq, Serialize)]
+#[avro(namespace = "org.example", doc = "A test record")]
+struct Test {
+    a: i64,
+    #[avro(doc = "The b field")]
+    b: String,
+    #[avro(default = 42)]
+    c: i32,
+    d: Option<f64>,
+    e: Vec<bool>,
+    f: HashMap<String, Inner>,
+    g: Suit,
+}
+
+#[derive(AvroSchema, Debug, Deserialize, PartialEq, Serialize, ToAvro)]
+#[avro(namespace = "org.example")]
+struct Inner {
+    #[serde(rename = "inner_value")]
+    #[avro(rename = "inner_value")]
+    value: f32,
+}
+
+#[derive(AvroSchema, Debug, Deserialize, PartialEq, Serialize, ToAvro)]
+#[avro(rename = "CardSuit")]
+enum Suit {
+    Spades,
+    #[serde(rename = "HEARTS")]
+    #[avro(rename = "HEARTS")]
+    Hearts,
+}
+
+#[allow(dead_code)]
+#[derive(AvroSchema)]
+struct Generic<T> {
+    items: Vec<T>,
+}
+
+#[test]
+fn test_derive_record() {
+    let expected = Schema::parse_str(
+        r#"{
+            "type": "record",
+            "name": "Test",
+            "namespace": "org.example",
+            "doc": "A test record",
+            "fields": [
+                {"name": "a", "type": "long"},
+                {"name": "b", "type": "string", "doc": "The b field"},
+                {"name": "c", "type": "int", "default": 42},
+                {"name": "d", "type": ["null", "double"]},
+                {"name": "e", "type": {"type": "array", "items": "boolean"}},
+                {"name": "f", "type": {"type": "map", "values": {
+                    "type": "record",
+                    "name": "Inner",
+                    "fields": [{"name": "inner_value", "type": "float"}]
+                }}},
+                {"name": "g", "type": {
+                    "type": "enum",
+                    "name": "CardSuit",
+                    "namespace": "",
+                    "symbols": ["Spades", "HEARTS"]
+                }}
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(Test::schema(), expected);
+}
+
+#[test]
+fn test_derive_enum() {
+    let expected = Schema::parse_str(
+        r#"{"type": "enum", "name": "CardSuit", "symbols": ["Spades", "HEARTS"]}"#,
+    )
+    .unwrap();
+
+    assert_eq!(Suit::schema(), expected);
+}
+
+#[test]
+fn test_derive_generic() {
+    let expected = Schema::parse_str(
+        r#"{"type": "record", "name": "Generic", "fields": [
+            {"name": "items", "type": {"type": "array", "items": "string"}}
+        ]}"#,
+    )
+    .unwrap();
+
+    assert_eq!(Generic::<String>::schema(), expected);
+}
+
+#[test]
+fn test_derive_round_trip() {
+    let mut f = HashMap::new();
+    f.insert("x".to_owned(), Inner { value: 1.5 });
+    let test = Test {
+        a: 27,
+        b: "foo".to_owned(),
+        c: 3,
+        d: Some(0.5),
+        e: vec![true, false],
+        f,
+        g: Suit::Hearts,
+    };
+
+    let value = to_value(&test).unwrap();
+    assert!(value.validate(&Test::schema()));
+    assert_eq!(from_value::<Test>(&value).unwrap(), test);
+}
+
+#[derive(AvroSchema, ToAvro)]
+struct Card {
+    suit: Suit,
+    #[avro(rename = "value")]
+    rank: i32,
+    #[avro(skip)]
+    #[allow(dead_code)]
+    face_up: bool,
+    owner: Option<String>,
+    history: Vec<Inner>,
+    annotations: HashMap<String, Option<Inner>>,
+}
+
+#[test]
+fn test_derive_skip() {
+    let expected = Schema::parse_str(
+        r#"{"type": "record", "name": "Card", "fields": [
+            {"name": "suit", "type": {"type": "enum", "name": "CardSuit", "symbols": ["Spades", "HEARTS"]}},
+            {"name": "value", "type": "int"},
+            {"name": "owner", "type": ["null", "string"]},
+            {"name": "history", "type": {"type": "array", "items": {
+                "type": "record",
+                "name": "Inner",
+                "namespace": "org.example",
+                "fields": [{"name": "inner_value", "type": "float"}]
+            }}},
+            {"name": "annotations", "type": {"type": "map", "values": ["null", "org.example.Inner"]}}
+        ]}"#,
+    )
+    .unwrap();
+
+    assert_eq!(Card::schema(), expected);
+}
+
+#[test]
+fn test_derive_to_avro() {
+    assert_eq!(Suit::Spades.avro(), Value::Enum(0, "Spades".to_owned()));
+    assert_eq!(Suit::Hearts.avro(), Value::Enum(1, "HEARTS".to_owned()));
+
+    let mut annotations = HashMap::new();
+    annotations.insert("first".to_owned(), Some(Inner { value: 2.0 }));
+    annotations.insert("second".to_owned(), None);
+    let card = Card {
+        suit: Suit::Hearts,
+        rank: 12,
+        face_up: true,
+        owner: Some("alice".to_owned()),
+        history: vec![Inner { value: 1.0 }],
+        annotations,
+    };
+    let inner = |value: f32| Value::Record(vec![("inner_value".to_owned(), Value::Float(value))]);
+    let mut expected_annotations = HashMap::new();
+    expected_annotations.insert("first".to_owned(), Value::Union(Box::new(inner(2.0))));
+    expected_annotations.insert("second".to_owned(), Value::Union(Box::new(Value::Null)));
+
+    let value = card.avro();
+    assert_eq!(
+        value,
+        Value::Record(vec![
+            ("suit".to_owned(), Value::Enum(1, "HEARTS".to_owned())),
+            ("value".to_owned(), Value::Int(12)),
+            (
+                "owner".to_owned(),
+                Value::Union(Box::new(Value::String("alice".to_owned())))
+            ),
+            ("history".to_owned(), Value::Array(vec![inner(1.0)])),
+            ("annotations".to_owned(), Value::Map(expected_annotations)),
+        ])
+    );
+    assert!(value.validate(&Card::schema()));
+}
@@ -2,7 +2,9 @@
 use std::io::Cursor;
 
 use avro_rs::{
-    from_avro_datum, to_avro_datum, types::Value, Schema, SchemaResolutionError, ValidationError,
+    check_compatibility, decode_single_object, encode_single_object, from_avro_datum,
+    to_avro_datum, types::Value, CompatibilityMode, Decimal, Duration, Reader, Schema,
+    SchemaResolutionError, ValidationError, Writer,
 };
 use lazy_static::lazy_static;
 
@@ -21,7 +23,15 @@ lazy_static! {
         (r#"{"type": "array", "items": "long"}"#, Value::Array(vec![Value::Long(1), Value::Long(3), Value::Long(2)])),
         (r#"{"type": "map", "values": "long"}"#, Value::Map([("a".to_string(), Value::Long(1i64)), ("b".to_string(), Value::Long(3i64)), ("c".to_string(), Value::Long(2i64))].iter().cloned().collect())),
         (r#"["string", "null", "long"]"#, Value::Union(Box::new(Value::Null))),
-        (r#"{"type": "record", "name": "Test", "fields": [{"name": "f", "type": "long"}]}"#, Value::Record(vec![("f".to_string(), Value::Long(1))]))
+        (r#"{"type": "record", "name": "Test", "fields": [{"name": "f", "type": "long"}]}"#, Value::Record(vec![("f".to_string(), Value::Long(1))])),
+        (r#"{"type": "bytes", "logicalType": "decimal", "precision": 4, "scale": 2}"#, Value::Decimal(Decimal::from(vec![0xFB, 0x2E]))),
+        (r#"{"type": "fixed", "name": "Test", "size": 2, "logicalType": "decimal", "precision": 4, "scale": 2}"#, Value::Decimal(Decimal::from(vec![0xFB, 0x2E]))),
+        (r#"{"type": "int", "logicalType": "date"}"#, Value::Date(17_000)),
+        (r#"{"type": "int", "logicalType": "time-millis"}"#, Value::TimeMillis(49_062_123)),
+        (r#"{"type": "long", "logicalType": "time-micros"}"#, Value::TimeMicros(49_062_123_456)),
+        (r#"{"type": "long", "logicalType": "timestamp-millis"}"#, Value::TimestampMillis(1_500_000_000_123)),
+        (r#"{"type": "long", "logicalType": "timestamp-micros"}"#, Value::TimestampMicros(1_500_000_000_123_456)),
+        (r#"{"type": "fixed", "name": "Test", "size": 12, "logicalType": "duration"}"#, Value::Duration(Duration::new(1, 2, 3)))
     ];
 
     static ref BINARY_ENCODINGS: Vec<(i64, Vec<u8>)> = vec![
@@ -252,6 +262,60 @@ fn test_projection() {
     assert_eq!(datum_to_read, datum_read);
 }
 
+#[test]
+fn test_schema_evolution_with_default() {
+    let writer_schema = Schema::parse_str(
+        r#"
+        {
+            "type": "record",
+            "name": "User",
+            "fields": [
+                {"name": "name", "type": "string"},
+                {"name": "age", "type": "int"}
+            ]
+        }
+    "#,
+    )
+    .unwrap();
+    let reader_schema = Schema::parse_str(
+        r#"
+        {
+            "type": "record",
+            "name": "User",
+            "fields": [
+                {"name": "name", "type": "string"},
+                {"name": "age", "type": "long"},
+                {"name": "email", "type": "string", "default": "unknown"}
+            ]
+        }
+    "#,
+    )
+    .unwrap();
+
+    let mut writer = Writer::new(&writer_schema, Vec::new());
+    writer
+        .append(Value::Record(vec![
+            ("name".to_string(), Value::String("foo".to_string())),
+            ("age".to_string(), Value::Int(42)),
+        ]))
+        .unwrap();
+    writer.flush().unwrap();
+    let encoded = writer.into_inner();
+
+    let values = Reader::with_schema(&reader_schema, &encoded[..])
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        values,
+        vec![Value::Record(vec![
+            ("name".to_string(), Value::String("foo".to_string())),
+            ("age".to_string(), Value::Long(42)),
+            ("email".to_string(), Value::String("unknown".to_string())),
+        ])]
+    );
+}
+
 #[test]
 fn test_field_order() {
     let reader_schema = Schema::parse_str(
@@ -304,8 +368,208 @@ fn test_type_exception() -> Result<(), String> {
     match encoded {
         Ok(_) => Err(String::from("Expected ValidationError, got Ok")),
         Err(ref e) => match e.downcast_ref::<ValidationError>() {
-            Some(_) => Ok(()),
-            None => Err(format!("Expected ValidationError, got {}", e)),
+            Some(e) if e.to_string().starts_with("Invalid value at $.F") => Ok(()),
+            _ => Err(format!("Expected ValidationError, got {}", e)),
         },
     }
 }
+
+#[test]
+fn test_recursive_schema() {
+    let schema = Schema::parse_str(
+        r#"
+        {
+            "type": "record",
+            "name": "Tree",
+            "fields": [
+                {"name": "value", "type": "long"},
+                {"name": "children", "type": {"type": "array", "items": "Tree"}}
+            ]
+        }
+    "#,
+    )
+    .unwrap();
+    let node = |value: i64, children: Vec<Value>| {
+        Value::Record(vec![
+            ("value".to_string(), Value::Long(value)),
+            ("children".to_string(), Value::Array(children)),
+        ])
+    };
+    let tree = node(1, vec![node(2, vec![]), node(3, vec![node(4, vec![])])]);
+
+    let mut writer = Writer::new(&schema, Vec::new());
+    writer.append(tree.clone()).unwrap();
+    writer.flush().unwrap();
+    let encoded = writer.into_inner();
+
+    let values = Reader::new(&encoded[..])
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(values, vec![tree.clone()]);
+
+    let invalid = node(1, vec![Value::Long(2)]);
+    assert!(to_avro_datum(&schema, invalid).is_err());
+}
+
+#[test]
+fn test_recursive_type_reused_outside_its_parent() {
+    // `Child` refers back to `Parent`, which does not enclose its reuse as field `child`.
+    let schema = Schema::parse_str(
+        r#"
+        {
+            "type": "record",
+            "name": "Root",
+            "fields": [
+                {"name": "parent", "type": {
+                    "type": "record",
+                    "name": "Parent",
+                    "fields": [
+                        {"name": "child", "type": {
+                            "type": "record",
+                            "name": "Child",
+                            "fields": [{"name": "parent", "type": ["null", "Parent"]}]
+                        }}
+                    ]
+                }},
+                {"name": "child", "type": "Child"}
+            ]
+        }
+    "#,
+    )
+    .unwrap();
+    let child =
+        |parent: Value| Value::Record(vec![("parent".to_string(), Value::Union(Box::new(parent)))]);
+    let parent = |child: Value| Value::Record(vec![("child".to_string(), child)]);
+    let value = Value::Record(vec![
+        ("parent".to_string(), parent(child(Value::Null))),
+        ("child".to_string(), child(parent(child(Value::Null)))),
+    ]);
+    let value = value.resolve(&schema).unwrap();
+    assert!(value.validate(&schema));
+
+    let mut writer = Writer::new(&schema, Vec::new());
+    writer.append(value.clone()).unwrap();
+    writer.flush().unwrap();
+    let encoded = writer.into_inner();
+    let reader = Reader::new(&encoded[..]).unwrap();
+    assert_eq!(*reader.writer_schema(), schema);
+    assert_eq!(
+        reader.collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![value.clone()]
+    );
+    assert_eq!(Schema::parse_str(&schema.canonical_form()).unwrap(), schema);
+}
+
+#[test]
+fn test_aliases() {
+    let writer_schema = Schema::parse_str(
+        r#"
+        {
+            "type": "record",
+            "name": "Card",
+            "namespace": "com.example.old",
+            "fields": [
+                {"name": "suit", "type": {"type": "enum", "name": "Suit", "symbols": ["SPADES", "HEARTS"]}},
+                {"name": "hash", "type": {"type": "fixed", "name": "Hash", "size": 2}}
+            ]
+        }
+    "#,
+    )
+    .unwrap();
+    let reader_schema = Schema::parse_str(
+        r#"
+        {
+            "type": "record",
+            "name": "PlayingCard",
+            "namespace": "com.example",
+            "aliases": ["com.example.old.Card"],
+            "fields": [
+                {"name": "suit", "type": {"type": "enum", "name": "CardSuit", "aliases": ["Suit"], "symbols": ["HEARTS", "SPADES"]}},
+                {"name": "hash", "type": {"type": "fixed", "name": "CardHash", "aliases": ["Hash"], "size": 2}}
+            ]
+        }
+    "#,
+    )
+    .unwrap();
+    assert!(
+        check_compatibility(&writer_schema, &reader_schema, CompatibilityMode::Backward).is_ok()
+    );
+
+    let original_value = Value::Record(vec![
+        ("suit".to_string(), Value::Enum(1, "HEARTS".to_string())),
+        ("hash".to_string(), Value::Fixed(2, vec![0xCA, 0xFE])),
+    ]);
+    let encoded = to_avro_datum(&writer_schema, original_value).unwrap();
+    let decoded = from_avro_datum(
+        &writer_schema,
+        &mut Cursor::new(encoded),
+        Some(&reader_schema),
+    )
+    .unwrap();
+    assert_eq!(
+        decoded,
+        Value::Record(vec![
+            ("suit".to_string(), Value::Enum(0, "HEARTS".to_string())),
+            ("hash".to_string(), Value::Fixed(2, vec![0xCA, 0xFE])),
+        ])
+    );
+
+    // only compatibility checking compares names and aliases: without the alias, the renamed
+    // record is reported as incompatible, while resolution still reads the old data structurally
+    let unaliased_schema = Schema::parse_str(
+        r#"
+        {
+            "type": "record",
+            "name": "PlayingCard",
+            "namespace": "com.example",
+            "fields": [{"name": "hash", "type": {"type": "fixed", "name": "Hash", "size": 2}}]
+        }
+    "#,
+    )
+    .unwrap();
+    assert!(check_compatibility(
+        &writer_schema,
+        &unaliased_schema,
+        CompatibilityMode::Backward
+    )
+    .is_err());
+
+    let encoded = to_avro_datum(
+        &writer_schema,
+        Value::Record(vec![
+            ("suit".to_string(), Value::Enum(0, "SPADES".to_string())),
+            ("hash".to_string(), Value::Fixed(2, vec![0xCA, 0xFE])),
+        ]),
+    )
+    .unwrap();
+    let decoded = from_avro_datum(
+        &writer_schema,
+        &mut Cursor::new(encoded),
+        Some(&unaliased_schema),
+    )
+    .unwrap();
+    assert_eq!(
+        decoded,
+        Value::Record(vec![(
+            "hash".to_string(),
+            Value::Fixed(2, vec![0xCA, 0xFE])
+        )])
+    );
+}
+
+#[test]
+fn test_single_object_encoding() {
+    // Marker, then little-endian CRC-64-AVRO fingerprint of `"boolean"`
+    // (-6970731678124411036 in the reference test suite), then the datum.
+    let encoded = vec![
+        0xC3, 0x01, 0x64, 0xF7, 0xD4, 0xA4, 0x78, 0xFC, 0x42, 0x9F, 0x01,
+    ];
+    let schema = Schema::parse_str(r#""boolean""#).unwrap();
+
+    assert_eq!(encode_single_object(&schema, true).unwrap(), encoded);
+    assert_eq!(
+        decode_single_object(&schema, &encoded).unwrap(),
+        Value::Boolean(true)
+    );
+}
@@ -0,0 +1,197 @@
+//! Property-based tests generating arbitrary schemas along with values matching them.
+use std::collections::HashMap;
+
+use avro_rs::{
+    from_avro_datum, from_value, to_avro_datum, to_value, types::Value, value_from_avro_json,
+    value_to_avro_json, Schema,
+};
+use proptest::collection::{hash_map, vec};
+use proptest::prelude::*;
+use serde_json::{json, Value as JsonValue};
+
+/// The JSON of an arbitrary schema. Named types are all named `T` until
+/// [`rename`](fn.rename.html) gives them distinct names.
+fn arb_schema_json() -> impl Strategy<Value = JsonValue> {
+    let leaf = prop_oneof![
+        Just(json!("null")),
+        Just(json!("boolean")),
+        Just(json!("int")),
+        Just(json!("long")),
+        Just(json!("float")),
+        Just(json!("double")),
+        Just(json!("bytes")),
+        Just(json!("string")),
+        Just(json!({"type": "enum", "name": "T", "symbols": ["A", "B", "C"]})),
+        (1usize..8).prop_map(|size| json!({"type": "fixed", "name": "T", "size": size})),
+    ];
+    leaf.prop_recursive(3, 24, 4, |inner| {
+        prop_oneof![
+            inner
+                .clone()
+                .prop_map(|items| json!({"type": "array", "items": items})),
+            inner
+                .clone()
+                .prop_map(|values| json!({"type": "map", "values": values})),
+            vec(inner.clone(), 1..4).prop_map(|schemas| {
+                let fields = schemas
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, schema)| json!({"name": format!("f{}", i), "type": schema}))
+                    .collect::<Vec<_>>();
+                json!({"type": "record", "name": "T", "fields": fields})
+            }),
+            inner.prop_filter_map("not a variant", |schema| match schema {
+                JsonValue::Array(_) => None,
+                ref schema if *schema == json!("null") => None,
+                schema => Some(json!(["null", schema])),
+            }),
+        ]
+    })
+}
+
+/// Give distinct names to the named types of a schema.
+fn rename(schema: &mut JsonValue, count: &mut usize) {
+    match *schema {
+        JsonValue::Array(ref mut variants) => {
+            for variant in variants {
+                rename(variant, count);
+            }
+        }
+        JsonValue::Object(ref mut object) => {
+            if object.contains_key("symbols") || object.contains_key("size") {
+                object.insert("name".to_string(), json!(format!("T{}", count)));
+                *count += 1;
+            }
+            if let Some(fields) = object.get_mut("fields") {
+                for field in fields.as_array_mut().unwrap() {
+                    rename(&mut field["type"], count);
+                }
+                object.insert("name".to_string(), json!(format!("T{}", count)));
+                *count += 1;
+            }
+            for key in &["items", "values"] {
+                if let Some(inner) = object.get_mut(*key) {
+                    rename(inner, count);
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+/// An arbitrary value matching the schema of the given JSON.
+fn arb_value(schema: &JsonValue) -> BoxedStrategy<Value> {
+    match *schema {
+        JsonValue::String(ref name) => match name.as_str() {
+            "null" => Just(Value::Null).boxed(),
+            "boolean" => any::<bool>().prop_map(Value::Boolean).boxed(),
+            "int" => any::<i32>().prop_map(Value::Int).boxed(),
+            "long" => any::<i64>().prop_map(Value::Long).boxed(),
+            // NaN is not equal to itself
+            "float" => (-1e6f32..1e6).prop_map(Value::Float).boxed(),
+            "double" => (-1e12f64..1e12).prop_map(Value::Double).boxed(),
+            "bytes" => vec(any::<u8>(), 0..8).prop_map(Value::Bytes).boxed(),
+            "string" => "\\PC{0,8}".prop_map(Value::String).boxed(),
+            name => panic!("unexpected type {}", name),
+        },
+        JsonValue::Array(ref variants) => {
+            let inner = arb_value(&variants[1]);
+            prop_oneof![Just(Value::Null), inner]
+                .prop_map(|value| Value::Union(Box::new(value)))
+                .boxed()
+        }
+        JsonValue::Object(ref object) => match object["type"].as_str().unwrap() {
+            "array" => vec(arb_value(&object["items"]), 0..4)
+                .prop_map(Value::Array)
+                .boxed(),
+            "map" => hash_map("[a-z]{1,4}", arb_value(&object["values"]), 0..4)
+                .prop_map(Value::Map)
+                .boxed(),
+            "record" => {
+                let fields = object["fields"].as_array().unwrap();
+                let names = fields
+                    .iter()
+                    .map(|field| field["name"].as_str().unwrap().to_string())
+                    .collect::<Vec<_>>();
+                fields
+                    .iter()
+                    .map(|field| arb_value(&field["type"]))
+                    .collect::<Vec<_>>()
+                    .prop_map(move |values| {
+                        Value::Record(names.iter().cloned().zip(values).collect())
+                    })
+                    .boxed()
+            }
+            "enum" => (0..3)
+                .prop_map(|index| Value::Enum(index, ["A", "B", "C"][index as usize].to_string()))
+                .boxed(),
+            "fixed" => {
+                let size = object["size"].as_u64().unwrap() as usize;
+                vec(any::<u8>(), size)
+                    .prop_map(move |bytes| Value::Fixed(size, bytes))
+                    .boxed()
+            }
+            name => panic!("unexpected type {}", name),
+        },
+        _ => panic!("unexpected schema {}", schema),
+    }
+}
+
+/// An arbitrary value along with a schema it matches.
+fn arb_value_with_schema() -> impl Strategy<Value = (Value, Schema)> {
+    arb_schema_json().prop_flat_map(|mut json| {
+        rename(&mut json, &mut 0);
+        let schema = Schema::parse(&json).unwrap();
+        arb_value(&json).prop_map(move |value| (value, schema.clone()))
+    })
+}
+
+/// An arbitrary value made of the types of the data model of JSON, which serde round-trips as is.
+fn arb_json_shaped_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Boolean),
+        any::<i64>().prop_map(Value::Long),
+        (-1e12f64..1e12).prop_map(Value::Double),
+        "\\PC{0,8}".prop_map(Value::String),
+    ];
+    leaf.prop_recursive(3, 24, 4, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..4).prop_map(Value::Array),
+            hash_map("[a-z]{1,4}", inner, 0..4).prop_map(Value::Map),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn generated_values_are_valid((value, schema) in arb_value_with_schema()) {
+        prop_assert!(value.validate(&schema), "{:?} for {}", value, schema);
+    }
+
+    #[test]
+    fn binary_round_trip((value, schema) in arb_value_with_schema()) {
+        let encoded = to_avro_datum(&schema, value.clone()).unwrap();
+        let decoded = from_avro_datum(&schema, &mut &encoded[..], None).unwrap();
+        prop_assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn json_round_trip((value, schema) in arb_value_with_schema()) {
+        let json = value_to_avro_json(&value, &schema).unwrap();
+        prop_assert_eq!(value_from_avro_json(json, &schema).unwrap(), value);
+    }
+
+    #[test]
+    fn serde_round_trip(value in arb_json_shaped_value()) {
+        let serialized = to_value(&value).unwrap();
+        prop_assert_eq!(&serialized, &value);
+        prop_assert_eq!(from_value::<Value>(&serialized).unwrap(), value);
+    }
+
+    #[test]
+    fn serde_round_trip_of_maps(items in hash_map("\\PC{0,4}", any::<i64>(), 0..8)) {
+        let value = to_value(&items).unwrap();
+        prop_assert_eq!(from_value::<HashMap<String, i64>>(&value).unwrap(), items);
+    }
+}